@@ -227,6 +227,27 @@ fn bench_appendix_validate_only_sacp(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_appendix_validate_many_sacp(c: &mut Criterion) {
+    let appendix = load_appendix_canonical();
+    let total: u64 = appendix.iter().map(|b| b.as_bytes().len() as u64).sum();
+    let max_len = appendix
+        .iter()
+        .map(|b| b.as_bytes().len())
+        .max()
+        .unwrap_or(0);
+    let limits = sacp_cbor::DecodeLimits::for_bytes(max_len);
+
+    let mut group = c.benchmark_group("validate_many/appendix_a/sacp-cbor");
+    group.throughput(Throughput::Bytes(total));
+    group.bench_function("appendix_a_canonical", |b| {
+        b.iter(|| {
+            let frames = appendix.iter().map(|item| black_box(item.as_bytes()));
+            black_box(sacp_cbor::validate_many(frames, limits).unwrap())
+        })
+    });
+    group.finish();
+}
+
 fn bench_decode_canonical_trusted(c: &mut Criterion) {
     let appendix = load_appendix_canonical();
     let mut group = c.benchmark_group("decode_canonical_trusted/appendix_a/sacp-cbor");
@@ -544,6 +565,7 @@ criterion_group! {
     targets =
         bench_appendix_decode_ignored,
         bench_appendix_validate_only_sacp,
+        bench_appendix_validate_many_sacp,
         bench_decode_canonical_trusted,
         bench_synth_decode_ignored,
         bench_synth_decode_value,
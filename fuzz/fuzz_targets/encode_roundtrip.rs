@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    sacp_cbor::fuzz_decode(data);
+    sacp_cbor::fuzz_roundtrip(data);
+});
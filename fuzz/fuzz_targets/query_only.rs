@@ -2,23 +2,32 @@
 
 use libfuzzer_sys::fuzz_target;
 
-use sacp_cbor::{validate_canonical, DecodeLimits};
+use sacp_cbor::{validate_canonical, validate_relaxed, DecodeLimits};
 
 fn fuzz_limits(input_len: usize) -> DecodeLimits {
     let max = input_len.min(1 << 20);
-    DecodeLimits {
-        max_input_bytes: max,
-        max_depth: 64,
-        max_total_items: 1 << 16,
-        max_array_len: 1 << 12,
-        max_map_len: 1 << 12,
-        max_bytes_len: max,
-        max_text_len: max,
-    }
+    DecodeLimits::for_bytes(max)
+        .with_max_depth(64)
+        .with_max_total_items(1 << 16)
+        .with_max_array_len(1 << 12)
+        .with_max_map_len(1 << 12)
+        .with_max_key_len(max)
 }
 
 fuzz_target!(|data: &[u8]| {
     let limits = fuzz_limits(data.len());
+    let relaxed = limits
+        .with_allow_full_int_range(true)
+        .with_extra_allowed_tags(&[0, 1, 32]);
+    if let Ok(doc) = validate_relaxed(data, relaxed) {
+        let root = doc.root();
+        let _ = root.kind();
+        let _ = root.tag();
+        let _ = root.integer();
+        let _ = root.numeric_cmp(root);
+        let _ = root.pointer("/0");
+    }
+
     if let Ok(canon) = validate_canonical(data, limits) {
         let root = canon.root();
 
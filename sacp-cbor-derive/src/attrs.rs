@@ -1,16 +1,29 @@
 use proc_macro2::Span;
-use syn::{spanned::Spanned, Attribute, LitStr};
+use syn::{
+    spanned::Spanned, Attribute, DataEnum, DataStruct, Field, Fields, Index, LitStr, Member,
+    Variant,
+};
+
+use crate::types::{is_phantom_data_type, VariantKind};
 
 #[derive(Default, Clone)]
 pub(crate) struct CborFieldAttr {
     pub(crate) rename: Option<LitStr>,
     pub(crate) skip: bool,
     pub(crate) default: bool,
+    pub(crate) with: Option<syn::Path>,
 }
 
 #[derive(Default, Clone)]
 pub(crate) struct CborVariantAttr {
     pub(crate) rename: Option<LitStr>,
+    pub(crate) other: bool,
+}
+
+#[derive(Default, Clone, Copy)]
+pub(crate) struct CborStructAttr {
+    pub(crate) deny_unknown_keys: bool,
+    pub(crate) transparent: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
@@ -20,6 +33,13 @@ pub(crate) enum EnumTagging {
     Untagged,
 }
 
+#[derive(Default, Clone, Copy)]
+pub(crate) struct CborEnumAttr {
+    pub(crate) tagging: EnumTagging,
+    pub(crate) tag_hint: bool,
+    pub(crate) deny_unknown_keys: bool,
+}
+
 pub(crate) fn ensure_no_cbor_attrs(attrs: &[Attribute], ctx: &str) -> syn::Result<()> {
     for a in attrs {
         if a.path().is_ident("cbor") {
@@ -32,8 +52,39 @@ pub(crate) fn ensure_no_cbor_attrs(attrs: &[Attribute], ctx: &str) -> syn::Resul
     Ok(())
 }
 
-pub(crate) fn parse_cbor_field_attrs(attrs: &[Attribute]) -> syn::Result<CborFieldAttr> {
+/// Parse the `#[cbor(kind = "...")]` override on an untagged enum variant's single field.
+pub(crate) fn parse_cbor_kind_attr(attrs: &[Attribute]) -> syn::Result<Option<VariantKind>> {
+    let mut out = None;
+    for attr in attrs {
+        if !attr.path().is_ident("cbor") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("kind") {
+                if out.is_some() {
+                    return Err(meta.error("duplicate `cbor(kind=...)`"));
+                }
+                let lit: LitStr = meta.value()?.parse()?;
+                let kind = VariantKind::from_name(&lit.value()).ok_or_else(|| {
+                    syn::Error::new(
+                        lit.span(),
+                        "unknown CBOR kind (expected one of: null, bool, integer, float, bytes, text, array, map)",
+                    )
+                })?;
+                out = Some(kind);
+                return Ok(());
+            }
+            Err(meta.error(
+                "unsupported `cbor(...)` attribute on untagged variant field (allowed: kind)",
+            ))
+        })?;
+    }
+    Ok(out)
+}
+
+fn parse_cbor_field_attrs(attrs: &[Attribute]) -> syn::Result<CborFieldAttr> {
     let mut out = CborFieldAttr::default();
+    let mut with_span = None;
     for attr in attrs {
         if !attr.path().is_ident("cbor") {
             continue;
@@ -61,8 +112,18 @@ pub(crate) fn parse_cbor_field_attrs(attrs: &[Attribute]) -> syn::Result<CborFie
                 out.rename = Some(lit);
                 return Ok(());
             }
-            Err(meta
-                .error("unsupported `cbor(...)` field attribute (allowed: rename, skip, default)"))
+            if meta.path.is_ident("with") {
+                if out.with.is_some() {
+                    return Err(meta.error("duplicate `cbor(with=...)`"));
+                }
+                let lit: LitStr = meta.value()?.parse()?;
+                out.with = Some(lit.parse()?);
+                with_span = Some(meta.path.span());
+                return Ok(());
+            }
+            Err(meta.error(
+                "unsupported `cbor(...)` field attribute (allowed: rename, skip, default, with)",
+            ))
         })?;
     }
 
@@ -73,6 +134,24 @@ pub(crate) fn parse_cbor_field_attrs(attrs: &[Attribute]) -> syn::Result<CborFie
         ));
     }
 
+    if let (true, Some(span)) = (out.skip, with_span) {
+        return Err(syn::Error::new(
+            span,
+            "`cbor(skip)` cannot be combined with `with`: skipped fields are never encoded or decoded",
+        ));
+    }
+
+    Ok(out)
+}
+
+/// Parse a named field's attributes. `PhantomData` fields without other attributes are
+/// implicitly skipped: they carry no data and are absent on the wire.
+pub(crate) fn parse_named_field_attrs(field: &Field) -> syn::Result<CborFieldAttr> {
+    let mut out = parse_cbor_field_attrs(&field.attrs)?;
+    if is_phantom_data_type(&field.ty) && out.rename.is_none() && !out.default && out.with.is_none()
+    {
+        out.skip = true;
+    }
     Ok(out)
 }
 
@@ -91,20 +170,124 @@ pub(crate) fn parse_cbor_variant_attrs(attrs: &[Attribute]) -> syn::Result<CborV
                 out.rename = Some(lit);
                 return Ok(());
             }
+            if meta.path.is_ident("other") {
+                if out.other {
+                    return Err(meta.error("duplicate `cbor(other)` on variant"));
+                }
+                out.other = true;
+                return Ok(());
+            }
             if meta.path.is_ident("skip") || meta.path.is_ident("default") {
                 return Err(
                     meta.error("`cbor(skip)` / `cbor(default)` are not valid on enum variants")
                 );
             }
-            Err(meta.error("unsupported `cbor(...)` variant attribute (allowed: rename)"))
+            Err(meta.error("unsupported `cbor(...)` variant attribute (allowed: rename, other)"))
         })?;
     }
+    if let (true, Some(rename)) = (out.other, &out.rename) {
+        return Err(syn::Error::new(
+            rename.span(),
+            "`cbor(other)` cannot be combined with `rename`: the catch-all has no name of its own",
+        ));
+    }
     Ok(out)
 }
 
-pub(crate) fn parse_cbor_enum_attrs(attrs: &[Attribute]) -> syn::Result<EnumTagging> {
+/// Check the shape of a `#[cbor(other)]` variant: a unit variant, which discards the
+/// unknown entry, or a `(name, payload)` tuple variant, which keeps it.
+pub(crate) fn check_other_variant(variant: &Variant) -> syn::Result<()> {
+    match &variant.fields {
+        Fields::Unit => Ok(()),
+        Fields::Unnamed(fields) if fields.unnamed.len() == 2 => {
+            for field in &fields.unnamed {
+                ensure_no_cbor_attrs(&field.attrs, "`cbor(other)` variant fields")?;
+            }
+            Ok(())
+        }
+        _ => Err(syn::Error::new(
+            variant.span(),
+            "`cbor(other)` requires a unit variant or a `(name, payload)` tuple variant",
+        )),
+    }
+}
+
+pub(crate) fn parse_cbor_struct_attrs(
+    attrs: &[Attribute],
+    data: &DataStruct,
+) -> syn::Result<CborStructAttr> {
+    let mut out = CborStructAttr::default();
+    let mut deny_span = None;
+    for attr in attrs {
+        if !attr.path().is_ident("cbor") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("deny_unknown_keys") {
+                if out.deny_unknown_keys {
+                    return Err(meta.error("duplicate `cbor(deny_unknown_keys)`"));
+                }
+                out.deny_unknown_keys = true;
+                deny_span = Some(meta.path.span());
+                return Ok(());
+            }
+            if meta.path.is_ident("transparent") {
+                if out.transparent {
+                    return Err(meta.error("duplicate `cbor(transparent)`"));
+                }
+                out.transparent = true;
+                return Ok(());
+            }
+            Err(meta.error(
+                "unsupported `cbor(...)` struct attribute (allowed: deny_unknown_keys, transparent)",
+            ))
+        })?;
+    }
+
+    if out.transparent && out.deny_unknown_keys {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`cbor(transparent)` cannot be combined with `deny_unknown_keys`: there is no map",
+        ));
+    }
+
+    if let Some(span) = deny_span {
+        if !matches!(data.fields, Fields::Named(_)) {
+            return Err(syn::Error::new(
+                span,
+                "`cbor(deny_unknown_keys)` requires named fields: other structs do not decode from a map",
+            ));
+        }
+    }
+
+    Ok(out)
+}
+
+/// The single field of a `#[cbor(transparent)]` struct, as a member expression and field.
+pub(crate) fn transparent_field(data: &DataStruct) -> syn::Result<(Member, &Field)> {
+    let mut fields = data.fields.iter();
+    let (Some(field), None) = (fields.next(), fields.next()) else {
+        return Err(syn::Error::new(
+            data.struct_token.span(),
+            "`cbor(transparent)` requires a struct with exactly one field",
+        ));
+    };
+    ensure_no_cbor_attrs(&field.attrs, "fields of a transparent struct")?;
+    let member = field
+        .ident
+        .clone()
+        .map_or_else(|| Member::Unnamed(Index::from(0)), Member::Named);
+    Ok((member, field))
+}
+
+pub(crate) fn parse_cbor_enum_attrs(
+    attrs: &[Attribute],
+    data: &DataEnum,
+) -> syn::Result<CborEnumAttr> {
     let mut seen_tagged = false;
     let mut seen_untagged = false;
+    let mut tag_hint = false;
+    let mut deny_span = None;
 
     for attr in attrs {
         if !attr.path().is_ident("cbor") {
@@ -125,7 +308,23 @@ pub(crate) fn parse_cbor_enum_attrs(attrs: &[Attribute]) -> syn::Result<EnumTagg
                 seen_tagged = true;
                 return Ok(());
             }
-            Err(meta.error("unsupported `cbor(...)` enum attribute (allowed: tagged, untagged)"))
+            if meta.path.is_ident("tag_hint") {
+                if tag_hint {
+                    return Err(meta.error("duplicate `cbor(tag_hint)`"));
+                }
+                tag_hint = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("deny_unknown_keys") {
+                if deny_span.is_some() {
+                    return Err(meta.error("duplicate `cbor(deny_unknown_keys)`"));
+                }
+                deny_span = Some(meta.path.span());
+                return Ok(());
+            }
+            Err(meta.error(
+                "unsupported `cbor(...)` enum attribute (allowed: tagged, untagged, tag_hint, deny_unknown_keys)",
+            ))
         })?;
     }
 
@@ -135,10 +334,32 @@ pub(crate) fn parse_cbor_enum_attrs(attrs: &[Attribute]) -> syn::Result<EnumTagg
             "cbor enum cannot be both tagged and untagged",
         ));
     }
+    if seen_untagged && tag_hint {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`cbor(tag_hint)` only applies to tagged enums",
+        ));
+    }
+    if let Some(span) = deny_span {
+        if !data
+            .variants
+            .iter()
+            .any(|v| matches!(v.fields, Fields::Named(_)))
+        {
+            return Err(syn::Error::new(
+                span,
+                "`cbor(deny_unknown_keys)` on an enum requires at least one struct variant",
+            ));
+        }
+    }
 
-    Ok(if seen_untagged {
-        EnumTagging::Untagged
-    } else {
-        EnumTagging::Tagged
+    Ok(CborEnumAttr {
+        tagging: if seen_untagged {
+            EnumTagging::Untagged
+        } else {
+            EnumTagging::Tagged
+        },
+        tag_hint,
+        deny_unknown_keys: deny_span.is_some(),
     })
 }
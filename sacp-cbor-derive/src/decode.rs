@@ -4,19 +4,27 @@ use syn::{
     LifetimeParam, LitStr,
 };
 
-use crate::attrs::{ensure_no_cbor_attrs, parse_cbor_field_attrs, parse_cbor_variant_attrs};
-use crate::types::{is_option_type, type_kind, type_mentions_self, VariantKind};
+use crate::attrs::{
+    check_other_variant, ensure_no_cbor_attrs, parse_cbor_kind_attr, parse_cbor_variant_attrs,
+    parse_named_field_attrs, transparent_field, CborEnumAttr, CborStructAttr,
+};
+use crate::types::{
+    is_option_type, is_phantom_data_type, type_kind, type_mentions_self, VariantKind,
+};
 use crate::util::add_where_bound;
 
+/// Returns the field variables, their decode statements, and the array length on the wire
+/// (`PhantomData` fields are not encoded).
 fn tuple_decode_parts(
     name: &Ident,
     fields: &syn::FieldsUnnamed,
     wc: &mut syn::WhereClause,
     decode_lt: &Lifetime,
     ctx: &str,
-) -> syn::Result<(Vec<Ident>, Vec<proc_macro2::TokenStream>)> {
+) -> syn::Result<(Vec<Ident>, Vec<proc_macro2::TokenStream>, usize)> {
     let mut vars = Vec::new();
     let mut decodes = Vec::new();
+    let mut expected = 0usize;
 
     for (idx, field) in fields.unnamed.iter().enumerate() {
         ensure_no_cbor_attrs(&field.attrs, ctx)?;
@@ -24,6 +32,11 @@ fn tuple_decode_parts(
         let var = format_ident!("v{idx}");
         vars.push(var.clone());
 
+        if is_phantom_data_type(&field.ty) {
+            decodes.push(quote! { let #var = ::core::marker::PhantomData; });
+            continue;
+        }
+        expected += 1;
         if !type_mentions_self(&field.ty, name) {
             add_where_bound(wc, &field.ty, quote!(::sacp_cbor::CborDecode<#decode_lt>));
         }
@@ -40,7 +53,7 @@ fn tuple_decode_parts(
         });
     }
 
-    Ok((vars, decodes))
+    Ok((vars, decodes, expected))
 }
 
 fn add_decode_bounds_for_named_fields(
@@ -50,7 +63,7 @@ fn add_decode_bounds_for_named_fields(
     decode_lt: &Lifetime,
 ) -> syn::Result<()> {
     for field in &fields.named {
-        let attr = parse_cbor_field_attrs(&field.attrs)?;
+        let attr = parse_named_field_attrs(field)?;
         if attr.skip {
             add_where_bound(wc, &field.ty, quote!(::core::default::Default));
             continue;
@@ -58,7 +71,7 @@ fn add_decode_bounds_for_named_fields(
         if is_option_type(&field.ty) || attr.default {
             add_where_bound(wc, &field.ty, quote!(::core::default::Default));
         }
-        if !type_mentions_self(&field.ty, name) {
+        if attr.with.is_none() && !type_mentions_self(&field.ty, name) {
             add_where_bound(wc, &field.ty, quote!(::sacp_cbor::CborDecode<#decode_lt>));
         }
     }
@@ -72,14 +85,7 @@ fn array_decode_block(
 ) -> proc_macro2::TokenStream {
     quote! {
         let arr_off = decoder.position();
-        let mut array = decoder.array()?;
-        let arr_len = array.remaining();
-        if arr_len != #expected {
-            return Err(::sacp_cbor::CborError::new(
-                ::sacp_cbor::ErrorCode::ArrayLenMismatch,
-                arr_off,
-            ));
-        }
+        let mut array = decoder.array_exact(#expected)?;
         #(#decodes)*
         #result
     }
@@ -114,13 +120,14 @@ fn decode_lifetime(generics: &Generics) -> (Generics, Lifetime) {
 fn decode_named_fields(
     fields: &syn::FieldsNamed,
     target: proc_macro2::TokenStream,
+    deny_unknown_keys: bool,
 ) -> syn::Result<proc_macro2::TokenStream> {
     let mut inits = Vec::new();
     let mut matches = Vec::new();
     let mut finals = Vec::new();
 
     for field in &fields.named {
-        let attr = parse_cbor_field_attrs(&field.attrs)?;
+        let attr = parse_named_field_attrs(field)?;
         let ident = field.ident.as_ref().unwrap();
         let ty = &field.ty;
 
@@ -138,9 +145,16 @@ fn decode_named_fields(
             quote! { let mut #var: ::core::option::Option<#ty> = ::core::option::Option::None; },
         );
 
+        let value_ts = if let Some(with) = &attr.with {
+            quote!(map.decode_value(|d| #with::decode(d)))
+        } else {
+            quote!(map.next_value())
+        };
         matches.push(quote! {
             #key => {
-                #var = ::core::option::Option::Some(map.next_value()?);
+                #var = ::core::option::Option::Some(
+                    #value_ts.map_err(|err| err.__field_context(#key))?,
+                );
             }
         });
 
@@ -151,20 +165,39 @@ fn decode_named_fields(
             finals.push(quote! {
                 #ident: #var.ok_or_else(|| {
                     ::sacp_cbor::CborError::new(::sacp_cbor::ErrorCode::MissingKey, map_off)
+                        .with_context(#key)
                 })?,
             });
         }
     }
 
+    let unknown = if deny_unknown_keys {
+        quote! {
+            return Err(::sacp_cbor::CborError::new(
+                ::sacp_cbor::ErrorCode::UnknownKey,
+                key_off,
+            ));
+        }
+    } else {
+        quote! {
+            let _unused: ::sacp_cbor::CborValueRef = map.next_value()?;
+        }
+    };
+
     Ok(quote! {
         let map_off = decoder.position();
         let mut map = decoder.map()?;
         #(#inits)*
-        while let ::core::option::Option::Some(k) = map.next_key()? {
+        loop {
+            let key_off = map.position();
+            let k = match map.next_key()? {
+                ::core::option::Option::Some(k) => k,
+                ::core::option::Option::None => break,
+            };
             match k {
                 #(#matches)*
                 _ => {
-                    let _unused: ::sacp_cbor::CborValueRef = map.next_value()?;
+                    #unknown
                 }
             }
         }
@@ -176,6 +209,7 @@ pub(crate) fn decode_struct(
     name: &Ident,
     generics: &Generics,
     data: &DataStruct,
+    attrs: CborStructAttr,
 ) -> syn::Result<proc_macro2::TokenStream> {
     let (impl_generics2, decode_lt) = decode_lifetime(generics);
     let (impl_generics, _, where_clause) = impl_generics2.split_for_impl();
@@ -187,10 +221,24 @@ pub(crate) fn decode_struct(
         predicates: Default::default(),
     });
 
+    if attrs.transparent {
+        let (member, field) = transparent_field(data)?;
+        if !type_mentions_self(&field.ty, name) {
+            add_where_bound(wc, &field.ty, quote!(::sacp_cbor::CborDecode<#decode_lt>));
+        }
+        return Ok(quote! {
+            impl #impl_generics ::sacp_cbor::CborDecode<#decode_lt> for #name #ty_generics #where_clause {
+                fn decode<const CHECKED: bool>(decoder: &mut ::sacp_cbor::Decoder<#decode_lt, CHECKED>) -> Result<Self, ::sacp_cbor::CborError> {
+                    Ok(Self { #member: ::sacp_cbor::CborDecode::decode(decoder)? })
+                }
+            }
+        });
+    }
+
     match &data.fields {
         Fields::Named(fields) => {
             add_decode_bounds_for_named_fields(name, fields, wc, &decode_lt)?;
-            let body = decode_named_fields(fields, quote!(Self))?;
+            let body = decode_named_fields(fields, quote!(Self), attrs.deny_unknown_keys)?;
             Ok(quote! {
                 impl #impl_generics ::sacp_cbor::CborDecode<#decode_lt> for #name #ty_generics #where_clause {
                     fn decode<const CHECKED: bool>(decoder: &mut ::sacp_cbor::Decoder<#decode_lt, CHECKED>) -> Result<Self, ::sacp_cbor::CborError> {
@@ -201,9 +249,8 @@ pub(crate) fn decode_struct(
         }
 
         Fields::Unnamed(fields) => {
-            let (vars, decodes) =
+            let (vars, decodes, expected) =
                 tuple_decode_parts(name, fields, wc, &decode_lt, "tuple struct fields")?;
-            let expected = vars.len();
             let body = array_decode_block(expected, &decodes, quote!(Ok(Self(#(#vars),*))));
             Ok(quote! {
                 impl #impl_generics ::sacp_cbor::CborDecode<#decode_lt> for #name #ty_generics #where_clause {
@@ -229,6 +276,7 @@ pub(crate) fn decode_enum(
     name: &Ident,
     generics: &Generics,
     data: &DataEnum,
+    attrs: CborEnumAttr,
 ) -> syn::Result<proc_macro2::TokenStream> {
     let (impl_generics2, decode_lt) = decode_lifetime(generics);
     let (impl_generics, _, where_clause) = impl_generics2.split_for_impl();
@@ -241,13 +289,42 @@ pub(crate) fn decode_enum(
     });
 
     let mut arms = Vec::new();
+    let mut fallback = None;
 
     for variant in &data.variants {
         let v_attr = parse_cbor_variant_attrs(&variant.attrs)?;
+        let ident = &variant.ident;
+        if v_attr.other {
+            if fallback.is_some() {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    "only one variant may be marked `cbor(other)`",
+                ));
+            }
+            check_other_variant(variant)?;
+            fallback = Some(if let Fields::Unit = variant.fields {
+                quote! {
+                    map.decode_value(|decoder| {
+                        decoder.skip_value()?;
+                        Ok(Self::#ident)
+                    })
+                }
+            } else {
+                quote! {
+                    map.decode_value(|decoder| {
+                        Ok(Self::#ident(
+                            ::core::convert::From::from(k),
+                            ::sacp_cbor::CborDecode::decode(decoder)?,
+                        ))
+                    })
+                }
+            });
+            // Unknown names, the catch-all's own included, all land in the fallback.
+            continue;
+        }
         let vname = v_attr
             .rename
             .unwrap_or_else(|| LitStr::new(&variant.ident.to_string(), variant.ident.span()));
-        let ident = &variant.ident;
 
         match &variant.fields {
             Fields::Unit => {
@@ -260,9 +337,8 @@ pub(crate) fn decode_enum(
             }
 
             Fields::Unnamed(fields) => {
-                let (vars, decodes) =
+                let (vars, decodes, expected) =
                     tuple_decode_parts(name, fields, wc, &decode_lt, "tuple enum variant fields")?;
-                let expected = vars.len();
                 let body =
                     array_decode_block(expected, &decodes, quote!(Ok(Self::#ident(#(#vars),*))));
                 arms.push(quote! {
@@ -274,23 +350,40 @@ pub(crate) fn decode_enum(
 
             Fields::Named(fields) => {
                 add_decode_bounds_for_named_fields(name, fields, wc, &decode_lt)?;
-                let body = decode_named_fields(fields, quote!(Self::#ident))?;
+                let body =
+                    decode_named_fields(fields, quote!(Self::#ident), attrs.deny_unknown_keys)?;
                 arms.push(quote! { #vname => map.decode_value(|decoder| { #body }) });
             }
         }
     }
 
+    let fallback = fallback.unwrap_or_else(|| {
+        quote! {
+            Err(::sacp_cbor::CborError::new(
+                ::sacp_cbor::ErrorCode::UnknownEnumVariant,
+                map_off,
+            ))
+        }
+    });
+
+    // Reject anything but a map before reading a header, reporting the enum's own offset.
+    let hint = attrs.tag_hint.then(|| {
+        quote! {
+            if decoder.peek_kind()? != ::sacp_cbor::CborKind::Map {
+                return Err(::sacp_cbor::CborError::new(
+                    ::sacp_cbor::ErrorCode::ExpectedEnum,
+                    map_off,
+                ));
+            }
+        }
+    });
+
     Ok(quote! {
         impl #impl_generics ::sacp_cbor::CborDecode<#decode_lt> for #name #ty_generics #where_clause {
             fn decode<const CHECKED: bool>(decoder: &mut ::sacp_cbor::Decoder<#decode_lt, CHECKED>) -> Result<Self, ::sacp_cbor::CborError> {
                 let map_off = decoder.position();
-                let mut map = decoder.map()?;
-                if map.remaining() != 1 {
-                    return Err(::sacp_cbor::CborError::new(
-                        ::sacp_cbor::ErrorCode::MapLenMismatch,
-                        map_off,
-                    ));
-                }
+                #hint
+                let mut map = decoder.map_exact(1)?;
                 let k = match map.next_key()? {
                     ::core::option::Option::Some(key) => key,
                     ::core::option::Option::None => {
@@ -302,10 +395,7 @@ pub(crate) fn decode_enum(
                 };
                 let result = match k {
                     #(#arms),*,
-                    _ => Err(::sacp_cbor::CborError::new(
-                        ::sacp_cbor::ErrorCode::UnknownEnumVariant,
-                        map_off,
-                    )),
+                    _ => #fallback,
                 };
                 result
             }
@@ -317,6 +407,7 @@ pub(crate) fn decode_enum_untagged(
     name: &Ident,
     generics: &Generics,
     data: &DataEnum,
+    deny_unknown_keys: bool,
 ) -> syn::Result<proc_macro2::TokenStream> {
     let (impl_generics2, decode_lt) = decode_lifetime(generics);
     let (impl_generics, _, where_clause) = impl_generics2.split_for_impl();
@@ -338,6 +429,12 @@ pub(crate) fn decode_enum_untagged(
                 "variant `cbor(rename=...)` is meaningless for `#[cbor(untagged)]` enums",
             ));
         }
+        if v_attr.other {
+            return Err(syn::Error::new(
+                variant.span(),
+                "`cbor(other)` only applies to tagged enums",
+            ));
+        }
 
         let ident = &variant.ident;
         let kind = match &variant.fields {
@@ -346,13 +443,16 @@ pub(crate) fn decode_enum_untagged(
             Fields::Unnamed(fields) => {
                 if fields.unnamed.len() == 1 {
                     let field = fields.unnamed.first().unwrap();
-                    ensure_no_cbor_attrs(&field.attrs, "tuple enum variant fields")?;
-                    type_kind(&field.ty).ok_or_else(|| {
-                        syn::Error::new(
-                            field.span(),
-                            "untagged enum single-field variants must map to a concrete CBOR kind",
-                        )
-                    })?
+                    match parse_cbor_kind_attr(&field.attrs)? {
+                        Some(kind) => kind,
+                        None => type_kind(&field.ty).ok_or_else(|| {
+                            syn::Error::new(
+                                field.span(),
+                                "untagged enum single-field variants must map to a concrete CBOR kind \
+                                 (declare one with `#[cbor(kind = \"...\")]`)",
+                            )
+                        })?,
+                    }
                 } else {
                     VariantKind::Array
                 }
@@ -385,21 +485,20 @@ pub(crate) fn decode_enum_untagged(
                         Ok(Self::#ident(::sacp_cbor::CborDecode::decode(decoder)?))
                     }
                 } else {
-                    let (vars, decodes) = tuple_decode_parts(
+                    let (vars, decodes, expected) = tuple_decode_parts(
                         name,
                         fields,
                         wc,
                         &decode_lt,
                         "tuple enum variant fields",
                     )?;
-                    let expected = vars.len();
                     array_decode_block(expected, &decodes, quote!(Ok(Self::#ident(#(#vars),*))))
                 }
             }
 
             Fields::Named(fields) => {
                 add_decode_bounds_for_named_fields(name, fields, wc, &decode_lt)?;
-                decode_named_fields(fields, quote!(Self::#ident))?
+                decode_named_fields(fields, quote!(Self::#ident), deny_unknown_keys)?
             }
         };
 
@@ -1,8 +1,11 @@
 use quote::{format_ident, quote};
 use syn::{spanned::Spanned, DataEnum, DataStruct, Fields, Generics, Ident, LitStr, Type};
 
-use crate::attrs::{ensure_no_cbor_attrs, parse_cbor_field_attrs, parse_cbor_variant_attrs};
-use crate::types::type_mentions_self;
+use crate::attrs::{
+    check_other_variant, ensure_no_cbor_attrs, parse_cbor_kind_attr, parse_cbor_variant_attrs,
+    parse_named_field_attrs, transparent_field, CborStructAttr,
+};
+use crate::types::{is_phantom_data_type, type_mentions_self};
 use crate::util::add_where_bound;
 
 fn named_entries_with_pats<'a, F>(
@@ -23,7 +26,7 @@ where
     }
 
     for field in &fields.named {
-        let attr = parse_cbor_field_attrs(&field.attrs)?;
+        let attr = parse_named_field_attrs(field)?;
         let f_ident = field.ident.as_ref().unwrap();
         pats.push(f_ident.clone());
 
@@ -35,16 +38,21 @@ where
             .rename
             .unwrap_or_else(|| LitStr::new(&f_ident.to_string(), f_ident.span()));
 
-        if !type_mentions_self(&field.ty, name) {
-            bounds.push(&field.ty);
-        }
-
         let value_ts = value(f_ident);
+        let encode_ts = if let Some(with) = &attr.with {
+            quote!(#with::encode(#value_ts, enc))
+        } else {
+            if !type_mentions_self(&field.ty, name) {
+                bounds.push(&field.ty);
+            }
+            quote!(::sacp_cbor::CborEncode::encode(#value_ts, enc))
+        };
+
         let key_bytes = key.value().into_bytes();
         entries.push(NamedEntry {
             key_bytes,
             entry: quote! {
-                m.entry(#key, |enc| ::sacp_cbor::CborEncode::encode(#value_ts, enc))?;
+                m.entry(#key, |enc| #encode_ts)?;
             },
         });
     }
@@ -71,6 +79,11 @@ fn tuple_variant_parts<'a>(
 
     for (idx, field) in fields.unnamed.iter().enumerate() {
         ensure_no_cbor_attrs(&field.attrs, "tuple enum variant fields")?;
+        if is_phantom_data_type(&field.ty) {
+            // Bound but never encoded; the underscore keeps the binding warning-free.
+            pats.push(format_ident!("_v{idx}"));
+            continue;
+        }
         let var = format_ident!("v{idx}");
         pats.push(var.clone());
 
@@ -87,10 +100,32 @@ pub(crate) fn encode_struct(
     name: &Ident,
     generics: &Generics,
     data: &DataStruct,
+    attrs: CborStructAttr,
 ) -> syn::Result<proc_macro2::TokenStream> {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let base_where_clause = where_clause;
 
+    if attrs.transparent {
+        let (member, field) = transparent_field(data)?;
+        let mut encode_where_clause = base_where_clause.cloned();
+        if !type_mentions_self(&field.ty, name) {
+            let wc = encode_where_clause.get_or_insert_with(|| syn::WhereClause {
+                where_token: Default::default(),
+                predicates: Default::default(),
+            });
+            add_where_bound(wc, &field.ty, quote!(::sacp_cbor::CborEncode));
+        }
+        return Ok(quote! {
+            impl #impl_generics ::sacp_cbor::CborEncode for #name #ty_generics #encode_where_clause {
+                fn encode(&self, enc: &mut ::sacp_cbor::Encoder) -> Result<(), ::sacp_cbor::CborError> {
+                    ::sacp_cbor::CborEncode::encode(&self.#member, enc)
+                }
+            }
+
+            impl #impl_generics ::sacp_cbor::CborArrayElem for #name #ty_generics #encode_where_clause {}
+        });
+    }
+
     match &data.fields {
         Fields::Named(fields) => {
             let mut bounds = Vec::new();
@@ -132,6 +167,9 @@ pub(crate) fn encode_struct(
                 ensure_no_cbor_attrs(&field.attrs, "tuple struct fields")?;
                 let index = syn::Index::from(idx);
 
+                if is_phantom_data_type(&field.ty) {
+                    continue;
+                }
                 if !type_mentions_self(&field.ty, name) {
                     bounds.push(&field.ty);
                 }
@@ -195,6 +233,33 @@ pub(crate) fn encode_enum(
             .unwrap_or_else(|| LitStr::new(&variant.ident.to_string(), variant.ident.span()));
         let ident = &variant.ident;
 
+        if v_attr.other {
+            check_other_variant(variant)?;
+            if let Fields::Unnamed(fields) = &variant.fields {
+                let payload_ty = &fields.unnamed[1].ty;
+                if !type_mentions_self(payload_ty, name) {
+                    bounds.push(payload_ty);
+                }
+                arms.push(quote! {
+                    Self::#ident(name, payload) => enc.map(1, |m| {
+                        m.entry(::core::convert::AsRef::<str>::as_ref(name), |enc| {
+                            ::sacp_cbor::CborEncode::encode(payload, enc)
+                        })?;
+                        Ok(())
+                    })
+                });
+            } else {
+                // The unknown name was discarded on decode, so there is nothing to write.
+                arms.push(quote! {
+                    Self::#ident => Err(::sacp_cbor::CborError::new(
+                        ::sacp_cbor::ErrorCode::UnknownEnumVariant,
+                        enc.len(),
+                    ))
+                });
+            }
+            continue;
+        }
+
         match &variant.fields {
             Fields::Unit => {
                 arms.push(quote! {
@@ -283,6 +348,12 @@ pub(crate) fn encode_enum_untagged(
                 "variant `cbor(rename=...)` is meaningless for `#[cbor(untagged)]` enums",
             ));
         }
+        if v_attr.other {
+            return Err(syn::Error::new(
+                variant.span(),
+                "`cbor(other)` only applies to tagged enums",
+            ));
+        }
 
         let ident = &variant.ident;
         match &variant.fields {
@@ -294,7 +365,8 @@ pub(crate) fn encode_enum_untagged(
                 let n = fields.unnamed.len();
                 if n == 1 {
                     let field = fields.unnamed.first().unwrap();
-                    ensure_no_cbor_attrs(&field.attrs, "tuple enum variant fields")?;
+                    // `kind` only affects decoding dispatch; validate it here as well.
+                    parse_cbor_kind_attr(&field.attrs)?;
                     if !type_mentions_self(&field.ty, name) {
                         bounds.push(&field.ty);
                     }
@@ -305,8 +377,9 @@ pub(crate) fn encode_enum_untagged(
                 } else {
                     let (pats, items) = tuple_variant_parts(name, fields, &mut bounds)?;
 
+                    let len = items.len();
                     arms.push(quote! {
-                        Self::#ident( #(#pats),* ) => enc.array(#n, |a| {
+                        Self::#ident( #(#pats),* ) => enc.array(#len, |a| {
                             #(#items)*
                             Ok(())
                         })
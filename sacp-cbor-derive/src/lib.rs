@@ -15,7 +15,7 @@ mod util;
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput};
 
-use crate::attrs::{parse_cbor_enum_attrs, EnumTagging};
+use crate::attrs::{parse_cbor_enum_attrs, parse_cbor_struct_attrs, EnumTagging};
 use crate::cbor_bytes::expand as expand_cbor_bytes;
 use crate::decode::{decode_enum, decode_enum_untagged, decode_struct};
 use crate::encode::{encode_enum, encode_enum_untagged, encode_struct};
@@ -26,10 +26,15 @@ pub fn derive_cbor_encode(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let out = (|| -> syn::Result<proc_macro2::TokenStream> {
         match &input.data {
-            Data::Struct(data) => encode_struct(&input.ident, &input.generics, data),
+            Data::Struct(data) => {
+                // `deny_unknown_keys` only affects decoding, but reject typos here too.
+                let attrs = parse_cbor_struct_attrs(&input.attrs, data)?;
+                encode_struct(&input.ident, &input.generics, data, attrs)
+            }
             Data::Enum(data) => {
-                let tagging = parse_cbor_enum_attrs(&input.attrs)?;
-                match tagging {
+                // `tag_hint` and `deny_unknown_keys` only affect decoding.
+                let attrs = parse_cbor_enum_attrs(&input.attrs, data)?;
+                match attrs.tagging {
                     EnumTagging::Untagged => {
                         encode_enum_untagged(&input.ident, &input.generics, data)
                     }
@@ -55,14 +60,20 @@ pub fn derive_cbor_decode(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let out = (|| -> syn::Result<proc_macro2::TokenStream> {
         match &input.data {
-            Data::Struct(data) => decode_struct(&input.ident, &input.generics, data),
+            Data::Struct(data) => {
+                let attrs = parse_cbor_struct_attrs(&input.attrs, data)?;
+                decode_struct(&input.ident, &input.generics, data, attrs)
+            }
             Data::Enum(data) => {
-                let tagging = parse_cbor_enum_attrs(&input.attrs)?;
-                match tagging {
-                    EnumTagging::Untagged => {
-                        decode_enum_untagged(&input.ident, &input.generics, data)
-                    }
-                    EnumTagging::Tagged => decode_enum(&input.ident, &input.generics, data),
+                let attrs = parse_cbor_enum_attrs(&input.attrs, data)?;
+                match attrs.tagging {
+                    EnumTagging::Untagged => decode_enum_untagged(
+                        &input.ident,
+                        &input.generics,
+                        data,
+                        attrs.deny_unknown_keys,
+                    ),
+                    EnumTagging::Tagged => decode_enum(&input.ident, &input.generics, data, attrs),
                 }
             }
             Data::Union(u) => Err(syn::Error::new(
@@ -39,6 +39,20 @@ impl VariantKind {
         }
     }
 
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => VariantKind::Null,
+            "bool" => VariantKind::Bool,
+            "integer" => VariantKind::Integer,
+            "float" => VariantKind::Float,
+            "bytes" => VariantKind::Bytes,
+            "text" => VariantKind::Text,
+            "array" => VariantKind::Array,
+            "map" => VariantKind::Map,
+            _ => return None,
+        })
+    }
+
     pub(crate) fn to_cbor_kind_ts(self) -> TokenStream {
         match self {
             VariantKind::Null => quote!(::sacp_cbor::CborKind::Null),
@@ -61,6 +75,14 @@ pub(crate) fn is_option_type(ty: &Type) -> bool {
     seg.ident == "Option"
 }
 
+pub(crate) fn is_phantom_data_type(ty: &Type) -> bool {
+    let Type::Path(tp) = ty else { return false };
+    let Some(seg) = tp.path.segments.last() else {
+        return false;
+    };
+    seg.ident == "PhantomData"
+}
+
 pub(crate) fn vec_inner_type(ty: &Type) -> Option<&Type> {
     let Type::Path(tp) = ty else { return None };
     let seg = tp.path.segments.last()?;
@@ -0,0 +1,110 @@
+//! `arbitrary::Arbitrary` for [`CanonicalCbor`], for round-trip fuzzing downstream.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::codec::{encode_to_canonical, CborArrayElem, CborEncode};
+use crate::encode::Encoder;
+use crate::profile::{MAX_SAFE_INTEGER_I64, MIN_SAFE_INTEGER};
+use crate::scalar::F64Bits;
+use crate::{CanonicalCbor, CborError};
+
+/// Nesting levels below the root that generated items may use.
+const MAX_DEPTH: usize = 8;
+/// Upper bound on generated array and map lengths.
+const MAX_LEN: usize = 8;
+
+/// A generated item. Every variant is constructed profile-valid, so encoding cannot fail.
+enum Node {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Wide(i128),
+    Bignum(bool, Vec<u8>),
+    Float(f64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Self>),
+    Map(BTreeMap<String, Self>),
+}
+
+impl Node {
+    fn arbitrary(u: &mut Unstructured<'_>, depth: usize) -> arbitrary::Result<Self> {
+        // Exhausted input yields 0, so generation bottoms out in `Null`.
+        let last = if depth == 0 { 7 } else { 9 };
+        Ok(match u.int_in_range(0..=last)? {
+            0 => Self::Null,
+            1 => Self::Bool(u.arbitrary()?),
+            2 => Self::Int(u.int_in_range(MIN_SAFE_INTEGER..=MAX_SAFE_INTEGER_I64)?),
+            // `int_i128` picks the integer or bignum form as the value requires.
+            3 => Self::Wide(u.arbitrary()?),
+            4 => {
+                // Eight or more bytes with a nonzero lead byte is canonical and above 2^53.
+                let len = u.int_in_range(8..=32)?;
+                let mut magnitude = u.bytes(len)?.to_vec();
+                magnitude[0] = magnitude[0].max(1);
+                Self::Bignum(u.arbitrary()?, magnitude)
+            }
+            5 => {
+                let value = f64::from_bits(u.arbitrary()?);
+                // `-0.0` is forbidden; NaN payloads are canonicalized when encoding.
+                Self::Float(if value == 0.0 { 0.0 } else { value })
+            }
+            6 => Self::Bytes(u.arbitrary()?),
+            7 => Self::Text(u.arbitrary()?),
+            8 => {
+                let len = u.int_in_range(0..=MAX_LEN)?;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(Self::arbitrary(u, depth - 1)?);
+                }
+                Self::Array(items)
+            }
+            _ => {
+                let len = u.int_in_range(0..=MAX_LEN)?;
+                let mut entries = BTreeMap::new();
+                for _ in 0..len {
+                    entries.insert(u.arbitrary()?, Self::arbitrary(u, depth - 1)?);
+                }
+                Self::Map(entries)
+            }
+        })
+    }
+}
+
+impl CborEncode for Node {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        match self {
+            Self::Null => enc.null(),
+            Self::Bool(v) => enc.bool(*v),
+            Self::Int(v) => enc.int(*v),
+            Self::Wide(v) => enc.int_i128(*v),
+            Self::Bignum(negative, magnitude) => enc.bignum(*negative, magnitude),
+            Self::Float(v) => enc.float(F64Bits::try_from_f64(*v)?),
+            Self::Bytes(v) => enc.bytes(v),
+            Self::Text(v) => enc.text(v),
+            Self::Array(items) => items.encode(enc),
+            // `BTreeMap<String, _>` encodes in canonical key order.
+            Self::Map(entries) => entries.encode(enc),
+        }
+    }
+}
+
+impl CborArrayElem for Node {}
+
+/// Generates a random, always profile-valid SACP-CBOR/1 item.
+///
+/// Output covers every kind: safe-range integers, bignums outside the range, float64 with
+/// canonical NaN and never `-0.0`, byte and text strings, and arrays and maps nested up to
+/// eight levels with canonically ordered text keys. It never produces invalid data, so it
+/// suits round-trip properties such as `decode(encode(v)) == v`; feed raw bytes to a
+/// separate target to explore the invalid space.
+impl<'a> Arbitrary<'a> for CanonicalCbor {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let node = Node::arbitrary(u, MAX_DEPTH)?;
+        encode_to_canonical(&node).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
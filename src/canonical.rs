@@ -53,6 +53,35 @@ impl<'a> CanonicalCborRef<'a> {
         self.bytes.is_empty()
     }
 
+    /// Re-run validation and report whether the bytes are a single canonical item.
+    ///
+    /// Every safe constructor already guarantees this, so the check is a safety net for
+    /// values built through `unsafe` constructors or crossing a trust boundary; untrusted
+    /// input must still go through [`crate::validate_canonical`].
+    #[must_use]
+    pub fn is_canonical(self, limits: DecodeLimits) -> bool {
+        crate::validate(self.bytes, limits).is_ok()
+    }
+
+    /// Panic in debug builds if the bytes are not a single canonical item. A no-op in release.
+    ///
+    /// Only structural and canonical-form rules are asserted: the bytes may have been
+    /// validated under any [`DecodeLimits`], so size caps are ignored.
+    ///
+    /// Called by [`crate::decode_canonical`] to catch mis-constructed values during development.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if the bytes break a structural or canonical-form rule.
+    #[inline]
+    pub fn debug_assert_canonical(self) {
+        if cfg!(debug_assertions) {
+            if let Err(err) = crate::validate(self.bytes, DecodeLimits::unlimited()) {
+                panic!("CanonicalCborRef does not hold canonical SACP-CBOR/1 bytes: {err}");
+            }
+        }
+    }
+
     /// Compute the SHA-256 digest of the canonical bytes.
     #[cfg(feature = "sha2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
@@ -98,6 +127,52 @@ impl AsRef<[u8]> for CanonicalCborRef<'_> {
     }
 }
 
+/// A data item validated by [`crate::validate_relaxed`] under the profile relaxations.
+///
+/// The item is well-formed and follows every SACP-CBOR/1 rule except those the limits
+/// relaxed: it may hold integers outside the safe range or tags from `extra_allowed_tags`.
+/// It is therefore not canonical, and unlike [`CanonicalCborRef`] it cannot be spliced
+/// into an [`crate::Encoder`] or copied into a [`CanonicalCbor`]. Query it through
+/// [`RelaxedCborRef::root`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelaxedCborRef<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> RelaxedCborRef<'a> {
+    #[inline]
+    pub(crate) const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Return the validated bytes.
+    #[inline]
+    #[must_use]
+    pub const fn as_bytes(self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Length in bytes of the item.
+    #[inline]
+    #[must_use]
+    pub const fn len(self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns `true` iff the item is empty (this never happens for a valid item).
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+impl AsRef<[u8]> for RelaxedCborRef<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
 /// A validated canonical CBOR-encoded text-string key.
 ///
 /// This wraps the exact canonical encoding bytes for a CBOR text string.
@@ -107,7 +182,6 @@ pub struct EncodedTextKey<'a> {
 }
 
 impl<'a> EncodedTextKey<'a> {
-    #[cfg(feature = "alloc")]
     #[inline]
     pub(crate) const fn new_unchecked(bytes: &'a [u8]) -> Self {
         Self { bytes }
@@ -130,6 +204,21 @@ impl<'a> EncodedTextKey<'a> {
         Ok(Self { bytes })
     }
 
+    /// Encode `key` once so it can be written many times with [`crate::MapEncoder::entry_raw_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `AllocationFailed` if the buffer cannot be allocated.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encode(key: &str) -> Result<OwnedEncodedKey, CborError> {
+        let mut enc = crate::Encoder::with_capacity(key.len().saturating_add(9));
+        enc.text(key)?;
+        Ok(OwnedEncodedKey {
+            bytes: enc.try_into_vec()?,
+        })
+    }
+
     /// Return the canonical encoded bytes.
     #[inline]
     #[must_use]
@@ -147,6 +236,34 @@ impl AsRef<[u8]> for EncodedTextKey<'_> {
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 
+/// An owned canonical text-key encoding, built by [`EncodedTextKey::encode`].
+///
+/// `Ord` compares the encoded bytes, which for canonical text keys is exactly canonical
+/// map-key order, so a sorted set of these can be streamed straight into a map.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OwnedEncodedKey {
+    bytes: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedEncodedKey {
+    /// Borrow as an [`EncodedTextKey`].
+    #[inline]
+    #[must_use]
+    pub fn as_key(&self) -> EncodedTextKey<'_> {
+        EncodedTextKey::new_unchecked(&self.bytes)
+    }
+
+    /// Return the canonical encoded bytes.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
 /// An owned canonical SACP-CBOR/1 data item.
 ///
 /// This type is useful for durable storage of canonical CBOR (e.g., protocol state).
@@ -191,6 +308,28 @@ impl CanonicalCbor {
         Ok(Self { bytes })
     }
 
+    /// Read one canonical data item from `reader` and validate it.
+    ///
+    /// Reading stops right after the item ends, so any bytes that follow (for example
+    /// the next message in a stream) are left unread. At most `limits.max_input_bytes`
+    /// bytes are consumed. The reader is read in small chunks; wrap unbuffered sources
+    /// in a [`std::io::BufReader`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `UnexpectedEof` if the stream ends mid-item, `IoError` for other read
+    /// failures, `MessageLenLimitExceeded` if the item would exceed the input limit, or
+    /// any validation error if the item is not canonical.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn from_reader<R: std::io::Read>(
+        reader: R,
+        limits: DecodeLimits,
+    ) -> Result<Self, CborError> {
+        let bytes = crate::reader::read_item(reader, limits)?;
+        Self::from_vec(bytes, limits)
+    }
+
     /// Validate and wrap an owned canonical CBOR buffer using default limits.
     ///
     /// # Errors
@@ -215,11 +354,34 @@ impl CanonicalCbor {
         CanonicalCborRef::new(self.as_bytes())
     }
 
+    /// Returns the heap footprint of this item in bytes, for budgeting caches of owned items.
+    ///
+    /// This is the capacity of the single backing buffer. It may exceed the encoded length when
+    /// the buffer came from [`Self::from_vec`] or an encoder with spare room. The struct itself
+    /// is not counted.
+    #[inline]
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    /// Clone the buffer, reporting allocation failure instead of aborting like `clone`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AllocationFailed` if the copy cannot be allocated.
+    pub fn try_clone(&self) -> Result<Self, CborError> {
+        self.as_ref().to_owned()
+    }
+
     /// Consume and return the canonical bytes.
+    ///
+    /// With the `zeroize` feature, this hands the buffer off without wiping it; the caller
+    /// becomes responsible for clearing it.
     #[inline]
     #[must_use]
-    pub fn into_bytes(self) -> Vec<u8> {
-        self.bytes
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        core::mem::take(&mut self.bytes)
     }
 
     /// Compute the SHA-256 digest of the canonical bytes.
@@ -231,6 +393,19 @@ impl CanonicalCbor {
     }
 }
 
+/// Wipes the backing buffer (including spare capacity) before it is freed.
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl Drop for CanonicalCbor {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.bytes);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl zeroize::ZeroizeOnDrop for CanonicalCbor {}
+
 #[cfg(feature = "alloc")]
 impl AsRef<[u8]> for CanonicalCbor {
     fn as_ref(&self) -> &[u8] {
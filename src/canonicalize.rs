@@ -0,0 +1,458 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::alloc_util::{try_reserve, try_vec_with_capacity};
+use crate::canonical::CanonicalCbor;
+use crate::codec::CborEncode;
+use crate::int::{encode_int, trim_be};
+use crate::profile::cmp_text_keys_canonical;
+use crate::scalar::F64Bits;
+use crate::utf8;
+use crate::{CborError, DecodeLimits, Encoder, ErrorCode};
+
+/// How [`canonicalize_with`] treats `-0.0`, which the profile forbids.
+///
+/// NaN handling does not depend on the policy: re-encoding a float as float64 always
+/// rewrites its bits to the canonical NaN. Validation and decoding ignore this and always
+/// reject `-0.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatPolicy {
+    /// Reject `-0.0` with `NegativeZeroForbidden`.
+    #[default]
+    Strict,
+    /// Rewrite `-0.0` to `+0.0`.
+    NormalizeNegativeZero,
+}
+
+/// Options for [`canonicalize_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct CanonicalizeOptions {
+    /// Treatment of `-0.0`. Defaults to [`FloatPolicy::Strict`].
+    pub float_policy: FloatPolicy,
+}
+
+impl CanonicalizeOptions {
+    /// The default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            float_policy: FloatPolicy::Strict,
+        }
+    }
+
+    /// Set [`CanonicalizeOptions::float_policy`].
+    #[must_use]
+    pub const fn with_float_policy(self, float_policy: FloatPolicy) -> Self {
+        Self {
+            float_policy,
+            ..self
+        }
+    }
+}
+
+/// Convert well-formed RFC 8949 CBOR into canonical SACP-CBOR/1 bytes.
+///
+/// This is a migration and interop aid for peers that emit valid but non-canonical CBOR.
+/// It is deliberately separate from (and much slower than) [`crate::validate_canonical`],
+/// which stays the strict fast path. The input is parsed permissively and re-emitted:
+///
+/// - integers of any width are minimized, and those outside the safe range become
+///   bignums (bignum magnitudes with leading zeros, or small enough to be safe, are
+///   normalized the same way);
+/// - indefinite-length strings are concatenated and indefinite-length arrays and maps
+///   become definite;
+/// - map keys are sorted into canonical order;
+/// - half- and single-precision floats are widened to float64, and NaNs are canonicalized.
+///
+/// Anything outside the data model is still rejected: non-text map keys, duplicate keys,
+/// tags other than 2 and 3, simple values other than `false`/`true`/`null`, `-0.0`, and
+/// invalid UTF-8. `limits` bounds the input as it does for validation. Use
+/// [`canonicalize_with`] to rewrite `-0.0` to `+0.0` instead of rejecting it.
+///
+/// # Errors
+///
+/// Returns the relevant `ErrorCode` at the input offset of the offending item,
+/// `TrailingBytes` if more than one item is present, or a limit or allocation error.
+pub fn canonicalize(input: &[u8], limits: DecodeLimits) -> Result<CanonicalCbor, CborError> {
+    canonicalize_with(input, limits, CanonicalizeOptions::new())
+}
+
+/// Like [`canonicalize`], with non-default [`CanonicalizeOptions`].
+///
+/// # Errors
+///
+/// As for [`canonicalize`].
+pub fn canonicalize_with(
+    input: &[u8],
+    limits: DecodeLimits,
+    options: CanonicalizeOptions,
+) -> Result<CanonicalCbor, CborError> {
+    if input.len() > limits.max_input_bytes {
+        return Err(CborError::new(ErrorCode::MessageLenLimitExceeded, 0));
+    }
+    let mut parser = Parser {
+        data: input,
+        pos: 0,
+        limits,
+        options,
+        items_seen: 0,
+    };
+    let node = parser.parse_value(0)?;
+    if parser.pos != input.len() {
+        return Err(CborError::new(ErrorCode::TrailingBytes, parser.pos));
+    }
+    let mut enc = Encoder::with_capacity(input.len());
+    node.encode(&mut enc)?;
+    enc.into_canonical()
+}
+
+/// An owned CBOR item, built by the permissive parsers here and in `editable.rs` and
+/// re-emitted canonically.
+pub enum Node {
+    Null,
+    Bool(bool),
+    Int {
+        negative: bool,
+        /// Big-endian CBOR argument `n` (the value is `n` or `-1 - n`), without leading zeros.
+        n: Vec<u8>,
+    },
+    Float(F64Bits),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Self>),
+    Map(Vec<(String, Self)>),
+}
+
+impl CborEncode for Node {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        match self {
+            Self::Null => enc.null(),
+            Self::Bool(v) => enc.bool(*v),
+            Self::Int { negative, n } => encode_int(enc, *negative, n),
+            Self::Float(bits) => enc.float(*bits),
+            Self::Bytes(b) => enc.bytes(b),
+            Self::Text(s) => enc.text(s),
+            Self::Array(items) => enc.array(items.len(), |a| {
+                for item in items {
+                    a.value(item)?;
+                }
+                Ok(())
+            }),
+            Self::Map(entries) => enc.map(entries.len(), |m| {
+                for (k, v) in entries {
+                    m.entry(k, |e| v.encode(e))?;
+                }
+                Ok(())
+            }),
+        }
+    }
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    limits: DecodeLimits,
+    options: CanonicalizeOptions,
+    items_seen: usize,
+}
+
+impl<'a> Parser<'a> {
+    const fn err(code: ErrorCode, off: usize) -> CborError {
+        CborError::new(code, off)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, CborError> {
+        let b = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| Self::err(ErrorCode::UnexpectedEof, self.pos))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn read_exact(&mut self, n: usize) -> Result<&'a [u8], CborError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| Self::err(ErrorCode::UnexpectedEof, self.pos))?;
+        let out = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    /// Read the argument for `ai`, accepting any width. Returns `None` for ai 31.
+    fn read_arg(&mut self, ai: u8, off: usize) -> Result<Option<u64>, CborError> {
+        let v = match ai {
+            0..=23 => u64::from(ai),
+            24 => u64::from(self.read_u8()?),
+            25 => {
+                let b = self.read_exact(2)?;
+                u64::from(u16::from_be_bytes([b[0], b[1]]))
+            }
+            26 => {
+                let b = self.read_exact(4)?;
+                u64::from(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            }
+            27 => {
+                let b = self.read_exact(8)?;
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(b);
+                u64::from_be_bytes(buf)
+            }
+            28..=30 => return Err(Self::err(ErrorCode::ReservedAdditionalInfo, off)),
+            _ => return Ok(None),
+        };
+        Ok(Some(v))
+    }
+
+    fn read_len(&mut self, ai: u8, off: usize) -> Result<Option<usize>, CborError> {
+        self.read_arg(ai, off)?
+            .map(|v| usize::try_from(v).map_err(|_| Self::err(ErrorCode::LengthOverflow, off)))
+            .transpose()
+    }
+
+    fn bump_items(&mut self, add: usize, off: usize) -> Result<(), CborError> {
+        self.items_seen = self
+            .items_seen
+            .checked_add(add)
+            .ok_or_else(|| Self::err(ErrorCode::LengthOverflow, off))?;
+        if self.items_seen > self.limits.max_total_items {
+            return Err(Self::err(ErrorCode::TotalItemsLimitExceeded, off));
+        }
+        Ok(())
+    }
+
+    /// Returns `true` and consumes the byte if the next item is a break (`0xff`).
+    fn at_break(&mut self) -> Result<bool, CborError> {
+        match self.data.get(self.pos) {
+            Some(0xff) => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(Self::err(ErrorCode::UnexpectedEof, self.pos)),
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Node, CborError> {
+        let off = self.pos;
+        let ib = self.read_u8()?;
+        let major = ib >> 5;
+        let ai = ib & 0x1f;
+        match major {
+            0 | 1 => {
+                let n = self
+                    .read_arg(ai, off)?
+                    .ok_or_else(|| Self::err(ErrorCode::ReservedAdditionalInfo, off))?;
+                Ok(Node::Int {
+                    negative: major == 1,
+                    n: trim_be(&n.to_be_bytes()).to_vec(),
+                })
+            }
+            2 => Ok(Node::Bytes(self.parse_string(2, ai, off)?)),
+            3 => {
+                let bytes = self.parse_string(3, ai, off)?;
+                let text =
+                    String::from_utf8(bytes).map_err(|_| Self::err(ErrorCode::Utf8Invalid, off))?;
+                Ok(Node::Text(text))
+            }
+            4 => self.parse_array(ai, off, depth),
+            5 => self.parse_map(ai, off, depth),
+            6 => {
+                let negative = match self.read_arg(ai, off)? {
+                    Some(2) => false,
+                    Some(3) => true,
+                    None => return Err(Self::err(ErrorCode::ReservedAdditionalInfo, off)),
+                    Some(_) => return Err(Self::err(ErrorCode::ForbiddenOrMalformedTag, off)),
+                };
+                let m_off = self.pos;
+                let ib = self.read_u8()?;
+                if ib >> 5 != 2 {
+                    return Err(Self::err(ErrorCode::ForbiddenOrMalformedTag, m_off));
+                }
+                let mag = self.parse_string(2, ib & 0x1f, m_off)?;
+                Ok(Node::Int {
+                    negative,
+                    n: trim_be(&mag).to_vec(),
+                })
+            }
+            _ => self.parse_simple(ai, off),
+        }
+    }
+
+    fn parse_simple(&mut self, ai: u8, off: usize) -> Result<Node, CborError> {
+        let value = match ai {
+            20 => return Ok(Node::Bool(false)),
+            21 => return Ok(Node::Bool(true)),
+            22 => return Ok(Node::Null),
+            25 => {
+                let b = self.read_exact(2)?;
+                f16_to_f64(u16::from_be_bytes([b[0], b[1]]))
+            }
+            26 => {
+                let b = self.read_exact(4)?;
+                f64::from(f32::from_bits(u32::from_be_bytes([b[0], b[1], b[2], b[3]])))
+            }
+            27 => {
+                let b = self.read_exact(8)?;
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(b);
+                f64::from_bits(u64::from_be_bytes(buf))
+            }
+            // 31 is a break outside an indefinite-length item.
+            28..=31 => return Err(Self::err(ErrorCode::ReservedAdditionalInfo, off)),
+            _ => return Err(Self::err(ErrorCode::UnsupportedSimpleValue, off)),
+        };
+        let value =
+            if value == 0.0 && self.options.float_policy == FloatPolicy::NormalizeNegativeZero {
+                0.0
+            } else {
+                value
+            };
+        F64Bits::try_from_f64(value)
+            .map(Node::Float)
+            .map_err(|err| Self::err(err.code(), off))
+    }
+
+    fn parse_string(&mut self, major: u8, ai: u8, off: usize) -> Result<Vec<u8>, CborError> {
+        let max = if major == 2 {
+            self.limits.max_bytes_len
+        } else {
+            self.limits.max_text_len
+        };
+        let code = if major == 2 {
+            ErrorCode::BytesLenLimitExceeded
+        } else {
+            ErrorCode::TextLenLimitExceeded
+        };
+
+        if let Some(len) = self.read_len(ai, off)? {
+            if len > max {
+                return Err(Self::err(code, off));
+            }
+            let bytes = self.read_exact(len)?;
+            let mut out = try_vec_with_capacity(len, off)?;
+            out.extend_from_slice(bytes);
+            return Ok(out);
+        }
+
+        // Indefinite length: definite chunks of the same major type until a break.
+        let mut out = Vec::new();
+        while !self.at_break()? {
+            let c_off = self.pos;
+            let ib = self.read_u8()?;
+            if ib >> 5 != major {
+                return Err(Self::err(
+                    if major == 2 {
+                        ErrorCode::ExpectedBytes
+                    } else {
+                        ErrorCode::ExpectedText
+                    },
+                    c_off,
+                ));
+            }
+            // Chunks must themselves be definite-length.
+            let len = self
+                .read_len(ib & 0x1f, c_off)?
+                .ok_or_else(|| Self::err(ErrorCode::IndefiniteLengthForbidden, c_off))?;
+            if len > max.saturating_sub(out.len()) {
+                return Err(Self::err(code, off));
+            }
+            let chunk = self.read_exact(len)?;
+            // RFC 8949 requires each text chunk to be valid UTF-8 on its own.
+            if major == 3 && utf8::validate(chunk).is_err() {
+                return Err(Self::err(ErrorCode::Utf8Invalid, c_off));
+            }
+            try_reserve(&mut out, len, c_off)?;
+            out.extend_from_slice(chunk);
+        }
+        Ok(out)
+    }
+
+    const fn enter(&self, depth: usize, off: usize) -> Result<usize, CborError> {
+        let next = depth + 1;
+        if next > self.limits.max_depth {
+            return Err(Self::err(ErrorCode::DepthLimitExceeded, off));
+        }
+        Ok(next)
+    }
+
+    fn parse_array(&mut self, ai: u8, off: usize, depth: usize) -> Result<Node, CborError> {
+        let depth = self.enter(depth, off)?;
+        let len = self.read_len(ai, off)?;
+        let mut items = Vec::new();
+        loop {
+            match len {
+                Some(len) if items.len() == len => break,
+                None if self.at_break()? => break,
+                _ => {}
+            }
+            if items.len() >= self.limits.max_array_len {
+                return Err(Self::err(ErrorCode::ArrayLenLimitExceeded, off));
+            }
+            self.bump_items(1, off)?;
+            try_reserve(&mut items, 1, off)?;
+            items.push(self.parse_value(depth)?);
+        }
+        Ok(Node::Array(items))
+    }
+
+    fn parse_map(&mut self, ai: u8, off: usize, depth: usize) -> Result<Node, CborError> {
+        let depth = self.enter(depth, off)?;
+        let len = self.read_len(ai, off)?;
+        let mut entries: Vec<(usize, String, Node)> = Vec::new();
+        loop {
+            match len {
+                Some(len) if entries.len() == len => break,
+                None if self.at_break()? => break,
+                _ => {}
+            }
+            if entries.len() >= self.limits.max_map_len {
+                return Err(Self::err(ErrorCode::MapLenLimitExceeded, off));
+            }
+            self.bump_items(2, off)?;
+
+            let key_off = self.pos;
+            let ib = self.read_u8()?;
+            if ib >> 5 != 3 {
+                return Err(Self::err(ErrorCode::MapKeyMustBeText, key_off));
+            }
+            let key = self.parse_string(3, ib & 0x1f, key_off)?;
+            if key.len() > self.limits.max_key_len {
+                return Err(Self::err(ErrorCode::KeyLenLimitExceeded, key_off));
+            }
+            let key =
+                String::from_utf8(key).map_err(|_| Self::err(ErrorCode::Utf8Invalid, key_off))?;
+            let value = self.parse_value(depth)?;
+            try_reserve(&mut entries, 1, off)?;
+            entries.push((key_off, key, value));
+        }
+
+        entries.sort_by(|a, b| cmp_text_keys_canonical(&a.1, &b.1));
+        for w in entries.windows(2) {
+            if w[0].1 == w[1].1 {
+                let later = w[0].0.max(w[1].0);
+                return Err(Self::err(ErrorCode::DuplicateMapKey, later));
+            }
+        }
+        Ok(Node::Map(
+            entries.into_iter().map(|(_, k, v)| (k, v)).collect(),
+        ))
+    }
+}
+
+/// Widen an IEEE-754 binary16 value; every half-precision value is exact in `f64`.
+fn f16_to_f64(h: u16) -> f64 {
+    let sign = u64::from(h >> 15) << 63;
+    let exp = u64::from((h >> 10) & 0x1f);
+    let mant = u64::from(h & 0x3ff);
+    let bits = match exp {
+        // Subnormal: mant * 2^-24, which is exact (and normal) in f64.
+        0 => (f64::from(h & 0x3ff) * f64::from_bits(999 << 52)).to_bits() | sign,
+        31 => sign | (0x7ff << 52) | (mant << 42),
+        _ => sign | ((exp + 1008) << 52) | (mant << 42),
+    };
+    f64::from_bits(bits)
+}
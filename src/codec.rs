@@ -1,15 +1,26 @@
 #[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(feature = "alloc")]
 use alloc::string::String;
 #[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::marker::PhantomData;
 
 #[cfg(feature = "alloc")]
 use crate::alloc_util;
 use crate::canonical::CanonicalCborRef;
-use crate::profile::{validate_f64_bits, MAX_SAFE_INTEGER};
-use crate::query::{CborKind, CborValueRef};
+use crate::int::mag_to_u128;
+use crate::profile::{cmp_text_keys_canonical, validate_f64_bits, MAX_SAFE_INTEGER};
+use crate::query::{CborKind, CborValueRef, RawCbor};
 use crate::wire::{self, Cursor};
-use crate::{CborError, DecodeLimits, ErrorCode};
+use crate::{CborError, DecodeLimits, DecodeOptions, ErrorCode};
 
 #[cfg(feature = "alloc")]
 use crate::encode::Encoder;
@@ -17,6 +28,12 @@ use crate::encode::Encoder;
 use crate::CanonicalCbor;
 #[cfg(feature = "alloc")]
 use crate::{BigInt, CborInteger};
+#[cfg(all(feature = "std", feature = "alloc"))]
+use std::collections::HashMap;
+#[cfg(all(feature = "std", feature = "alloc"))]
+use std::hash::BuildHasher;
+#[cfg(feature = "std")]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 
 /// A CBOR map represented as ordered key/value entries.
 #[cfg(feature = "alloc")]
@@ -36,6 +53,7 @@ impl<K, V> MapEntries<K, V> {
 pub struct Decoder<'de, const CHECKED: bool> {
     cursor: Cursor<'de, CborError>,
     limits: DecodeLimits,
+    options: DecodeOptions,
     depth: usize,
     items_seen: usize,
     poison: Option<CborError>,
@@ -53,6 +71,17 @@ pub struct ArrayDecoder<'a, 'de, const CHECKED: bool> {
     entered: bool,
 }
 
+/// Iterator that decodes the elements of an array one at a time.
+///
+/// Created by [`Decoder::array_stream`]. Yields `Err` at most once; after an error the
+/// stream ends. Dropping the stream before it is exhausted poisons the decoder, just
+/// like dropping an [`ArrayDecoder`] early.
+pub struct ArrayStream<'a, 'de, T, const CHECKED: bool> {
+    array: ArrayDecoder<'a, 'de, CHECKED>,
+    failed: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
 /// Map decoder guard that manages depth, length, and key ordering.
 pub struct MapDecoder<'a, 'de, const CHECKED: bool> {
     decoder: &'a mut Decoder<'de, CHECKED>,
@@ -93,7 +122,7 @@ impl<'de> Decoder<'de, true> {
     ///
     /// Returns `MessageLenLimitExceeded` if `bytes` exceeds the input limit.
     pub const fn new_checked(bytes: &'de [u8], limits: DecodeLimits) -> Result<Self, CborError> {
-        Self::new_with(bytes, limits)
+        Self::new_with(bytes, limits, DecodeOptions::new())
     }
 }
 
@@ -109,18 +138,36 @@ impl<'de> Decoder<'de, false> {
         canon: CanonicalCborRef<'de>,
         limits: DecodeLimits,
     ) -> Result<Self, CborError> {
-        Self::new_with(canon.as_bytes(), limits)
+        Self::new_with(canon.as_bytes(), limits, DecodeOptions::new())
+    }
+
+    /// Like [`Decoder::new_trusted`], with non-default [`DecodeOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `MessageLenLimitExceeded` if `bytes` exceeds the input limit.
+    pub const fn new_trusted_with(
+        canon: CanonicalCborRef<'de>,
+        limits: DecodeLimits,
+        options: DecodeOptions,
+    ) -> Result<Self, CborError> {
+        Self::new_with(canon.as_bytes(), limits, options)
     }
 }
 
 impl<'de, const CHECKED: bool> Decoder<'de, CHECKED> {
-    const fn new_with(bytes: &'de [u8], limits: DecodeLimits) -> Result<Self, CborError> {
+    const fn new_with(
+        bytes: &'de [u8],
+        limits: DecodeLimits,
+        options: DecodeOptions,
+    ) -> Result<Self, CborError> {
         if bytes.len() > limits.max_input_bytes {
             return Err(CborError::new(ErrorCode::MessageLenLimitExceeded, 0));
         }
         Ok(Self {
             cursor: Cursor::with_pos(bytes, 0),
             limits,
+            options,
             depth: 0,
             items_seen: 0,
             poison: None,
@@ -218,6 +265,18 @@ impl<'de, const CHECKED: bool> Decoder<'de, CHECKED> {
         wire::parse_text_from_header::<CHECKED, CborError>(
             &mut self.cursor,
             Some(&self.limits),
+            self.options.utf8_mode,
+            off,
+            ai,
+        )
+    }
+
+    #[inline]
+    fn parse_text_key(&mut self, off: usize, ai: u8) -> Result<&'de str, CborError> {
+        wire::parse_text_key::<CHECKED, CborError>(
+            &mut self.cursor,
+            Some(&self.limits),
+            self.options.utf8_mode,
             off,
             ai,
         )
@@ -237,32 +296,41 @@ impl<'de, const CHECKED: bool> Decoder<'de, CHECKED> {
         wire::parse_bignum::<CHECKED, CborError>(&mut self.cursor, Some(&self.limits), off, ai)
     }
 
-    fn parse_safe_i64(&mut self) -> Result<i64, CborError> {
-        let (major, ai, off) = self.read_header()?;
+    /// Whether major 0/1 integers must stay within the safe range.
+    #[inline]
+    const fn int_range_checked(&self) -> bool {
+        CHECKED && !self.limits.allow_full_int_range
+    }
+
+    /// Read a major 0/1 integer as `i128`, enforcing the profile range when checked.
+    fn parse_int_i128(&mut self, major: u8, ai: u8, off: usize) -> Result<i128, CborError> {
+        let arg = self.read_uint_arg(ai, off)?;
         match major {
             0 => {
-                let v = self.read_uint_arg(ai, off)?;
-                if CHECKED && v > MAX_SAFE_INTEGER {
+                if self.int_range_checked() && arg > MAX_SAFE_INTEGER {
                     return Err(CborError::new(ErrorCode::IntegerOutsideSafeRange, off));
                 }
-                i64::try_from(v).map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))
+                Ok(i128::from(arg))
             }
             1 => {
-                let n = self.read_uint_arg(ai, off)?;
-                if CHECKED && n >= MAX_SAFE_INTEGER {
+                if self.int_range_checked() && arg >= MAX_SAFE_INTEGER {
                     return Err(CborError::new(ErrorCode::IntegerOutsideSafeRange, off));
                 }
-                let n = i64::try_from(n)
-                    .map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))?;
-                Ok(-1 - n)
+                Ok(-1 - i128::from(arg))
             }
             _ => Err(CborError::new(ErrorCode::ExpectedInteger, off)),
         }
     }
 
+    fn parse_safe_i64(&mut self) -> Result<i64, CborError> {
+        let (major, ai, off) = self.read_header()?;
+        let v = self.parse_int_i128(major, ai, off)?;
+        i64::try_from(v).map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))
+    }
+
     fn parse_safe_u64(&mut self) -> Result<u64, CborError> {
-        let off = self.position();
-        let v = self.parse_safe_i64()?;
+        let (major, ai, off) = self.read_header()?;
+        let v = self.parse_int_i128(major, ai, off)?;
         u64::try_from(v).map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))
     }
 
@@ -405,6 +473,39 @@ impl<'de, const CHECKED: bool> Decoder<'de, CHECKED> {
         })
     }
 
+    /// Decode an array header that must announce exactly `len` elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArrayLenMismatch` at the header offset if the length differs, or any error
+    /// from [`Decoder::array`].
+    pub fn array_exact(&mut self, len: usize) -> Result<ArrayDecoder<'_, 'de, CHECKED>, CborError> {
+        let off = self.position();
+        let array = self.array()?;
+        if array.remaining() != len {
+            return Err(CborError::new(ErrorCode::ArrayLenMismatch, off));
+        }
+        Ok(array)
+    }
+
+    /// Decode an array header and return an iterator over its elements decoded as `T`.
+    ///
+    /// Elements are decoded lazily, so limits are enforced per element as the stream
+    /// advances and nothing is buffered.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpectedArray` if the next value is not an array, or a limit error.
+    pub fn array_stream<T: CborDecode<'de>>(
+        &mut self,
+    ) -> Result<ArrayStream<'_, 'de, T, CHECKED>, CborError> {
+        Ok(ArrayStream {
+            array: self.array()?,
+            failed: false,
+            _marker: PhantomData,
+        })
+    }
+
     /// Decode a map header and return a guard for its entries.
     ///
     /// # Errors
@@ -433,6 +534,48 @@ impl<'de, const CHECKED: bool> Decoder<'de, CHECKED> {
         })
     }
 
+    /// Decode a map header that must announce exactly `len` entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MapLenMismatch` at the header offset if the length differs, or any error
+    /// from [`Decoder::map`].
+    pub fn map_exact(&mut self, len: usize) -> Result<MapDecoder<'_, 'de, CHECKED>, CborError> {
+        let off = self.position();
+        let map = self.map()?;
+        if map.remaining() != len {
+            return Err(CborError::new(ErrorCode::MapLenMismatch, off));
+        }
+        Ok(map)
+    }
+
+    /// Decode a numeric value as `f64`, accepting either a float64 or an integer.
+    ///
+    /// Safe-range integers widen exactly. Bignums are converted with rounding and may
+    /// lose precision (very large magnitudes become infinite). Use this where a schema
+    /// allows producers to send `3` or `3.0` interchangeably; `f64`'s `CborDecode` impl
+    /// stays strict and only accepts float64.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpectedFloat` if the next value is not numeric, or a decode error if it is
+    /// malformed.
+    #[allow(clippy::cast_precision_loss, clippy::suboptimal_flops)]
+    pub fn parse_number(&mut self) -> Result<f64, CborError> {
+        let ib = self.peek_u8()?;
+        match ib >> 5 {
+            0 | 1 => Ok(self.parse_safe_i64()? as f64),
+            6 => {
+                let (_, ai, off) = self.read_header()?;
+                let (negative, mag) = self.parse_bignum(off, ai)?;
+                let n = mag.iter().fold(0f64, |acc, &b| acc * 256.0 + f64::from(b));
+                Ok(if negative { -1.0 - n } else { n })
+            }
+            7 if ib & 0x1f == 27 => self.parse_float64(),
+            _ => Err(CborError::new(ErrorCode::ExpectedFloat, self.position())),
+        }
+    }
+
     /// Skip exactly one CBOR value while enforcing decode limits.
     ///
     /// # Errors
@@ -471,6 +614,9 @@ impl<'de, const CHECKED: bool> Decoder<'de, CHECKED> {
                     wire::read_uint_arg_at::<CHECKED, CborError>(self.data(), &mut pos, ai, off)?;
                 match tag {
                     2 | 3 => Ok(CborKind::Integer),
+                    _ if !CHECKED || self.limits.extra_allowed_tags.contains(&tag) => {
+                        Ok(CborKind::Tag)
+                    }
                     _ => Err(CborError::new(ErrorCode::MalformedCanonical, off)),
                 }
             }
@@ -564,6 +710,36 @@ impl<'de, const CHECKED: bool> ArrayDecoder<'_, 'de, CHECKED> {
     }
 }
 
+impl<'de, T: CborDecode<'de>, const CHECKED: bool> Iterator for ArrayStream<'_, 'de, T, CHECKED> {
+    type Item = Result<T, CborError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.array.next_value() {
+            Ok(value) => value.map(Ok),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            (0, Some(0))
+        } else {
+            (0, Some(self.array.remaining()))
+        }
+    }
+}
+
+impl<'de, T: CborDecode<'de>, const CHECKED: bool> core::iter::FusedIterator
+    for ArrayStream<'_, 'de, T, CHECKED>
+{
+}
+
 impl<'de, const CHECKED: bool> MapDecoder<'_, 'de, CHECKED> {
     /// Remaining entries in the map.
     #[inline]
@@ -572,6 +748,13 @@ impl<'de, const CHECKED: bool> MapDecoder<'_, 'de, CHECKED> {
         self.remaining
     }
 
+    /// Current byte offset in the input, e.g. the start of the next key.
+    #[inline]
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.decoder.position()
+    }
+
     /// Decode the next map key as text.
     ///
     /// Returns `Ok(None)` when the map is exhausted.
@@ -594,7 +777,7 @@ impl<'de, const CHECKED: bool> MapDecoder<'_, 'de, CHECKED> {
         if major != 3 {
             return Err(CborError::new(ErrorCode::MapKeyMustBeText, off));
         }
-        let key = self.decoder.parse_text_from_header(off, ai)?;
+        let key = self.decoder.parse_text_key(off, ai)?;
         let key_end = self.decoder.position();
         if CHECKED {
             wire::check_map_key_order(
@@ -651,6 +834,133 @@ impl<'de, const CHECKED: bool> MapDecoder<'_, 'de, CHECKED> {
         Ok(Some((key, value)))
     }
 
+    /// Advance to `key`, skipping the values of any smaller keys.
+    ///
+    /// Returns `true` with the decoder positioned on the matching value (read it with
+    /// [`MapDecoder::next_value`] or [`MapDecoder::decode_value`]). Because keys are in
+    /// canonical order, the scan stops at the first larger key and returns `false`; that
+    /// key is left unread so iteration can continue from it. A value pending from a
+    /// previous [`MapDecoder::next_key`] is skipped first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if skipping fails or the map is malformed.
+    pub fn seek_key(&mut self, key: &str) -> Result<bool, CborError> {
+        if self.pending_value {
+            self.decoder.skip_value()?;
+            self.pending_value = false;
+            self.remaining -= 1;
+        }
+        while self.remaining > 0 {
+            let key_start = self.decoder.position();
+            let prev_key_range = self.prev_key_range;
+            let Some(found) = self.next_key()? else {
+                break;
+            };
+            match cmp_text_keys_canonical(found, key) {
+                Ordering::Equal => return Ok(true),
+                Ordering::Less => {
+                    self.decoder.skip_value()?;
+                    self.pending_value = false;
+                    self.remaining -= 1;
+                }
+                Ordering::Greater => {
+                    self.decoder.cursor = Cursor::with_pos(self.decoder.data(), key_start);
+                    self.prev_key_range = prev_key_range;
+                    self.pending_value = false;
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Check the unread entries against an expected key set without consuming them.
+    ///
+    /// Scans the remaining keys (skipping their values) and then rewinds, so entries can
+    /// still be read normally afterwards. Call it right after [`Decoder::map`] to check
+    /// the whole map.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MissingKey` (with the key as context) for the first absent `required`
+    /// key, `UnknownKey` at the offending key when `allow_extra` is false and a key
+    /// outside `required` is present, or a decode error if the map is malformed. Calling
+    /// it while a value is pending returns `MalformedCanonical`.
+    pub fn expect_keys(
+        &mut self,
+        required: &[&'static str],
+        allow_extra: bool,
+    ) -> Result<(), CborError> {
+        if self.pending_value {
+            return Err(CborError::new(
+                ErrorCode::MalformedCanonical,
+                self.decoder.position(),
+            ));
+        }
+        let start = self.decoder.position();
+        let saved = (self.remaining, self.prev_key_range, self.decoder.items_seen);
+
+        let res = self
+            .scan_keys(required, allow_extra)
+            .and_then(|all_present| {
+                if all_present {
+                    return Ok(());
+                }
+                // Something is missing (or `required` repeats a key); rescan to name it.
+                for &want in required {
+                    self.rewind(start, saved);
+                    if !self.scan_for_key(want)? {
+                        return Err(CborError::new(ErrorCode::MissingKey, start).with_context(want));
+                    }
+                }
+                Ok(())
+            });
+
+        self.rewind(start, saved);
+        res
+    }
+
+    fn rewind(&mut self, pos: usize, saved: (usize, Option<(usize, usize)>, usize)) {
+        self.decoder.cursor = Cursor::with_pos(self.decoder.data(), pos);
+        (self.remaining, self.prev_key_range, self.decoder.items_seen) = saved;
+        self.pending_value = false;
+    }
+
+    fn skip_pending_value(&mut self) -> Result<(), CborError> {
+        self.decoder.skip_value()?;
+        self.pending_value = false;
+        self.remaining -= 1;
+        Ok(())
+    }
+
+    /// Returns whether every `required` key was seen exactly once.
+    fn scan_keys(&mut self, required: &[&str], allow_extra: bool) -> Result<bool, CborError> {
+        let mut matched = 0usize;
+        loop {
+            let key_start = self.decoder.position();
+            let Some(key) = self.next_key()? else {
+                return Ok(matched == required.len());
+            };
+            if required.contains(&key) {
+                matched += 1;
+            } else if !allow_extra {
+                return Err(CborError::new(ErrorCode::UnknownKey, key_start));
+            }
+            self.skip_pending_value()?;
+        }
+    }
+
+    fn scan_for_key(&mut self, want: &str) -> Result<bool, CborError> {
+        while let Some(key) = self.next_key()? {
+            if key == want {
+                return Ok(true);
+            }
+            self.skip_pending_value()?;
+        }
+        Ok(false)
+    }
+
     /// Skip all remaining map entries.
     ///
     /// # Errors
@@ -714,12 +1024,15 @@ pub fn decode<'de, T: CborDecode<'de>>(
 
 /// Decode a value from validated canonical bytes.
 ///
+/// Debug builds re-validate `canon` first (see [`CanonicalCborRef::debug_assert_canonical`]).
+///
 /// # Errors
 ///
 /// Returns an error if decoding fails.
 pub fn decode_canonical<'de, T: CborDecode<'de>>(
     canon: CanonicalCborRef<'de>,
 ) -> Result<T, CborError> {
+    canon.debug_assert_canonical();
     let limits = DecodeLimits::for_bytes(canon.len());
     let mut decoder = Decoder::<false>::new_trusted(canon, limits)?;
     let value = T::decode(&mut decoder)?;
@@ -750,7 +1063,7 @@ pub fn decode_canonical_owned<'de, T: CborDecode<'de>>(
 pub fn encode_to_vec<T: CborEncode>(value: &T) -> Result<Vec<u8>, CborError> {
     let mut enc = Encoder::new();
     value.encode(&mut enc)?;
-    Ok(enc.into_vec())
+    enc.try_into_vec()
 }
 
 #[cfg(feature = "alloc")]
@@ -776,14 +1089,25 @@ pub fn encode_to_canonical<T: CborEncode>(value: &T) -> Result<CanonicalCbor, Cb
     enc.into_canonical()
 }
 
-fn mag_to_u128(mag: &[u8]) -> Option<u128> {
-    if mag.len() > 16 {
-        return None;
-    }
-    let mut buf = [0u8; 16];
-    let start = 16 - mag.len();
-    buf[start..].copy_from_slice(mag);
-    Some(u128::from_be_bytes(buf))
+#[cfg(feature = "alloc")]
+/// Returns `true` if `value` encodes to exactly the bytes of `expected`.
+///
+/// Canonical bytes are unique per value, so this is value equality. The value is encoded
+/// into a scratch buffer sized to `expected` and then compared.
+///
+/// # Errors
+///
+/// Returns an error if encoding fails, or if the encoder ends poisoned, incomplete, or out of
+/// profile (for example after [`Encoder::int_exact`]), exactly as
+/// [`Encoder::into_canonical`] would.
+pub fn encodes_to<T: CborEncode + ?Sized>(
+    value: &T,
+    expected: CanonicalCborRef<'_>,
+) -> Result<bool, CborError> {
+    // A comparing sink that stops at the first differing byte would avoid the buffer.
+    let mut enc = Encoder::with_capacity(expected.len());
+    value.encode(&mut enc)?;
+    Ok(enc.into_canonical()?.as_bytes() == expected.as_bytes())
 }
 
 impl<'de> CborDecode<'de> for () {
@@ -792,6 +1116,18 @@ impl<'de> CborDecode<'de> for () {
     }
 }
 
+/// Decodes `null`, like `()`.
+///
+/// Derived structs and enums skip `PhantomData` fields entirely, named or in a tuple of two or
+/// more fields, so they are absent on the wire; this impl only applies where a marker appears
+/// in a value position of its own, including a single-field tuple such as `M(PhantomData<T>)`.
+impl<'de, T: ?Sized> CborDecode<'de> for PhantomData<T> {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        decoder.parse_null()?;
+        Ok(Self)
+    }
+}
+
 #[allow(clippy::use_self)]
 impl<'de> CborDecode<'de> for bool {
     fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
@@ -841,24 +1177,7 @@ impl<'de> CborDecode<'de> for i128 {
     fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
         let (major, ai, off) = decoder.read_header()?;
         match major {
-            0 => {
-                let v = decoder.read_uint_arg(ai, off)?;
-                if CHECKED && v > MAX_SAFE_INTEGER {
-                    return Err(CborError::new(ErrorCode::IntegerOutsideSafeRange, off));
-                }
-                let v_i = i64::try_from(v)
-                    .map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))?;
-                Ok(Self::from(v_i))
-            }
-            1 => {
-                let n = decoder.read_uint_arg(ai, off)?;
-                if CHECKED && n >= MAX_SAFE_INTEGER {
-                    return Err(CborError::new(ErrorCode::IntegerOutsideSafeRange, off));
-                }
-                let n_i = i64::try_from(n)
-                    .map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))?;
-                Ok(Self::from(-1 - n_i))
-            }
+            0 | 1 => decoder.parse_int_i128(major, ai, off),
             6 => {
                 let (negative, mag) = decoder.parse_bignum(off, ai)?;
                 let n = mag_to_u128(mag)
@@ -914,16 +1233,9 @@ impl<'de> CborDecode<'de> for u128 {
     fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
         let (major, ai, off) = decoder.read_header()?;
         match major {
-            0 => {
-                let v = decoder.read_uint_arg(ai, off)?;
-                if CHECKED && v > MAX_SAFE_INTEGER {
-                    return Err(CborError::new(ErrorCode::IntegerOutsideSafeRange, off));
-                }
-                let v_i = i64::try_from(v)
-                    .map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))?;
-                let v_u64 = u64::try_from(v_i)
-                    .map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))?;
-                Ok(Self::from(v_u64))
+            0 | 1 => {
+                let v = decoder.parse_int_i128(major, ai, off)?;
+                Self::try_from(v).map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))
             }
             6 => {
                 let (negative, mag) = decoder.parse_bignum(off, ai)?;
@@ -946,7 +1258,7 @@ impl<'de> CborDecode<'de> for BigInt {
         }
         let (negative, mag) = decoder.parse_bignum(off, ai)?;
         let magnitude = alloc_util::try_vec_from_slice(mag, off)?;
-        Self::new(negative, magnitude).map_err(|err| CborError::new(err.code, off))
+        Self::new(negative, magnitude).map_err(|err| CborError::new(err.code(), off))
     }
 }
 
@@ -962,7 +1274,7 @@ impl<'de> CborDecode<'de> for CborInteger {
                 }
                 let v_i = i64::try_from(v)
                     .map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))?;
-                Self::safe(v_i).map_err(|err| CborError::new(err.code, off))
+                Self::safe(v_i).map_err(|err| CborError::new(err.code(), off))
             }
             1 => {
                 let n = decoder.read_uint_arg(ai, off)?;
@@ -971,12 +1283,12 @@ impl<'de> CborDecode<'de> for CborInteger {
                 }
                 let n_i = i64::try_from(n)
                     .map_err(|_| CborError::new(ErrorCode::ExpectedInteger, off))?;
-                Self::safe(-1 - n_i).map_err(|err| CborError::new(err.code, off))
+                Self::safe(-1 - n_i).map_err(|err| CborError::new(err.code(), off))
             }
             6 => {
                 let (negative, mag) = decoder.parse_bignum(off, ai)?;
                 let magnitude = alloc_util::try_vec_from_slice(mag, off)?;
-                Self::big(negative, magnitude).map_err(|err| CborError::new(err.code, off))
+                Self::big(negative, magnitude).map_err(|err| CborError::new(err.code(), off))
             }
             _ => Err(CborError::new(ErrorCode::ExpectedInteger, off)),
         }
@@ -1069,6 +1381,21 @@ impl<'de> CborDecode<'de> for CborValueRef<'de> {
     }
 }
 
+impl<'de> CborDecode<'de> for RawCbor<'de> {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        let start = decoder.position();
+        decoder.skip_value()?;
+        let end = decoder.position();
+        if CHECKED && decoder.limits.is_relaxed() {
+            // `RawCbor` is spliced verbatim when encoded, so it must not carry wide
+            // integers or passthrough tags admitted by the relaxations.
+            crate::validate_canonical(&decoder.data()[start..end], decoder.limits)
+                .map_err(|err| err.shifted(start))?;
+        }
+        Ok(RawCbor::new(decoder.data(), start, end))
+    }
+}
+
 impl<'de, T: CborDecode<'de>> CborDecode<'de> for Option<T> {
     fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
         if decoder.peek_u8()? == 0xf6 {
@@ -1080,6 +1407,41 @@ impl<'de, T: CborDecode<'de>> CborDecode<'de> for Option<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<'de, T: CborDecode<'de>> CborDecode<'de> for Box<T> {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        T::decode(decoder).map(Self::new)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, T: CborDecode<'de>> CborDecode<'de> for Rc<T> {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        T::decode(decoder).map(Self::new)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, T: CborDecode<'de>> CborDecode<'de> for Arc<T> {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        T::decode(decoder).map(Self::new)
+    }
+}
+
+/// Decodes `{"Ok": T}` or `{"Err": E}`, the shape serde uses for `Result`.
+impl<'de, T: CborDecode<'de>, E: CborDecode<'de>> CborDecode<'de> for Result<T, E> {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        let off = decoder.position();
+        let mut map = decoder.map_exact(1)?;
+        match map.next_key()? {
+            Some("Ok") => map.next_value().map(Ok),
+            Some("Err") => map.next_value().map(Err),
+            Some(_) => Err(CborError::new(ErrorCode::UnknownEnumVariant, off)),
+            None => Err(CborError::new(ErrorCode::MapLenMismatch, off)),
+        }
+    }
+}
+
 #[cfg(feature = "alloc")]
 impl<'de, T: CborDecode<'de> + CborArrayElem> CborDecode<'de> for Vec<T> {
     fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
@@ -1121,6 +1483,113 @@ impl<'de, V: CborDecode<'de>> CborDecode<'de> for MapEntries<String, V> {
     }
 }
 
+/// Decodes a map with text keys; duplicate keys are rejected with `DuplicateMapKey`.
+#[cfg(feature = "alloc")]
+impl<'de, V: CborDecode<'de>> CborDecode<'de> for BTreeMap<String, V> {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        let mut map = decoder.map()?;
+        let mut out = Self::new();
+        loop {
+            let off = map.decoder.position();
+            let Some(key) = map.next_key()? else {
+                return Ok(out);
+            };
+            let value = map.next_value()?;
+            let owned = alloc_util::try_string_from_str(key, off)?;
+            if out.insert(owned, value).is_some() {
+                return Err(CborError::new(ErrorCode::DuplicateMapKey, off));
+            }
+        }
+    }
+}
+
+/// Decodes a map with text keys; duplicate keys are rejected with `DuplicateMapKey`.
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl<'de, V, S> CborDecode<'de> for HashMap<String, V, S>
+where
+    V: CborDecode<'de>,
+    S: BuildHasher + Default,
+{
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        let start = decoder.position();
+        let mut map = decoder.map()?;
+        let mut out = Self::with_hasher(S::default());
+        out.try_reserve(map.remaining())
+            .map_err(|_| CborError::new(ErrorCode::AllocationFailed, start))?;
+        loop {
+            let off = map.decoder.position();
+            let Some(key) = map.next_key()? else {
+                return Ok(out);
+            };
+            let value = map.next_value()?;
+            let owned = alloc_util::try_string_from_str(key, off)?;
+            if out.insert(owned, value).is_some() {
+                return Err(CborError::new(ErrorCode::DuplicateMapKey, off));
+            }
+        }
+    }
+}
+
+/// Decodes a 4-byte byte string; other lengths fail with `BytesLenMismatch`.
+#[cfg(feature = "std")]
+impl<'de> CborDecode<'de> for Ipv4Addr {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        let off = decoder.position();
+        let octets: [u8; 4] = decoder
+            .parse_bytes()?
+            .try_into()
+            .map_err(|_| CborError::new(ErrorCode::BytesLenMismatch, off))?;
+        Ok(Self::from(octets))
+    }
+}
+
+/// Decodes a 16-byte byte string; other lengths fail with `BytesLenMismatch`.
+#[cfg(feature = "std")]
+impl<'de> CborDecode<'de> for Ipv6Addr {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        let off = decoder.position();
+        let octets: [u8; 16] = decoder
+            .parse_bytes()?
+            .try_into()
+            .map_err(|_| CborError::new(ErrorCode::BytesLenMismatch, off))?;
+        Ok(Self::from(octets))
+    }
+}
+
+/// Decodes a 4-byte byte string as `V4` and a 16-byte one as `V6`; other lengths fail with
+/// `BytesLenMismatch`.
+///
+/// IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) stay `V6`; they are never folded to `V4`.
+#[cfg(feature = "std")]
+impl<'de> CborDecode<'de> for IpAddr {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        let off = decoder.position();
+        let bytes = decoder.parse_bytes()?;
+        if let Ok(octets) = <[u8; 4]>::try_from(bytes) {
+            return Ok(Self::V4(Ipv4Addr::from(octets)));
+        }
+        if let Ok(octets) = <[u8; 16]>::try_from(bytes) {
+            return Ok(Self::V6(Ipv6Addr::from(octets)));
+        }
+        Err(CborError::new(ErrorCode::BytesLenMismatch, off))
+    }
+}
+
+/// Decodes the two-element array `[address, port]`.
+///
+/// The address width picks the variant as for [`IpAddr`]. A `V6` result has zero flow
+/// info and scope id, since neither is encoded.
+#[cfg(feature = "std")]
+impl<'de> CborDecode<'de> for SocketAddr {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        let (ip, port) = <(IpAddr, u16)>::decode(decoder)?;
+        Ok(match ip {
+            IpAddr::V4(ip) => Self::V4(SocketAddrV4::new(ip, port)),
+            IpAddr::V6(ip) => Self::V6(SocketAddrV6::new(ip, port, 0, 0)),
+        })
+    }
+}
+
 #[cfg(feature = "alloc")]
 impl<'de> CborDecode<'de> for String {
     fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
@@ -1146,6 +1615,15 @@ impl CborEncode for () {
     }
 }
 
+/// Encodes `null`, like `()`. Derived structs and enums omit `PhantomData` fields instead
+/// (see the `CborDecode` impl for the single-field tuple exception).
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> CborEncode for PhantomData<T> {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        enc.null()
+    }
+}
+
 #[cfg(feature = "alloc")]
 impl CborEncode for bool {
     fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
@@ -1295,14 +1773,14 @@ impl CborEncode for f32 {
 }
 
 #[cfg(feature = "alloc")]
-impl CborEncode for &str {
+impl CborEncode for str {
     fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
         enc.text(self)
     }
 }
 
 #[cfg(feature = "alloc")]
-impl CborEncode for &[u8] {
+impl CborEncode for [u8] {
     fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
         enc.bytes(self)
     }
@@ -1329,6 +1807,13 @@ impl CborEncode for CborValueRef<'_> {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl CborEncode for RawCbor<'_> {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        enc.raw_cbor(self.as_canonical())
+    }
+}
+
 #[cfg(feature = "alloc")]
 impl CborEncode for CanonicalCborRef<'_> {
     fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
@@ -1336,6 +1821,23 @@ impl CborEncode for CanonicalCborRef<'_> {
     }
 }
 
+/// References and smart pointers encode exactly like the value they point to.
+macro_rules! forward_impls {
+    ($($ptr:ty),+) => {$(
+        #[cfg(feature = "alloc")]
+        impl<T: CborEncode + ?Sized> CborEncode for $ptr {
+            fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+                (**self).encode(enc)
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<T: CborArrayElem + ?Sized> CborArrayElem for $ptr {}
+    )+};
+}
+
+forward_impls!(&T, Box<T>, Rc<T>, Arc<T>);
+
 #[cfg(feature = "alloc")]
 impl<T: CborEncode> CborEncode for Option<T> {
     fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
@@ -1346,6 +1848,20 @@ impl<T: CborEncode> CborEncode for Option<T> {
     }
 }
 
+/// Encodes as the single-entry map `{"Ok": T}` or `{"Err": E}`.
+///
+/// The payload is not wrapped in an array, unlike a derived `enum { Ok(T), Err(E) }`,
+/// which encodes every tuple variant's fields as an array.
+#[cfg(feature = "alloc")]
+impl<T: CborEncode, E: CborEncode> CborEncode for Result<T, E> {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        enc.map(1, |m| match self {
+            Ok(v) => m.entry("Ok", |e| v.encode(e)),
+            Err(v) => m.entry("Err", |e| v.encode(e)),
+        })
+    }
+}
+
 #[cfg(feature = "alloc")]
 impl<T: CborEncode + CborArrayElem> CborEncode for Vec<T> {
     fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
@@ -1374,6 +1890,85 @@ where
     }
 }
 
+/// Encodes entries in canonical key order.
+///
+/// `BTreeMap` iterates in Rust's lexicographic `Ord`, which differs from canonical
+/// (length-first) order, so keys are collected and re-sorted before emitting.
+#[cfg(feature = "alloc")]
+impl<V: CborEncode> CborEncode for BTreeMap<String, V> {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        encode_sorted_entries(enc, self.len(), self.iter())
+    }
+}
+
+/// Encodes entries in canonical key order.
+///
+/// `HashMap` iteration order is arbitrary, so entries are buffered and sorted before
+/// emitting; the output is identical for equal maps regardless of hasher state.
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl<V: CborEncode, S> CborEncode for HashMap<String, V, S> {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        encode_sorted_entries(enc, self.len(), self.iter())
+    }
+}
+
+/// Encodes the 4 octets as a byte string.
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl CborEncode for Ipv4Addr {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        enc.bytes(&self.octets())
+    }
+}
+
+/// Encodes the 16 octets as a byte string.
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl CborEncode for Ipv6Addr {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        enc.bytes(&self.octets())
+    }
+}
+
+/// Encodes a 4-byte (`V4`) or 16-byte (`V6`) byte string.
+///
+/// The width is the only discriminant. An IPv4-mapped IPv6 address is written as its
+/// 16 bytes, so it round-trips as `V6` and never collides with the plain `V4` form.
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl CborEncode for IpAddr {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        match self {
+            Self::V4(ip) => ip.encode(enc),
+            Self::V6(ip) => ip.encode(enc),
+        }
+    }
+}
+
+/// Encodes the two-element array `[address, port]`, with the address as for [`IpAddr`].
+///
+/// `SocketAddrV6` flow info and scope id are not encoded.
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl CborEncode for SocketAddr {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        (self.ip(), self.port()).encode(enc)
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn encode_sorted_entries<'a, V: CborEncode + 'a>(
+    enc: &mut Encoder,
+    len: usize,
+    entries: impl Iterator<Item = (&'a String, &'a V)>,
+) -> Result<(), CborError> {
+    let mut sorted = alloc_util::try_vec_with_capacity::<(&str, &V)>(len, 0)?;
+    sorted.extend(entries.map(|(k, v)| (k.as_str(), v)));
+    sorted.sort_unstable_by(|(a, _), (b, _)| cmp_text_keys_canonical(a, b));
+    enc.map(sorted.len(), |m| {
+        for (k, v) in sorted {
+            m.entry(k, |enc| v.encode(enc))?;
+        }
+        Ok(())
+    })
+}
+
 #[cfg(feature = "alloc")]
 impl CborArrayElem for bool {}
 #[cfg(feature = "alloc")]
@@ -1405,9 +2000,9 @@ impl CborArrayElem for f32 {}
 #[cfg(feature = "alloc")]
 impl CborArrayElem for String {}
 #[cfg(feature = "alloc")]
-impl CborArrayElem for &str {}
+impl CborArrayElem for str {}
 #[cfg(feature = "alloc")]
-impl CborArrayElem for &[u8] {}
+impl CborArrayElem for [u8] {}
 #[cfg(feature = "alloc")]
 impl CborArrayElem for BigInt {}
 #[cfg(feature = "alloc")]
@@ -1417,13 +2012,80 @@ impl CborArrayElem for CborValueRef<'_> {}
 #[cfg(feature = "alloc")]
 impl CborArrayElem for CanonicalCborRef<'_> {}
 #[cfg(feature = "alloc")]
+impl CborArrayElem for RawCbor<'_> {}
+#[cfg(feature = "alloc")]
 impl<T: CborArrayElem> CborArrayElem for Option<T> {}
 #[cfg(feature = "alloc")]
+impl<T, E> CborArrayElem for Result<T, E> {}
+#[cfg(feature = "alloc")]
 impl<T: CborArrayElem> CborArrayElem for Vec<T> {}
 #[cfg(feature = "alloc")]
+impl<V: CborArrayElem> CborArrayElem for BTreeMap<String, V> {}
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl<V: CborArrayElem, S> CborArrayElem for HashMap<String, V, S> {}
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl CborArrayElem for Ipv4Addr {}
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl CborArrayElem for Ipv6Addr {}
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl CborArrayElem for IpAddr {}
+#[cfg(all(feature = "std", feature = "alloc"))]
+impl CborArrayElem for SocketAddr {}
+#[cfg(feature = "alloc")]
 impl<K, V> CborArrayElem for MapEntries<K, V>
 where
     K: AsRef<str>,
     V: CborArrayElem,
 {
 }
+
+/// Tuples map to fixed-length arrays: decoding requires exactly the tuple's arity
+/// (`ArrayLenMismatch` otherwise), and encoding writes the elements in order.
+macro_rules! tuple_impls {
+    ($($len:literal => ($($name:ident)+),)+) => {$(
+        impl<'de, $($name: CborDecode<'de>),+> CborDecode<'de> for ($($name,)+) {
+            fn decode<const CHECKED: bool>(
+                decoder: &mut Decoder<'de, CHECKED>,
+            ) -> Result<Self, CborError> {
+                let off = decoder.position();
+                let mut array = decoder.array_exact($len)?;
+                Ok(($(
+                    match array.next_value::<$name>()? {
+                        Some(value) => value,
+                        None => return Err(CborError::new(ErrorCode::ArrayLenMismatch, off)),
+                    },
+                )+))
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<$($name: CborEncode),+> CborEncode for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+                let ($($name,)+) = self;
+                enc.array($len, |a| {
+                    $(a.value($name)?;)+
+                    Ok(())
+                })
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<$($name),+> CborArrayElem for ($($name,)+) {}
+    )+};
+}
+
+tuple_impls! {
+    1 => (A),
+    2 => (A B),
+    3 => (A B C),
+    4 => (A B C D),
+    5 => (A B C D E),
+    6 => (A B C D E F),
+    7 => (A B C D E F G),
+    8 => (A B C D E F G H),
+    9 => (A B C D E F G H I),
+    10 => (A B C D E F G H I J),
+    11 => (A B C D E F G H I J K),
+    12 => (A B C D E F G H I J K L),
+}
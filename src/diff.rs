@@ -0,0 +1,263 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::alloc_util::{try_reserve, try_vec_with_capacity};
+use crate::canonical::{CanonicalCbor, CanonicalCborRef};
+use crate::encode::MapEncoder;
+use crate::profile::cmp_text_keys_canonical;
+use crate::query::{ArrayRef, CborKind, CborValueRef, MapRef, PathElem};
+use crate::{CborError, Encoder, ErrorCode};
+
+#[derive(Debug)]
+enum DiffOp<'a> {
+    Set {
+        path: Vec<PathElem<'a>>,
+        value: CborValueRef<'a>,
+    },
+    Delete {
+        path: Vec<PathElem<'a>>,
+    },
+    Splice {
+        path: Vec<PathElem<'a>>,
+        pos: usize,
+        delete: usize,
+        insert: Vec<CborValueRef<'a>>,
+    },
+}
+
+/// Compute a structural patch that rewrites `old` into `new`.
+///
+/// The patch is itself canonical CBOR: an array of operation maps, applied in order
+/// against the original (pre-edit) document, exactly like recorded [`crate::Editor`]
+/// operations. Paths are arrays whose elements are text (map key) or unsigned
+/// integers (array index against the original array).
+///
+/// | Operation | Entries |
+/// |---|---|
+/// | set | `{"op": "set", "path": [...], "value": any}` |
+/// | delete | `{"op": "delete", "path": [...]}` |
+/// | splice | `{"op": "splice", "pos": uint, "path": [...], "delete": uint, "insert": [any, ...]}` |
+///
+/// `set` upserts a map key or replaces an array element; an empty path replaces the
+/// whole document. `splice` removes `delete` items of the array at `path` starting at
+/// `pos`, then inserts the `insert` items there.
+///
+/// Maps are aligned by canonical key order. Arrays keep their common prefix and
+/// suffix, pair up the differing middle section element by element, and express any
+/// length change as a single splice. The result is correct but not guaranteed to be
+/// minimal.
+///
+/// # Errors
+///
+/// Returns `CborError` if either input is malformed or allocation fails.
+pub fn diff(
+    old: CanonicalCborRef<'_>,
+    new: CanonicalCborRef<'_>,
+) -> Result<CanonicalCbor, CborError> {
+    let mut ops = Vec::new();
+    let mut path = Vec::new();
+    diff_value(old.root(), new.root(), &mut path, &mut ops)?;
+    encode_ops(&ops)
+}
+
+fn diff_value<'a>(
+    old: CborValueRef<'a>,
+    new: CborValueRef<'a>,
+    path: &mut Vec<PathElem<'a>>,
+    ops: &mut Vec<DiffOp<'a>>,
+) -> Result<(), CborError> {
+    if old == new {
+        return Ok(());
+    }
+    match (old.kind()?, new.kind()?) {
+        (CborKind::Map, CborKind::Map) => diff_map(old.map()?, new.map()?, path, ops),
+        (CborKind::Array, CborKind::Array) => diff_array(old.array()?, new.array()?, path, ops),
+        _ => {
+            let path = clone_path(path)?;
+            push_op(ops, DiffOp::Set { path, value: new })
+        }
+    }
+}
+
+fn diff_map<'a>(
+    old: MapRef<'a>,
+    new: MapRef<'a>,
+    path: &mut Vec<PathElem<'a>>,
+    ops: &mut Vec<DiffOp<'a>>,
+) -> Result<(), CborError> {
+    let mut old_iter = old.iter();
+    let mut new_iter = new.iter();
+    let mut old_entry = old_iter.next().transpose()?;
+    let mut new_entry = new_iter.next().transpose()?;
+
+    loop {
+        let ord = match (old_entry, new_entry) {
+            (None, None) => return Ok(()),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((ok, _)), Some((nk, _))) => cmp_text_keys_canonical(ok, nk),
+        };
+        match ord {
+            Ordering::Less => {
+                let Some((key, _)) = old_entry else {
+                    return Ok(());
+                };
+                let mut child = clone_path(path)?;
+                child.push(PathElem::Key(key));
+                push_op(ops, DiffOp::Delete { path: child })?;
+                old_entry = old_iter.next().transpose()?;
+            }
+            Ordering::Greater => {
+                let Some((key, value)) = new_entry else {
+                    return Ok(());
+                };
+                let mut child = clone_path(path)?;
+                child.push(PathElem::Key(key));
+                push_op(ops, DiffOp::Set { path: child, value })?;
+                new_entry = new_iter.next().transpose()?;
+            }
+            Ordering::Equal => {
+                let (Some((_, ov)), Some((key, nv))) = (old_entry, new_entry) else {
+                    return Ok(());
+                };
+                try_reserve(path, 1, 0)?;
+                path.push(PathElem::Key(key));
+                let res = diff_value(ov, nv, path, ops);
+                path.pop();
+                res?;
+                old_entry = old_iter.next().transpose()?;
+                new_entry = new_iter.next().transpose()?;
+            }
+        }
+    }
+}
+
+fn diff_array<'a>(
+    old: ArrayRef<'a>,
+    new: ArrayRef<'a>,
+    path: &mut Vec<PathElem<'a>>,
+    ops: &mut Vec<DiffOp<'a>>,
+) -> Result<(), CborError> {
+    let old_items = collect_items(old)?;
+    let new_items = collect_items(new)?;
+
+    let prefix = old_items
+        .iter()
+        .zip(&new_items)
+        .take_while(|(o, n)| o == n)
+        .count();
+    let suffix = old_items[prefix..]
+        .iter()
+        .rev()
+        .zip(new_items[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+
+    let old_mid = &old_items[prefix..old_items.len() - suffix];
+    let new_mid = &new_items[prefix..new_items.len() - suffix];
+    let paired = old_mid.len().min(new_mid.len());
+
+    for (i, (&ov, &nv)) in old_mid.iter().zip(new_mid).enumerate() {
+        try_reserve(path, 1, 0)?;
+        path.push(PathElem::Index(prefix + i));
+        let res = diff_value(ov, nv, path, ops);
+        path.pop();
+        res?;
+    }
+
+    let delete = old_mid.len() - paired;
+    let extra = &new_mid[paired..];
+    if delete == 0 && extra.is_empty() {
+        return Ok(());
+    }
+    let mut insert = try_vec_with_capacity(extra.len(), 0)?;
+    insert.extend_from_slice(extra);
+    let path = clone_path(path)?;
+    push_op(
+        ops,
+        DiffOp::Splice {
+            path,
+            pos: prefix + paired,
+            delete,
+            insert,
+        },
+    )
+}
+
+fn collect_items(array: ArrayRef<'_>) -> Result<Vec<CborValueRef<'_>>, CborError> {
+    let mut out = try_vec_with_capacity(array.len(), 0)?;
+    for item in array.iter() {
+        out.push(item?);
+    }
+    Ok(out)
+}
+
+fn clone_path<'a>(path: &[PathElem<'a>]) -> Result<Vec<PathElem<'a>>, CborError> {
+    let mut out = try_vec_with_capacity(path.len() + 1, 0)?;
+    out.extend_from_slice(path);
+    Ok(out)
+}
+
+fn push_op<'a>(ops: &mut Vec<DiffOp<'a>>, op: DiffOp<'a>) -> Result<(), CborError> {
+    try_reserve(ops, 1, 0)?;
+    ops.push(op);
+    Ok(())
+}
+
+fn encode_ops(ops: &[DiffOp<'_>]) -> Result<CanonicalCbor, CborError> {
+    let mut enc = Encoder::new();
+    enc.array(ops.len(), |a| {
+        for op in ops {
+            match op {
+                DiffOp::Set { path, value } => a.map(3, |m| {
+                    m.entry("op", |e| e.text("set"))?;
+                    entry_path(m, path)?;
+                    m.entry("value", |e| e.raw_value_ref(*value))
+                })?,
+                DiffOp::Delete { path } => a.map(2, |m| {
+                    m.entry("op", |e| e.text("delete"))?;
+                    entry_path(m, path)
+                })?,
+                DiffOp::Splice {
+                    path,
+                    pos,
+                    delete,
+                    insert,
+                } => a.map(5, |m| {
+                    m.entry("op", |e| e.text("splice"))?;
+                    m.entry("pos", |e| e.int(index_to_i64(*pos)?))?;
+                    entry_path(m, path)?;
+                    m.entry("delete", |e| e.int(index_to_i64(*delete)?))?;
+                    m.entry("insert", |e| {
+                        e.array(insert.len(), |ia| {
+                            for v in insert {
+                                ia.raw_value_ref(*v)?;
+                            }
+                            Ok(())
+                        })
+                    })
+                })?,
+            }
+        }
+        Ok(())
+    })?;
+    enc.into_canonical()
+}
+
+fn entry_path(m: &mut MapEncoder<'_>, path: &[PathElem<'_>]) -> Result<(), CborError> {
+    m.entry("path", |e| {
+        e.array(path.len(), |a| {
+            for pe in path {
+                match pe {
+                    PathElem::Key(k) => a.text(k)?,
+                    PathElem::Index(i) => a.int(index_to_i64(*i)?)?,
+                }
+            }
+            Ok(())
+        })
+    })
+}
+
+fn index_to_i64(v: usize) -> Result<i64, CborError> {
+    i64::try_from(v).map_err(|_| CborError::new(ErrorCode::LengthOverflow, 0))
+}
@@ -9,6 +9,7 @@ use crate::encode::{ArrayEncoder, MapEncoder};
 use crate::profile::{checked_text_len, cmp_text_keys_canonical};
 use crate::query::{CborValueRef, PathElem};
 use crate::scalar::F64Bits;
+use crate::wire;
 use crate::{CborError, Encoder, ErrorCode};
 
 const fn err(code: ErrorCode, offset: usize) -> CborError {
@@ -45,6 +46,34 @@ const fn length_overflow(offset: usize) -> CborError {
     err(ErrorCode::LengthOverflow, offset)
 }
 
+#[cold]
+#[inline(never)]
+const fn patch_shape(offset: usize) -> CborError {
+    err(ErrorCode::InvalidQuery, offset)
+}
+
+fn patch_uint(v: CborValueRef<'_>) -> Result<usize, CborError> {
+    v.integer()
+        .ok()
+        .and_then(crate::query::CborIntegerRef::as_i64)
+        .and_then(|i| usize::try_from(i).ok())
+        .ok_or_else(|| patch_shape(v.offset()))
+}
+
+fn patch_path(v: CborValueRef<'_>) -> Result<Vec<PathElem<'_>>, CborError> {
+    let items = v.array().map_err(|_| patch_shape(v.offset()))?;
+    let mut path = crate::alloc_util::try_vec_with_capacity(items.len(), v.offset())?;
+    for item in items.iter() {
+        let item = item?;
+        let elem = match item.text() {
+            Ok(key) => PathElem::Key(key),
+            Err(_) => PathElem::Index(patch_uint(item)?),
+        };
+        path.push(elem);
+    }
+    Ok(path)
+}
+
 /// Mode for map set operations.
 #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -281,6 +310,27 @@ impl<'a> Editor<'a> {
         )
     }
 
+    /// Record the operations of a serialized patch, as produced by [`crate::diff`].
+    ///
+    /// The patch is an array of operation maps (see [`crate::diff`] for the schema).
+    /// Each operation is recorded exactly like the corresponding programmatic call
+    /// (`set_raw`, `delete`, or `splice`), so conflicts and ordering rules are identical.
+    /// A `set` with an empty path replaces the whole document and must be the only
+    /// operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidQuery` if the patch does not match the schema, or the same errors
+    /// as the programmatic edit APIs (including `PatchConflict`).
+    pub fn apply_patch(&mut self, patch: CanonicalCborRef<'a>) -> Result<(), CborError> {
+        let root = patch.root();
+        let ops = root.array().map_err(|_| patch_shape(root.offset()))?;
+        for op in ops.iter() {
+            self.record_patch_op(op?)?;
+        }
+        Ok(())
+    }
+
     /// Apply all recorded edits and return updated canonical CBOR.
     ///
     /// # Errors
@@ -292,6 +342,82 @@ impl<'a> Editor<'a> {
         enc.into_canonical()
     }
 
+    /// Check the recorded edits against the source without producing any bytes.
+    ///
+    /// Runs the same path, bounds, and mode checks as [`Editor::apply`] (`MissingKey`,
+    /// `IndexOutOfBounds`, `PatchConflict`, `InvalidQuery`), so a successful result means
+    /// `apply` can only fail on encoding errors such as allocation failure.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error `apply` would report for an invalid or conflicting edit.
+    pub fn validate(&self) -> Result<(), CborError> {
+        self.estimated_len().map(|_| ())
+    }
+
+    /// Returns the byte length [`Editor::apply`] would produce, without encoding.
+    ///
+    /// The length is computed by the same traversal as [`Editor::validate`], from the sizes
+    /// of retained source values, new values, and re-encoded container headers (a length
+    /// crossing 23/24, 255/256, or 65535/65536 changes the header width).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error `apply` would report for an invalid or conflicting edit.
+    pub fn estimated_len(&self) -> Result<usize, CborError> {
+        measure_value(self.root, &self.ops, self.options)
+    }
+
+    fn record_patch_op(&mut self, op: CborValueRef<'a>) -> Result<(), CborError> {
+        let off = op.offset();
+        let map = op.map().map_err(|_| patch_shape(off))?;
+        let field = |key: &str| map.require(key).map_err(|_| patch_shape(off));
+        let kind = field("op")?.text().map_err(|_| patch_shape(off))?;
+        let path = patch_path(field("path")?)?;
+
+        let expected_len = match kind {
+            "set" => 3,
+            "delete" => 2,
+            "splice" => 5,
+            _ => return Err(patch_shape(off)),
+        };
+        if map.len() != expected_len {
+            return Err(patch_shape(off));
+        }
+
+        match kind {
+            "set" if path.is_empty() => {
+                if !self.ops.is_empty() {
+                    return Err(patch_conflict());
+                }
+                self.ops.terminal = Some(Terminal::Set {
+                    mode: SetMode::Upsert,
+                    value: EditValue::raw(field("value")?),
+                });
+                Ok(())
+            }
+            "set" => self.set_raw(&path, field("value")?),
+            "delete" => self.delete(&path),
+            _ => {
+                let pos = patch_uint(field("pos")?)?;
+                let delete = patch_uint(field("delete")?)?;
+                let insert = field("insert")?;
+                let items = insert.array().map_err(|_| patch_shape(insert.offset()))?;
+                let mut inserts = crate::alloc_util::try_vec_with_capacity(items.len(), off)?;
+                for item in items.iter() {
+                    inserts.push(EditValue::raw(item?));
+                }
+                let splice = ArraySplice {
+                    pos: ArrayPos::At(pos),
+                    delete,
+                    inserts,
+                    bounds: BoundsMode::Require,
+                };
+                self.ops.insert_splice(&path, splice)
+            }
+        }
+    }
+
     fn set_with_mode<T: EditEncode<'a>>(
         &mut self,
         path: &[PathElem<'_>],
@@ -522,6 +648,38 @@ impl<'a> ArraySpliceBuilder<'_, 'a, '_> {
         Ok(self)
     }
 
+    /// Insert every value from `values`, in order, reserving once for the iterator's size hint.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` if encoding any value fails, or on allocation failure.
+    pub fn insert_many<T, I>(mut self, values: I) -> Result<Self, CborError>
+    where
+        T: EditEncode<'a>,
+        I: IntoIterator<Item = T>,
+    {
+        let values = values.into_iter();
+        try_reserve(&mut self.inserts, values.size_hint().0, 0)?;
+        for value in values {
+            let value = value.into_value()?;
+            try_reserve(&mut self.inserts, 1, 0)?;
+            self.inserts.push(value);
+        }
+        Ok(self)
+    }
+
+    /// Insert raw canonical value references into the splice, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` on allocation failure.
+    pub fn insert_raw_many(mut self, values: &[CborValueRef<'a>]) -> Result<Self, CborError> {
+        try_reserve(&mut self.inserts, values.len(), 0)?;
+        self.inserts
+            .extend(values.iter().map(|&value| EditValue::raw(value)));
+        Ok(self)
+    }
+
     /// Insert a value encoded via `Encoder` into the splice.
     ///
     /// # Errors
@@ -631,6 +789,14 @@ impl<'a> EditValue<'a> {
     pub(crate) const fn bytes_owned(value: Vec<u8>) -> Self {
         Self(EditValueInner::BytesOwned(value))
     }
+
+    fn encoded_len(&self) -> usize {
+        match &self.0 {
+            EditValueInner::Raw(v) => v.len(),
+            EditValueInner::BytesRef(b) => b.len(),
+            EditValueInner::BytesOwned(b) => b.len(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -915,10 +1081,7 @@ fn emit_value<'a, E: ValueEncoder>(
     }
 
     if let Some(term) = node.terminal.as_ref() {
-        return match term {
-            Terminal::Set { value, .. } => write_new_value(enc, value),
-            Terminal::Delete { .. } => Err(invalid_query()),
-        };
+        return write_new_value(enc, replacement(term)?);
     }
 
     match node.children {
@@ -928,24 +1091,203 @@ fn emit_value<'a, E: ValueEncoder>(
     }
 }
 
+/// The value a terminal writes in place of an existing or created value.
+const fn replacement<'t, 'a>(term: &'t Terminal<'a>) -> Result<&'t EditValue<'a>, CborError> {
+    match term {
+        Terminal::Set { value, .. } => Ok(value),
+        Terminal::Delete { .. } => Err(invalid_query()),
+    }
+}
+
+/// The value a terminal writes over an existing array item.
+const fn array_replacement<'t, 'a>(term: &'t Terminal<'a>) -> Result<&'t EditValue<'a>, CborError> {
+    match term {
+        Terminal::Set {
+            mode: SetMode::InsertOnly,
+            ..
+        }
+        | Terminal::Delete { .. } => Err(invalid_query()),
+        Terminal::Set { value, .. } => Ok(value),
+    }
+}
+
+/// Check `node` against `src` exactly as [`emit_value`] would and return the length it
+/// would write, without encoding.
+fn measure_value(
+    src: CborValueRef<'_>,
+    node: &Node<'_>,
+    options: EditOptions,
+) -> Result<usize, CborError> {
+    if node.is_empty() {
+        return Ok(src.len());
+    }
+
+    if let Some(term) = node.terminal.as_ref() {
+        return replacement(term).map(EditValue::encoded_len);
+    }
+
+    match node.children {
+        Children::None => Ok(src.len()),
+        Children::Keys(_) => measure_patched_map(src, node, options),
+        Children::Indices(_) => measure_patched_array(src, node, options),
+    }
+}
+
+fn measure_patched_map(
+    src: CborValueRef<'_>,
+    node: &Node<'_>,
+    options: EditOptions,
+) -> Result<usize, CborError> {
+    let Some((map, mods, out_len)) = plan_patched_map(src, node, options)? else {
+        return Ok(src.len());
+    };
+    let off = src.offset();
+    let mut size = Size::new(src.len(), off);
+    size.sub(wire::head_len(map.len()))?;
+    size.add(wire::head_len(out_len))?;
+
+    for (key, child) in mods {
+        let key_len = wire::head_len(key.len()) + key.len();
+        let old = map.get(key)?;
+        if let Some(old) = old {
+            size.sub(key_len + old.len())?;
+        }
+        // `plan_patched_map` has rejected every mode that conflicts with presence.
+        let new = match (child.terminal.as_ref(), old) {
+            (Some(Terminal::Delete { .. }), _) => continue,
+            (Some(term), _) => replacement(term)?.encoded_len(),
+            (None, Some(old)) => measure_value(old, child, options)?,
+            (None, None) => measure_created(child, options)?,
+        };
+        size.add(key_len)?;
+        size.add(new)?;
+    }
+    Ok(size.total)
+}
+
+fn measure_patched_array(
+    src: CborValueRef<'_>,
+    node: &Node<'_>,
+    options: EditOptions,
+) -> Result<usize, CborError> {
+    let Some(plan) = plan_patched_array(src, node)? else {
+        return Ok(src.len());
+    };
+    let mut size = Size::new(src.len(), src.offset());
+    size.sub(wire::head_len(plan.array.len()))?;
+    size.add(wire::head_len(plan.out_len))?;
+
+    let mut splices = plan.splices.iter().filter(|s| s.delete > 0).peekable();
+    let mut mods = plan.mods.iter().peekable();
+    for (idx, item) in plan.array.iter().enumerate() {
+        if splices.peek().is_none() && mods.peek().is_none() {
+            break;
+        }
+        let item = item?;
+        // Splices were bounds-checked against the array, so `start + delete` cannot overflow.
+        while splices.next_if(|s| s.start + s.delete <= idx).is_some() {}
+        if splices.peek().is_some_and(|s| s.start <= idx) {
+            size.sub(item.len())?;
+            continue;
+        }
+        if let Some((_, child)) = mods.next_if(|(m_idx, _)| *m_idx == idx) {
+            size.sub(item.len())?;
+            size.add(match child.terminal.as_ref() {
+                Some(term) => array_replacement(term)?.encoded_len(),
+                None => measure_value(item, child, options)?,
+            })?;
+        }
+    }
+
+    for splice in &plan.splices {
+        for value in splice.inserts {
+            size.add(value.encoded_len())?;
+        }
+    }
+    Ok(size.total)
+}
+
+/// Check a node that will be written as a newly created value, as [`emit_created_value`]
+/// would, and return its encoded length.
+fn measure_created(node: &Node<'_>, options: EditOptions) -> Result<usize, CborError> {
+    if let Some(term) = node.terminal.as_ref() {
+        return replacement(term).map(EditValue::encoded_len);
+    }
+    match node.children {
+        Children::Keys(_) => {
+            let mods = node.key_children(0)?;
+            let mut size = Size::new(wire::head_len(created_map_len(mods, options)?), 0);
+            for (key, child) in mods {
+                size.add(wire::head_len(key.len()) + key.len())?;
+                size.add(measure_created(child, options)?)?;
+            }
+            Ok(size.total)
+        }
+        _ => Err(invalid_query()),
+    }
+}
+
+/// Running output length with overflow reported at the container's offset.
+struct Size {
+    total: usize,
+    offset: usize,
+}
+
+impl Size {
+    const fn new(total: usize, offset: usize) -> Self {
+        Self { total, offset }
+    }
+
+    fn add(&mut self, n: usize) -> Result<(), CborError> {
+        self.total = self
+            .total
+            .checked_add(n)
+            .ok_or_else(|| length_overflow(self.offset))?;
+        Ok(())
+    }
+
+    fn sub(&mut self, n: usize) -> Result<(), CborError> {
+        self.total = self
+            .total
+            .checked_sub(n)
+            .ok_or_else(|| length_overflow(self.offset))?;
+        Ok(())
+    }
+}
+
 fn emit_patched_map<'a, E: ValueEncoder>(
     enc: &mut E,
     src: CborValueRef<'a>,
     node: &Node<'a>,
     options: EditOptions,
 ) -> Result<(), CborError> {
+    let Some((map, mods, out_len)) = plan_patched_map(src, node, options)? else {
+        return enc.raw_value_ref(src);
+    };
+    let map_off = src.offset();
+    enc.map(out_len, |menc| {
+        emit_map_entries(menc, map, mods, options, map_off)
+    })
+}
+
+type MapPlan<'n, 'a> = (crate::query::MapRef<'a>, &'n [(Box<str>, Node<'a>)], usize);
+
+/// Resolve map edits against `src` and return the output length, or `None` if unchanged.
+fn plan_patched_map<'n, 'a>(
+    src: CborValueRef<'a>,
+    node: &'n Node<'a>,
+    options: EditOptions,
+) -> Result<Option<MapPlan<'n, 'a>>, CborError> {
     let map = src.map()?;
     let map_off = src.offset();
     let mods = node.key_children(map_off)?;
 
     if mods.is_empty() {
-        return enc.raw_value_ref(src);
+        return Ok(None);
     }
 
     let out_len = compute_map_len_and_validate(map, mods, options, map_off)?;
-    enc.map(out_len, |menc| {
-        emit_map_entries(menc, map, mods, options, map_off)
-    })
+    Ok(Some((map, mods, out_len)))
 }
 
 fn emit_patched_array<'a, E: ValueEncoder>(
@@ -954,6 +1296,36 @@ fn emit_patched_array<'a, E: ValueEncoder>(
     node: &Node<'a>,
     options: EditOptions,
 ) -> Result<(), CborError> {
+    let Some(plan) = plan_patched_array(src, node)? else {
+        return enc.raw_value_ref(src);
+    };
+    let array_off = src.offset();
+    let len = plan.array.len();
+    enc.array(plan.out_len, |aenc| {
+        emit_array_items(
+            aenc,
+            plan.array,
+            plan.mods,
+            &plan.splices,
+            options,
+            array_off,
+            len,
+        )
+    })
+}
+
+struct ArrayPlan<'n, 'a> {
+    array: crate::query::ArrayRef<'a>,
+    mods: &'n [(usize, Node<'a>)],
+    splices: Vec<ResolvedSplice<'n>>,
+    out_len: usize,
+}
+
+/// Resolve array edits against `src` and return the output length, or `None` if unchanged.
+fn plan_patched_array<'n, 'a: 'n>(
+    src: CborValueRef<'a>,
+    node: &'n Node<'a>,
+) -> Result<Option<ArrayPlan<'n, 'a>>, CborError> {
     let array = src.array()?;
     let len = array.len();
     let array_off = src.offset();
@@ -961,7 +1333,7 @@ fn emit_patched_array<'a, E: ValueEncoder>(
     let splices = collect_splices(node, len, array_off)?;
 
     if mods.is_empty() && splices.is_empty() {
-        return enc.raw_value_ref(src);
+        return Ok(None);
     }
 
     if let Some(max) = mods.last().map(|m| m.0) {
@@ -972,10 +1344,12 @@ fn emit_patched_array<'a, E: ValueEncoder>(
 
     ensure_splice_mod_conflicts(mods, &splices, array_off)?;
     let out_len = compute_array_out_len(len, &splices, array_off)?;
-
-    enc.array(out_len, |aenc| {
-        emit_array_items(aenc, array, mods, &splices, options, array_off, len)
-    })
+    Ok(Some(ArrayPlan {
+        array,
+        mods,
+        splices,
+        out_len,
+    }))
 }
 
 fn ensure_splice_mod_conflicts<'a>(
@@ -1067,15 +1441,7 @@ fn emit_array_items<'a, E: ValueEncoder>(
                 let m_entry = mods_iter.next().ok_or_else(invalid_query)?;
                 let m_node = &m_entry.1;
                 if let Some(term) = m_node.terminal.as_ref() {
-                    match term {
-                        Terminal::Delete { .. } => return Err(invalid_query()),
-                        Terminal::Set { mode, value } => {
-                            if *mode == SetMode::InsertOnly {
-                                return Err(invalid_query());
-                            }
-                            write_new_value(aenc, value)?;
-                        }
-                    }
+                    write_new_value(aenc, array_replacement(term)?)?;
                 } else {
                     emit_value(aenc, item, m_node, options)?;
                 }
@@ -1353,10 +1719,7 @@ fn emit_created_value<E: ValueEncoder>(
     options: EditOptions,
 ) -> Result<(), CborError> {
     if let Some(term) = node.terminal.as_ref() {
-        return match term {
-            Terminal::Set { value, .. } => write_new_value(enc, value),
-            Terminal::Delete { .. } => Err(invalid_query()),
-        };
+        return write_new_value(enc, replacement(term)?);
     }
 
     match node.children {
@@ -1371,7 +1734,31 @@ fn emit_created_map<E: ValueEncoder>(
     options: EditOptions,
 ) -> Result<(), CborError> {
     let mods = node.key_children(0)?;
+    let out_len = created_map_len(mods, options)?;
 
+    enc.map(out_len, |menc| {
+        for (key, child) in mods {
+            match child.terminal.as_ref() {
+                Some(Terminal::Delete { .. }) => return Err(invalid_query()),
+                Some(Terminal::Set { value, .. }) => {
+                    menc.entry(key.as_ref(), |venc| write_new_value(venc, value))?;
+                }
+                None => {
+                    menc.entry(key.as_ref(), |venc| {
+                        emit_created_value(venc, child, options)
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Count the entries of a newly created map, rejecting edits that need an existing value.
+fn created_map_len(
+    mods: &[(Box<str>, Node<'_>)],
+    options: EditOptions,
+) -> Result<usize, CborError> {
     let mut out_len = 0usize;
     for (_key, child) in mods {
         match child.terminal.as_ref() {
@@ -1391,23 +1778,7 @@ fn emit_created_map<E: ValueEncoder>(
             },
         }
     }
-
-    enc.map(out_len, |menc| {
-        for (key, child) in mods {
-            match child.terminal.as_ref() {
-                Some(Terminal::Delete { .. }) => return Err(invalid_query()),
-                Some(Terminal::Set { value, .. }) => {
-                    menc.entry(key.as_ref(), |venc| write_new_value(venc, value))?;
-                }
-                None => {
-                    menc.entry(key.as_ref(), |venc| {
-                        emit_created_value(venc, child, options)
-                    })?;
-                }
-            }
-        }
-        Ok(())
-    })
+    Ok(out_len)
 }
 
 fn next_map_entry<'a, I>(iter: &mut I) -> Result<Option<(&'a str, CborValueRef<'a>)>, CborError>
@@ -0,0 +1,539 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+use crate::alloc_util::{try_reserve, try_vec_with_capacity};
+use crate::canonical::{CanonicalCbor, CanonicalCborRef};
+use crate::canonicalize::Node;
+use crate::codec::CborEncode;
+use crate::int::{decrement_be, increment_be, push_decimal, trim_be};
+use crate::limits::DEFAULT_MAX_DEPTH;
+use crate::profile::cmp_text_keys_canonical;
+use crate::query::{CborIntegerRef, CborKind, CborValueRef};
+use crate::scalar::F64Bits;
+use crate::{CborError, Encoder, ErrorCode};
+
+/// Export a canonical document as pretty-printed, editable JSON-like text.
+///
+/// The output is meant for humans to edit and feed back to [`from_editable_text`], which
+/// reproduces the original bytes exactly when nothing was changed. It is a JSON superset
+/// with the following mapping:
+///
+/// | CBOR | Text |
+/// |---|---|
+/// | null / bool | `null`, `true`, `false` |
+/// | safe integer | JSON number without fraction or exponent, e.g. `-42` |
+/// | bignum | decimal JSON string, e.g. `"18446744073709551616"` |
+/// | float | number with a `.` or exponent (`1.0`, `1e300`), or `NaN`, `Infinity`, `-Infinity` |
+/// | byte string | JSON string `"0x"` followed by lowercase hex, e.g. `"0x00ff"` |
+/// | text | JSON string (see below) |
+/// | array / map | JSON array / object, two-space indentation |
+///
+/// Strings use standard JSON escaping: `"` and `\` are backslash-escaped, `\n`, `\r`, `\t`,
+/// `\b` and `\f` use their short escapes, other control characters below U+0020 become
+/// `\u00XX`, and everything else is written as raw UTF-8. A text value whose contents begin
+/// with `'` or `0x`, or consist of an optional `-` followed only by ASCII digits, gets one
+/// extra leading `'` so it cannot be confused with bytes or a bignum. Map keys are always
+/// text and are never prefixed.
+///
+/// # Errors
+///
+/// Returns `CborError` if the document is malformed or allocation fails.
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub fn to_editable_text(doc: CanonicalCborRef<'_>) -> Result<String, CborError> {
+    let mut out = String::new();
+    write_value(&mut out, doc.root(), 0)?;
+    Ok(out)
+}
+
+/// Parse editable text produced by [`to_editable_text`] back into canonical CBOR.
+///
+/// Accepts the mapping documented on [`to_editable_text`] with a few conveniences for hand
+/// editing: object keys may appear in any order (they are re-sorted canonically),
+/// trailing commas are allowed, hex digits may be upper case, and integers of any size
+/// may be written either as bare numbers or as decimal strings; each is encoded as a safe
+/// integer or bignum as the profile requires. A JSON string is read as:
+///
+/// 1. text, if it starts with `'` (one leading `'` is removed);
+/// 2. a byte string, if it starts with `0x`;
+/// 3. an integer, if it is an optional `-` followed only by ASCII digits;
+/// 4. text otherwise.
+///
+/// # Errors
+///
+/// Returns `InvalidTextSyntax` at the byte offset of malformed input, `DuplicateMapKey`
+/// for repeated object keys, `DepthLimitExceeded` past the default nesting depth,
+/// `NegativeZeroForbidden` for `-0.0`, or an allocation error.
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub fn from_editable_text(text: &str) -> Result<CanonicalCbor, CborError> {
+    let mut parser = Parser {
+        src: text.as_bytes(),
+        pos: 0,
+    };
+    let node = parser.parse_value(0)?;
+    parser.skip_ws();
+    if parser.pos != parser.src.len() {
+        return Err(CborError::new(ErrorCode::TrailingBytes, parser.pos));
+    }
+    let mut enc = Encoder::new();
+    node.encode(&mut enc)?;
+    enc.into_canonical()
+}
+
+/* =========================
+ * Export
+ * ========================= */
+
+fn write_value(out: &mut String, value: CborValueRef<'_>, depth: usize) -> Result<(), CborError> {
+    let off = value.offset();
+    match value.kind()? {
+        CborKind::Null => out.push_str("null"),
+        CborKind::Bool => out.push_str(if value.bool()? { "true" } else { "false" }),
+        CborKind::Integer => match value.integer()? {
+            CborIntegerRef::Safe(v) => push_fmt(out, format_args!("{v}"), off)?,
+            // Out of the safe range, so written like a bignum of the same value.
+            CborIntegerRef::Wide(v) => push_fmt(out, format_args!("\"{v}\""), off)?,
+            CborIntegerRef::Big(b) => {
+                out.push('"');
+                if b.is_negative() {
+                    // Tag 3 encodes -1 - n, so the absolute value is n + 1.
+                    out.push('-');
+                    let mut abs = b.magnitude().to_vec();
+                    increment_be(&mut abs);
+                    push_decimal(out, abs)?;
+                } else {
+                    push_decimal(out, b.magnitude().to_vec())?;
+                }
+                out.push('"');
+            }
+        },
+        // Canonical documents never hold passthrough tags.
+        CborKind::Tag => return Err(CborError::new(ErrorCode::MalformedCanonical, off)),
+        CborKind::Float => {
+            let v = value.float64()?;
+            if v.is_nan() {
+                out.push_str("NaN");
+            } else if v.is_infinite() {
+                out.push_str(if v < 0.0 { "-Infinity" } else { "Infinity" });
+            } else {
+                // `Debug` yields the shortest round-tripping form and always keeps a `.`
+                // or exponent, which is what distinguishes floats from integers.
+                push_fmt(out, format_args!("{v:?}"), off)?;
+            }
+        }
+        CborKind::Bytes => {
+            let bytes = value.bytes()?;
+            out.push_str("\"0x");
+            for b in bytes {
+                push_fmt(out, format_args!("{b:02x}"), off)?;
+            }
+            out.push('"');
+        }
+        CborKind::Text => {
+            let s = value.text()?;
+            out.push('"');
+            if needs_text_prefix(s) {
+                out.push('\'');
+            }
+            push_escaped(out, s, off)?;
+            out.push('"');
+        }
+        CborKind::Array => {
+            let array = value.array()?;
+            if array.is_empty() {
+                out.push_str("[]");
+                return Ok(());
+            }
+            out.push('[');
+            for (i, item) in array.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                push_newline(out, depth + 1);
+                write_value(out, item?, depth + 1)?;
+            }
+            push_newline(out, depth);
+            out.push(']');
+        }
+        CborKind::Map => {
+            let map = value.map()?;
+            if map.is_empty() {
+                out.push_str("{}");
+                return Ok(());
+            }
+            out.push('{');
+            for (i, entry) in map.iter().enumerate() {
+                let (key, v) = entry?;
+                if i > 0 {
+                    out.push(',');
+                }
+                push_newline(out, depth + 1);
+                out.push('"');
+                push_escaped(out, key, off)?;
+                out.push_str("\": ");
+                write_value(out, v, depth + 1)?;
+            }
+            push_newline(out, depth);
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn push_fmt(out: &mut String, args: core::fmt::Arguments<'_>, off: usize) -> Result<(), CborError> {
+    out.write_fmt(args)
+        .map_err(|_| CborError::new(ErrorCode::WriteFailed, off))
+}
+
+fn push_newline(out: &mut String, depth: usize) {
+    out.push('\n');
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+fn push_escaped(out: &mut String, s: &str, off: usize) -> Result<(), CborError> {
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if c < ' ' => push_fmt(out, format_args!("\\u{:04x}", u32::from(c)), off)?,
+            c => out.push(c),
+        }
+    }
+    Ok(())
+}
+
+fn is_decimal_integer(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn needs_text_prefix(s: &str) -> bool {
+    s.starts_with('\'') || s.starts_with("0x") || is_decimal_integer(s)
+}
+
+/* =========================
+ * Import
+ * ========================= */
+
+struct Parser<'t> {
+    src: &'t [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    const fn err(&self, code: ErrorCode) -> CborError {
+        CborError::new(code, self.pos)
+    }
+
+    const fn syntax(&self) -> CborError {
+        self.err(ErrorCode::InvalidTextSyntax)
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.src.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, lit: &str) -> bool {
+        if self.src[self.pos..].starts_with(lit.as_bytes()) {
+            self.pos += lit.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Node, CborError> {
+        self.skip_ws();
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') => self.parse_object(depth),
+            Some(b'[') => self.parse_array(depth),
+            Some(b'"') => {
+                let s = self.parse_string()?;
+                classify_string(s, start)
+            }
+            Some(b'-' | b'0'..=b'9' | b'N' | b'I') => self.parse_number(),
+            _ if self.eat("null") => Ok(Node::Null),
+            _ if self.eat("true") => Ok(Node::Bool(true)),
+            _ if self.eat("false") => Ok(Node::Bool(false)),
+            _ => Err(self.syntax()),
+        }
+    }
+
+    const fn enter(&self, depth: usize) -> Result<usize, CborError> {
+        if depth >= DEFAULT_MAX_DEPTH {
+            return Err(self.err(ErrorCode::DepthLimitExceeded));
+        }
+        Ok(depth + 1)
+    }
+
+    /// Consume a `,` between elements; returns `true` when `close` ends the container.
+    fn list_sep(&mut self, close: u8) -> Result<bool, CborError> {
+        self.skip_ws();
+        match self.peek() {
+            Some(b',') => {
+                self.pos += 1;
+                self.skip_ws();
+                if self.peek() == Some(close) {
+                    self.pos += 1;
+                    return Ok(true);
+                }
+                Ok(false)
+            }
+            Some(c) if c == close => {
+                self.pos += 1;
+                Ok(true)
+            }
+            _ => Err(self.syntax()),
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Result<Node, CborError> {
+        let depth = self.enter(depth)?;
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Node::Array(items));
+        }
+        loop {
+            let item = self.parse_value(depth)?;
+            try_reserve(&mut items, 1, self.pos)?;
+            items.push(item);
+            if self.list_sep(b']')? {
+                return Ok(Node::Array(items));
+            }
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Result<Node, CborError> {
+        let depth = self.enter(depth)?;
+        self.pos += 1;
+        let mut entries: Vec<(usize, String, Node)> = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+        } else {
+            loop {
+                self.skip_ws();
+                let key_off = self.pos;
+                if self.peek() != Some(b'"') {
+                    return Err(self.syntax());
+                }
+                let key = self.parse_string()?;
+                self.skip_ws();
+                if !self.eat(":") {
+                    return Err(self.syntax());
+                }
+                let value = self.parse_value(depth)?;
+                try_reserve(&mut entries, 1, self.pos)?;
+                entries.push((key_off, key, value));
+                if self.list_sep(b'}')? {
+                    break;
+                }
+            }
+        }
+
+        entries.sort_by(|a, b| cmp_text_keys_canonical(&a.1, &b.1));
+        if let Some(w) = entries.windows(2).find(|w| w[0].1 == w[1].1) {
+            let off = w[0].0.max(w[1].0);
+            return Err(CborError::new(ErrorCode::DuplicateMapKey, off));
+        }
+        Ok(Node::Map(
+            entries.into_iter().map(|(_, k, v)| (k, v)).collect(),
+        ))
+    }
+
+    fn parse_number(&mut self) -> Result<Node, CborError> {
+        let start = self.pos;
+        if self.eat("NaN") {
+            return Ok(Node::Float(F64Bits::try_from_f64(f64::NAN)?));
+        }
+        let negative = self.eat("-");
+        if self.eat("Infinity") {
+            let v = if negative {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            };
+            return Ok(Node::Float(F64Bits::try_from_f64(v)?));
+        }
+
+        let mut is_float = false;
+        while let Some(c) = self.peek() {
+            match c {
+                b'0'..=b'9' => {}
+                b'.' | b'e' | b'E' | b'+' | b'-' => is_float = true,
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        let lit = core::str::from_utf8(&self.src[start..self.pos])
+            .map_err(|_| CborError::new(ErrorCode::InvalidTextSyntax, start))?;
+        if is_float {
+            let v: f64 = lit
+                .parse()
+                .map_err(|_| CborError::new(ErrorCode::InvalidTextSyntax, start))?;
+            let bits = F64Bits::try_from_f64(v).map_err(|e| CborError::new(e.code(), start))?;
+            return Ok(Node::Float(bits));
+        }
+        parse_integer(lit, start)
+    }
+
+    fn parse_string(&mut self) -> Result<String, CborError> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let run_start = self.pos;
+            while let Some(c) = self.peek() {
+                if c == b'"' || c == b'\\' || c < 0x20 {
+                    break;
+                }
+                self.pos += 1;
+            }
+            // Runs end on ASCII bytes, so they are always on a char boundary.
+            let run = core::str::from_utf8(&self.src[run_start..self.pos])
+                .map_err(|_| CborError::new(ErrorCode::Utf8Invalid, run_start))?;
+            out.push_str(run);
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = self.parse_escape()?;
+                    out.push(c);
+                }
+                _ => return Err(self.syntax()),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, CborError> {
+        let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'b') => '\u{08}',
+            Some(b'f') => '\u{0c}',
+            Some(b'u') => {
+                self.pos += 1;
+                let hi = self.parse_hex4()?;
+                let code = if (0xd800..0xdc00).contains(&hi) {
+                    if !self.eat("\\u") {
+                        return Err(self.syntax());
+                    }
+                    let lo = self.parse_hex4()?;
+                    if !(0xdc00..0xe000).contains(&lo) {
+                        return Err(self.syntax());
+                    }
+                    0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00)
+                } else {
+                    hi
+                };
+                return char::from_u32(code).ok_or_else(|| self.syntax());
+            }
+            _ => return Err(self.syntax()),
+        };
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, CborError> {
+        let mut v = 0u32;
+        for _ in 0..4 {
+            let d = self.peek().and_then(hex_val).ok_or_else(|| self.syntax())?;
+            v = (v << 4) | u32::from(d);
+            self.pos += 1;
+        }
+        Ok(v)
+    }
+}
+
+const fn hex_val(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn classify_string(s: String, off: usize) -> Result<Node, CborError> {
+    if let Some(rest) = s.strip_prefix('\'') {
+        return Ok(Node::Text(String::from(rest)));
+    }
+    if let Some(hex) = s.strip_prefix("0x") {
+        let hex = hex.as_bytes();
+        if hex.len() % 2 != 0 {
+            return Err(CborError::new(ErrorCode::InvalidTextSyntax, off));
+        }
+        let mut out = try_vec_with_capacity(hex.len() / 2, off)?;
+        for pair in hex.chunks_exact(2) {
+            match (hex_val(pair[0]), hex_val(pair[1])) {
+                (Some(h), Some(l)) => out.push((h << 4) | l),
+                _ => return Err(CborError::new(ErrorCode::InvalidTextSyntax, off)),
+            }
+        }
+        return Ok(Node::Bytes(out));
+    }
+    if is_decimal_integer(&s) {
+        return parse_integer(&s, off);
+    }
+    Ok(Node::Text(s))
+}
+
+/// Parse `-?[0-9]+` of any length into an integer node.
+fn parse_integer(lit: &str, off: usize) -> Result<Node, CborError> {
+    if !is_decimal_integer(lit) {
+        return Err(CborError::new(ErrorCode::InvalidTextSyntax, off));
+    }
+    let (negative, digits) = lit.strip_prefix('-').map_or((false, lit), |d| (true, d));
+    let mut abs: Vec<u8> = Vec::new();
+    for d in digits.bytes() {
+        let mut carry = u16::from(d - b'0');
+        for b in abs.iter_mut().rev() {
+            let cur = u16::from(*b) * 10 + carry;
+            *b = cur.to_be_bytes()[1];
+            carry = cur >> 8;
+        }
+        if carry != 0 {
+            try_reserve(&mut abs, 1, off)?;
+            abs.insert(0, carry.to_be_bytes()[1]);
+        }
+    }
+    if abs.is_empty() {
+        // `-0` is plain zero.
+        return Ok(Node::Int {
+            negative: false,
+            n: abs,
+        });
+    }
+    // CBOR stores negative values as -1 - n.
+    if negative {
+        decrement_be(&mut abs);
+    }
+    Ok(Node::Int {
+        negative,
+        n: trim_be(&abs).to_vec(),
+    })
+}
@@ -1,22 +1,18 @@
-use crate::alloc_util::try_reserve;
+use crate::alloc_util::{try_reserve, try_vec_with_capacity};
 use crate::canonical::{CanonicalCbor, CanonicalCborRef, EncodedTextKey};
 use crate::codec::CborEncode;
-use crate::profile::{check_encoded_key_order, validate_bignum_bytes, validate_int_safe_i64};
+use crate::profile::{
+    check_encoded_key_order, cmp_encoded_key_bytes, validate_bignum_bytes, validate_int_safe_i64,
+};
 use crate::query::CborValueRef;
 use crate::scalar::F64Bits;
-use crate::{CborError, ErrorCode};
+use crate::wire::{
+    encode_bytes, encode_float64, encode_int, encode_major_len, encode_major_uint, encode_text,
+    Sink,
+};
+use crate::{CborError, DecodeLimits, ErrorCode};
 use alloc::vec::Vec;
 
-trait Sink {
-    fn write(&mut self, bytes: &[u8]) -> Result<(), CborError>;
-
-    fn write_u8(&mut self, byte: u8) -> Result<(), CborError> {
-        self.write(&[byte])
-    }
-
-    fn position(&self) -> usize;
-}
-
 struct VecSink {
     buf: Vec<u8>,
 }
@@ -67,69 +63,12 @@ impl Sink for VecSink {
     }
 }
 
-fn err_at<S: Sink>(sink: &S, code: ErrorCode) -> CborError {
-    CborError::new(code, sink.position())
-}
-
-fn encode_int<S: Sink>(sink: &mut S, v: i64) -> Result<(), CborError> {
-    if v >= 0 {
-        let u = u64::try_from(v).map_err(|_| err_at(sink, ErrorCode::LengthOverflow))?;
-        encode_major_uint(sink, 0, u)
-    } else {
-        let n_i128 = -1_i128 - i128::from(v);
-        let n_u64 = u64::try_from(n_i128).map_err(|_| err_at(sink, ErrorCode::LengthOverflow))?;
-        encode_major_uint(sink, 1, n_u64)
-    }
-}
-
-fn encode_bytes<S: Sink>(sink: &mut S, bytes: &[u8]) -> Result<(), CborError> {
-    encode_major_len(sink, 2, bytes.len())?;
-    sink.write(bytes)
-}
-
-fn encode_text<S: Sink>(sink: &mut S, s: &str) -> Result<(), CborError> {
-    // `str` guarantees valid UTF-8.
-    let b = s.as_bytes();
-    encode_major_len(sink, 3, b.len())?;
-    sink.write(b)
-}
-
-fn encode_float64<S: Sink>(sink: &mut S, bits: F64Bits) -> Result<(), CborError> {
-    let raw = bits.bits();
-    let mut buf = [0u8; 9];
-    buf[0] = 0xfb;
-    buf[1..9].copy_from_slice(&raw.to_be_bytes());
-    sink.write(&buf)
-}
-
-fn encode_major_len<S: Sink>(sink: &mut S, major: u8, len: usize) -> Result<(), CborError> {
-    let len_u64 = u64::try_from(len).map_err(|_| err_at(sink, ErrorCode::LengthOverflow))?;
-    encode_major_uint(sink, major, len_u64)
-}
-
-fn encode_major_uint<S: Sink>(sink: &mut S, major: u8, value: u64) -> Result<(), CborError> {
-    debug_assert!(major <= 7);
-    if value < 24 {
-        let v = u8::try_from(value).unwrap();
-        return sink.write_u8((major << 5) | v);
-    }
-    if value <= 0xff {
-        let v = u8::try_from(value).unwrap();
-        sink.write_u8((major << 5) | 24)?;
-        return sink.write_u8(v);
-    }
-    if value <= 0xffff {
-        let v = u16::try_from(value).unwrap();
-        sink.write_u8((major << 5) | 25)?;
-        return sink.write(&v.to_be_bytes());
-    }
-    if value <= 0xffff_ffff {
-        let v = u32::try_from(value).unwrap();
-        sink.write_u8((major << 5) | 26)?;
-        return sink.write(&v.to_be_bytes());
-    }
-    sink.write_u8((major << 5) | 27)?;
-    sink.write(&value.to_be_bytes())
+/// Opaque output position returned by [`Encoder::checkpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    len: usize,
+    root_done: bool,
+    root_end: usize,
 }
 
 /// Streaming encoder that writes canonical CBOR directly into a `Vec<u8>`.
@@ -140,6 +79,9 @@ pub struct Encoder {
     depth: usize,
     root_done: bool,
     root_end: usize,
+    poison: Option<CborError>,
+    /// Offset of the first integer [`Encoder::int_exact`] wrote outside the safe range.
+    out_of_profile: Option<usize>,
 }
 
 impl Encoder {
@@ -151,6 +93,8 @@ impl Encoder {
             depth: 0,
             root_done: false,
             root_end: 0,
+            poison: None,
+            out_of_profile: None,
         }
     }
 
@@ -162,9 +106,21 @@ impl Encoder {
             depth: 0,
             root_done: false,
             root_end: 0,
+            poison: None,
+            out_of_profile: None,
         }
     }
 
+    /// Reserve capacity for at least `additional` more bytes without panicking on OOM.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LengthOverflow` if the requested capacity overflows, or
+    /// `AllocationFailed` if the allocator cannot satisfy the request.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), CborError> {
+        self.sink.reserve(additional)
+    }
+
     /// Return the number of bytes written so far.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -178,27 +134,91 @@ impl Encoder {
     }
 
     /// Consume and return the encoded bytes.
+    ///
+    /// The buffer is returned as written: neither poisoning nor completeness is checked. Use
+    /// [`Encoder::try_into_vec`] or [`Encoder::into_canonical`] when the encoder may have
+    /// been poisoned by an under-filled [`OpenArray`]/[`OpenMap`].
     #[must_use]
     pub fn into_vec(self) -> Vec<u8> {
         self.sink.into_vec()
     }
 
+    /// Consume and return the encoded bytes, failing if the encoder is poisoned.
+    ///
+    /// Unlike [`Encoder::into_canonical`], this does not check that exactly one complete
+    /// item was encoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that poisoned the encoder, such as an under-filled [`OpenArray`].
+    pub fn try_into_vec(self) -> Result<Vec<u8>, CborError> {
+        if let Some(err) = self.poison {
+            return Err(err);
+        }
+        Ok(self.sink.into_vec())
+    }
+
+    /// Consume the encoder and write its bytes to `w`.
+    ///
+    /// Like [`Encoder::try_into_vec`], this does not check that a complete item was encoded;
+    /// see [`crate::WriterEncoder`] to stream without buffering the whole output.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `w`. A poisoned encoder writes nothing and fails with
+    /// `InvalidData`, carrying the poisoning [`CborError`] as the source.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn into_writer<W: std::io::Write>(self, mut w: W) -> std::io::Result<()> {
+        if let Some(err) = self.poison {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
+        }
+        w.write_all(&self.sink.buf)
+    }
+
     /// Consume and return canonical bytes as a `CanonicalCbor`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the buffer does not contain exactly one canonical CBOR item.
+    /// Returns an error if the buffer does not contain exactly one canonical CBOR item,
+    /// `MalformedCanonical` if an [`OpenArray`]/[`OpenMap`] was leaked without closing,
+    /// `IntegerOutsideSafeRange` if [`Encoder::int_exact`] wrote an integer outside the
+    /// profile (use [`Encoder::into_vec`] for such output), or the error that poisoned the
+    /// encoder.
     pub fn into_canonical(self) -> Result<CanonicalCbor, CborError> {
+        if let Some(err) = self.poison {
+            return Err(err);
+        }
+        if let Some(off) = self.out_of_profile {
+            return Err(CborError::new(ErrorCode::IntegerOutsideSafeRange, off));
+        }
         if self.depth != 0 {
             return Err(CborError::new(
-                ErrorCode::UnexpectedEof,
+                ErrorCode::MalformedCanonical,
                 self.sink.position(),
             ));
         }
         if !self.root_done {
             return Err(CborError::new(ErrorCode::UnexpectedEof, 0));
         }
-        Ok(CanonicalCbor::new_unchecked(self.into_vec()))
+        Ok(CanonicalCbor::new_unchecked(self.sink.into_vec()))
+    }
+
+    /// Consume the encoder and return its SHA-256 digest alongside the canonical bytes.
+    ///
+    /// The digest is computed over the finished buffer, not during encoding: length
+    /// headers of open containers and entry reordering can still rewrite earlier bytes
+    /// until the encoding is complete. This is a convenience over
+    /// `into_canonical` + `sha256`, not a way to avoid reading the bytes a second time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer does not contain exactly one canonical CBOR item.
+    #[cfg(feature = "sha2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+    pub fn into_sha256(self) -> Result<([u8; 32], Vec<u8>), CborError> {
+        let canon = self.into_canonical()?;
+        Ok((canon.sha256(), canon.into_bytes()))
     }
 
     /// Clear the encoder while retaining allocated capacity.
@@ -207,6 +227,8 @@ impl Encoder {
         self.depth = 0;
         self.root_done = false;
         self.root_end = 0;
+        self.poison = None;
+        self.out_of_profile = None;
     }
 
     /// Borrow the bytes emitted so far.
@@ -217,6 +239,9 @@ impl Encoder {
 
     #[inline]
     const fn begin_value(&self) -> Result<bool, CborError> {
+        if let Some(err) = self.poison {
+            return Err(err);
+        }
         if self.depth == 0 {
             if self.root_done {
                 return Err(CborError::new(ErrorCode::TrailingBytes, self.root_end));
@@ -341,6 +366,29 @@ impl Encoder {
         encode_bytes(&mut self.sink, b)
     }
 
+    fn emit_bytes_chunks<'c>(
+        &mut self,
+        total_len: usize,
+        chunks: impl IntoIterator<Item = &'c [u8]>,
+    ) -> Result<(), CborError> {
+        let off = self.sink.position();
+        let mismatch = || CborError::new(ErrorCode::LengthOverflow, off);
+        encode_major_len(&mut self.sink, 2, total_len)?;
+        let mut written = 0usize;
+        for chunk in chunks {
+            written = written
+                .checked_add(chunk.len())
+                .filter(|&n| n <= total_len)
+                .ok_or_else(mismatch)?;
+            self.sink.write(chunk)?;
+        }
+        if written == total_len {
+            Ok(())
+        } else {
+            Err(mismatch())
+        }
+    }
+
     #[inline]
     pub(crate) fn emit_text(&mut self, s: &str) -> Result<(), CborError> {
         encode_text(&mut self.sink, s)
@@ -366,9 +414,45 @@ impl Encoder {
         self.sink.buf.len()
     }
 
-    #[cfg(feature = "serde")]
+    /// Drop output past `len`, forgetting an out-of-profile integer written there.
     pub(crate) fn truncate(&mut self, len: usize) {
         self.sink.buf.truncate(len);
+        if self.out_of_profile.is_some_and(|off| off >= len) {
+            self.out_of_profile = None;
+        }
+    }
+
+    /// Record the current output position so partial writes can be undone.
+    ///
+    /// Pair with [`Encoder::rollback`] in hand-written [`CborEncode`] impls that may fail
+    /// midway.
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            len: self.sink.buf.len(),
+            root_done: self.root_done,
+            root_end: self.root_end,
+        }
+    }
+
+    /// Discard everything written since `cp` was taken.
+    ///
+    /// Only output bytes and root-item tracking are restored; roll back at the same
+    /// nesting level the checkpoint was taken at, for example inside the same
+    /// [`CborEncode::encode`] call. A checkpoint ahead of the current output (taken before
+    /// a later [`Encoder::clear`] or rollback) is a caller bug: it panics in debug builds
+    /// and is ignored in release builds.
+    pub fn rollback(&mut self, cp: Checkpoint) {
+        debug_assert!(
+            cp.len <= self.sink.buf.len(),
+            "checkpoint is past the end of the encoder output"
+        );
+        if cp.len > self.sink.buf.len() {
+            return;
+        }
+        self.truncate(cp.len);
+        self.root_done = cp.root_done;
+        self.root_end = cp.root_end;
     }
 
     /// Encode CBOR null.
@@ -427,6 +511,11 @@ impl Encoder {
 
     /// Encode a signed integer, using a bignum when outside the safe range.
     ///
+    /// The safe range is inclusive on both ends: `-(2^53-1)..=2^53-1` is emitted as a major
+    /// 0/1 integer, and anything beyond it (starting at `±2^53`) as a tag 2/3 bignum with a
+    /// minimal magnitude. This is the exact split strict validation enforces, so the output is
+    /// always canonical.
+    ///
     /// # Errors
     ///
     /// Returns an error if encoding fails or allocation for the bignum magnitude fails.
@@ -455,6 +544,37 @@ impl Encoder {
         self.bignum(negative, &magnitude)
     }
 
+    /// Encode an integer as a plain major 0/1 item, never promoting to a bignum.
+    ///
+    /// This is a deliberate relaxation of the SACP-CBOR/1 profile for protocols that want
+    /// full 64-bit integers: values outside the safe range produce bytes that strict
+    /// validation rejects. Decode them with [`crate::DecodeLimits::allow_full_int_range`]
+    /// set, and take the output with [`Encoder::into_vec`]: once such an integer has been
+    /// written, [`Encoder::into_canonical`] refuses to certify the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IntegerOutsideSafeRange` if `v` does not fit the CBOR 64-bit integer range
+    /// (`-2^64..=2^64-1`), or an error if encoding fails.
+    pub fn int_exact(&mut self, v: i128) -> Result<(), CborError> {
+        let (major, arg) = if v >= 0 {
+            (0, u64::try_from(v))
+        } else {
+            (1, u64::try_from(-1 - v))
+        };
+        let arg = arg.map_err(|_| {
+            CborError::new(ErrorCode::IntegerOutsideSafeRange, self.sink.position())
+        })?;
+        let root = self.begin_value()?;
+        let off = self.sink.position();
+        encode_major_uint(&mut self.sink, major, arg)?;
+        if validate_int_safe_i64(i64::try_from(v).unwrap_or(i64::MAX)).is_err() {
+            self.out_of_profile.get_or_insert(off);
+        }
+        self.finish_value(root);
+        Ok(())
+    }
+
     /// Encode a CBOR bignum (tag 2/3 + byte string magnitude).
     ///
     /// # Errors
@@ -479,6 +599,34 @@ impl Encoder {
         Ok(())
     }
 
+    /// Encode one definite-length byte string of `total_len` bytes from `chunks`.
+    ///
+    /// The header is written once and the chunks are appended in order, so non-contiguous
+    /// pieces need no intermediate buffer. Nothing is written if the call fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LengthOverflow` if the chunk lengths do not sum to `total_len`, or an error
+    /// if writing fails.
+    pub fn bytes_from_chunks<'c>(
+        &mut self,
+        total_len: usize,
+        chunks: impl IntoIterator<Item = &'c [u8]>,
+    ) -> Result<(), CborError> {
+        let root = self.begin_value()?;
+        let cp = self.checkpoint();
+        match self.emit_bytes_chunks(total_len, chunks) {
+            Ok(()) => {
+                self.finish_value(root);
+                Ok(())
+            }
+            Err(err) => {
+                self.rollback(cp);
+                Err(err)
+            }
+        }
+    }
+
     /// Encode a text string.
     ///
     /// # Errors
@@ -515,6 +663,25 @@ impl Encoder {
         Ok(())
     }
 
+    /// Validate `bytes` as one canonical item under `limits`, then splice them as the next value.
+    ///
+    /// The checked counterpart of [`Encoder::raw_cbor`], for fragments of uncertain
+    /// provenance. The profile relaxations in `limits` (`allow_full_int_range`,
+    /// `extra_allowed_tags`) are ignored, so the output stays canonical. Nothing is written
+    /// if validation fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns the validation error (with offsets relative to `bytes`), or an error if
+    /// writing to the underlying buffer fails.
+    pub fn raw_cbor_checked(
+        &mut self,
+        bytes: &[u8],
+        limits: DecodeLimits,
+    ) -> Result<(), CborError> {
+        self.raw_cbor(crate::validate_canonical(bytes, limits)?)
+    }
+
     /// Splice a canonical sub-value reference.
     ///
     /// # Errors
@@ -539,11 +706,11 @@ impl Encoder {
         let root = self.begin_value()?;
         let start = self.sink.buf.len();
         if let Err(err) = encode_major_len(&mut self.sink, 4, len) {
-            self.sink.buf.truncate(start);
+            self.truncate(start);
             return Err(err);
         }
         if let Err(err) = self.reserve_min_array_items(len) {
-            self.sink.buf.truncate(start);
+            self.truncate(start);
             return Err(err);
         }
         self.enter_container();
@@ -557,12 +724,12 @@ impl Encoder {
         };
         self.exit_container();
         if let Err(err) = res {
-            self.sink.buf.truncate(start);
+            self.truncate(start);
             return Err(err);
         }
         if remaining != 0 {
             let err = CborError::new(ErrorCode::ArrayLenMismatch, self.sink.position());
-            self.sink.buf.truncate(start);
+            self.truncate(start);
             return Err(err);
         }
         self.finish_value(root);
@@ -584,43 +751,110 @@ impl Encoder {
     ///
     /// Returns an error if encoding fails or if the builder emits a different number of entries.
     pub fn map<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut MapEncoder<'_>) -> Result<(), CborError>,
+    {
+        self.map_impl(len, None, f)
+    }
+
+    /// Encode a definite-length map whose entries may be written in any order.
+    ///
+    /// Entries are written as they arrive while their byte ranges are recorded; once the
+    /// builder returns, the map body is reordered into canonical key order. Compared to
+    /// the strictly streaming [`Encoder::map`], this costs one span record per entry plus
+    /// a temporary copy of the encoded map body.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DuplicateMapKey` if two entries share a key, or an error if encoding fails
+    /// or the builder emits a different number of entries.
+    pub fn map_sorted<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut MapEncoder<'_>) -> Result<(), CborError>,
+    {
+        let spans = try_vec_with_capacity(len, self.sink.position())?;
+        self.map_impl(len, Some(spans), f)
+    }
+
+    fn map_impl<F>(
+        &mut self,
+        len: usize,
+        spans: Option<Vec<EntrySpan>>,
+        f: F,
+    ) -> Result<(), CborError>
     where
         F: FnOnce(&mut MapEncoder<'_>) -> Result<(), CborError>,
     {
         let root = self.begin_value()?;
         let start = self.sink.buf.len();
         if let Err(err) = encode_major_len(&mut self.sink, 5, len) {
-            self.sink.buf.truncate(start);
+            self.truncate(start);
             return Err(err);
         }
         if let Err(err) = self.reserve_min_map_items(len) {
-            self.sink.buf.truncate(start);
+            self.truncate(start);
             return Err(err);
         }
         self.enter_container();
-        let (res, remaining) = {
+        let (res, remaining, spans) = {
             let mut m = MapEncoder {
                 enc: self,
                 remaining: len,
                 prev_key_range: None,
+                spans,
             };
             let res = f(&mut m);
-            (res, m.remaining)
+            (res, m.remaining, m.spans)
         };
         self.exit_container();
         if let Err(err) = res {
-            self.sink.buf.truncate(start);
+            self.truncate(start);
             return Err(err);
         }
         if remaining != 0 {
             let err = CborError::new(ErrorCode::MapLenMismatch, self.sink.position());
-            self.sink.buf.truncate(start);
+            self.truncate(start);
             return Err(err);
         }
+        if let Some(spans) = spans {
+            if let Err(err) = self.reorder_entries(spans) {
+                self.truncate(start);
+                return Err(err);
+            }
+        }
         self.finish_value(root);
         Ok(())
     }
 
+    pub(crate) fn reorder_entries(&mut self, mut spans: Vec<EntrySpan>) -> Result<(), CborError> {
+        let Some(body_start) = spans.iter().map(|s| s.key_start).min() else {
+            return Ok(());
+        };
+        let buf = &mut self.sink.buf;
+        spans.sort_unstable_by(|a, b| {
+            cmp_encoded_key_bytes(&buf[a.key_start..a.key_end], &buf[b.key_start..b.key_end])
+        });
+        for pair in spans.windows(2) {
+            if buf[pair[0].key_start..pair[0].key_end] == buf[pair[1].key_start..pair[1].key_end] {
+                return Err(CborError::new(
+                    ErrorCode::DuplicateMapKey,
+                    pair[1].key_start,
+                ));
+            }
+        }
+
+        let body_len = buf.len() - body_start;
+        let mut body = try_vec_with_capacity(body_len, body_start)?;
+        body.extend_from_slice(&buf[body_start..]);
+        let mut pos = body_start;
+        for span in &spans {
+            let entry = &body[span.key_start - body_start..span.end - body_start];
+            buf[pos..pos + entry.len()].copy_from_slice(entry);
+            pos += entry.len();
+        }
+        Ok(())
+    }
+
     #[cfg(feature = "serde")]
     pub(crate) fn map_header(&mut self, len: usize) -> Result<bool, CborError> {
         let root = self.begin_value()?;
@@ -630,6 +864,129 @@ impl Encoder {
         Ok(root)
     }
 
+    /// Open a definite-length array and return a guard for writing its elements.
+    ///
+    /// This is the imperative counterpart of [`Encoder::array`]. The guard dereferences to
+    /// [`ArrayEncoder`]; the array is closed when the guard is dropped or
+    /// [`OpenArray::finish`]ed. Closing with fewer than `len` elements written poisons the
+    /// encoder: every later write and [`Encoder::into_canonical`] fail with
+    /// `ArrayLenMismatch`. Unlike [`Encoder::array`], nothing is rolled back.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the encoder is poisoned, a root item was already written, or
+    /// writing the header fails.
+    pub fn open_array(&mut self, len: usize) -> Result<OpenArray<'_>, CborError> {
+        let root = self.begin_value()?;
+        let start = self.sink.buf.len();
+        if let Err(err) = encode_major_len(&mut self.sink, 4, len)
+            .and_then(|()| self.reserve_min_array_items(len))
+        {
+            self.truncate(start);
+            return Err(err);
+        }
+        self.enter_container();
+        Ok(OpenArray {
+            inner: ArrayEncoder {
+                enc: self,
+                remaining: len,
+            },
+            root,
+        })
+    }
+
+    /// Open a definite-length map and return a guard for writing its entries.
+    ///
+    /// The map counterpart of [`Encoder::open_array`]: entries must still be written in
+    /// canonical key order, and closing with fewer than `len` entries poisons the encoder
+    /// with `MapLenMismatch`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the encoder is poisoned, a root item was already written, or
+    /// writing the header fails.
+    pub fn open_map(&mut self, len: usize) -> Result<OpenMap<'_>, CborError> {
+        let root = self.begin_value()?;
+        let start = self.sink.buf.len();
+        if let Err(err) =
+            encode_major_len(&mut self.sink, 5, len).and_then(|()| self.reserve_min_map_items(len))
+        {
+            self.truncate(start);
+            return Err(err);
+        }
+        self.enter_container();
+        Ok(OpenMap {
+            inner: MapEncoder {
+                enc: self,
+                remaining: len,
+                prev_key_range: None,
+                spans: None,
+            },
+            root,
+        })
+    }
+
+    /// Open a map whose length is counted as entries are written.
+    ///
+    /// A one-byte placeholder header is written up front and backpatched with the real entry
+    /// count when the returned guard is finished or dropped, so optional fields can be skipped
+    /// with [`MapEncoder::entry_if`] or [`MapEncoder::entry_opt`] without counting them first.
+    /// Keys must still be written in canonical order. Maps of 24 or more entries need a longer
+    /// header, which shifts the already-written entries once on close.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the encoder is poisoned, a root item was already written, or
+    /// writing the placeholder header fails.
+    pub fn map_builder(&mut self) -> Result<DeferredMapEncoder<'_>, CborError> {
+        let root = self.begin_value()?;
+        let header = self.sink.buf.len();
+        self.sink.write_u8(0xa0)?;
+        self.enter_container();
+        Ok(DeferredMapEncoder {
+            inner: MapEncoder {
+                enc: self,
+                remaining: usize::MAX,
+                prev_key_range: None,
+                spans: None,
+            },
+            root,
+            header,
+            closed: false,
+        })
+    }
+
+    /// Rewrite the placeholder header of a [`DeferredMapEncoder`] with the final count.
+    fn patch_map_header(&mut self, header: usize, len: usize) -> Result<(), CborError> {
+        if let Ok(small @ 0..=23) = u8::try_from(len) {
+            self.sink.buf[header] = 0xa0 | small;
+            return Ok(());
+        }
+        // Append the full header, then rotate it into place over the placeholder byte.
+        let end = self.sink.buf.len();
+        encode_major_len(&mut self.sink, 5, len).map_err(|err| {
+            self.truncate(end);
+            err
+        })?;
+        let header_len = self.sink.buf.len() - end;
+        self.sink.buf[header..].rotate_right(header_len);
+        self.sink.buf.remove(header + header_len);
+        if let Some(off) = self.out_of_profile.as_mut().filter(|off| **off > header) {
+            *off += header_len - 1;
+        }
+        Ok(())
+    }
+
+    /// Close a container opened by [`Encoder::open_array`] or [`Encoder::open_map`].
+    fn close_open_container(&mut self, root: bool, remaining: usize, code: ErrorCode) {
+        self.exit_container();
+        if remaining == 0 {
+            self.finish_value(root);
+        } else if self.poison.is_none() {
+            self.poison = Some(CborError::new(code, self.sink.position()));
+        }
+    }
+
     /// Internal hook used by `cbor_bytes!` for `$expr` values.
     #[doc(hidden)]
     #[allow(missing_docs)]
@@ -763,6 +1120,22 @@ impl ArrayEncoder<'_> {
         self.enc.emit_raw_cbor(v)
     }
 
+    /// Validate `bytes` as one canonical item under `limits`, then splice them as the next
+    /// array element. As with [`Encoder::raw_cbor_checked`], the profile relaxations in
+    /// `limits` are ignored. Nothing is written if validation fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns the validation error (with offsets relative to `bytes`), or an error if the
+    /// array length is exceeded or encoding fails.
+    pub fn raw_cbor_checked(
+        &mut self,
+        bytes: &[u8],
+        limits: DecodeLimits,
+    ) -> Result<(), CborError> {
+        self.raw_cbor(crate::validate_canonical(bytes, limits)?)
+    }
+
     /// Splice a canonical sub-value reference as the next array element.
     ///
     /// # Errors
@@ -783,6 +1156,23 @@ impl ArrayEncoder<'_> {
         value.encode(self.enc)
     }
 
+    /// Encode a value through its serde `Serialize` implementation.
+    ///
+    /// The value is serialized directly into this array, producing the same bytes as
+    /// [`crate::to_vec`] would for it standalone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the array length is exceeded or if serialization fails.
+    #[cfg(feature = "serde")]
+    pub fn serde_value<T: ?Sized + serde::Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), CborError> {
+        self.consume_one()?;
+        crate::serde_impl::serialize_into(self.enc, value)
+    }
+
     /// Encode a nested array.
     ///
     /// # Errors
@@ -809,6 +1199,21 @@ impl ArrayEncoder<'_> {
         self.enc.map(len, f)
     }
 
+    /// Encode a nested map whose entries may be written in any order.
+    ///
+    /// See [`Encoder::map_sorted`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the array length is exceeded or if encoding fails.
+    pub fn map_sorted<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut MapEncoder<'_>) -> Result<(), CborError>,
+    {
+        self.consume_one()?;
+        self.enc.map_sorted(len, f)
+    }
+
     #[doc(hidden)]
     #[allow(missing_docs)]
     pub fn __encode_any<T>(&mut self, v: T) -> Result<(), CborError>
@@ -825,6 +1230,14 @@ pub struct MapEncoder<'a> {
     enc: &'a mut Encoder,
     remaining: usize,
     prev_key_range: Option<(usize, usize)>,
+    spans: Option<Vec<EntrySpan>>,
+}
+
+/// Byte range of one written entry, recorded by [`Encoder::map_sorted`].
+pub struct EntrySpan {
+    pub key_start: usize,
+    pub key_end: usize,
+    pub end: usize,
 }
 
 #[allow(missing_docs)]
@@ -854,6 +1267,9 @@ impl MapEncoder<'_> {
         key_start: usize,
         key_end: usize,
     ) -> Result<(), CborError> {
+        if self.spans.is_some() {
+            return Ok(());
+        }
         if let Some((ps, pe)) = self.prev_key_range {
             let prev = &self.enc.sink.buf[ps..pe];
             let curr = &self.enc.sink.buf[key_start..key_end];
@@ -874,6 +1290,13 @@ impl MapEncoder<'_> {
         if let Err(err) = res {
             return self.fail_entry(entry_start, err);
         }
+        if let Some(spans) = &mut self.spans {
+            spans.push(EntrySpan {
+                key_start,
+                key_end,
+                end: self.enc.sink.buf.len(),
+            });
+        }
         self.prev_key_range = Some((key_start, key_end));
         self.remaining -= 1;
         Ok(())
@@ -890,7 +1313,7 @@ impl MapEncoder<'_> {
     }
 
     fn fail_entry<T>(&mut self, entry_start: usize, err: CborError) -> Result<T, CborError> {
-        self.enc.sink.buf.truncate(entry_start);
+        self.enc.truncate(entry_start);
         Err(err)
     }
 
@@ -906,9 +1329,79 @@ impl MapEncoder<'_> {
         self.write_entry(|sink| encode_text(sink, key), f)
     }
 
+    /// Insert a map entry only when `cond` holds, returning whether it was written.
+    ///
+    /// Like [`MapEncoder::entry_opt`], a skipped entry does not count toward the map length;
+    /// pair it with [`Encoder::map_builder`] to have the length counted for you.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails, if keys are out of order, or if duplicates are found.
+    pub fn entry_if<F>(&mut self, cond: bool, key: &str, f: F) -> Result<bool, CborError>
+    where
+        F: FnOnce(&mut Encoder) -> Result<(), CborError>,
+    {
+        if !cond {
+            return Ok(false);
+        }
+        self.entry(key, f)?;
+        Ok(true)
+    }
+
+    /// Insert a map entry only when `value` is `Some`, returning whether it was written.
+    ///
+    /// `None` writes nothing and does not count toward the map length, so the length
+    /// passed to [`Encoder::map`] must be the number of entries actually emitted
+    /// (count the `Some` values up front).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails, if keys are out of order, or if duplicates are found.
+    pub fn entry_opt<T: CborEncode>(
+        &mut self,
+        key: &str,
+        value: &Option<T>,
+    ) -> Result<bool, CborError> {
+        let Some(value) = value else {
+            return Ok(false);
+        };
+        self.entry(key, |enc| value.encode(enc))?;
+        Ok(true)
+    }
+
+    /// Insert a map entry whose value is encoded through serde `Serialize`.
+    ///
+    /// The value is serialized directly into this map, producing the same bytes as
+    /// [`crate::to_vec`] would for it standalone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails, if keys are out of order, or if duplicates are found.
+    #[cfg(feature = "serde")]
+    pub fn entry_serde<T: ?Sized + serde::Serialize>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), CborError> {
+        self.entry(key, |enc| crate::serde_impl::serialize_into(enc, value))
+    }
+
+    /// Insert a map entry whose value is copied verbatim from canonical bytes.
+    ///
+    /// For splicing an already-encoded sub-message without decoding and re-encoding it.
+    /// Key order is checked as for [`MapEncoder::entry`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails, if keys are out of order, or if duplicates are found.
+    pub fn entry_raw(&mut self, key: &str, value: CanonicalCborRef<'_>) -> Result<(), CborError> {
+        self.entry(key, |enc| enc.raw_cbor(value))
+    }
+
     /// Insert a map entry using a pre-encoded canonical text key.
     ///
-    /// This avoids re-encoding keys when splicing from validated canonical bytes.
+    /// This avoids re-encoding keys when splicing from validated canonical bytes, or when
+    /// streaming many records with the same keys prepared once by [`EncodedTextKey::encode`].
     ///
     /// # Errors
     ///
@@ -921,3 +1414,184 @@ impl MapEncoder<'_> {
         self.write_entry(|sink| sink.write(key_bytes), f)
     }
 }
+
+/// Guard for an array opened with [`Encoder::open_array`].
+///
+/// Dereferences to [`ArrayEncoder`] for writing elements. Dropping the guard closes the
+/// array; if fewer elements than declared were written, the encoder is poisoned.
+pub struct OpenArray<'a> {
+    inner: ArrayEncoder<'a>,
+    root: bool,
+}
+
+impl OpenArray<'_> {
+    /// Number of elements still expected.
+    #[must_use]
+    pub const fn remaining(&self) -> usize {
+        self.inner.remaining
+    }
+
+    /// Close the array, reporting a miscount instead of only poisoning the encoder.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArrayLenMismatch` if fewer elements than declared were written.
+    pub fn finish(self) -> Result<(), CborError> {
+        if self.inner.remaining == 0 {
+            return Ok(());
+        }
+        Err(CborError::new(
+            ErrorCode::ArrayLenMismatch,
+            self.inner.enc.sink.position(),
+        ))
+    }
+}
+
+impl<'a> core::ops::Deref for OpenArray<'a> {
+    type Target = ArrayEncoder<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl core::ops::DerefMut for OpenArray<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl Drop for OpenArray<'_> {
+    fn drop(&mut self) {
+        let remaining = self.inner.remaining;
+        self.inner
+            .enc
+            .close_open_container(self.root, remaining, ErrorCode::ArrayLenMismatch);
+    }
+}
+
+/// Guard for a map opened with [`Encoder::open_map`].
+///
+/// Dereferences to [`MapEncoder`] for writing entries. Dropping the guard closes the map;
+/// if fewer entries than declared were written, the encoder is poisoned.
+pub struct OpenMap<'a> {
+    inner: MapEncoder<'a>,
+    root: bool,
+}
+
+impl OpenMap<'_> {
+    /// Number of entries still expected.
+    #[must_use]
+    pub const fn remaining(&self) -> usize {
+        self.inner.remaining
+    }
+
+    /// Close the map, reporting a miscount instead of only poisoning the encoder.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MapLenMismatch` if fewer entries than declared were written.
+    pub fn finish(self) -> Result<(), CborError> {
+        if self.inner.remaining == 0 {
+            return Ok(());
+        }
+        Err(CborError::new(
+            ErrorCode::MapLenMismatch,
+            self.inner.enc.sink.position(),
+        ))
+    }
+}
+
+impl<'a> core::ops::Deref for OpenMap<'a> {
+    type Target = MapEncoder<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl core::ops::DerefMut for OpenMap<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl Drop for OpenMap<'_> {
+    fn drop(&mut self) {
+        let remaining = self.inner.remaining;
+        self.inner
+            .enc
+            .close_open_container(self.root, remaining, ErrorCode::MapLenMismatch);
+    }
+}
+
+/// Guard returned by [`Encoder::map_builder`] for a map whose length is backpatched on close.
+///
+/// Dereferences to [`MapEncoder`] for writing entries. Dropping the guard closes the map just
+/// like [`DeferredMapEncoder::finish`], but an error while patching the header can then only
+/// poison the encoder.
+pub struct DeferredMapEncoder<'a> {
+    inner: MapEncoder<'a>,
+    root: bool,
+    header: usize,
+    closed: bool,
+}
+
+impl DeferredMapEncoder<'_> {
+    /// Number of entries written so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        usize::MAX - self.inner.remaining
+    }
+
+    /// Returns `true` if no entries have been written yet.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Close the map, writing the final entry count into its header.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AllocationFailed` if growing the header fails; the encoder is then poisoned.
+    pub fn finish(mut self) -> Result<(), CborError> {
+        self.close()
+    }
+
+    fn close(&mut self) -> Result<(), CborError> {
+        self.closed = true;
+        let len = self.len();
+        let enc = &mut *self.inner.enc;
+        enc.exit_container();
+        match enc.patch_map_header(self.header, len) {
+            Ok(()) => {
+                enc.finish_value(self.root);
+                Ok(())
+            }
+            Err(err) => Err(*enc.poison.get_or_insert(err)),
+        }
+    }
+}
+
+impl<'a> core::ops::Deref for DeferredMapEncoder<'a> {
+    type Target = MapEncoder<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl core::ops::DerefMut for DeferredMapEncoder<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl Drop for DeferredMapEncoder<'_> {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self.close();
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use core::fmt;
 
+use crate::query::CborKind;
+
 /// A structured error code identifying the reason a CBOR item was rejected.
 ///
-/// This enum is intentionally stable and string-free to support `no_std` and to remain hot-path friendly.
+/// This enum is intentionally stable and allocation-free to support `no_std` and to remain
+/// hot-path friendly. Codes are plain discriminants; the only strings are the `&'static str`
+/// returned by [`ErrorCode::as_str`] and [`ErrorCode::message`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum ErrorCode {
@@ -21,6 +25,10 @@ pub enum ErrorCode {
     ArrayLenMismatch,
     /// Map builder length mismatch (encoder).
     MapLenMismatch,
+    /// An I/O error occurred while reading input or writing output.
+    IoError,
+    /// The caller-provided output buffer is too small (fixed-buffer encoder).
+    BufferTooSmall,
 
     /// Nesting depth limit exceeded.
     DepthLimitExceeded,
@@ -36,8 +44,11 @@ pub enum ErrorCode {
     TextLenLimitExceeded,
     /// Total input length exceeds limits.
     MessageLenLimitExceeded,
+    /// Map key length exceeds limits.
+    KeyLenLimitExceeded,
 
-    /// Reserved additional-info value (28..30) was used.
+    /// Reserved additional-info value (28..30), or a break outside an indefinite-length item,
+    /// was used.
     ReservedAdditionalInfo,
     /// Indefinite-length encoding was used where forbidden.
     IndefiniteLengthForbidden,
@@ -94,6 +105,15 @@ pub enum ErrorCode {
     ExpectedEnum,
     /// Unknown enum variant key.
     UnknownEnumVariant,
+    /// Expected a numeric value (integer or float).
+    ExpectedNumber,
+    /// A byte string has the wrong length for the target type (such as an IP address).
+    BytesLenMismatch,
+    /// A decoded value is outside the range the target type can represent (such as a
+    /// timestamp).
+    ValueOutOfRange,
+    /// Expected a passthrough tag admitted by `DecodeLimits::extra_allowed_tags`.
+    ExpectedTag,
 
     /// Patch operations overlap or conflict.
     PatchConflict,
@@ -104,93 +124,511 @@ pub enum ErrorCode {
     InvalidQuery,
     /// Required key missing from map.
     MissingKey,
+    /// Map contains a key outside the expected key set.
+    UnknownKey,
     /// Malformed canonical CBOR during query traversal.
     MalformedCanonical,
+
+    /// A formatting sink returned an error.
+    WriteFailed,
+
+    /// Malformed input to a text-format parser.
+    InvalidTextSyntax,
+}
+
+/// Coarse classification of an [`ErrorCode`], returned by [`ErrorCode::category`].
+///
+/// Lets callers map failures onto a response class (for example "payload too large" versus
+/// "bad request") without matching every code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// Input is not well-formed CBOR or text, or a call was malformed (invalid limits, a
+    /// builder miscount, an invalid query or patch).
+    Structural,
+    /// Input is well-formed CBOR but violates the SACP-CBOR/1 canonical profile.
+    Profile,
+    /// A configured decode limit, allocation, or output buffer was exhausted.
+    Limit,
+    /// Conversion between CBOR and a Rust type failed (serde, `CborDecode`, or a type mismatch).
+    Serde,
+    /// The underlying reader, writer, or formatter failed.
+    Io,
+}
+
+impl ErrorCode {
+    /// Returns the coarse category this code belongs to.
+    #[must_use]
+    pub const fn category(self) -> ErrorCategory {
+        match self {
+            Self::InvalidLimits
+            | Self::UnexpectedEof
+            | Self::LengthOverflow
+            | Self::TrailingBytes
+            | Self::ArrayLenMismatch
+            | Self::MapLenMismatch
+            | Self::ReservedAdditionalInfo
+            | Self::PatchConflict
+            | Self::IndexOutOfBounds
+            | Self::InvalidQuery
+            | Self::MalformedCanonical
+            | Self::InvalidTextSyntax => ErrorCategory::Structural,
+
+            Self::IndefiniteLengthForbidden
+            | Self::NonCanonicalEncoding
+            | Self::MapKeyMustBeText
+            | Self::DuplicateMapKey
+            | Self::NonCanonicalMapOrder
+            | Self::ForbiddenOrMalformedTag
+            | Self::BignumNotCanonical
+            | Self::BignumMustBeOutsideSafeRange
+            | Self::UnsupportedSimpleValue
+            | Self::IntegerOutsideSafeRange
+            | Self::Utf8Invalid
+            | Self::NegativeZeroForbidden
+            | Self::NonCanonicalNaN => ErrorCategory::Profile,
+
+            Self::AllocationFailed
+            | Self::BufferTooSmall
+            | Self::DepthLimitExceeded
+            | Self::TotalItemsLimitExceeded
+            | Self::ArrayLenLimitExceeded
+            | Self::MapLenLimitExceeded
+            | Self::BytesLenLimitExceeded
+            | Self::TextLenLimitExceeded
+            | Self::MessageLenLimitExceeded
+            | Self::KeyLenLimitExceeded => ErrorCategory::Limit,
+
+            Self::SerdeError
+            | Self::ExpectedMap
+            | Self::ExpectedArray
+            | Self::ExpectedInteger
+            | Self::ExpectedText
+            | Self::ExpectedBytes
+            | Self::ExpectedBool
+            | Self::ExpectedNull
+            | Self::ExpectedFloat
+            | Self::ExpectedEnum
+            | Self::UnknownEnumVariant
+            | Self::ExpectedNumber
+            | Self::BytesLenMismatch
+            | Self::ValueOutOfRange
+            | Self::ExpectedTag
+            | Self::MissingKey
+            | Self::UnknownKey => ErrorCategory::Serde,
+
+            Self::IoError | Self::WriteFailed => ErrorCategory::Io,
+        }
+    }
+}
+
+macro_rules! error_code_numbers {
+    ($($variant:ident = $num:literal => $label:literal,)*) => {
+        impl ErrorCode {
+            /// Returns the stable numeric identifier for this code.
+            ///
+            /// Numbers are assigned explicitly and never reused, so they are safe to send
+            /// across process or network boundaries. They are grouped by category:
+            ///
+            /// | Range | Category |
+            /// |---|---|
+            /// | `1` | configuration (`InvalidLimits`) |
+            /// | `100..` | input framing, I/O, allocation, and encoder builder errors |
+            /// | `200..` | decode limits |
+            /// | `300..` | canonical profile violations |
+            /// | `400..` | serde conversion |
+            /// | `500..` | type mismatches during decoding |
+            /// | `600..` | editing, querying, and formatting |
+            /// | `700..` | text-format parsing (such as JSON Pointer syntax) |
+            #[must_use]
+            pub const fn as_u16(self) -> u16 {
+                match self {
+                    $(Self::$variant => $num,)*
+                }
+            }
+
+            /// Returns a stable `SCREAMING_SNAKE_CASE` label for this code, such as
+            /// `"DUPLICATE_MAP_KEY"`.
+            ///
+            /// Labels follow the same stability rule as [`ErrorCode::as_u16`]: they are never
+            /// changed or reused, so they can be sent to clients alongside the number.
+            #[must_use]
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $label,)*
+                }
+            }
+
+            /// Looks up a code from its stable numeric identifier.
+            ///
+            /// Returns `None` for numbers that are not assigned (including codes added by a
+            /// newer version of this crate).
+            #[must_use]
+            pub const fn from_u16(num: u16) -> Option<Self> {
+                match num {
+                    $($num => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+
+        // Checked while compiling, so `no_std` builds cover it too: every code has a message
+        // and a label, and its number maps back to it (which also rules out shared numbers).
+        const _: () = {
+            let all = [$(ErrorCode::$variant,)*];
+            let mut i = 0;
+            while i < all.len() {
+                let code = all[i];
+                assert!(!code.message().is_empty(), "ErrorCode without a message");
+                assert!(!code.as_str().is_empty(), "ErrorCode without a label");
+                match ErrorCode::from_u16(code.as_u16()) {
+                    Some(back) if back as u16 == code as u16 => {}
+                    _ => panic!("ErrorCode number does not round-trip"),
+                }
+                i += 1;
+            }
+        };
+    };
+}
+
+error_code_numbers! {
+    InvalidLimits = 1 => "INVALID_LIMITS",
+
+    UnexpectedEof = 100 => "UNEXPECTED_EOF",
+    LengthOverflow = 101 => "LENGTH_OVERFLOW",
+    TrailingBytes = 102 => "TRAILING_BYTES",
+    AllocationFailed = 103 => "ALLOCATION_FAILED",
+    ArrayLenMismatch = 104 => "ARRAY_LEN_MISMATCH",
+    MapLenMismatch = 105 => "MAP_LEN_MISMATCH",
+    IoError = 106 => "IO_ERROR",
+    BufferTooSmall = 107 => "BUFFER_TOO_SMALL",
+
+    DepthLimitExceeded = 200 => "DEPTH_LIMIT_EXCEEDED",
+    TotalItemsLimitExceeded = 201 => "TOTAL_ITEMS_LIMIT_EXCEEDED",
+    ArrayLenLimitExceeded = 202 => "ARRAY_LEN_LIMIT_EXCEEDED",
+    MapLenLimitExceeded = 203 => "MAP_LEN_LIMIT_EXCEEDED",
+    BytesLenLimitExceeded = 204 => "BYTES_LEN_LIMIT_EXCEEDED",
+    TextLenLimitExceeded = 205 => "TEXT_LEN_LIMIT_EXCEEDED",
+    MessageLenLimitExceeded = 206 => "MESSAGE_LEN_LIMIT_EXCEEDED",
+    KeyLenLimitExceeded = 207 => "KEY_LEN_LIMIT_EXCEEDED",
+
+    ReservedAdditionalInfo = 300 => "RESERVED_ADDITIONAL_INFO",
+    IndefiniteLengthForbidden = 301 => "INDEFINITE_LENGTH_FORBIDDEN",
+    NonCanonicalEncoding = 302 => "NON_CANONICAL_ENCODING",
+    MapKeyMustBeText = 303 => "MAP_KEY_MUST_BE_TEXT",
+    DuplicateMapKey = 304 => "DUPLICATE_MAP_KEY",
+    NonCanonicalMapOrder = 305 => "NON_CANONICAL_MAP_ORDER",
+    ForbiddenOrMalformedTag = 306 => "FORBIDDEN_OR_MALFORMED_TAG",
+    BignumNotCanonical = 307 => "BIGNUM_NOT_CANONICAL",
+    BignumMustBeOutsideSafeRange = 308 => "BIGNUM_MUST_BE_OUTSIDE_SAFE_RANGE",
+    UnsupportedSimpleValue = 309 => "UNSUPPORTED_SIMPLE_VALUE",
+    IntegerOutsideSafeRange = 310 => "INTEGER_OUTSIDE_SAFE_RANGE",
+    Utf8Invalid = 311 => "UTF8_INVALID",
+    NegativeZeroForbidden = 312 => "NEGATIVE_ZERO_FORBIDDEN",
+    NonCanonicalNaN = 313 => "NON_CANONICAL_NAN",
+
+    SerdeError = 400 => "SERDE_ERROR",
+
+    ExpectedMap = 500 => "EXPECTED_MAP",
+    ExpectedArray = 501 => "EXPECTED_ARRAY",
+    ExpectedInteger = 502 => "EXPECTED_INTEGER",
+    ExpectedText = 503 => "EXPECTED_TEXT",
+    ExpectedBytes = 504 => "EXPECTED_BYTES",
+    ExpectedBool = 505 => "EXPECTED_BOOL",
+    ExpectedNull = 506 => "EXPECTED_NULL",
+    ExpectedFloat = 507 => "EXPECTED_FLOAT",
+    ExpectedEnum = 508 => "EXPECTED_ENUM",
+    UnknownEnumVariant = 509 => "UNKNOWN_ENUM_VARIANT",
+    ExpectedNumber = 510 => "EXPECTED_NUMBER",
+    BytesLenMismatch = 511 => "BYTES_LEN_MISMATCH",
+    ValueOutOfRange = 512 => "VALUE_OUT_OF_RANGE",
+    ExpectedTag = 513 => "EXPECTED_TAG",
+
+    PatchConflict = 600 => "PATCH_CONFLICT",
+    IndexOutOfBounds = 601 => "INDEX_OUT_OF_BOUNDS",
+    InvalidQuery = 602 => "INVALID_QUERY",
+    MissingKey = 603 => "MISSING_KEY",
+    MalformedCanonical = 604 => "MALFORMED_CANONICAL",
+    WriteFailed = 605 => "WRITE_FAILED",
+    UnknownKey = 606 => "UNKNOWN_KEY",
+
+    InvalidTextSyntax = 700 => "INVALID_TEXT_SYNTAX",
 }
 
 /// An SACP-CBOR/1 error with structured classification, a stable code, and a byte offset.
 ///
-/// Offsets refer to the byte position where the error was detected.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Offsets refer to the byte position where the error was detected. Fields are read through
+/// accessors so the layout can stay compact: the type is `Copy` and 32 bytes on 64-bit
+/// targets, with the secondary offset stored as a `u32`.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct CborError {
-    /// The error code.
-    pub code: ErrorCode,
-    /// Byte offset into the input where the error was detected.
-    pub offset: usize,
+    offset: usize,
+    context: Option<&'static str>,
+    /// Secondary offset, or `NO_DETAIL`.
+    detail: u32,
+    code: ErrorCode,
+    found: Option<CborKind>,
 }
 
+const NO_DETAIL: u32 = u32::MAX;
+
+#[cfg(target_pointer_width = "64")]
+const _: () = assert!(core::mem::size_of::<CborError>() <= 32);
+
 impl CborError {
     /// Construct a decode error at `offset`.
     #[inline]
     #[must_use]
     pub const fn new(code: ErrorCode, offset: usize) -> Self {
-        Self { code, offset }
+        Self {
+            offset,
+            context: None,
+            detail: NO_DETAIL,
+            code,
+            found: None,
+        }
+    }
+
+    /// The error code.
+    #[inline]
+    #[must_use]
+    pub const fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    /// Byte offset into the input where the error was detected.
+    #[inline]
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Optional static context (for example, the field being decoded).
+    #[inline]
+    #[must_use]
+    pub const fn context(&self) -> Option<&'static str> {
+        self.context
+    }
+
+    /// Optional secondary offset. For `DuplicateMapKey` and `NonCanonicalMapOrder` raised
+    /// while validating or decoding, this is the offset where the previous key starts.
+    #[inline]
+    #[must_use]
+    pub const fn detail(&self) -> Option<u32> {
+        if self.detail == NO_DETAIL {
+            None
+        } else {
+            Some(self.detail)
+        }
+    }
+
+    /// Returns `true` if a resource limit was hit ([`ErrorCategory::Limit`]) rather than the
+    /// input being invalid.
+    #[inline]
+    #[must_use]
+    pub const fn is_limit_error(&self) -> bool {
+        matches!(self.code.category(), ErrorCategory::Limit)
+    }
+
+    /// Returns `true` if the input was malformed or non-canonical
+    /// ([`ErrorCategory::Structural`] or [`ErrorCategory::Profile`]).
+    #[inline]
+    #[must_use]
+    pub const fn is_malformed(&self) -> bool {
+        matches!(
+            self.code.category(),
+            ErrorCategory::Structural | ErrorCategory::Profile
+        )
+    }
+
+    /// Attach a secondary offset, dropped if it does not fit below `u32::MAX`.
+    #[inline]
+    #[must_use]
+    pub fn with_detail(self, offset: usize) -> Self {
+        Self {
+            detail: u32::try_from(offset).unwrap_or(NO_DETAIL),
+            ..self
+        }
+    }
+
+    /// Set the secondary offset as already narrowed to `u32`.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub(crate) const fn with_detail_u32(self, detail: Option<u32>) -> Self {
+        Self {
+            detail: match detail {
+                Some(d) => d,
+                None => NO_DETAIL,
+            },
+            ..self
+        }
+    }
+
+    /// Attach the kind actually found, for an `Expected*` error.
+    #[inline]
+    #[must_use]
+    pub const fn with_found(self, kind: CborKind) -> Self {
+        Self {
+            found: Some(kind),
+            ..self
+        }
+    }
+
+    /// Returns the kind actually found, for an `Expected*` error raised by
+    /// [`crate::CborValueRef::expect`].
+    #[inline]
+    #[must_use]
+    pub const fn found_kind(&self) -> Option<CborKind> {
+        self.found
+    }
+
+    /// Move both offsets by `base`, for errors raised on a sub-slice of a larger input.
+    pub(crate) fn shifted(self, base: usize) -> Self {
+        Self {
+            offset: self.offset.saturating_add(base),
+            detail: self
+                .detail()
+                .and_then(|d| u32::try_from(base).ok().and_then(|b| d.checked_add(b)))
+                .unwrap_or(NO_DETAIL),
+            ..self
+        }
+    }
+
+    /// Attach a static context string, replacing any existing context.
+    #[inline]
+    #[must_use]
+    pub const fn with_context(self, ctx: &'static str) -> Self {
+        Self {
+            context: Some(ctx),
+            ..self
+        }
+    }
+
+    /// Attach `ctx` only if no context is set, so the innermost field wins.
+    #[doc(hidden)]
+    #[inline]
+    #[must_use]
+    pub const fn __field_context(self, ctx: &'static str) -> Self {
+        if self.context.is_some() {
+            self
+        } else {
+            self.with_context(ctx)
+        }
     }
 }
 
-impl fmt::Display for CborError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let msg = match self.code {
-            ErrorCode::InvalidLimits => "invalid CBOR limits",
-
-            ErrorCode::UnexpectedEof => "unexpected end of input",
-            ErrorCode::LengthOverflow => "length overflow",
-            ErrorCode::TrailingBytes => "trailing bytes after single CBOR item",
-            ErrorCode::AllocationFailed => "allocation failed",
-            ErrorCode::ArrayLenMismatch => "array length mismatch",
-            ErrorCode::MapLenMismatch => "map length mismatch",
-
-            ErrorCode::DepthLimitExceeded => "nesting depth limit exceeded",
-            ErrorCode::TotalItemsLimitExceeded => "total items limit exceeded",
-            ErrorCode::ArrayLenLimitExceeded => "array length exceeds decode limits",
-            ErrorCode::MapLenLimitExceeded => "map length exceeds decode limits",
-            ErrorCode::BytesLenLimitExceeded => "byte string length exceeds decode limits",
-            ErrorCode::TextLenLimitExceeded => "text string length exceeds decode limits",
-            ErrorCode::MessageLenLimitExceeded => "input length exceeds decode limits",
-
-            ErrorCode::ReservedAdditionalInfo => "reserved additional info value",
-            ErrorCode::IndefiniteLengthForbidden => "indefinite length forbidden",
-            ErrorCode::NonCanonicalEncoding => "non-canonical integer/length encoding",
-
-            ErrorCode::MapKeyMustBeText => "map keys must be text strings",
-            ErrorCode::DuplicateMapKey => "duplicate map key",
-            ErrorCode::NonCanonicalMapOrder => "non-canonical map key order",
-
-            ErrorCode::ForbiddenOrMalformedTag => "forbidden or malformed CBOR tag",
-            ErrorCode::BignumNotCanonical => {
+impl ErrorCode {
+    /// Returns a short human-readable description, as used by `CborError`'s `Display`.
+    ///
+    /// Available without `std` or `alloc`. Unlike [`ErrorCode::as_str`], the wording is not
+    /// a stability contract.
+    #[must_use]
+    pub const fn message(self) -> &'static str {
+        match self {
+            Self::InvalidLimits => "invalid CBOR limits",
+
+            Self::UnexpectedEof => "unexpected end of input",
+            Self::LengthOverflow => "length overflow",
+            Self::TrailingBytes => "trailing bytes after single CBOR item",
+            Self::AllocationFailed => "allocation failed",
+            Self::ArrayLenMismatch => "array length mismatch",
+            Self::MapLenMismatch => "map length mismatch",
+            Self::IoError => "I/O error while reading or writing",
+            Self::BufferTooSmall => "output buffer too small",
+
+            Self::DepthLimitExceeded => "nesting depth limit exceeded",
+            Self::TotalItemsLimitExceeded => "total items limit exceeded",
+            Self::ArrayLenLimitExceeded => "array length exceeds decode limits",
+            Self::MapLenLimitExceeded => "map length exceeds decode limits",
+            Self::BytesLenLimitExceeded => "byte string length exceeds decode limits",
+            Self::TextLenLimitExceeded => "text string length exceeds decode limits",
+            Self::MessageLenLimitExceeded => "input length exceeds decode limits",
+            Self::KeyLenLimitExceeded => "map key length exceeds decode limits",
+
+            Self::ReservedAdditionalInfo => "reserved additional info value",
+            Self::IndefiniteLengthForbidden => "indefinite length forbidden",
+            Self::NonCanonicalEncoding => "non-canonical integer/length encoding",
+
+            Self::MapKeyMustBeText => "map keys must be text strings",
+            Self::DuplicateMapKey => "duplicate map key",
+            Self::NonCanonicalMapOrder => "non-canonical map key order",
+
+            Self::ForbiddenOrMalformedTag => "forbidden or malformed CBOR tag",
+            Self::BignumNotCanonical => {
                 "bignum magnitude must be canonical (non-empty, no leading zero)"
             }
-            ErrorCode::BignumMustBeOutsideSafeRange => "bignum must be outside int_safe range",
-
-            ErrorCode::UnsupportedSimpleValue => "unsupported CBOR simple value",
-            ErrorCode::IntegerOutsideSafeRange => "integer outside int_safe range",
-
-            ErrorCode::Utf8Invalid => "text must be valid UTF-8",
-
-            ErrorCode::NegativeZeroForbidden => "negative zero forbidden",
-            ErrorCode::NonCanonicalNaN => "non-canonical NaN encoding",
-            ErrorCode::SerdeError => "serde conversion failed",
-
-            ErrorCode::ExpectedMap => "expected CBOR map",
-            ErrorCode::ExpectedArray => "expected CBOR array",
-            ErrorCode::ExpectedInteger => "expected CBOR integer",
-            ErrorCode::ExpectedText => "expected CBOR text string",
-            ErrorCode::ExpectedBytes => "expected CBOR byte string",
-            ErrorCode::ExpectedBool => "expected CBOR bool",
-            ErrorCode::ExpectedNull => "expected CBOR null",
-            ErrorCode::ExpectedFloat => "expected CBOR float64",
-            ErrorCode::ExpectedEnum => "expected CBOR enum value",
-            ErrorCode::UnknownEnumVariant => "unknown CBOR enum variant",
-            ErrorCode::PatchConflict => "patch operations conflict",
-            ErrorCode::IndexOutOfBounds => "array index out of bounds",
-            ErrorCode::InvalidQuery => "invalid query arguments",
-            ErrorCode::MissingKey => "missing required map key",
-            ErrorCode::MalformedCanonical => "malformed canonical CBOR",
-        };
+            Self::BignumMustBeOutsideSafeRange => "bignum must be outside int_safe range",
+
+            Self::UnsupportedSimpleValue => "unsupported CBOR simple value",
+            Self::IntegerOutsideSafeRange => "integer outside int_safe range",
+
+            Self::Utf8Invalid => "text must be valid UTF-8",
 
-        write!(f, "cbor error at {}: {msg}", self.offset)
+            Self::NegativeZeroForbidden => "negative zero forbidden",
+            Self::NonCanonicalNaN => "non-canonical NaN encoding",
+            Self::SerdeError => "serde conversion failed",
+
+            Self::ExpectedMap => "expected CBOR map",
+            Self::ExpectedArray => "expected CBOR array",
+            Self::ExpectedInteger => "expected CBOR integer",
+            Self::ExpectedText => "expected CBOR text string",
+            Self::ExpectedBytes => "expected CBOR byte string",
+            Self::ExpectedBool => "expected CBOR bool",
+            Self::ExpectedNull => "expected CBOR null",
+            Self::ExpectedFloat => "expected CBOR float64",
+            Self::ExpectedEnum => "expected CBOR enum value",
+            Self::UnknownEnumVariant => "unknown CBOR enum variant",
+            Self::ExpectedNumber => "expected CBOR integer or float",
+            Self::BytesLenMismatch => "byte string has the wrong length for the target type",
+            Self::ValueOutOfRange => "value outside the range of the target type",
+            Self::ExpectedTag => "expected CBOR tag",
+            Self::PatchConflict => "patch operations conflict",
+            Self::IndexOutOfBounds => "array index out of bounds",
+            Self::InvalidQuery => "invalid query arguments",
+            Self::MissingKey => "missing required map key",
+            Self::UnknownKey => "unexpected map key",
+            Self::MalformedCanonical => "malformed canonical CBOR",
+
+            Self::WriteFailed => "formatting sink write failed",
+
+            Self::InvalidTextSyntax => "invalid text syntax",
+        }
+    }
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = self.code.message();
+
+        write!(f, "cbor error at {}: {msg}", self.offset)?;
+        if let Some(kind) = self.found {
+            write!(f, " (found {})", kind.name())?;
+        }
+        if let Some(prev) = self.detail() {
+            write!(f, " (prev key at {prev})")?;
+        }
+        if let Some(ctx) = self.context {
+            write!(f, " (at {ctx})")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CborError")
+            .field("code", &self.code)
+            .field("offset", &self.offset)
+            .field("context", &self.context)
+            .field("detail", &self.detail())
+            .field("found", &self.found)
+            .finish()
     }
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for CborError {}
+
+/// `no_std` only: with `std`, the `std::error::Error` impl above is the same trait.
+#[cfg(all(feature = "core_error", not(feature = "std")))]
+impl core::error::Error for CborError {}
@@ -0,0 +1,245 @@
+use crate::canonical::CanonicalCborRef;
+use crate::profile::check_encoded_key_order;
+use crate::scalar::F64Bits;
+use crate::stream::{StreamCore, StreamEncoder, StreamSink};
+use crate::wire::{encode_text, Sink};
+use crate::{CborError, ErrorCode};
+
+/// A [`Sink`] over a caller-provided buffer that fails with `BufferTooSmall` when full.
+pub struct SliceSink<'buf> {
+    buf: &'buf mut [u8],
+    len: usize,
+}
+
+impl<'buf> SliceSink<'buf> {
+    pub fn new(buf: &'buf mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+}
+
+impl Sink for SliceSink<'_> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), CborError> {
+        let end = self
+            .len
+            .checked_add(bytes.len())
+            .filter(|&end| end <= self.buf.len())
+            .ok_or_else(|| CborError::new(ErrorCode::BufferTooSmall, self.len))?;
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    fn position(&self) -> usize {
+        self.len
+    }
+}
+
+impl StreamSink for SliceSink<'_> {
+    fn check(&self) -> Result<(), CborError> {
+        Ok(())
+    }
+
+    fn recover(&mut self, start: usize, _err: CborError) {
+        self.len = start;
+    }
+}
+
+/// Allocation-free canonical encoder that writes into a caller-provided buffer.
+///
+/// This mirrors the core of [`crate::Encoder`] for targets without a global allocator:
+/// scalars, definite-length arrays, and maps with keys written in canonical order.
+/// Exceeding the buffer fails with `BufferTooSmall`, and any failed value is rolled
+/// back so the bytes written so far stay well-formed.
+pub struct ArrayEncoderFixed<'buf> {
+    core: StreamCore<SliceSink<'buf>>,
+}
+
+impl<'buf> StreamEncoder for ArrayEncoderFixed<'buf> {
+    type Sink = SliceSink<'buf>;
+
+    fn core(&mut self) -> &mut StreamCore<SliceSink<'buf>> {
+        &mut self.core
+    }
+}
+
+impl<'buf> ArrayEncoderFixed<'buf> {
+    /// Create an encoder that writes into `buf`.
+    #[must_use]
+    pub fn new(buf: &'buf mut [u8]) -> Self {
+        Self {
+            core: StreamCore::new(SliceSink::new(buf)),
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.core.sink.len
+    }
+
+    /// Returns `true` if no bytes have been written.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.core.sink.len == 0
+    }
+
+    /// Borrow the bytes emitted so far.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.core.sink.buf[..self.core.sink.len]
+    }
+
+    /// Consume the encoder and return the written prefix of the buffer as canonical CBOR.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UnexpectedEof` if no complete value has been written.
+    pub fn into_canonical(self) -> Result<CanonicalCborRef<'buf>, CborError> {
+        if !self.core.root_done() {
+            return Err(CborError::new(ErrorCode::UnexpectedEof, 0));
+        }
+        let SliceSink { buf, len } = self.core.sink;
+        let buf: &'buf [u8] = buf;
+        Ok(CanonicalCborRef::new(&buf[..len]))
+    }
+
+    /// Encode CBOR null.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BufferTooSmall` if the value does not fit.
+    pub fn null(&mut self) -> Result<(), CborError> {
+        self.write_null()
+    }
+
+    /// Encode a CBOR boolean.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BufferTooSmall` if the value does not fit.
+    pub fn bool(&mut self, v: bool) -> Result<(), CborError> {
+        self.write_bool(v)
+    }
+
+    /// Encode a safe-range integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IntegerOutsideSafeRange` if `v` is outside the safe range, or
+    /// `BufferTooSmall` if the value does not fit.
+    pub fn int(&mut self, v: i64) -> Result<(), CborError> {
+        self.write_int(v)
+    }
+
+    /// Encode a byte string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BufferTooSmall` if the value does not fit.
+    pub fn bytes(&mut self, b: &[u8]) -> Result<(), CborError> {
+        self.write_bytes(b)
+    }
+
+    /// Encode a text string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BufferTooSmall` if the value does not fit.
+    pub fn text(&mut self, s: &str) -> Result<(), CborError> {
+        self.write_text(s)
+    }
+
+    /// Encode a float64 value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BufferTooSmall` if the value does not fit.
+    pub fn float(&mut self, bits: F64Bits) -> Result<(), CborError> {
+        self.write_float(bits)
+    }
+
+    /// Encode a definite-length array whose items are written by `f`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArrayLenMismatch` if `f` writes a different number of items, or the first
+    /// error raised while writing them.
+    pub fn array<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), CborError>,
+    {
+        self.write_array(len, f)
+    }
+
+    /// Encode a definite-length map whose entries are written by `f`.
+    ///
+    /// Entries must be inserted in canonical key order; there is no buffer to sort into.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MapLenMismatch` if `f` writes a different number of entries, or the first
+    /// error raised while writing them.
+    pub fn map<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut MapEncoderFixed<'_, 'buf>) -> Result<(), CborError>,
+    {
+        self.write_map(len, |e| {
+            let mut m = MapEncoderFixed {
+                enc: e,
+                remaining: len,
+                prev_key_range: None,
+            };
+            f(&mut m)?;
+            Ok(m.remaining)
+        })
+    }
+}
+
+/// Builder for writing map entries into an [`ArrayEncoderFixed`].
+pub struct MapEncoderFixed<'a, 'buf> {
+    enc: &'a mut ArrayEncoderFixed<'buf>,
+    remaining: usize,
+    prev_key_range: Option<(usize, usize)>,
+}
+
+impl<'buf> MapEncoderFixed<'_, 'buf> {
+    /// Insert a map entry whose value is written by `f`. Keys must be in canonical order;
+    /// duplicates are rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NonCanonicalMapOrder`/`DuplicateMapKey` for misordered keys,
+    /// `MapLenMismatch` if the map is already full or `f` does not write exactly one value,
+    /// or the first error raised while writing.
+    pub fn entry<F>(&mut self, key: &str, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut ArrayEncoderFixed<'buf>) -> Result<(), CborError>,
+    {
+        let enc = &mut *self.enc;
+        if self.remaining == 0 {
+            return Err(enc.err_here(ErrorCode::MapLenMismatch));
+        }
+        let key_start = enc.core.sink.len;
+        let res = encode_text(&mut enc.core.sink, key).and_then(|()| {
+            let key_end = enc.core.sink.len;
+            if let Some((ps, pe)) = self.prev_key_range {
+                let buf = &enc.core.sink.buf;
+                check_encoded_key_order(&buf[ps..pe], &buf[key_start..key_end])
+                    .map_err(|code| CborError::new(code, key_start))?;
+            }
+            enc.write_entry_value(f)?;
+            Ok(key_end)
+        });
+        match res {
+            Ok(key_end) => {
+                self.prev_key_range = Some((key_start, key_end));
+                self.remaining -= 1;
+                Ok(())
+            }
+            Err(err) => {
+                enc.core.sink.recover(key_start, err);
+                Err(err)
+            }
+        }
+    }
+}
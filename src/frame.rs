@@ -0,0 +1,72 @@
+//! Length-delimited framing for sequences of canonical messages.
+//!
+//! A frame is a canonical CBOR unsigned integer holding the message length, followed
+//! by exactly that many bytes of canonical CBOR.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::alloc_util::try_reserve;
+use crate::canonical::CanonicalCborRef;
+#[cfg(feature = "alloc")]
+use crate::fixed::SliceSink;
+use crate::parse::validate_canonical;
+use crate::wire;
+#[cfg(feature = "alloc")]
+use crate::wire::Sink;
+use crate::{CborError, DecodeLimits, ErrorCode};
+
+/// Append `msg` to `out` as a length-prefixed frame.
+///
+/// # Errors
+///
+/// Returns `AllocationFailed` or `LengthOverflow` if `out` cannot grow.
+#[cfg(feature = "alloc")]
+pub fn write_frame(msg: CanonicalCborRef<'_>, out: &mut Vec<u8>) -> Result<(), CborError> {
+    let bytes = msg.as_bytes();
+    let mut header = [0u8; 9];
+    let mut sink = SliceSink::new(&mut header);
+    wire::encode_major_len(&mut sink, 0, bytes.len())
+        .map_err(|_| CborError::new(ErrorCode::LengthOverflow, out.len()))?;
+    let header_len = sink.position();
+    let total = header_len
+        .checked_add(bytes.len())
+        .ok_or_else(|| CborError::new(ErrorCode::LengthOverflow, out.len()))?;
+    try_reserve(out, total, out.len())?;
+    out.extend_from_slice(&header[..header_len]);
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Parse one length-prefixed frame from the start of `buf`.
+///
+/// The length prefix must be a canonically encoded unsigned integer, and the enclosed
+/// message must be exactly that long and valid under `limits`. Returns the message and
+/// the total number of bytes consumed (prefix plus message), so callers can advance to
+/// the next frame.
+///
+/// # Errors
+///
+/// Returns `ExpectedInteger` or `NonCanonicalEncoding` for a bad prefix, `UnexpectedEof`
+/// if `buf` ends before the frame does, or any validation error for the message.
+/// Message error offsets are relative to `buf`.
+pub fn read_frame(
+    buf: &[u8],
+    limits: DecodeLimits,
+) -> Result<(CanonicalCborRef<'_>, usize), CborError> {
+    let mut pos = 0;
+    let ib = wire::read_u8(buf, &mut pos)?;
+    if ib >> 5 != 0 {
+        return Err(CborError::new(ErrorCode::ExpectedInteger, 0));
+    }
+    let len = wire::read_len_at::<true, CborError>(buf, &mut pos, ib & 0x1f, 0)?;
+    let end = pos
+        .checked_add(len)
+        .ok_or_else(|| CborError::new(ErrorCode::LengthOverflow, 0))?;
+    let body = buf
+        .get(pos..end)
+        .ok_or_else(|| CborError::new(ErrorCode::UnexpectedEof, buf.len()))?;
+    let msg = validate_canonical(body, limits).map_err(|err| err.shifted(pos))?;
+    Ok((msg, end))
+}
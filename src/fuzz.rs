@@ -0,0 +1,139 @@
+//! Reusable fuzzing entry points.
+//!
+//! These helpers bundle the crate's core invariants so downstream fuzzers can call them
+//! directly from a `fuzz_target!`. They accept arbitrary input and only panic when an
+//! invariant is violated (that is, on a bug in this crate).
+
+use crate::codec::{decode, CborEncode};
+use crate::encode::Encoder;
+use crate::parse::{validate_canonical, validate_relaxed};
+use crate::query::{CborIntegerRef, CborKind, CborValueRef};
+use crate::scalar::F64Bits;
+use crate::{CborError, DecodeLimits, ErrorCode};
+
+/// Passthrough tags admitted when fuzzing relaxed validation.
+const FUZZ_TAGS: &[u64] = &[0, 1, 32];
+
+fn fuzz_limits(input_len: usize) -> DecodeLimits {
+    // Tight enough to avoid pathological allocations while still exploring structure.
+    let max = input_len.min(1 << 20);
+    DecodeLimits::for_bytes(max)
+        .with_max_depth(64)
+        .with_max_total_items(1 << 16)
+        .with_max_array_len(1 << 12)
+        .with_max_map_len(1 << 12)
+        .with_max_key_len(max)
+}
+
+/// Exercise validation, checked decoding, and queries on arbitrary input.
+///
+/// Never panics; all errors are discarded.
+pub fn fuzz_decode(data: &[u8]) {
+    let limits = fuzz_limits(data.len());
+    let _ = decode::<CborValueRef<'_>>(data, limits);
+    fuzz_relaxed(data, limits);
+    let Ok(canon) = validate_canonical(data, limits) else {
+        return;
+    };
+    let root = canon.root();
+    let _ = root.kind();
+    let _ = root.integer();
+    let _ = root.text();
+    let _ = root.float64();
+    if let Ok(map) = root.map() {
+        for (key, value) in map.iter().take(4).flatten() {
+            let _ = map.get(key);
+            let _ = value.kind();
+        }
+    }
+    if let Ok(array) = root.array() {
+        let _ = array.get(array.len().saturating_sub(1));
+    }
+}
+
+/// Queries that must also cope with wide integers and passthrough tags.
+fn fuzz_relaxed(data: &[u8], limits: DecodeLimits) {
+    let limits = limits
+        .with_allow_full_int_range(true)
+        .with_extra_allowed_tags(FUZZ_TAGS);
+    let Ok(doc) = validate_relaxed(data, limits) else {
+        return;
+    };
+    let mut value = doc.root();
+    let _ = value.numeric_cmp(value);
+    while let Ok(Some((_, content))) = value.tag() {
+        value = content;
+    }
+    let _ = value.kind();
+    let _ = value.integer();
+    let _ = doc.pointer("/0");
+}
+
+/// Check that valid input survives a full decode and re-encode unchanged.
+///
+/// Input that fails validation is ignored. For valid input, the checked decoder must
+/// accept it, and walking the value and re-emitting it through [`Encoder`] must
+/// reproduce the exact bytes.
+///
+/// # Panics
+///
+/// Panics if a canonical-stability invariant is violated.
+pub fn fuzz_roundtrip(data: &[u8]) {
+    let limits = fuzz_limits(data.len());
+    let Ok(canon) = validate_canonical(data, limits) else {
+        return;
+    };
+
+    let decoded = decode::<CborValueRef<'_>>(data, limits);
+    assert!(
+        decoded.is_ok(),
+        "checked decoder rejected validated input: {decoded:?}"
+    );
+
+    let mut enc = Encoder::with_capacity(data.len());
+    let res = Reencode(canon.root()).encode(&mut enc);
+    assert!(res.is_ok(), "re-encoding validated input failed: {res:?}");
+    assert_eq!(enc.as_bytes(), data, "re-encoding is not byte-identical");
+}
+
+/// Re-emits a value item by item so every encoder path is exercised.
+struct Reencode<'a>(CborValueRef<'a>);
+
+impl CborEncode for Reencode<'_> {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        let v = self.0;
+        match v.kind()? {
+            CborKind::Integer => match v.integer()? {
+                CborIntegerRef::Safe(i) => enc.int(i),
+                CborIntegerRef::Wide(i) => enc.int_exact(i),
+                CborIntegerRef::Big(b) => enc.bignum(b.is_negative(), b.magnitude()),
+            },
+            CborKind::Bytes => enc.bytes(v.bytes()?),
+            CborKind::Text => enc.text(v.text()?),
+            CborKind::Array => {
+                let array = v.array()?;
+                enc.array(array.len(), |a| {
+                    for item in array.iter() {
+                        a.value(&Reencode(item?))?;
+                    }
+                    Ok(())
+                })
+            }
+            CborKind::Map => {
+                let map = v.map()?;
+                enc.map(map.len(), |m| {
+                    for entry in map.iter() {
+                        let (key, value) = entry?;
+                        m.entry(key, |e| Reencode(value).encode(e))?;
+                    }
+                    Ok(())
+                })
+            }
+            CborKind::Bool => enc.bool(v.bool()?),
+            CborKind::Null => enc.null(),
+            CborKind::Float => enc.float(F64Bits::try_from_f64(v.float64()?)?),
+            // Only reached through validated canonical input, which has no passthrough tags.
+            CborKind::Tag => Err(CborError::new(ErrorCode::MalformedCanonical, v.offset())),
+        }
+    }
+}
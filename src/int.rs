@@ -1,15 +1,160 @@
+use core::cmp::Ordering;
+
+#[cfg(feature = "alloc")]
 use crate::alloc_util::try_reserve_exact;
-use crate::ErrorCode;
+#[cfg(feature = "json")]
+use crate::alloc_util::try_vec_with_capacity;
+#[cfg(feature = "alloc")]
+use crate::profile::MAX_SAFE_INTEGER;
+#[cfg(feature = "alloc")]
+use crate::{CborError, Encoder, ErrorCode};
+#[cfg(feature = "json")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 
+/// Strip leading zero bytes from a big-endian magnitude (zero becomes empty).
+pub fn trim_be(mag: &[u8]) -> &[u8] {
+    let first = mag.iter().position(|&b| b != 0).unwrap_or(mag.len());
+    &mag[first..]
+}
+
+/// Compare big-endian magnitudes numerically, ignoring leading zeros.
+pub fn cmp_big_endian(a: &[u8], b: &[u8]) -> Ordering {
+    let (a, b) = (trim_be(a), trim_be(b));
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Read a big-endian magnitude as `u128`, or `None` if it is wider than 16 bytes.
+pub fn mag_to_u128(mag: &[u8]) -> Option<u128> {
+    if mag.len() > 16 {
+        return None;
+    }
+    let mut buf = [0u8; 16];
+    let start = 16 - mag.len();
+    buf[start..].copy_from_slice(mag);
+    Some(u128::from_be_bytes(buf))
+}
+
+/// Subtract one from a nonzero big-endian magnitude in place.
+pub fn decrement_be(mag: &mut [u8]) {
+    for b in mag.iter_mut().rev() {
+        let (v, borrow) = b.overflowing_sub(1);
+        *b = v;
+        if !borrow {
+            return;
+        }
+    }
+}
+
+/// Add one to a big-endian magnitude, growing it on carry out of the top byte.
+#[cfg(feature = "alloc")]
+pub fn increment_be(mag: &mut Vec<u8>) {
+    for b in mag.iter_mut().rev() {
+        let (v, carry) = b.overflowing_add(1);
+        *b = v;
+        if !carry {
+            return;
+        }
+    }
+    mag.insert(0, 1);
+}
+
+/// The minimal big-endian magnitude of a nonzero `u128`.
+#[cfg(feature = "alloc")]
 pub fn magnitude_from_u128(n: u128) -> Result<Vec<u8>, ErrorCode> {
     if n == 0 {
         return Err(ErrorCode::BignumNotCanonical);
     }
-    let leading = (n.leading_zeros() / 8) as usize;
     let raw = n.to_be_bytes();
+    let mag = trim_be(&raw);
+    let mut out = Vec::new();
+    try_reserve_exact(&mut out, mag.len(), 0).map_err(|err| err.code())?;
+    out.extend_from_slice(mag);
+    Ok(out)
+}
+
+/// Big-endian `a + b`.
+#[cfg(feature = "alloc")]
+pub fn add_be(a: &[u8], b: &[u8]) -> Result<Vec<u8>, ErrorCode> {
+    let (a, b) = (trim_be(a), trim_be(b));
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
     let mut out = Vec::new();
-    try_reserve_exact(&mut out, raw.len().saturating_sub(leading), 0).map_err(|err| err.code)?;
-    out.extend_from_slice(&raw[leading..]);
+    try_reserve_exact(&mut out, long.len() + 1, 0).map_err(|err| err.code())?;
+    out.resize(long.len() + 1, 0);
+    let mut carry = 0u16;
+    for i in 0..long.len() {
+        let x = u16::from(long[long.len() - 1 - i]);
+        let y = short
+            .len()
+            .checked_sub(1 + i)
+            .map_or(0, |j| u16::from(short[j]));
+        let sum = x + y + carry;
+        out[long.len() - i] = sum.to_be_bytes()[1];
+        carry = sum >> 8;
+    }
+    out[0] = carry.to_be_bytes()[1];
+    let lead = out.len() - trim_be(&out).len();
+    out.drain(..lead);
     Ok(out)
 }
+
+/// Big-endian `a - b` for `a >= b` (empty when the result is zero).
+#[cfg(feature = "alloc")]
+pub fn sub_be(a: &[u8], b: &[u8]) -> Result<Vec<u8>, ErrorCode> {
+    let (a, b) = (trim_be(a), trim_be(b));
+    debug_assert!(cmp_big_endian(a, b) != Ordering::Less);
+    let mut out = Vec::new();
+    try_reserve_exact(&mut out, a.len(), 0).map_err(|err| err.code())?;
+    out.extend_from_slice(a);
+    let mut borrow = false;
+    for i in 0..a.len() {
+        let y = b.len().checked_sub(1 + i).map_or(0, |j| b[j]);
+        let slot = &mut out[a.len() - 1 - i];
+        let (v, b1) = slot.overflowing_sub(y);
+        let (v, b2) = v.overflowing_sub(u8::from(borrow));
+        *slot = v;
+        borrow = b1 || b2;
+    }
+    let lead = out.len() - trim_be(&out).len();
+    out.drain(..lead);
+    Ok(out)
+}
+
+/// Write a big-endian magnitude in decimal, consuming it as scratch space.
+#[cfg(feature = "json")]
+pub fn push_decimal(out: &mut String, mut mag: Vec<u8>) -> Result<(), CborError> {
+    let mut digits = try_vec_with_capacity(mag.len() * 3 + 1, 0)?;
+    loop {
+        let mut rem = 0u16;
+        for b in &mut mag {
+            let cur = (rem << 8) | u16::from(*b);
+            // `cur / 10` is at most 255 because `rem < 10`.
+            *b = u8::try_from(cur / 10).unwrap_or(u8::MAX);
+            rem = cur % 10;
+        }
+        digits.push(b'0' + u8::try_from(rem).unwrap_or(0));
+        let first_nonzero = mag.iter().position(|&b| b != 0).unwrap_or(mag.len());
+        mag.drain(..first_nonzero);
+        if mag.is_empty() {
+            break;
+        }
+    }
+    out.extend(digits.iter().rev().map(|&d| char::from(d)));
+    Ok(())
+}
+
+/// Encode the integer with CBOR argument `n` (the value is `n`, or `-1 - n` when
+/// `negative`) as a safe integer or, outside the safe range, a bignum.
+#[cfg(feature = "alloc")]
+pub fn encode_int(enc: &mut Encoder, negative: bool, n: &[u8]) -> Result<(), CborError> {
+    let n = trim_be(n);
+    if let Some(v) = mag_to_u128(n) {
+        let max = u128::from(MAX_SAFE_INTEGER);
+        if (negative && v < max) || (!negative && v <= max) {
+            let v = i64::try_from(v).map_err(|_| CborError::new(ErrorCode::LengthOverflow, 0))?;
+            return enc.int(if negative { -1 - v } else { v });
+        }
+    }
+    enc.bignum(negative, n)
+}
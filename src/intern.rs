@@ -0,0 +1,82 @@
+//! Sharing one allocation between byte-identical canonical payloads.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::alloc_util::try_reserve;
+use crate::canonical::{CanonicalCbor, CanonicalCborRef};
+use crate::CborError;
+
+#[cfg(feature = "sha2")]
+type Digest = [u8; 32];
+#[cfg(not(feature = "sha2"))]
+type Digest = u64;
+
+#[cfg(feature = "sha2")]
+fn digest(bytes: CanonicalCborRef<'_>) -> Digest {
+    bytes.sha256()
+}
+
+#[cfg(not(feature = "sha2"))]
+fn digest(bytes: CanonicalCborRef<'_>) -> Digest {
+    bytes.root().content_hash()
+}
+
+/// Interning pool that hands out one shared `Arc<CanonicalCbor>` per distinct payload.
+///
+/// Canonical bytes are the value, so byte equality is value equality and interning needs no
+/// decoding. Payloads are bucketed by SHA-256 with the `sha2` feature (FNV-1a otherwise) and
+/// always confirmed by a full byte comparison, so a hash collision never merges distinct
+/// payloads. Entries live until [`Interner::clear`] or the pool is dropped.
+#[derive(Debug, Default)]
+pub struct Interner {
+    buckets: BTreeMap<Digest, Vec<Arc<CanonicalCbor>>>,
+    len: usize,
+}
+
+impl Interner {
+    /// Create an empty pool.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            buckets: BTreeMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of distinct payloads held.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the pool holds no payloads.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the shared copy of `bytes`, copying it into the pool on first sight.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AllocationFailed` if copying a new payload fails.
+    pub fn intern(&mut self, bytes: CanonicalCborRef<'_>) -> Result<Arc<CanonicalCbor>, CborError> {
+        let bucket = self.buckets.entry(digest(bytes)).or_default();
+        if let Some(shared) = bucket.iter().find(|c| c.as_bytes() == bytes.as_bytes()) {
+            return Ok(Arc::clone(shared));
+        }
+        try_reserve(bucket, 1, 0)?;
+        let shared = Arc::new(bytes.to_owned()?);
+        bucket.push(Arc::clone(&shared));
+        self.len += 1;
+        Ok(shared)
+    }
+
+    /// Drop every pooled payload. Outstanding `Arc`s stay valid.
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+        self.len = 0;
+    }
+}
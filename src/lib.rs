@@ -37,10 +37,18 @@
 //! ## Feature flags
 //!
 //! - `std` *(default)*: implements `std::error::Error` for [`CborError`].
+//! - `core_error`: implements `core::error::Error` for [`CborError`] without `std` (Rust 1.81+).
 //! - `alloc` *(default)*: enables owned canonical bytes (`CanonicalCbor`), editing, and encoding helpers.
 //! - `sha2` *(default)*: enables SHA-256 hashing helpers for canonical bytes.
 //! - `simdutf8`: enables SIMD-accelerated UTF-8 validation where supported.
+//! - `subtle`: uses the `subtle` crate for [`CborValueRef::ct_eq_bytes`].
+//! - `zeroize`: wipes `CanonicalCbor` buffers on drop (`into_bytes` hands off without wiping).
+//! - `fuzz`: exposes `fuzz_decode` / `fuzz_roundtrip` entry points for downstream fuzzers.
 //! - `unsafe`: allows unchecked UTF-8 for canonical-trusted inputs.
+//! - `chrono` / `time`: encode `chrono::DateTime<Utc>` / `time::OffsetDateTime` as integer
+//!   milliseconds since the Unix epoch.
+//! - `arbitrary`: implements `arbitrary::Arbitrary` for `CanonicalCbor`, generating only
+//!   profile-valid items for round-trip property tests.
 //!
 //! ## Safety
 //!
@@ -65,36 +73,66 @@ extern crate alloc;
 
 #[cfg(feature = "alloc")]
 mod alloc_util;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
 mod canonical;
+#[cfg(feature = "alloc")]
+mod canonicalize;
 mod codec;
 mod error;
+mod fixed;
+mod frame;
 mod limits;
 mod parse;
 mod profile;
 mod query;
+mod raw;
 mod scalar;
 #[cfg(feature = "serde")]
 mod serde_impl;
+mod stream;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod timestamp;
 pub(crate) mod utf8;
 mod wire;
 
 #[cfg(feature = "alloc")]
-mod edit;
+mod diff;
 #[cfg(feature = "alloc")]
+mod edit;
+#[cfg(feature = "json")]
+mod editable;
+#[cfg(feature = "fuzz")]
+mod fuzz;
 mod int;
+#[cfg(feature = "alloc")]
+mod intern;
+#[cfg(feature = "alloc")]
+mod merge;
+#[cfg(all(feature = "std", feature = "alloc"))]
+mod reader;
+#[cfg(all(feature = "std", feature = "alloc"))]
+mod writer;
 
-pub use crate::canonical::{CanonicalCborRef, EncodedTextKey};
+pub use crate::canonical::{CanonicalCborRef, EncodedTextKey, RelaxedCborRef};
 pub use crate::codec::{
-    decode, decode_canonical, ArrayDecoder, CborDecode, CheckedDecoder, Decoder, MapDecoder,
-    TrustedDecoder,
+    decode, decode_canonical, ArrayDecoder, ArrayStream, CborDecode, CheckedDecoder, Decoder,
+    MapDecoder, TrustedDecoder,
+};
+pub use crate::error::{CborError, ErrorCategory, ErrorCode};
+pub use crate::fixed::{ArrayEncoderFixed, MapEncoderFixed};
+pub use crate::frame::read_frame;
+pub use crate::limits::{CborLimits, DecodeLimits, DecodeOptions, Utf8Mode};
+pub use crate::parse::{
+    validate, validate_canonical, validate_many, validate_prefix, validate_relaxed,
+};
+pub use crate::profile::{
+    canonical_key_cmp, MAX_SAFE_INTEGER, MAX_SAFE_INTEGER_I64, MIN_SAFE_INTEGER,
 };
-pub use crate::error::{CborError, ErrorCode};
-pub use crate::limits::{CborLimits, DecodeLimits};
-pub use crate::parse::{validate, validate_canonical};
-pub use crate::profile::{MAX_SAFE_INTEGER, MAX_SAFE_INTEGER_I64, MIN_SAFE_INTEGER};
 pub use crate::query::{
-    ArrayRef, BigIntRef, CborIntegerRef, CborKind, CborValueRef, MapRef, PathElem,
+    ArrayRef, BigIntRef, CborIntegerRef, CborKind, CborValueRef, MapRef, PathElem, RawCbor,
 };
+pub use crate::raw::RawReader;
 pub use crate::scalar::F64Bits;
 
 #[cfg(feature = "alloc")]
@@ -104,30 +142,51 @@ mod macros;
 #[cfg(feature = "alloc")]
 mod value;
 #[cfg(feature = "alloc")]
-pub use crate::canonical::CanonicalCbor;
+pub use crate::canonical::{CanonicalCbor, OwnedEncodedKey};
+#[cfg(feature = "alloc")]
+pub use crate::canonicalize::{canonicalize, canonicalize_with, CanonicalizeOptions, FloatPolicy};
 #[cfg(feature = "alloc")]
 pub use crate::codec::{
-    decode_canonical_owned, encode_into, encode_to_canonical, encode_to_vec, CborArrayElem,
-    CborEncode, MapEntries,
+    decode_canonical_owned, encode_into, encode_to_canonical, encode_to_vec, encodes_to,
+    CborArrayElem, CborEncode, MapEntries,
 };
 #[cfg(feature = "alloc")]
+pub use crate::diff::diff;
+#[cfg(feature = "alloc")]
 pub use crate::edit::{
     ArrayPos, ArraySpliceBuilder, DeleteMode, EditEncode, EditOptions, EditValue, Editor, SetMode,
 };
+#[cfg(feature = "json")]
+pub use crate::editable::{from_editable_text, to_editable_text};
+#[cfg(feature = "alloc")]
+pub use crate::encode::{
+    ArrayEncoder, Checkpoint, DeferredMapEncoder, Encoder, MapEncoder, OpenArray, OpenMap,
+};
+#[cfg(feature = "alloc")]
+pub use crate::frame::write_frame;
 #[cfg(feature = "alloc")]
-pub use crate::encode::{ArrayEncoder, Encoder, MapEncoder};
+pub use crate::intern::Interner;
 #[cfg(feature = "alloc")]
 #[doc(hidden)]
 pub use crate::macros::__cbor_macro;
 #[cfg(feature = "alloc")]
+pub use crate::merge::{merge, MergeStrategy};
+#[cfg(feature = "alloc")]
+pub use crate::query::{dedup, MapIndex};
+#[cfg(feature = "alloc")]
 pub use crate::value::{BigInt, CborInteger};
+#[cfg(all(feature = "std", feature = "alloc"))]
+pub use crate::writer::{MapWriterEncoder, WriterEncoder};
 #[cfg(feature = "alloc")]
 pub use sacp_cbor_derive::cbor_bytes;
 
+#[cfg(feature = "fuzz")]
+pub use crate::fuzz::{fuzz_decode, fuzz_roundtrip};
+
 #[cfg(feature = "serde")]
 pub use crate::serde_impl::{
-    from_canonical_bytes, from_canonical_bytes_ref, from_slice, from_slice_borrowed, to_vec,
-    DeError,
+    from_canonical_bytes, from_canonical_bytes_ref, from_slice, from_slice_borrowed,
+    from_slice_prefix, from_slice_traced, to_vec, to_vec_with, DeError, MapKeyPolicy, TracedError,
 };
 
 pub use sacp_cbor_derive::{CborDecode, CborEncode};
@@ -1,5 +1,13 @@
 use crate::{CborError, ErrorCode};
 
+const fn min(a: usize, b: usize) -> usize {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
 /// Default maximum nesting depth limit.
 pub const DEFAULT_MAX_DEPTH: usize = 256;
 
@@ -8,10 +16,60 @@ pub const DEFAULT_MAX_DEPTH: usize = 256;
 /// This is a safety limit; adjust explicitly for your deployment.
 pub const DEFAULT_MAX_CONTAINER_LEN: usize = 1 << 16;
 
+/// Default maximum map key length in UTF-8 bytes.
+///
+/// Keys dominate comparison cost during canonical-order checks, so they are bounded
+/// separately from (and much more tightly than) general text.
+pub const DEFAULT_MAX_KEY_LEN: usize = 256;
+
+/// How decoders over trusted canonical input handle UTF-8 in text strings.
+///
+/// Untrusted input is always validated; this only matters for decoders built with
+/// [`crate::Decoder::new_trusted_with`]. [`crate::decode_canonical`] and
+/// [`crate::Decoder::new_trusted`] use the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Utf8Mode {
+    /// Validate UTF-8 even on trusted input.
+    Strict,
+    /// Skip UTF-8 validation on trusted input when the `unsafe` feature is compiled in.
+    ///
+    /// Without the feature this validates exactly like `Strict`.
+    #[default]
+    TrustedSkip,
+}
+
+/// Decoding behavior that is not a resource limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct DecodeOptions {
+    /// UTF-8 handling for trusted canonical input. Defaults to [`Utf8Mode::TrustedSkip`],
+    /// so the `unsafe` feature alone decides.
+    pub utf8_mode: Utf8Mode,
+}
+
+impl DecodeOptions {
+    /// The default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            utf8_mode: Utf8Mode::TrustedSkip,
+        }
+    }
+
+    /// Set [`DecodeOptions::utf8_mode`].
+    #[must_use]
+    pub const fn with_utf8_mode(self, utf8_mode: Utf8Mode) -> Self {
+        Self { utf8_mode, ..self }
+    }
+}
+
 /// Decode-time resource limits for validation and decoding.
 ///
-/// Limits are enforced deterministically and must not depend on background timers.
+/// Limits are enforced deterministically and must not depend on background timers. Start
+/// from [`DecodeLimits::for_bytes`], [`DecodeLimits::try_new`] or [`DecodeLimits::unlimited`]
+/// and adjust fields with the `with_*` setters.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct DecodeLimits {
     /// Maximum total input length in bytes.
     pub max_input_bytes: usize,
@@ -29,9 +87,91 @@ pub struct DecodeLimits {
     pub max_bytes_len: usize,
     /// Maximum text-string length in UTF-8 bytes.
     pub max_text_len: usize,
+    /// Maximum map key length in UTF-8 bytes.
+    ///
+    /// Applies on top of `max_text_len`, so keys can be bounded more tightly than values.
+    /// Must not exceed `max_text_len`.
+    pub max_key_len: usize,
+    /// Accept major 0/1 integers beyond the safe range (up to the full 64-bit CBOR range).
+    ///
+    /// This is a deliberate relaxation of the SACP-CBOR/1 profile for adjacent protocols
+    /// that need full `u64`/`i64` values; such inputs are not canonical under the profile.
+    /// Honored by the checked decoders and [`crate::validate_relaxed`]; the other validators
+    /// ignore it. Defaults to `false`.
+    pub allow_full_int_range: bool,
+    /// Tags accepted in addition to the bignum tags 2 and 3.
+    ///
+    /// A listed tag may wrap exactly one data item, which is validated recursively under
+    /// the same rules (and counts toward `max_depth`). Read it back with
+    /// [`crate::CborValueRef::tag`]. Like `allow_full_int_range`, this extends the
+    /// SACP-CBOR/1 profile for adjacent protocols and is only honored where that field is.
+    /// Defaults to empty.
+    pub extra_allowed_tags: &'static [u64],
 }
 
 impl DecodeLimits {
+    /// Construct limits after checking that they are internally consistent.
+    ///
+    /// See [`DecodeLimits::validate`] for the rules. `max_key_len` is set to 256 (or
+    /// `max_text_len`, if smaller) and the profile relaxations are off; adjust those with the
+    /// `with_*` setters if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidLimits` if the combination is contradictory.
+    pub const fn try_new(
+        max_input_bytes: usize,
+        max_depth: usize,
+        max_total_items: usize,
+        max_array_len: usize,
+        max_map_len: usize,
+        max_bytes_len: usize,
+        max_text_len: usize,
+    ) -> Result<Self, CborError> {
+        let limits = Self {
+            max_input_bytes,
+            max_depth,
+            max_total_items,
+            max_array_len,
+            max_map_len,
+            max_bytes_len,
+            max_text_len,
+            max_key_len: min(DEFAULT_MAX_KEY_LEN, max_text_len),
+            allow_full_int_range: false,
+            extra_allowed_tags: &[],
+        };
+        match limits.validate() {
+            Ok(()) => Ok(limits),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Check that the limits are internally consistent.
+    ///
+    /// A configuration is rejected when some cap can never be reached because another
+    /// limit is stricter, which would otherwise surface later as a confusing decode
+    /// error:
+    /// - `max_input_bytes` must be nonzero (every CBOR item is at least one byte);
+    /// - `max_array_len` and `max_map_len` must not exceed `max_total_items`;
+    /// - `max_bytes_len` and `max_text_len` must not exceed `max_input_bytes`;
+    /// - `max_key_len` must not exceed `max_text_len`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidLimits` if the combination is contradictory.
+    pub const fn validate(&self) -> Result<(), CborError> {
+        if self.max_input_bytes == 0
+            || self.max_array_len > self.max_total_items
+            || self.max_map_len > self.max_total_items
+            || self.max_bytes_len > self.max_input_bytes
+            || self.max_text_len > self.max_input_bytes
+            || self.max_key_len > self.max_text_len
+        {
+            return Err(CborError::new(ErrorCode::InvalidLimits, 0));
+        }
+        Ok(())
+    }
+
     /// Construct conservative limits derived from a maximum message size.
     ///
     /// The defaults are:
@@ -40,6 +180,7 @@ impl DecodeLimits {
     /// - `max_bytes_len = max_message_bytes`
     /// - `max_text_len = max_message_bytes`
     /// - `max_array_len` and `max_map_len` are capped by `DEFAULT_MAX_CONTAINER_LEN`
+    /// - `max_key_len = min(max_message_bytes, 256)`
     ///
     /// This is a pragmatic baseline. Production deployments should tune these explicitly.
     #[must_use]
@@ -53,6 +194,136 @@ impl DecodeLimits {
             max_map_len: max_container_len,
             max_bytes_len: max_message_bytes,
             max_text_len: max_message_bytes,
+            max_key_len: min(DEFAULT_MAX_KEY_LEN, max_message_bytes),
+            allow_full_int_range: false,
+            extra_allowed_tags: &[],
+        }
+    }
+
+    /// Construct limits with every cap set to `usize::MAX`, for fully trusted input.
+    ///
+    /// The checks still run, but against `usize::MAX` they never fire, so decoding costs
+    /// the same as with tight limits and nothing is rejected for size. Structural and
+    /// profile rules are unaffected, and the profile relaxations stay off.
+    ///
+    /// **Warning:** these limits provide no protection against hostile input. A small
+    /// message can claim huge containers or nest deeply enough to exhaust memory or, in
+    /// recursive `CborDecode` implementations, the native stack. Use them only for bytes
+    /// your own process produced or otherwise fully trusts.
+    #[must_use]
+    pub const fn unlimited() -> Self {
+        Self {
+            max_input_bytes: usize::MAX,
+            max_depth: usize::MAX,
+            max_total_items: usize::MAX,
+            max_array_len: usize::MAX,
+            max_map_len: usize::MAX,
+            max_bytes_len: usize::MAX,
+            max_text_len: usize::MAX,
+            max_key_len: usize::MAX,
+            allow_full_int_range: false,
+            extra_allowed_tags: &[],
+        }
+    }
+
+    /// Whether a profile relaxation (`allow_full_int_range` or `extra_allowed_tags`) is on.
+    pub(crate) const fn is_relaxed(&self) -> bool {
+        self.allow_full_int_range || !self.extra_allowed_tags.is_empty()
+    }
+
+    /// The same caps with the profile relaxations turned off.
+    pub(crate) const fn without_relaxations(self) -> Self {
+        Self {
+            allow_full_int_range: false,
+            extra_allowed_tags: &[],
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::max_input_bytes`].
+    #[must_use]
+    pub const fn with_max_input_bytes(self, max_input_bytes: usize) -> Self {
+        Self {
+            max_input_bytes,
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::max_depth`].
+    #[must_use]
+    pub const fn with_max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// Set [`DecodeLimits::max_total_items`].
+    #[must_use]
+    pub const fn with_max_total_items(self, max_total_items: usize) -> Self {
+        Self {
+            max_total_items,
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::max_array_len`].
+    #[must_use]
+    pub const fn with_max_array_len(self, max_array_len: usize) -> Self {
+        Self {
+            max_array_len,
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::max_map_len`].
+    #[must_use]
+    pub const fn with_max_map_len(self, max_map_len: usize) -> Self {
+        Self {
+            max_map_len,
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::max_bytes_len`].
+    #[must_use]
+    pub const fn with_max_bytes_len(self, max_bytes_len: usize) -> Self {
+        Self {
+            max_bytes_len,
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::max_text_len`].
+    #[must_use]
+    pub const fn with_max_text_len(self, max_text_len: usize) -> Self {
+        Self {
+            max_text_len,
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::max_key_len`].
+    #[must_use]
+    pub const fn with_max_key_len(self, max_key_len: usize) -> Self {
+        Self {
+            max_key_len,
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::allow_full_int_range`].
+    #[must_use]
+    pub const fn with_allow_full_int_range(self, allow_full_int_range: bool) -> Self {
+        Self {
+            allow_full_int_range,
+            ..self
+        }
+    }
+
+    /// Set [`DecodeLimits::extra_allowed_tags`].
+    #[must_use]
+    pub const fn with_extra_allowed_tags(self, extra_allowed_tags: &'static [u64]) -> Self {
+        Self {
+            extra_allowed_tags,
+            ..self
         }
     }
 }
@@ -0,0 +1,194 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::alloc_util::try_reserve_exact;
+use crate::canonical::{CanonicalCbor, CanonicalCborRef};
+use crate::encode::MapEncoder;
+use crate::profile::cmp_text_keys_canonical;
+use crate::query::{ArrayRef, CborKind, CborValueRef, MapRef};
+use crate::{CborError, Encoder, ErrorCode};
+
+/// How [`merge`] combines two arrays found at the same path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// The overlay array replaces the base array.
+    #[default]
+    Replace,
+    /// Overlay items not already in the base array (by canonical bytes) follow the base items.
+    /// Repeats within the overlay are kept once, at their first position.
+    Append,
+    /// All overlay items follow all base items.
+    Concat,
+}
+
+/// Deep-merge `overlay` onto `base`, as when layering configuration overrides on defaults.
+///
+/// Maps merge recursively: keys from both sides are kept, and a key present in both
+/// holds the merge of the two values. Arrays on both sides combine according to
+/// `strategy`. In every other case, including a type clash such as a map in `base`
+/// and a scalar in `overlay`, the overlay value wins. Values are copied as raw canonical
+/// bytes, so nothing is re-encoded except the headers of merged containers.
+///
+/// # Errors
+///
+/// Returns `CborError` if either input is malformed, a merged container length overflows,
+/// or allocation fails.
+pub fn merge(
+    base: CanonicalCborRef<'_>,
+    overlay: CanonicalCborRef<'_>,
+    strategy: MergeStrategy,
+) -> Result<CanonicalCbor, CborError> {
+    let mut enc = Encoder::with_capacity(base.len().saturating_add(overlay.len()));
+    merge_value(&mut enc, base.root(), overlay.root(), strategy)?;
+    enc.into_canonical()
+}
+
+fn merge_value(
+    enc: &mut Encoder,
+    base: CborValueRef<'_>,
+    overlay: CborValueRef<'_>,
+    strategy: MergeStrategy,
+) -> Result<(), CborError> {
+    if base == overlay {
+        return enc.raw_value_ref(overlay);
+    }
+    match (base.kind()?, overlay.kind()?) {
+        (CborKind::Map, CborKind::Map) => merge_map(enc, base.map()?, overlay.map()?, strategy),
+        (CborKind::Array, CborKind::Array) if strategy != MergeStrategy::Replace => {
+            merge_array(enc, base.array()?, overlay.array()?, strategy)
+        }
+        _ => enc.raw_value_ref(overlay),
+    }
+}
+
+fn merge_map(
+    enc: &mut Encoder,
+    base: MapRef<'_>,
+    overlay: MapRef<'_>,
+    strategy: MergeStrategy,
+) -> Result<(), CborError> {
+    // Both maps are in canonical order, so one merge walk counts the union of keys.
+    let mut shared = 0usize;
+    let mut b_iter = base.iter();
+    let mut o_iter = overlay.iter();
+    let (mut b, mut o) = (b_iter.next().transpose()?, o_iter.next().transpose()?);
+    while let (Some((bk, _)), Some((ok, _))) = (b, o) {
+        match cmp_text_keys_canonical(bk, ok) {
+            Ordering::Less => b = b_iter.next().transpose()?,
+            Ordering::Greater => o = o_iter.next().transpose()?,
+            Ordering::Equal => {
+                shared += 1;
+                b = b_iter.next().transpose()?;
+                o = o_iter.next().transpose()?;
+            }
+        }
+    }
+    let len = base
+        .len()
+        .checked_add(overlay.len())
+        .ok_or_else(|| CborError::new(ErrorCode::LengthOverflow, enc.len()))?
+        - shared;
+
+    enc.map(len, |m| write_map_entries(m, base, overlay, strategy))
+}
+
+fn write_map_entries(
+    m: &mut MapEncoder<'_>,
+    base: MapRef<'_>,
+    overlay: MapRef<'_>,
+    strategy: MergeStrategy,
+) -> Result<(), CborError> {
+    let mut b_iter = base.iter_encoded();
+    let mut o_iter = overlay.iter_encoded();
+    let (mut b, mut o) = (b_iter.next().transpose()?, o_iter.next().transpose()?);
+    loop {
+        let take_base = match (b, o) {
+            (None, None) => return Ok(()),
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (Some((bk, key, bv)), Some((ok, _, ov))) => match cmp_text_keys_canonical(bk, ok) {
+                Ordering::Less => true,
+                Ordering::Greater => false,
+                Ordering::Equal => {
+                    m.entry_raw_key(key, |e| merge_value(e, bv, ov, strategy))?;
+                    b = b_iter.next().transpose()?;
+                    o = o_iter.next().transpose()?;
+                    continue;
+                }
+            },
+        };
+        let (entry, iter) = if take_base {
+            (&mut b, &mut b_iter)
+        } else {
+            (&mut o, &mut o_iter)
+        };
+        if let Some((_, key, value)) = *entry {
+            m.entry_raw_key(key, |e| e.raw_value_ref(value))?;
+        }
+        *entry = iter.next().transpose()?;
+    }
+}
+
+fn merge_array(
+    enc: &mut Encoder,
+    base: ArrayRef<'_>,
+    overlay: ArrayRef<'_>,
+    strategy: MergeStrategy,
+) -> Result<(), CborError> {
+    let keep = if strategy == MergeStrategy::Append {
+        Some(append_keep(base, overlay)?)
+    } else {
+        None
+    };
+    let added = keep
+        .as_ref()
+        .map_or(overlay.len(), |keep| keep.iter().filter(|&&k| k).count());
+    let len = base
+        .len()
+        .checked_add(added)
+        .ok_or_else(|| CborError::new(ErrorCode::LengthOverflow, enc.len()))?;
+
+    enc.array(len, |a| {
+        for item in base.iter() {
+            a.raw_value_ref(item?)?;
+        }
+        for (i, item) in overlay.iter().enumerate() {
+            let item = item?;
+            if keep.as_ref().map_or(true, |keep| keep[i]) {
+                a.raw_value_ref(item)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// For each overlay item, whether `Append` keeps it: the first occurrence of bytes
+/// not present in `base`. Sorting both sides once keeps this `O((n + m) log(n + m))`.
+fn append_keep(base: ArrayRef<'_>, overlay: ArrayRef<'_>) -> Result<Vec<bool>, CborError> {
+    let mut seen: Vec<&[u8]> = Vec::new();
+    try_reserve_exact(&mut seen, base.len(), 0)?;
+    for item in base.iter() {
+        seen.push(item?.as_bytes());
+    }
+    seen.sort_unstable();
+
+    let mut items: Vec<(&[u8], usize)> = Vec::new();
+    try_reserve_exact(&mut items, overlay.len(), 0)?;
+    for (i, item) in overlay.iter().enumerate() {
+        items.push((item?.as_bytes(), i));
+    }
+    // Equal bytes end up adjacent with the earliest index first.
+    items.sort_unstable();
+
+    let mut keep = Vec::new();
+    try_reserve_exact(&mut keep, overlay.len(), 0)?;
+    keep.resize(overlay.len(), false);
+    let mut prev: Option<&[u8]> = None;
+    for (bytes, i) in items {
+        if prev != Some(bytes) && seen.binary_search(&bytes).is_err() {
+            keep[i] = true;
+        }
+        prev = Some(bytes);
+    }
+    Ok(keep)
+}
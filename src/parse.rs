@@ -1,10 +1,11 @@
-use crate::canonical::CanonicalCborRef;
+use crate::canonical::{CanonicalCborRef, RelaxedCborRef};
 use crate::wire::{self, Cursor};
 use crate::{CborError, DecodeLimits, ErrorCode};
 
 /// Validate that `bytes` contain exactly one canonical SACP-CBOR/1 data item.
 ///
-/// This is an allocation-free hot-path validator.
+/// This is an allocation-free hot-path validator. As for [`validate_canonical`], the
+/// profile relaxations in `limits` are ignored.
 ///
 /// # Errors
 ///
@@ -16,6 +17,12 @@ pub fn validate(bytes: &[u8], limits: DecodeLimits) -> Result<(), CborError> {
 
 /// Validate that `bytes` contain exactly one canonical SACP-CBOR/1 data item and return a wrapper.
 ///
+/// Validation stops at the first error, so its running time reveals roughly where the input
+/// went wrong; authenticate untrusted frames before validating them if that matters.
+///
+/// The profile relaxations in `limits` (`allow_full_int_range`, `extra_allowed_tags`) are
+/// ignored, so the result is always canonical; use [`validate_relaxed`] to accept them.
+///
 /// # Errors
 ///
 /// Returns an error if decoding fails (EOF, trailing bytes, limit violations) or if validation fails
@@ -24,6 +31,29 @@ pub fn validate_canonical(
     bytes: &'_ [u8],
     limits: DecodeLimits,
 ) -> Result<CanonicalCborRef<'_>, CborError> {
+    validate_single(bytes, limits.without_relaxations())?;
+    Ok(CanonicalCborRef::new(bytes))
+}
+
+/// Validate one data item under `limits`, honoring its profile relaxations.
+///
+/// Like [`validate_canonical`], except that integers outside the safe range (with
+/// `allow_full_int_range`) and tags in `extra_allowed_tags` are accepted. Such documents
+/// are not canonical SACP-CBOR/1, so the result is a [`RelaxedCborRef`], which can be
+/// queried but not spliced into an [`crate::Encoder`] as canonical bytes.
+///
+/// # Errors
+///
+/// As for [`validate_canonical`].
+pub fn validate_relaxed(
+    bytes: &'_ [u8],
+    limits: DecodeLimits,
+) -> Result<RelaxedCborRef<'_>, CborError> {
+    validate_single(bytes, limits)?;
+    Ok(RelaxedCborRef::new(bytes))
+}
+
+fn validate_single(bytes: &[u8], limits: DecodeLimits) -> Result<(), CborError> {
     if bytes.len() > limits.max_input_bytes {
         return Err(CborError::new(ErrorCode::MessageLenLimitExceeded, 0));
     }
@@ -31,7 +61,50 @@ pub fn validate_canonical(
     if end != bytes.len() {
         return Err(CborError::new(ErrorCode::TrailingBytes, end));
     }
-    Ok(CanonicalCborRef::new(bytes))
+    Ok(())
+}
+
+/// Validate the canonical SACP-CBOR/1 data item at the start of `bytes` and return its length.
+///
+/// Unlike [`validate_canonical`], bytes after the first item are ignored rather than
+/// reported as `TrailingBytes`, which suits framed or concatenated streams. The item
+/// itself (not the whole slice) must fit within `max_input_bytes`. As for
+/// [`validate_canonical`], the profile relaxations in `limits` are ignored.
+///
+/// # Errors
+///
+/// Returns `UnexpectedEof` if `bytes` ends before the first item does (more input may
+/// complete it), `MessageLenLimitExceeded` if the item is longer than `max_input_bytes`,
+/// or any validation error for the item.
+pub fn validate_prefix(bytes: &[u8], limits: DecodeLimits) -> Result<usize, CborError> {
+    let window = &bytes[..bytes.len().min(limits.max_input_bytes)];
+    value_end_internal(window, 0, Some(limits.without_relaxations())).map_err(|err| {
+        if err.code() == ErrorCode::UnexpectedEof && window.len() < bytes.len() {
+            CborError::new(ErrorCode::MessageLenLimitExceeded, 0)
+        } else {
+            err
+        }
+    })
+}
+
+/// Validate a sequence of independent frames, each of which must be exactly one canonical item.
+///
+/// Every frame is checked against the same `limits`, and validation stops at the first
+/// failure. Returns the number of frames validated. Allocation-free, like [`validate`].
+///
+/// # Errors
+///
+/// Returns the zero-based index of the first invalid frame together with its error.
+pub fn validate_many<'a, I>(frames: I, limits: DecodeLimits) -> Result<usize, (usize, CborError)>
+where
+    I: IntoIterator<Item = &'a [u8]>,
+{
+    let mut count = 0;
+    for frame in frames {
+        validate(frame, limits).map_err(|err| (count, err))?;
+        count += 1;
+    }
+    Ok(count)
 }
 
 fn value_end_internal(
@@ -31,6 +31,7 @@
 
 use core::cmp::Ordering;
 
+use crate::int::cmp_big_endian;
 use crate::ErrorCode;
 
 /// Maximum safe integer (2^53-1).
@@ -76,7 +77,6 @@ pub const fn validate_f64_bits(bits: u64) -> Result<(), ErrorCode> {
 
 /// Validate that an i64 is within the SACP-CBOR/1 safe integer range.
 #[inline]
-#[cfg(feature = "alloc")]
 pub const fn validate_int_safe_i64(v: i64) -> Result<(), ErrorCode> {
     if v < MIN_SAFE_INTEGER || v > MAX_SAFE_INTEGER_I64 {
         return Err(ErrorCode::IntegerOutsideSafeRange);
@@ -107,13 +107,6 @@ pub fn validate_bignum_bytes(negative: bool, magnitude: &[u8]) -> Result<(), Err
     Ok(())
 }
 
-fn cmp_big_endian(a: &[u8], b: &[u8]) -> Ordering {
-    match a.len().cmp(&b.len()) {
-        Ordering::Equal => a.cmp(b),
-        other => other,
-    }
-}
-
 /// Compare two CBOR-encoded map keys by the canonical CBOR ordering rule.
 ///
 /// Canonical ordering is:
@@ -162,6 +155,29 @@ pub fn cmp_text_keys_canonical(a: &str, b: &str) -> Ordering {
     }
 }
 
+/// Compare two map keys by canonical CBOR map ordering.
+///
+/// Keys sort by encoded length first, then by their encoded bytes, so shorter keys
+/// always come first. Use this to pre-sort entries before feeding [`crate::Encoder`]'s
+/// `map` builder.
+///
+/// ```
+/// use core::cmp::Ordering;
+/// use sacp_cbor::canonical_key_cmp;
+///
+/// // Length-first: "z" sorts before "aa".
+/// assert_eq!(canonical_key_cmp("z", "aa"), Ordering::Less);
+///
+/// let mut keys = vec!["bb", "z", "a"];
+/// keys.sort_by(|a, b| canonical_key_cmp(a, b));
+/// assert_eq!(keys, ["a", "z", "bb"]);
+/// ```
+#[inline]
+#[must_use]
+pub fn canonical_key_cmp(a: &str, b: &str) -> Ordering {
+    cmp_text_keys_canonical(a, b)
+}
+
 /// Return the length in bytes of the canonical CBOR encoding of a text string payload of length `n`.
 ///
 /// This is `header_len(n) + n`, where `header_len(n)` depends on the canonical CBOR length encoding:
@@ -9,18 +9,26 @@
 //! APIs may return [`ErrorCode::MalformedCanonical`].
 
 use core::cmp::Ordering;
-
-use crate::canonical::CanonicalCborRef;
-use crate::profile::{checked_text_len, cmp_text_keys_canonical};
+use core::fmt;
+
+use crate::canonical::{CanonicalCborRef, RelaxedCborRef};
+use crate::codec::{CborDecode, Decoder};
+use crate::int::{cmp_big_endian, decrement_be};
+use crate::profile::{
+    checked_text_len, cmp_text_keys_canonical, MAX_SAFE_INTEGER_I64, MIN_SAFE_INTEGER,
+};
 use crate::utf8;
 use crate::wire;
-use crate::{CborError, ErrorCode};
+use crate::{CborError, DecodeLimits, ErrorCode};
 
 #[cfg(feature = "alloc")]
 use crate::canonical::CanonicalCbor;
-#[cfg(feature = "alloc")]
 use crate::canonical::EncodedTextKey;
 
+#[cfg(feature = "alloc")]
+use crate::codec::MapEntries;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 
@@ -43,6 +51,43 @@ pub enum CborKind {
     Null,
     /// IEEE-754 float64 (major 7, ai 27).
     Float,
+    /// Major type 6 other than bignums, only found in documents validated under
+    /// [`crate::DecodeLimits::extra_allowed_tags`]. Read it with [`CborValueRef::tag`].
+    Tag,
+}
+
+impl CborKind {
+    /// Returns the `Expected*` error code reported when a value of this kind was required.
+    #[must_use]
+    pub const fn expected_code(self) -> ErrorCode {
+        match self {
+            Self::Integer => ErrorCode::ExpectedInteger,
+            Self::Bytes => ErrorCode::ExpectedBytes,
+            Self::Text => ErrorCode::ExpectedText,
+            Self::Array => ErrorCode::ExpectedArray,
+            Self::Map => ErrorCode::ExpectedMap,
+            Self::Bool => ErrorCode::ExpectedBool,
+            Self::Null => ErrorCode::ExpectedNull,
+            Self::Float => ErrorCode::ExpectedFloat,
+            Self::Tag => ErrorCode::ExpectedTag,
+        }
+    }
+
+    /// Returns a short lowercase name, as used in error messages.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Integer => "integer",
+            Self::Bytes => "bytes",
+            Self::Text => "text",
+            Self::Array => "array",
+            Self::Map => "map",
+            Self::Bool => "bool",
+            Self::Null => "null",
+            Self::Float => "float",
+            Self::Tag => "tag",
+        }
+    }
 }
 
 const fn err(code: ErrorCode, offset: usize) -> CborError {
@@ -129,11 +174,14 @@ impl<'a> BigIntRef<'a> {
     }
 }
 
-/// A borrowed view of an integer (safe or bignum).
+/// A borrowed view of an integer (safe, wide, or bignum).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CborIntegerRef<'a> {
-    /// Safe-range integer (major 0/1).
+    /// Major 0/1 integer within the safe range.
     Safe(i64),
+    /// Major 0/1 integer outside the safe range, only found in documents validated under
+    /// [`crate::DecodeLimits::allow_full_int_range`].
+    Wide(i128),
     /// Bignum integer (tag 2/3).
     Big(BigIntRef<'a>),
 }
@@ -144,6 +192,16 @@ impl<'a> CborIntegerRef<'a> {
     pub const fn as_i64(self) -> Option<i64> {
         match self {
             Self::Safe(v) => Some(v),
+            Self::Wide(_) | Self::Big(_) => None,
+        }
+    }
+
+    /// Return the value of a major 0/1 integer, safe or wide.
+    #[must_use]
+    pub const fn as_i128(self) -> Option<i128> {
+        match self {
+            Self::Safe(v) => Some(v as i128),
+            Self::Wide(v) => Some(v),
             Self::Big(_) => None,
         }
     }
@@ -152,7 +210,7 @@ impl<'a> CborIntegerRef<'a> {
     #[must_use]
     pub const fn as_bigint(self) -> Option<BigIntRef<'a>> {
         match self {
-            Self::Safe(_) => None,
+            Self::Safe(_) | Self::Wide(_) => None,
             Self::Big(b) => Some(b),
         }
     }
@@ -238,7 +296,7 @@ impl<'a> CborValueRef<'a> {
                 let tag = read_uint_trusted(self.data, &mut pos, ai, off)?;
                 match tag {
                     2 | 3 => Ok(CborKind::Integer),
-                    _ => Err(malformed(off)),
+                    _ => Ok(CborKind::Tag),
                 }
             }
             7 => match ai {
@@ -251,6 +309,49 @@ impl<'a> CborValueRef<'a> {
         }
     }
 
+    /// Returns `self` if it is of `kind`, for fluent chaining such as
+    /// `v.expect(CborKind::Map)?.map()?`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `Expected*` code for `kind` (see [`CborKind::expected_code`]) at this value's
+    /// offset, with the actual kind available through [`CborError::found_kind`].
+    pub fn expect(self, kind: CborKind) -> Result<Self, CborError> {
+        let actual = self.kind()?;
+        if actual == kind {
+            return Ok(self);
+        }
+        Err(err(kind.expected_code(), self.start).with_found(actual))
+    }
+
+    /// Render this value as an indented, human-readable tree for logs.
+    ///
+    /// Map entries become `key: value` lines and array items `- value` lines, with nested
+    /// containers indented by `indent` spaces per level. Text is quoted, bytes are shown as
+    /// `0x…` hex, and bignums as signed decimals (or signed hex beyond 128 bits). Keys made
+    /// only of ASCII letters, digits, `_`, `-` and `.` are written bare; any other key is
+    /// quoted and escaped like text, so keys cannot inject lines into the output. This is
+    /// not diagnostic notation and cannot be parsed back; see `to_editable_text` (`json`
+    /// feature) for a round-trippable format.
+    ///
+    /// ```text
+    /// id: 42
+    /// tags:
+    ///   - "x"
+    ///   - 0x0102
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` for malformed canonical input.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn display_pretty(self, indent: usize) -> Result<String, CborError> {
+        let mut out = String::new();
+        pretty_value(&mut out, self, indent, 0)?;
+        Ok(out)
+    }
+
     /// Returns `true` if this value is CBOR `null`.
     #[must_use]
     pub fn is_null(self) -> bool {
@@ -287,6 +388,21 @@ impl<'a> CborValueRef<'a> {
         })
     }
 
+    /// Returns the item count of an array or the entry count of a map, or `None` for any
+    /// other kind. Only the header is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MalformedCanonical` if the header is truncated.
+    pub fn container_len(self) -> Result<Option<usize>, CborError> {
+        let mut pos = self.start;
+        let ib = read_u8_trusted(self.data, &mut pos)?;
+        match ib >> 5 {
+            4 | 5 => read_len_trusted(self.data, &mut pos, ib & 0x1f, self.start).map(Some),
+            _ => Ok(None),
+        }
+    }
+
     /// Retrieves a value by map key from this value (which must be a map).
     ///
     /// # Errors
@@ -330,7 +446,162 @@ impl<'a> CborValueRef<'a> {
         Ok(Some(cur))
     }
 
-    /// Decodes this value as a CBOR integer (safe or bignum).
+    /// Traverses an RFC 6901 JSON Pointer such as `/items/0/name`.
+    ///
+    /// Each `/`-separated token is unescaped (`~1` is `/`, `~0` is `~`). On an array the token
+    /// must be a decimal index without leading zeros; on a map it is a key. The empty pointer
+    /// refers to this value.
+    ///
+    /// Unlike [`Self::at`], missing keys, out-of-range indices, and type mismatches all give
+    /// `Ok(None)`, which suits string-driven lookups from CLIs and scripts. Passthrough tags
+    /// (see [`Self::tag`]) are stepped through, so a token applies to the tagged content.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidTextSyntax` (offset into `ptr`) if `ptr` is non-empty and does not start
+    /// with `/`, or contains `~` not followed by `0` or `1`; or `CborError` for malformed
+    /// canonical input.
+    pub fn pointer(self, ptr: &str) -> Result<Option<Self>, CborError> {
+        if ptr.is_empty() {
+            return Ok(Some(self));
+        }
+        let Some(rest) = ptr.strip_prefix('/') else {
+            return Err(CborError::new(ErrorCode::InvalidTextSyntax, 0));
+        };
+        let mut cur = Some(self);
+        let mut off = 1;
+        for token in rest.split('/') {
+            check_pointer_token(token, off)?;
+            off += token.len() + 1;
+            let Some(mut value) = cur else { continue };
+            while let Some((_, content)) = value.tag()? {
+                value = content;
+            }
+            cur = match value.kind()? {
+                CborKind::Array => match parse_pointer_index(token) {
+                    Some(i) => value.get_index(i)?,
+                    None => None,
+                },
+                CborKind::Map if token.contains('~') => pointer_map_lookup(value.map()?, token)?,
+                CborKind::Map => value.get_key(token)?,
+                _ => None,
+            };
+        }
+        Ok(cur)
+    }
+
+    /// Traverses `path` and decodes the terminal value as text.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpectedText` if the terminal value is not text, or `CborError` for type
+    /// mismatches along the path.
+    pub fn at_text(self, path: &[PathElem<'_>]) -> Result<Option<&'a str>, CborError> {
+        self.at(path)?.map(Self::text).transpose()
+    }
+
+    /// Traverses `path` and decodes the terminal value as a safe-range integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpectedInteger` if the terminal value is not an integer,
+    /// `IntegerOutsideSafeRange` for bignums, or `CborError` for type mismatches along
+    /// the path.
+    pub fn at_i64(self, path: &[PathElem<'_>]) -> Result<Option<i64>, CborError> {
+        self.at(path)?
+            .map(|v| {
+                v.integer()?
+                    .as_i64()
+                    .ok_or_else(|| CborError::new(ErrorCode::IntegerOutsideSafeRange, v.start))
+            })
+            .transpose()
+    }
+
+    /// Traverses `path` and decodes the terminal value as a float64.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpectedFloat` if the terminal value is not a float, or `CborError` for
+    /// type mismatches along the path.
+    pub fn at_f64(self, path: &[PathElem<'_>]) -> Result<Option<f64>, CborError> {
+        self.at(path)?.map(Self::float64).transpose()
+    }
+
+    /// Traverses `path` and decodes the terminal value as a boolean.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpectedBool` if the terminal value is not a boolean, or `CborError` for
+    /// type mismatches along the path.
+    pub fn at_bool(self, path: &[PathElem<'_>]) -> Result<Option<bool>, CborError> {
+        self.at(path)?.map(Self::bool).transpose()
+    }
+
+    /// Traverses `path` and decodes the terminal value as a byte string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpectedBytes` if the terminal value is not a byte string, or `CborError`
+    /// for type mismatches along the path.
+    pub fn at_bytes(self, path: &[PathElem<'_>]) -> Result<Option<&'a [u8]>, CborError> {
+        self.at(path)?.map(Self::bytes).transpose()
+    }
+
+    /// Decodes this value into a typed [`CborDecode`] implementation.
+    ///
+    /// The value's bytes are already validated, so this uses the trusted decoder and
+    /// does not re-check canonical encoding. Error offsets are reported relative to the
+    /// enclosing message, like other query errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` if the value does not match the shape `T` expects.
+    pub fn decode_as<T: CborDecode<'a>>(self) -> Result<T, CborError> {
+        // Not `decode_canonical`: values reached through a `RelaxedCborRef` would trip its
+        // debug re-validation.
+        let bytes = self.as_bytes();
+        let mut decoder = Decoder::<false>::new_trusted(
+            CanonicalCborRef::new(bytes),
+            DecodeLimits::for_bytes(bytes.len()),
+        )
+        .map_err(|err| err.shifted(self.start))?;
+        let value = T::decode(&mut decoder).map_err(|err| err.shifted(self.start))?;
+        if decoder.position() != bytes.len() {
+            return Err(err(
+                ErrorCode::TrailingBytes,
+                self.start + decoder.position(),
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Returns the tag number and content if this value is a tag admitted by
+    /// [`crate::DecodeLimits::extra_allowed_tags`].
+    ///
+    /// Bignums (tags 2 and 3) are integers rather than passthrough tags, so they and all
+    /// untagged values return `None`. [`CborValueRef::kind`] reports passthrough tags as
+    /// [`CborKind::Tag`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` if the underlying bytes are malformed.
+    pub fn tag(self) -> Result<Option<(u64, Self)>, CborError> {
+        let mut pos = self.start;
+        let off = self.start;
+        let ib = read_u8_trusted(self.data, &mut pos)?;
+        if ib >> 5 != 6 {
+            return Ok(None);
+        }
+        match read_uint_trusted(self.data, &mut pos, ib & 0x1f, off)? {
+            2 | 3 => Ok(None),
+            tag => Ok(Some((tag, Self::new(self.data, pos, self.end)))),
+        }
+    }
+
+    /// Decodes this value as a CBOR integer (safe, wide, or bignum).
+    ///
+    /// Major 0/1 integers outside the safe range, which only validate under
+    /// [`crate::DecodeLimits::allow_full_int_range`], are returned as `Wide`.
     ///
     /// # Errors
     ///
@@ -343,15 +614,15 @@ impl<'a> CborValueRef<'a> {
         let ai = ib & 0x1f;
 
         match major {
-            0 => {
-                let u = read_uint_trusted(self.data, &mut pos, ai, off)?;
-                let i = i64::try_from(u).map_err(|_| malformed(off))?;
-                Ok(CborIntegerRef::Safe(i))
-            }
-            1 => {
-                let n = read_uint_trusted(self.data, &mut pos, ai, off)?;
-                let n_i = i64::try_from(n).map_err(|_| malformed(off))?;
-                Ok(CborIntegerRef::Safe(-1 - n_i))
+            0 | 1 => {
+                let n = i128::from(read_uint_trusted(self.data, &mut pos, ai, off)?);
+                let v = if major == 0 { n } else { -1 - n };
+                Ok(match i64::try_from(v) {
+                    Ok(v) if (MIN_SAFE_INTEGER..=MAX_SAFE_INTEGER_I64).contains(&v) => {
+                        CborIntegerRef::Safe(v)
+                    }
+                    _ => CborIntegerRef::Wide(v),
+                })
             }
             6 => {
                 let tag = read_uint_trusted(self.data, &mut pos, ai, off)?;
@@ -424,6 +695,25 @@ impl<'a> CborValueRef<'a> {
         Ok(bytes)
     }
 
+    /// Compares this byte string against `other` without early exit on content.
+    ///
+    /// Intended for secrets stored as CBOR byte strings (MAC tags, tokens). The running
+    /// time depends only on the lengths involved, not on where the first differing byte
+    /// is. Lengths are **not** hidden: a length mismatch returns `false` immediately,
+    /// matching the usual convention for fixed-size secrets.
+    ///
+    /// With the `subtle` feature this delegates to [`subtle::ConstantTimeEq`]. Without it,
+    /// a portable fallback accumulates differences through [`core::hint::black_box`],
+    /// which is best-effort: it discourages but cannot forbid compiler short-circuiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError::ExpectedBytes` if the value is not a byte string or is malformed.
+    pub fn ct_eq_bytes(self, other: &[u8]) -> Result<bool, CborError> {
+        let bytes = self.bytes()?;
+        Ok(ct_eq(bytes, other))
+    }
+
     /// Decodes this value as a CBOR boolean.
     ///
     /// # Errors
@@ -460,6 +750,114 @@ impl<'a> CborValueRef<'a> {
         let bits = u64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]);
         Ok(f64::from_bits(bits))
     }
+
+    /// Returns a fast, non-cryptographic hash of this value's canonical bytes.
+    ///
+    /// Byte-equal values always hash equal, so the hash can be used as a cheap
+    /// pre-filter before a full [`PartialEq`] comparison. The hash is the 64-bit FNV-1a
+    /// digest, stable across runs and platforms, so it may be persisted; it is not
+    /// collision resistant, so use the `sha2` canonical hashing for identifiers.
+    #[must_use]
+    pub fn content_hash(self) -> u64 {
+        fnv1a64(self.as_bytes())
+    }
+
+    /// Compare two numeric values by mathematical value.
+    ///
+    /// Safe integers, bignums, and floats compare exactly against each other (no lossy
+    /// conversion), so `3 < 3.5` and any positive bignum is greater than every safe
+    /// integer. The canonical NaN compares equal to itself and greater than every other
+    /// number, including `+Infinity`, which gives a total order suitable for sorting.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExpectedNumber` if either value is not an integer or float, or
+    /// `MalformedCanonical` if the bytes are corrupt.
+    pub fn numeric_cmp(self, other: CborValueRef<'_>) -> Result<Ordering, CborError> {
+        Ok(cmp_numeric(self.numeric()?, other.numeric()?))
+    }
+
+    fn numeric(self) -> Result<Numeric<'a>, CborError> {
+        match self.kind()? {
+            CborKind::Integer => Ok(match self.integer()? {
+                CborIntegerRef::Safe(v) => Numeric::Int(i128::from(v)),
+                CborIntegerRef::Wide(v) => Numeric::Int(v),
+                CborIntegerRef::Big(b) => Numeric::Big(b),
+            }),
+            CborKind::Float => Ok(Numeric::Float(self.float64()?)),
+            _ => Err(err(ErrorCode::ExpectedNumber, self.start)),
+        }
+    }
+
+    /// Returns a SHA-256 fingerprint of this value's shape, ignoring scalar contents.
+    ///
+    /// The digest covers each item's kind, container lengths, and map key names, but not
+    /// the values of integers, strings, floats, or booleans. Two documents with the same
+    /// structure and keys but different data therefore share a digest, which makes it
+    /// suitable for grouping messages by schema. No allocation is performed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` if the value is malformed.
+    #[cfg(feature = "sha2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+    pub fn shape_digest(self) -> Result<[u8; 32], CborError> {
+        use sha2::{Digest, Sha256};
+        let mut h = Sha256::new();
+        hash_shape(&mut h, self)?;
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(h.finalize().as_slice());
+        Ok(digest)
+    }
+}
+
+#[cfg(feature = "sha2")]
+fn hash_shape(h: &mut sha2::Sha256, value: CborValueRef<'_>) -> Result<(), CborError> {
+    use sha2::Digest;
+    let kind = value.kind()?;
+    h.update([kind_tag(kind)]);
+    match kind {
+        CborKind::Tag => {
+            // Tag numbers are structure, like map keys.
+            let (tag, content) = value.tag()?.ok_or_else(|| malformed(value.start))?;
+            h.update(tag.to_be_bytes());
+            hash_shape(h, content)?;
+        }
+        CborKind::Array => {
+            let array = value.array()?;
+            h.update((array.len() as u64).to_be_bytes());
+            for item in array.iter() {
+                hash_shape(h, item?)?;
+            }
+        }
+        CborKind::Map => {
+            let map = value.map()?;
+            h.update((map.len() as u64).to_be_bytes());
+            for entry in map.iter() {
+                let (key, v) = entry?;
+                h.update((key.len() as u64).to_be_bytes());
+                h.update(key.as_bytes());
+                hash_shape(h, v)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sha2")]
+const fn kind_tag(kind: CborKind) -> u8 {
+    match kind {
+        CborKind::Integer => 0,
+        CborKind::Bytes => 1,
+        CborKind::Text => 2,
+        CborKind::Array => 3,
+        CborKind::Map => 4,
+        CborKind::Bool => 5,
+        CborKind::Null => 6,
+        CborKind::Float => 7,
+        CborKind::Tag => 8,
+    }
 }
 
 impl PartialEq for CborValueRef<'_> {
@@ -470,6 +868,123 @@ impl PartialEq for CborValueRef<'_> {
 
 impl Eq for CborValueRef<'_> {}
 
+/// Hashes the canonical bytes, consistent with [`PartialEq`].
+///
+/// Suitable for `HashMap` keys over borrowed values. The result depends on the chosen
+/// [`Hasher`](core::hash::Hasher) and is not a stable digest; see
+/// [`CborValueRef::content_hash`] or the `sha2` helpers for that.
+impl core::hash::Hash for CborValueRef<'_> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+/// The exact canonical bytes of one value, captured while decoding.
+///
+/// `RawCbor` decodes like [`CborValueRef`] (the value is skipped and its byte range
+/// recorded) but exists to make intent explicit: use it for fields whose bytes are
+/// hashed, signed, or forwarded verbatim rather than inspected. Re-encoding it writes
+/// the captured bytes unchanged.
+#[derive(Debug, Clone, Copy)]
+pub struct RawCbor<'a> {
+    data: &'a [u8],
+    start: usize,
+    end: usize,
+}
+
+impl<'a> RawCbor<'a> {
+    #[inline]
+    pub(crate) const fn new(data: &'a [u8], start: usize, end: usize) -> Self {
+        Self { data, start, end }
+    }
+
+    /// Returns the captured value as a canonical CBOR reference, for hashing or forwarding.
+    #[must_use]
+    pub fn as_canonical(self) -> CanonicalCborRef<'a> {
+        CanonicalCborRef::new(self.as_bytes())
+    }
+
+    /// Returns the captured canonical bytes.
+    #[must_use]
+    pub fn as_bytes(self) -> &'a [u8] {
+        &self.data[self.start..self.end]
+    }
+
+    /// Returns the starting offset (in bytes) of the value within the message.
+    #[must_use]
+    pub const fn offset(self) -> usize {
+        self.start
+    }
+
+    /// Returns a query view of the captured value.
+    #[must_use]
+    pub const fn value(self) -> CborValueRef<'a> {
+        CborValueRef::new(self.data, self.start, self.end)
+    }
+}
+
+impl PartialEq for RawCbor<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for RawCbor<'_> {}
+
+/// Groups byte-equal values, returning the index of each value's first occurrence.
+///
+/// `out[i]` is the smallest `j <= i` such that `values[j] == values[i]`; values
+/// that are unique map to their own index. Candidates are bucketed by
+/// [`CborValueRef::content_hash`], so full byte comparisons only happen between
+/// values whose hashes collide.
+///
+/// This API is available with the `alloc` feature.
+///
+/// # Errors
+///
+/// Returns `CborError` if the scratch or output buffers cannot be allocated.
+#[cfg(feature = "alloc")]
+pub fn dedup(values: &[CborValueRef<'_>]) -> Result<Vec<usize>, CborError> {
+    use crate::alloc_util::try_vec_with_capacity;
+
+    let mut hashed: Vec<(u64, usize)> = try_vec_with_capacity(values.len(), 0)?;
+    hashed.extend(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.content_hash(), i)),
+    );
+    hashed.sort_unstable();
+
+    let mut out: Vec<usize> = try_vec_with_capacity(values.len(), 0)?;
+    out.extend(0..values.len());
+
+    let mut run_start = 0;
+    while run_start < hashed.len() {
+        let hash = hashed[run_start].0;
+        let mut run_end = run_start + 1;
+        while run_end < hashed.len() && hashed[run_end].0 == hash {
+            run_end += 1;
+        }
+
+        // Within a run, indices are ascending, so the first byte-equal match is the
+        // first occurrence. Runs longer than one are almost always true duplicates.
+        for i in run_start + 1..run_end {
+            let idx = hashed[i].1;
+            let bytes = values[idx].as_bytes();
+            for &(_, prev) in &hashed[run_start..i] {
+                if out[prev] == prev && values[prev].as_bytes() == bytes {
+                    out[idx] = prev;
+                    break;
+                }
+            }
+        }
+        run_start = run_end;
+    }
+
+    Ok(out)
+}
+
 /// A borrowed view into a canonical CBOR map.
 ///
 /// Map keys are text strings and appear in canonical order (encoded length then
@@ -497,7 +1012,9 @@ impl<'a> MapRef<'a> {
 
     /// Looks up a single key in the map.
     ///
-    /// This is efficient for canonical maps: it scans entries once and can stop early.
+    /// This is efficient for canonical maps: it scans entries once and stops at the first key
+    /// that sorts after `key`. Entries have variable length, so a true binary search needs
+    /// their offsets first; for repeated lookups build them once with [`MapRef::build_index`].
     ///
     /// # Errors
     ///
@@ -534,6 +1051,63 @@ impl<'a> MapRef<'a> {
         Ok(None)
     }
 
+    /// Looks up `key` and decodes its value as `T`, or returns `None` if the key is absent.
+    ///
+    /// The value is decoded with [`CborValueRef::decode_as`], so error offsets point into
+    /// the enclosing message.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` if the map is malformed or the value does not decode as `T`.
+    pub fn get_as<T: CborDecode<'a>>(self, key: &str) -> Result<Option<T>, CborError> {
+        self.get(key)?.map(CborValueRef::decode_as).transpose()
+    }
+
+    /// Returns the entry at position `i` in canonical key order, or `None` if out of bounds.
+    ///
+    /// This walks the preceding entries, so it is `O(bytes before the entry)`. For repeated
+    /// positional or keyed access, build a [`MapIndex`] once with [`MapRef::build_index`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` if the map is malformed.
+    pub fn index(self, i: usize) -> Result<Option<(&'a str, CborValueRef<'a>)>, CborError> {
+        if i >= self.len {
+            return Ok(None);
+        }
+        let mut pos = self.entries_start;
+        let mut scratch = wire::SkipScratch::new();
+        for n in 0..=i {
+            let key = read_text(self.data, &mut pos)?;
+            let value_start = pos;
+            pos = value_end_with_scratch(self.data, value_start, &mut scratch)?;
+            if n == i {
+                return Ok(Some((key, CborValueRef::new(self.data, value_start, pos))));
+            }
+        }
+        Err(malformed(self.map_off))
+    }
+
+    /// Scans the map once and returns an index for `O(log n)` lookups.
+    ///
+    /// This API is available with the `alloc` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` if the map is malformed or the index cannot be allocated.
+    #[cfg(feature = "alloc")]
+    pub fn build_index(self) -> Result<MapIndex<'a>, CborError> {
+        let mut entries = crate::alloc_util::try_vec_with_capacity(self.len, self.map_off)?;
+        for entry in self.iter() {
+            let (key, value) = entry?;
+            entries.push((key, value.start, value.end));
+        }
+        Ok(MapIndex {
+            data: self.data,
+            entries,
+        })
+    }
+
     /// Looks up a required key in the map.
     ///
     /// # Errors
@@ -630,6 +1204,9 @@ impl<'a> MapRef<'a> {
 
     /// Iterates over `(key, value)` pairs in canonical order.
     ///
+    /// Canonical order (length-first, then bytewise) is fixed by the encoding itself, so
+    /// iteration order is stable across runs, platforms, and re-encodings of the same value.
+    ///
     /// The iterator yields `Result` to remain robust if canonical invariants are violated.
     pub fn iter(self) -> impl Iterator<Item = Result<(&'a str, CborValueRef<'a>), CborError>> + 'a {
         MapIter {
@@ -640,13 +1217,54 @@ impl<'a> MapRef<'a> {
         }
     }
 
-    /// Iterates over `(key, encoded_key, value)` in canonical order.
+    /// Iterates over the keys in canonical order.
+    ///
+    /// Each value is still skipped to reach the next key.
+    pub fn keys(self) -> impl Iterator<Item = Result<&'a str, CborError>> + 'a {
+        self.iter().map(|entry| entry.map(|(key, _)| key))
+    }
+
+    /// Iterates over the values in canonical key order.
+    ///
+    /// Keys are skipped by length without being decoded as text.
+    pub fn values(self) -> impl Iterator<Item = Result<CborValueRef<'a>, CborError>> + 'a {
+        MapValues {
+            data: self.data,
+            pos: self.entries_start,
+            remaining: self.len,
+            scratch: wire::SkipScratch::new(),
+        }
+    }
+
+    /// Decodes every value as `V` and collects the entries with owned keys.
+    ///
+    /// Each value is decoded over its own span with the trusted decoder (see
+    /// [`CborValueRef::decode_as`]); entries keep canonical order.
+    ///
+    /// # Errors
     ///
-    /// The encoded key is the canonical CBOR encoding of the text key.
+    /// Returns `CborError` if any value does not decode as `V`, or on allocation failure.
     #[cfg(feature = "alloc")]
-    pub(crate) fn iter_encoded(
-        self,
-    ) -> impl Iterator<Item = Result<EncodedMapEntry<'a>, CborError>> + 'a {
+    pub fn to_map_entries<V: CborDecode<'a>>(self) -> Result<MapEntries<String, V>, CborError> {
+        use crate::alloc_util::{try_string_from_str, try_vec_with_capacity};
+
+        let mut out = try_vec_with_capacity(self.len, self.map_off)?;
+        for entry in self.iter() {
+            let (key, value) = entry?;
+            let key = try_string_from_str(key, value.start)?;
+            out.push((key, value.decode_as()?));
+        }
+        Ok(MapEntries(out))
+    }
+
+    /// Iterates over `(key, encoded_key, value)` in canonical order.
+    ///
+    /// The encoded key borrows the key's canonical CBOR bytes from the input, so entries can
+    /// be re-emitted with [`crate::MapEncoder::entry_raw_key`] without re-encoding the key,
+    /// for example when copying a map with one value changed.
+    ///
+    /// The iterator yields `Result` to remain robust if canonical invariants are violated.
+    pub fn iter_encoded(self) -> impl Iterator<Item = Result<EncodedMapEntry<'a>, CborError>> + 'a {
         MapIterEncoded {
             data: self.data,
             pos: self.entries_start,
@@ -831,6 +1449,52 @@ impl<'a> MapRef<'a> {
     }
 }
 
+/// Entry offsets of a [`MapRef`], built by [`MapRef::build_index`].
+///
+/// Keys are kept in canonical order, so lookups binary-search with the canonical
+/// comparator instead of scanning. The values still borrow the original message.
+///
+/// This API is available with the `alloc` feature.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct MapIndex<'a> {
+    data: &'a [u8],
+    entries: Vec<(&'a str, usize, usize)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> MapIndex<'a> {
+    /// Returns the number of entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the map is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Looks up `key` in `O(log n)`.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<CborValueRef<'a>> {
+        let i = self
+            .entries
+            .binary_search_by(|&(k, _, _)| cmp_text_keys_canonical(k, key))
+            .ok()?;
+        let (_, start, end) = self.entries[i];
+        Some(CborValueRef::new(self.data, start, end))
+    }
+
+    /// Returns the entry at position `i` in canonical key order.
+    #[must_use]
+    pub fn index(&self, i: usize) -> Option<(&'a str, CborValueRef<'a>)> {
+        let &(key, start, end) = self.entries.get(i)?;
+        Some((key, CborValueRef::new(self.data, start, end)))
+    }
+}
+
 /// A borrowed view into a canonical CBOR array.
 #[derive(Debug, Clone, Copy)]
 pub struct ArrayRef<'a> {
@@ -888,6 +1552,95 @@ impl<'a> ArrayRef<'a> {
             scratch: wire::SkipScratch::new(),
         }
     }
+
+    /// Iterates over the canonical encoding of each item, in order.
+    ///
+    /// Useful for forwarding items untouched; each slice is a complete canonical data item.
+    pub fn as_slices(self) -> impl Iterator<Item = Result<&'a [u8], CborError>> + 'a {
+        self.iter().map(|item| item.map(CborValueRef::as_bytes))
+    }
+
+    /// Iterates over non-overlapping runs of `size` items, in order.
+    ///
+    /// Each chunk is itself an `ArrayRef` over its items' byte range, so it supports `get`,
+    /// `iter`, and further chunking. The last chunk holds the remainder and may be shorter.
+    /// Chunks are found by walking forward, so the whole iteration is `O(bytes)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn chunks(self, size: usize) -> impl Iterator<Item = Result<Self, CborError>> + 'a {
+        assert!(size != 0, "chunk size must be non-zero");
+        ArrayChunks {
+            items: ArrayIter {
+                data: self.data,
+                pos: self.items_start,
+                remaining: self.len,
+                scratch: wire::SkipScratch::new(),
+            },
+            size,
+        }
+    }
+
+    /// Decodes every item as `T` and collects them in order.
+    ///
+    /// Each item is decoded over its own span with the trusted decoder (see
+    /// [`CborValueRef::decode_as`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` if any item does not decode as `T`, or on allocation failure.
+    #[cfg(feature = "alloc")]
+    pub fn to_vec<T: CborDecode<'a>>(self) -> Result<Vec<T>, CborError> {
+        use crate::alloc_util::try_vec_with_capacity;
+
+        let mut out = try_vec_with_capacity(self.len, self.array_off)?;
+        for item in self.iter() {
+            out.push(item?.decode_as()?);
+        }
+        Ok(out)
+    }
+}
+
+/// Adds query methods to `RelaxedCborRef`.
+impl<'a> RelaxedCborRef<'a> {
+    /// Returns a borrowed view of the root CBOR item.
+    ///
+    /// Values inside may use the relaxations the document was validated under, so
+    /// [`crate::Encoder::raw_value_ref`] copies them verbatim without making them canonical.
+    #[must_use]
+    pub const fn root(self) -> CborValueRef<'a> {
+        CborValueRef::new(self.as_bytes(), 0, self.len())
+    }
+
+    /// Convenience wrapper around `self.root().at(path)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` for type mismatches or malformed input.
+    pub fn at(self, path: &[PathElem<'_>]) -> Result<Option<CborValueRef<'a>>, CborError> {
+        self.root().at(path)
+    }
+
+    /// Convenience wrapper around `self.root().pointer(ptr)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidTextSyntax` for a malformed pointer, or `CborError` for malformed
+    /// input.
+    pub fn pointer(self, ptr: &str) -> Result<Option<CborValueRef<'a>>, CborError> {
+        self.root().pointer(ptr)
+    }
+
+    /// Writes a structural annotation like [`CanonicalCborRef::annotate`]; passthrough tags
+    /// are shown as `tag(N)` with their content one level deeper.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` for malformed input, or `WriteFailed` if `out` returns an error.
+    pub fn annotate(self, out: &mut impl fmt::Write) -> Result<(), CborError> {
+        annotate_value(out, self.root(), None, 0)
+    }
 }
 
 /// Adds query methods to `CanonicalCborRef`.
@@ -909,6 +1662,56 @@ impl<'a> CanonicalCborRef<'a> {
     pub fn at(self, path: &[PathElem<'_>]) -> Result<Option<CborValueRef<'a>>, CborError> {
         self.root().at(path)
     }
+
+    /// Convenience wrapper around `self.root().pointer(ptr)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidTextSyntax` for a malformed pointer, or `CborError` for malformed
+    /// canonical input.
+    pub fn pointer(self, ptr: &str) -> Result<Option<CborValueRef<'a>>, CborError> {
+        self.root().pointer(ptr)
+    }
+
+    /// Writes a structural annotation of the message to `out`, one item per line.
+    ///
+    /// Each line shows the item's byte offset (hex) and a summary of its header:
+    /// containers show their length and scalars their decoded value. Map entries are
+    /// written as `key => value` at the key's offset, and nested items are indented two
+    /// spaces per level. No allocation is performed.
+    ///
+    /// ```text
+    /// 0000: map(2)
+    ///   0001: "id" => int(42)
+    ///   0006: "tags" => array(1)
+    ///     000c: "x"
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError` for malformed canonical input, or `WriteFailed` if `out`
+    /// returns an error.
+    pub fn annotate(self, out: &mut impl fmt::Write) -> Result<(), CborError> {
+        annotate_value(out, self.root(), None, 0)
+    }
+
+    /// Returns the keys of the root map in canonical order.
+    ///
+    /// Only key entries are decoded; values are skipped by their encoded span without
+    /// being interpreted. This API is available with the `alloc` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CborError::ExpectedMap` if the root is not a map, or an allocation error.
+    #[cfg(feature = "alloc")]
+    pub fn top_level_keys(self) -> Result<Vec<&'a str>, CborError> {
+        let map = self.root().map()?;
+        let mut keys = crate::alloc_util::try_vec_with_capacity(map.len(), 0)?;
+        for entry in map.iter() {
+            keys.push(entry?.0);
+        }
+        Ok(keys)
+    }
 }
 
 #[cfg(feature = "alloc")]
@@ -928,24 +1731,235 @@ impl CanonicalCbor {
     pub fn at(&self, path: &[PathElem<'_>]) -> Result<Option<CborValueRef<'_>>, CborError> {
         self.root().at(path)
     }
+
+    /// Convenience wrapper around `self.root().pointer(ptr)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidTextSyntax` for a malformed pointer, or `CborError` for malformed
+    /// canonical input.
+    pub fn pointer(&self, ptr: &str) -> Result<Option<CborValueRef<'_>>, CborError> {
+        self.root().pointer(ptr)
+    }
 }
 
 /* =========================
  * Internal parsing helpers
  * ========================= */
 
+fn check_pointer_token(token: &str, off: usize) -> Result<(), CborError> {
+    let bytes = token.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        if b == b'~' && !matches!(bytes.get(i + 1), Some(b'0' | b'1')) {
+            return Err(CborError::new(ErrorCode::InvalidTextSyntax, off + i));
+        }
+    }
+    Ok(())
+}
+
+fn parse_pointer_index(token: &str) -> Option<usize> {
+    let bytes = token.as_bytes();
+    if bytes.is_empty() || !bytes.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    if bytes.len() > 1 && bytes[0] == b'0' {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Compare a map key with an escaped pointer token without allocating.
+fn pointer_token_eq(key: &str, token: &str) -> bool {
+    let mut key = key.bytes();
+    let mut token = token.bytes();
+    loop {
+        let expected = match token.next() {
+            None => return key.next().is_none(),
+            Some(b'~') => match token.next() {
+                Some(b'0') => b'~',
+                _ => b'/',
+            },
+            Some(b) => b,
+        };
+        if key.next() != Some(expected) {
+            return false;
+        }
+    }
+}
+
+/// Linear lookup for tokens containing escapes, whose unescaped form cannot be borrowed.
+fn pointer_map_lookup<'a>(
+    map: MapRef<'a>,
+    token: &str,
+) -> Result<Option<CborValueRef<'a>>, CborError> {
+    for entry in map.iter() {
+        let (key, value) = entry?;
+        if pointer_token_eq(key, token) {
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(feature = "subtle")]
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    use subtle::ConstantTimeEq;
+    a.ct_eq(b).into()
+}
+
+#[cfg(not(feature = "subtle"))]
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (&x, &y) in a.iter().zip(b) {
+        diff |= core::hint::black_box(x ^ y);
+    }
+    core::hint::black_box(diff) == 0
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+#[derive(Clone, Copy)]
+enum Numeric<'a> {
+    Int(i128),
+    Big(BigIntRef<'a>),
+    Float(f64),
+}
+
+/// 2^53: every float at or beyond this magnitude is an integer, and every bignum is too.
+const TWO_POW_53: f64 = 9_007_199_254_740_992.0;
+
+fn cmp_numeric(a: Numeric<'_>, b: Numeric<'_>) -> Ordering {
+    match (a, b) {
+        (Numeric::Int(x), Numeric::Int(y)) => x.cmp(&y),
+        (Numeric::Float(x), Numeric::Float(y)) => x.total_cmp(&y),
+        (Numeric::Big(x), Numeric::Big(y)) => {
+            cmp_args(x.negative, x.magnitude, y.negative, y.magnitude)
+        }
+        // A wide integer may share a sign with the bignum and exceed it in magnitude.
+        (Numeric::Big(x), Numeric::Int(y)) => {
+            let (y_negative, y_mag) = int_argument(y);
+            cmp_args(x.negative, x.magnitude, y_negative, &y_mag)
+        }
+        (Numeric::Int(x), Numeric::Float(y)) => cmp_int_float(x, y),
+        (Numeric::Big(x), Numeric::Float(y)) => cmp_big_float(x.negative, x.magnitude, y),
+        (Numeric::Int(_) | Numeric::Float(_), Numeric::Big(_))
+        | (Numeric::Float(_), Numeric::Int(_)) => cmp_numeric(b, a).reverse(),
+    }
+}
+
+/// Compares two integers given as CBOR arguments (the value is `n`, or `-1 - n` when
+/// negative).
+fn cmp_args(a_negative: bool, a: &[u8], b_negative: bool, b: &[u8]) -> Ordering {
+    match (a_negative, b_negative) {
+        (false, true) => Ordering::Greater,
+        (true, false) => Ordering::Less,
+        (false, false) => cmp_big_endian(a, b),
+        // -1 - n orders opposite to n.
+        (true, true) => cmp_big_endian(b, a),
+    }
+}
+
+/// Returns the CBOR argument of `x`: its sign and the big-endian `n` with `x = n` or
+/// `x = -1 - n`.
+const fn int_argument(x: i128) -> (bool, [u8; 16]) {
+    if x < 0 {
+        (true, (-1 - x).unsigned_abs().to_be_bytes())
+    } else {
+        (false, x.unsigned_abs().to_be_bytes())
+    }
+}
+
+fn cmp_int_float(x: i128, y: f64) -> Ordering {
+    if y.is_nan() {
+        return Ordering::Less;
+    }
+    if let Ok(small) = i64::try_from(x) {
+        if small.unsigned_abs() < 1 << 53 {
+            // Below 2^53 in magnitude, so the conversion is exact.
+            #[allow(clippy::cast_precision_loss)]
+            let x = small as f64;
+            return x.total_cmp(&y);
+        }
+    }
+    let (negative, mag) = int_argument(x);
+    cmp_big_float(negative, &mag, y)
+}
+
+/// Compares the integer with CBOR argument `n` against `y`; the integer must be at
+/// least 2^53 in magnitude.
+fn cmp_big_float(negative: bool, n: &[u8], y: f64) -> Ordering {
+    if y.is_nan() || y == f64::INFINITY {
+        return Ordering::Less;
+    }
+    if y == f64::NEG_INFINITY {
+        return Ordering::Greater;
+    }
+    let y_negative = y < 0.0;
+    if y_negative != negative || (y > -TWO_POW_53 && y < TWO_POW_53) {
+        return if negative {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+    }
+
+    // Same sign and |y| >= 2^53, so |y| is an integer: compare magnitudes exactly.
+    let mut y_abs = float_integer_magnitude(y);
+    if negative {
+        // x = -1 - n and y = -|y|, so x < y exactly when n >= |y|, i.e. n > |y| - 1.
+        decrement_be(&mut y_abs);
+        cmp_big_endian(&y_abs, n)
+    } else {
+        cmp_big_endian(n, &y_abs)
+    }
+}
+
+/// Returns the big-endian magnitude of an integral `f64` with `|v| >= 2^53`.
+///
+/// The largest finite `f64` is below 2^1024, which fits in 128 bytes; the extra room
+/// keeps the 16-byte window below in bounds.
+fn float_integer_magnitude(v: f64) -> [u8; 144] {
+    let mut buf = [0u8; 144];
+    let bits = v.to_bits();
+    let exp = usize::try_from((bits >> 52) & 0x7ff).unwrap_or(0);
+    let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+    // value = mantissa * 2^(exp - 1075); the caller guarantees exp >= 1075.
+    let shift = exp.saturating_sub(1075);
+    let (byte_shift, bit_shift) = (shift / 8, shift % 8);
+    let wide = u128::from(mantissa) << bit_shift;
+    let end = buf.len() - byte_shift;
+    buf[end - 16..end].copy_from_slice(&wide.to_be_bytes());
+    buf
+}
+
 #[inline]
 const fn map_trusted_err(cause: CborError) -> CborError {
-    err(ErrorCode::MalformedCanonical, cause.offset)
+    err(ErrorCode::MalformedCanonical, cause.offset())
 }
 
+// Shared with `raw.rs`; kept crate-private so they never leak into the public API.
 #[inline]
-fn read_u8_trusted(data: &[u8], pos: &mut usize) -> Result<u8, CborError> {
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) fn read_u8_trusted(data: &[u8], pos: &mut usize) -> Result<u8, CborError> {
     wire::read_u8(data, pos).map_err(map_trusted_err)
 }
 
 #[inline]
-fn read_exact_trusted<'a>(
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) fn read_exact_trusted<'a>(
     data: &'a [u8],
     pos: &mut usize,
     n: usize,
@@ -954,12 +1968,24 @@ fn read_exact_trusted<'a>(
 }
 
 #[inline]
-fn read_uint_trusted(data: &[u8], pos: &mut usize, ai: u8, off: usize) -> Result<u64, CborError> {
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) fn read_uint_trusted(
+    data: &[u8],
+    pos: &mut usize,
+    ai: u8,
+    off: usize,
+) -> Result<u64, CborError> {
     wire::read_uint_trusted(data, pos, ai, off).map_err(map_trusted_err)
 }
 
 #[inline]
-fn read_len_trusted(data: &[u8], pos: &mut usize, ai: u8, off: usize) -> Result<usize, CborError> {
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) fn read_len_trusted(
+    data: &[u8],
+    pos: &mut usize,
+    ai: u8,
+    off: usize,
+) -> Result<usize, CborError> {
     wire::read_len_trusted(data, pos, ai, off).map_err(map_trusted_err)
 }
 
@@ -1213,6 +2239,199 @@ impl<'a> Iterator for ExtrasIter<'a, '_> {
     }
 }
 
+fn annotate_value<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    value: CborValueRef<'_>,
+    key: Option<(usize, &str)>,
+    depth: usize,
+) -> Result<(), CborError> {
+    let off = key.map_or(value.start, |(key_off, _)| key_off);
+    let wr = |res: fmt::Result| res.map_err(|_| CborError::new(ErrorCode::WriteFailed, off));
+
+    wr(write!(out, "{:indent$}{off:04x}: ", "", indent = depth * 2))?;
+    if let Some((_, k)) = key {
+        wr(write!(out, "{k:?} => "))?;
+    }
+
+    match value.kind()? {
+        CborKind::Integer => match value.integer()? {
+            CborIntegerRef::Safe(v) => wr(writeln!(out, "int({v})")),
+            CborIntegerRef::Wide(v) => wr(writeln!(out, "int({v})")),
+            CborIntegerRef::Big(b) => {
+                let sign = if b.is_negative() { "-" } else { "" };
+                wr(writeln!(out, "bignum({sign}{} bytes)", b.magnitude().len()))
+            }
+        },
+        CborKind::Bytes => wr(writeln!(out, "bytes({})", value.bytes()?.len())),
+        CborKind::Text => wr(writeln!(out, "{:?}", value.text()?)),
+        CborKind::Array => {
+            let array = value.array()?;
+            wr(writeln!(out, "array({})", array.len()))?;
+            for item in array.iter() {
+                annotate_value(out, item?, None, depth + 1)?;
+            }
+            Ok(())
+        }
+        CborKind::Map => {
+            let map = value.map()?;
+            wr(writeln!(out, "map({})", map.len()))?;
+            let mut key_off = map.entries_start;
+            for entry in map.iter() {
+                let (k, v) = entry?;
+                annotate_value(out, v, Some((key_off, k)), depth + 1)?;
+                key_off = v.end;
+            }
+            Ok(())
+        }
+        CborKind::Bool => wr(writeln!(out, "{}", value.bool()?)),
+        CborKind::Null => wr(writeln!(out, "null")),
+        CborKind::Float => wr(writeln!(out, "float({:?})", value.float64()?)),
+        CborKind::Tag => {
+            let (tag, content) = value.tag()?.ok_or_else(|| malformed(value.start))?;
+            wr(writeln!(out, "tag({tag})"))?;
+            annotate_value(out, content, None, depth + 1)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn pretty_value(
+    out: &mut String,
+    value: CborValueRef<'_>,
+    indent: usize,
+    depth: usize,
+) -> Result<(), CborError> {
+    use core::fmt::Write as _;
+
+    match value.kind()? {
+        CborKind::Integer => match value.integer()? {
+            CborIntegerRef::Safe(v) => {
+                let _ = write!(out, "{v}");
+            }
+            CborIntegerRef::Wide(v) => {
+                let _ = write!(out, "{v}");
+            }
+            CborIntegerRef::Big(b) => pretty_bignum(out, b),
+        },
+        CborKind::Bytes => {
+            out.push_str("0x");
+            for byte in value.bytes()? {
+                let _ = write!(out, "{byte:02x}");
+            }
+        }
+        CborKind::Text => {
+            let _ = write!(out, "{:?}", value.text()?);
+        }
+        CborKind::Array => {
+            let array = value.array()?;
+            if array.is_empty() {
+                out.push_str("[]");
+            }
+            for item in array.iter() {
+                pretty_line(out, indent * depth);
+                out.push('-');
+                pretty_child(out, item?, indent, depth)?;
+            }
+        }
+        CborKind::Map => {
+            let map = value.map()?;
+            if map.is_empty() {
+                out.push_str("{}");
+            }
+            for entry in map.iter() {
+                let (k, v) = entry?;
+                pretty_line(out, indent * depth);
+                if is_plain_key(k) {
+                    out.push_str(k);
+                } else {
+                    let _ = write!(out, "{k:?}");
+                }
+                out.push(':');
+                pretty_child(out, v, indent, depth)?;
+            }
+        }
+        CborKind::Bool => out.push_str(if value.bool()? { "true" } else { "false" }),
+        CborKind::Null => out.push_str("null"),
+        CborKind::Float => {
+            let _ = write!(out, "{:?}", value.float64()?);
+        }
+        CborKind::Tag => {
+            let (tag, content) = value.tag()?.ok_or_else(|| malformed(value.start))?;
+            let _ = write!(out, "!{tag}");
+            pretty_child(out, content, indent, depth)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a map value or array item after its `key:` / `-` marker: scalars and empty
+/// containers on the same line, other containers on the following lines one level deeper.
+#[cfg(feature = "alloc")]
+fn pretty_child(
+    out: &mut String,
+    value: CborValueRef<'_>,
+    indent: usize,
+    depth: usize,
+) -> Result<(), CborError> {
+    let nested = match value.kind()? {
+        CborKind::Array => !value.array()?.is_empty(),
+        CborKind::Map => !value.map()?.is_empty(),
+        _ => false,
+    };
+    if nested {
+        pretty_value(out, value, indent, depth + 1)
+    } else {
+        out.push(' ');
+        pretty_value(out, value, indent, depth)
+    }
+}
+
+/// Keys that read unambiguously without quotes.
+#[cfg(feature = "alloc")]
+fn is_plain_key(k: &str) -> bool {
+    !k.is_empty()
+        && k.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.'))
+}
+
+#[cfg(feature = "alloc")]
+fn pretty_line(out: &mut String, pad: usize) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.extend(core::iter::repeat(' ').take(pad));
+}
+
+#[cfg(feature = "alloc")]
+fn pretty_bignum(out: &mut String, b: BigIntRef<'_>) {
+    use core::fmt::Write as _;
+
+    // A negative bignum with magnitude `m` is `-1 - m`.
+    let sign = if b.is_negative() { "-" } else { "" };
+    let value = crate::int::mag_to_u128(b.magnitude()).and_then(|m| {
+        if b.is_negative() {
+            m.checked_add(1)
+        } else {
+            Some(m)
+        }
+    });
+    if let Some(v) = value {
+        let _ = write!(out, "{sign}{v}");
+        return;
+    }
+    out.push_str(sign);
+    if b.is_negative() {
+        out.push_str("(1 + ");
+    }
+    out.push_str("0x");
+    for byte in b.magnitude() {
+        let _ = write!(out, "{byte:02x}");
+    }
+    if b.is_negative() {
+        out.push(')');
+    }
+}
+
 struct MapIter<'a> {
     data: &'a [u8],
     pos: usize,
@@ -1253,10 +2472,41 @@ impl<'a> Iterator for MapIter<'a> {
     }
 }
 
-#[cfg(feature = "alloc")]
+struct MapValues<'a> {
+    data: &'a [u8],
+    pos: usize,
+    remaining: usize,
+    scratch: wire::SkipScratch,
+}
+
+impl<'a> Iterator for MapValues<'a> {
+    type Item = Result<CborValueRef<'a>, CborError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let mut value_start = self.pos;
+        let end = match read_text_bytes(self.data, &mut value_start)
+            .and_then(|_| value_end_with_scratch(self.data, value_start, &mut self.scratch))
+        {
+            Ok(e) => e,
+            Err(e) => {
+                self.remaining = 0;
+                return Some(Err(e));
+            }
+        };
+
+        self.pos = end;
+        self.remaining -= 1;
+
+        Some(Ok(CborValueRef::new(self.data, value_start, end)))
+    }
+}
+
 type EncodedMapEntry<'a> = (&'a str, EncodedTextKey<'a>, CborValueRef<'a>);
 
-#[cfg(feature = "alloc")]
 struct MapIterEncoded<'a> {
     data: &'a [u8],
     pos: usize,
@@ -1264,7 +2514,6 @@ struct MapIterEncoded<'a> {
     scratch: wire::SkipScratch,
 }
 
-#[cfg(feature = "alloc")]
 impl<'a> Iterator for MapIterEncoded<'a> {
     type Item = Result<EncodedMapEntry<'a>, CborError>;
 
@@ -1311,6 +2560,34 @@ struct ArrayIter<'a> {
     scratch: wire::SkipScratch,
 }
 
+struct ArrayChunks<'a> {
+    items: ArrayIter<'a>,
+    size: usize,
+}
+
+impl<'a> Iterator for ArrayChunks<'a> {
+    type Item = Result<ArrayRef<'a>, CborError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = self.size.min(self.items.remaining);
+        if len == 0 {
+            return None;
+        }
+        let start = self.items.pos;
+        for _ in 0..len {
+            if let Err(e) = self.items.next()? {
+                return Some(Err(e));
+            }
+        }
+        Some(Ok(ArrayRef {
+            data: self.items.data,
+            array_off: start,
+            items_start: start,
+            len,
+        }))
+    }
+}
+
 impl<'a> Iterator for ArrayIter<'a> {
     type Item = Result<CborValueRef<'a>, CborError>;
 
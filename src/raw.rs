@@ -0,0 +1,83 @@
+//! Header-level reader over already-validated canonical bytes.
+
+use crate::canonical::CanonicalCborRef;
+use crate::query::{read_exact_trusted, read_len_trusted, read_u8_trusted, read_uint_trusted};
+use crate::CborError;
+
+/// Unchecked cursor over the headers and payloads of canonical CBOR.
+///
+/// This sits below [`crate::CborValueRef`] for custom walks where even the query layer's
+/// bookkeeping is too much. It trusts that the input is canonical: no canonical-form,
+/// profile, UTF-8, or limit checks are made, and nothing stops a caller from reading a
+/// header where a payload belongs. Reads stay in bounds, so misuse yields
+/// `MalformedCanonical` or garbage values, never undefined behavior.
+#[derive(Debug, Clone, Copy)]
+pub struct RawReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RawReader<'a> {
+    /// Start reading at the first byte of `canon`.
+    #[must_use]
+    pub const fn new(canon: CanonicalCborRef<'a>) -> Self {
+        Self {
+            data: canon.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    /// Returns the offset of the next byte to read.
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the number of bytes left to read.
+    #[must_use]
+    pub const fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    /// Read an initial byte and return `(major, additional_info, offset)`.
+    ///
+    /// The argument, if any, is not consumed; follow with [`RawReader::read_uint_arg`] or
+    /// [`RawReader::read_len`] as the major type requires.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MalformedCanonical` at the end of the input.
+    pub fn read_header(&mut self) -> Result<(u8, u8, usize), CborError> {
+        let off = self.pos;
+        let ib = read_u8_trusted(self.data, &mut self.pos)?;
+        Ok((ib >> 5, ib & 0x1f, off))
+    }
+
+    /// Read the argument for additional info `ai` of the header at `off`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MalformedCanonical` for reserved additional info or truncated input.
+    pub fn read_uint_arg(&mut self, ai: u8, off: usize) -> Result<u64, CborError> {
+        read_uint_trusted(self.data, &mut self.pos, ai, off)
+    }
+
+    /// Read a definite length for additional info `ai` of the header at `off`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MalformedCanonical` for indefinite or reserved lengths, lengths that do not
+    /// fit `usize`, or truncated input.
+    pub fn read_len(&mut self, ai: u8, off: usize) -> Result<usize, CborError> {
+        read_len_trusted(self.data, &mut self.pos, ai, off)
+    }
+
+    /// Read the next `n` bytes, such as a string payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MalformedCanonical` if fewer than `n` bytes remain.
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], CborError> {
+        read_exact_trusted(self.data, &mut self.pos, n)
+    }
+}
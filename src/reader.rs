@@ -0,0 +1,83 @@
+//! Reading a single CBOR item from a byte stream.
+
+use alloc::vec::Vec;
+use std::io::{self, Read};
+
+use crate::alloc_util::try_reserve;
+use crate::{CborError, DecodeLimits, ErrorCode};
+
+/// Read exactly one CBOR data item from `r`, leaving any following bytes unread.
+///
+/// Only the framing is interpreted here (headers, lengths, container arity); the
+/// result still has to be validated. Reads never exceed `limits.max_input_bytes`.
+pub fn read_item<R: Read>(mut r: R, limits: DecodeLimits) -> Result<Vec<u8>, CborError> {
+    let mut buf = Vec::new();
+    let mut pending: usize = 1;
+    while pending > 0 {
+        pending -= 1;
+        let off = buf.len();
+        read_into(&mut r, &mut buf, 1, limits)?;
+        let ib = buf[off];
+        let major = ib >> 5;
+        let ai = ib & 0x1f;
+
+        let arg = match ai {
+            0..=23 => u64::from(ai),
+            24..=27 => {
+                let start = buf.len();
+                read_into(&mut r, &mut buf, 1 << (ai - 24), limits)?;
+                buf[start..]
+                    .iter()
+                    .fold(0u64, |acc, &b| (acc << 8) | u64::from(b))
+            }
+            28..=30 => return Err(CborError::new(ErrorCode::ReservedAdditionalInfo, off)),
+            _ => return Err(CborError::new(ErrorCode::IndefiniteLengthForbidden, off)),
+        };
+
+        let children = match major {
+            2 | 3 => {
+                let len = usize::try_from(arg)
+                    .map_err(|_| CborError::new(ErrorCode::LengthOverflow, off))?;
+                read_into(&mut r, &mut buf, len, limits)?;
+                0
+            }
+            4 => arg,
+            5 => arg
+                .checked_mul(2)
+                .ok_or_else(|| CborError::new(ErrorCode::LengthOverflow, off))?,
+            6 => 1,
+            _ => 0,
+        };
+        pending = usize::try_from(children)
+            .ok()
+            .and_then(|c| pending.checked_add(c))
+            .ok_or_else(|| CborError::new(ErrorCode::LengthOverflow, off))?;
+        // Every pending item needs at least one more byte.
+        if pending > limits.max_input_bytes - buf.len() {
+            return Err(CborError::new(ErrorCode::MessageLenLimitExceeded, off));
+        }
+    }
+    Ok(buf)
+}
+
+fn read_into<R: Read>(
+    r: &mut R,
+    buf: &mut Vec<u8>,
+    n: usize,
+    limits: DecodeLimits,
+) -> Result<(), CborError> {
+    let start = buf.len();
+    if n > limits.max_input_bytes - start {
+        return Err(CborError::new(ErrorCode::MessageLenLimitExceeded, start));
+    }
+    try_reserve(buf, n, start)?;
+    buf.resize(start + n, 0);
+    r.read_exact(&mut buf[start..]).map_err(|err| {
+        let code = if err.kind() == io::ErrorKind::UnexpectedEof {
+            ErrorCode::UnexpectedEof
+        } else {
+            ErrorCode::IoError
+        };
+        CborError::new(code, start)
+    })
+}
@@ -1,6 +1,11 @@
+use core::cmp::Ordering;
+
 use crate::profile::{validate_f64_bits, CANONICAL_NAN_BITS, NEGATIVE_ZERO_BITS};
 use crate::{CborError, ErrorCode};
 
+const SIGN_BIT: u64 = 0x8000_0000_0000_0000;
+const INFINITY_BITS: u64 = 0x7ff0_0000_0000_0000;
+
 /// A validated float64 bit-pattern suitable for SACP-CBOR/1 encoding.
 ///
 /// - Encoded as CBOR float64 (major 7, ai 27).
@@ -20,6 +25,19 @@ impl F64Bits {
         Ok(Self(bits))
     }
 
+    /// Construct from raw IEEE-754 bits without going through `f64`.
+    ///
+    /// Unlike [`F64Bits::try_from_f64`], nothing is normalized: only the canonical NaN
+    /// pattern is accepted. This is equivalent to [`F64Bits::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `NegativeZeroForbidden` for `-0.0` bits and `NonCanonicalNaN` for any other
+    /// NaN payload.
+    pub fn from_bits_checked(bits: u64) -> Result<Self, CborError> {
+        Self::new(bits)
+    }
+
     /// Construct from an `f64` value.
     ///
     /// # Errors
@@ -43,10 +61,53 @@ impl F64Bits {
         self.0
     }
 
+    /// Return the raw IEEE-754 bits (alias of [`F64Bits::bits`]).
+    #[inline]
+    #[must_use]
+    pub const fn as_bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns `true` if this is the canonical NaN.
+    #[inline]
+    #[must_use]
+    pub const fn is_nan(self) -> bool {
+        self.0 == CANONICAL_NAN_BITS
+    }
+
+    /// Returns `true` if this is positive or negative infinity.
+    #[inline]
+    #[must_use]
+    pub const fn is_infinite(self) -> bool {
+        self.0 & !SIGN_BIT == INFINITY_BITS
+    }
+
     /// Convert into an `f64`.
     #[inline]
     #[must_use]
     pub fn to_f64(self) -> f64 {
         f64::from_bits(self.0)
     }
+
+    /// Compare by IEEE-754 `totalOrder`, the ordering used by the [`Ord`] impl.
+    ///
+    /// Since `-0.0` and NaN payloads are excluded, this is numeric order with the canonical
+    /// NaN sorted after positive infinity.
+    #[inline]
+    #[must_use]
+    pub fn total_cmp(self, other: Self) -> Ordering {
+        self.to_f64().total_cmp(&other.to_f64())
+    }
+}
+
+impl PartialOrd for F64Bits {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for F64Bits {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.total_cmp(*other)
+    }
 }
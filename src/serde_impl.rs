@@ -1,4 +1,5 @@
 use alloc::vec::Vec;
+use core::cell::RefCell;
 use core::fmt;
 use serde::de::{
     self, Deserialize, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
@@ -8,11 +9,12 @@ use serde::ser::{self, SerializeMap, SerializeSeq};
 use serde::Deserializer;
 use serde::Serialize;
 
+use crate::alloc_util::try_vec_with_capacity;
 use crate::canonical::{CanonicalCbor, CanonicalCborRef};
 use crate::codec::{ArrayDecoder, CborDecode, Decoder, MapDecoder};
-use crate::encode::Encoder;
+use crate::encode::{Encoder, EntrySpan};
 use crate::profile::check_encoded_key_order;
-use crate::query::{CborKind, CborValueRef};
+use crate::query::{CborKind, CborValueRef, PathElem};
 use crate::scalar::F64Bits;
 use crate::{CborError, DecodeLimits, ErrorCode};
 
@@ -39,6 +41,7 @@ fn check_map_key_order(
 
 fn write_text_entry<T: ?Sized + Serialize>(
     enc: &mut Encoder,
+    keys: MapKeyPolicy,
     key: &str,
     prev_key_range: Option<(usize, usize)>,
     entry_start: usize,
@@ -53,7 +56,7 @@ fn write_text_entry<T: ?Sized + Serialize>(
 
     check_map_key_order(enc, prev_key_range, key_start, key_end, entry_start)?;
 
-    if let Err(err) = value.serialize(EncoderSerializer::new(enc)) {
+    if let Err(err) = value.serialize(EncoderSerializer::new(enc, keys)) {
         enc.truncate(entry_start);
         return Err(err);
     }
@@ -63,6 +66,7 @@ fn write_text_entry<T: ?Sized + Serialize>(
 
 fn write_struct_field<T: ?Sized + Serialize>(
     enc: &mut Encoder,
+    keys: MapKeyPolicy,
     key: &'static str,
     value: &T,
     remaining: &mut usize,
@@ -72,7 +76,8 @@ fn write_struct_field<T: ?Sized + Serialize>(
         return Err(SerdeError::with_code(ErrorCode::MapLenMismatch));
     }
     let entry_start = enc.buf_len();
-    let (key_start, key_end) = write_text_entry(enc, key, *prev_key_range, entry_start, value)?;
+    let (key_start, key_end) =
+        write_text_entry(enc, keys, key, *prev_key_range, entry_start, value)?;
     *prev_key_range = Some((key_start, key_end));
     *remaining -= 1;
     Ok(())
@@ -88,17 +93,58 @@ fn finish_struct(enc: &mut Encoder, remaining: usize, roots: &[bool]) -> Result<
     Ok(())
 }
 
+/// How [`to_vec_with`] treats serde map keys that are not strings.
+///
+/// The SACP-CBOR/1 profile only allows text map keys, so [`MapKeyPolicy::TextOnly`] is the
+/// default everywhere. Other policies are an explicit escape hatch: they change what the
+/// Rust value encodes to, never what the profile accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum MapKeyPolicy {
+    /// Only `str` and `char` keys; anything else fails with `MapKeyMustBeText`.
+    #[default]
+    TextOnly,
+    /// Integer keys are written as their decimal text, such as `"42"` or `"-7"`.
+    ///
+    /// Decimal text does not sort like the integers it came from (`"-1"` follows `"1"`, and
+    /// a `HashMap` has no order at all), so under this policy each map's entries are
+    /// buffered and sorted into canonical order when the map ends. Two keys with the same
+    /// text fail with `DuplicateMapKey`.
+    IntegersAsDecimalText,
+}
+
 /// Serialize a Rust value into canonical SACP-CBOR/1 bytes.
 ///
 /// # Errors
 ///
 /// Returns an error if the value cannot be represented under SACP-CBOR/1 constraints.
 pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>, CborError> {
+    to_vec_with(value, MapKeyPolicy::TextOnly)
+}
+
+/// Serialize like [`to_vec`], coercing non-text map keys according to `keys`.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be represented under SACP-CBOR/1 constraints,
+/// including `MapKeyMustBeText` for keys the policy does not cover.
+pub fn to_vec_with<T: Serialize>(value: &T, keys: MapKeyPolicy) -> Result<Vec<u8>, CborError> {
     let mut enc = Encoder::new();
     value
-        .serialize(EncoderSerializer::new(&mut enc))
+        .serialize(EncoderSerializer::new(&mut enc, keys))
         .map_err(|err| CborError::new(err.code, 0))?;
-    Ok(enc.into_vec())
+    enc.try_into_vec()
+}
+
+/// Serialize `value` at the encoder's current position, e.g. inside an open container.
+pub fn serialize_into<T: ?Sized + Serialize>(
+    enc: &mut Encoder,
+    value: &T,
+) -> Result<(), CborError> {
+    let off = enc.buf_len();
+    value
+        .serialize(EncoderSerializer::new(enc, MapKeyPolicy::TextOnly))
+        .map_err(|err| CborError::new(err.code, off))
 }
 
 /// Deserialize a Rust value from canonical SACP-CBOR/1 bytes.
@@ -120,6 +166,172 @@ pub fn from_slice<'de, T: Deserialize<'de>>(
     Ok(value)
 }
 
+/// Deserialize the data item at the start of `bytes` and return it with the number of
+/// bytes it occupied.
+///
+/// The serde counterpart of [`crate::validate_prefix`]: bytes after the first item are
+/// ignored rather than reported as `TrailingBytes`, and only the item itself must fit
+/// within `max_input_bytes`.
+///
+/// # Errors
+///
+/// Returns `UnexpectedEof` if `bytes` ends before the first item does,
+/// `MessageLenLimitExceeded` if the item is longer than `max_input_bytes`, or an error if
+/// the item is invalid or doesn't match the target type.
+pub fn from_slice_prefix<'de, T: Deserialize<'de>>(
+    bytes: &'de [u8],
+    limits: DecodeLimits,
+) -> Result<(T, usize), CborError> {
+    let window = &bytes[..bytes.len().min(limits.max_input_bytes)];
+    let mut decoder = Decoder::<true>::new_checked(window, limits)?;
+    match T::deserialize(&mut decoder).map_err(DeError::into_cbor_error) {
+        Ok(value) => Ok((value, decoder.position())),
+        Err(err) if err.code() == ErrorCode::UnexpectedEof && window.len() < bytes.len() => {
+            Err(CborError::new(ErrorCode::MessageLenLimitExceeded, 0))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// A [`from_slice_traced`] failure: the error plus the path to the value that raised it.
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracedError<'de> {
+    /// The underlying error.
+    pub error: CborError,
+    /// Map keys and array indices from the root down to the failing value. Enum variant
+    /// names appear as keys, matching their `{"Variant": payload}` encoding.
+    pub path: Vec<PathElem<'de>>,
+}
+
+impl From<CborError> for TracedError<'_> {
+    fn from(error: CborError) -> Self {
+        Self {
+            error,
+            path: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for TracedError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)?;
+        if self.path.is_empty() {
+            return Ok(());
+        }
+        f.write_str(" at ")?;
+        for elem in &self.path {
+            match elem {
+                PathElem::Key(key) => {
+                    f.write_str("/")?;
+                    for c in key.chars() {
+                        match c {
+                            '~' => f.write_str("~0")?,
+                            '/' => f.write_str("~1")?,
+                            c => fmt::Write::write_char(f, c)?,
+                        }
+                    }
+                }
+                PathElem::Index(idx) => write!(f, "/{idx}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TracedError<'_> {}
+
+/// Deserialize like [`from_slice`], reporting where in the document a failure happened.
+///
+/// The path is written as an RFC 6901 pointer by `Display` (the syntax
+/// [`CborValueRef::pointer`] reads), so `missing required map key at /servers/2/tls` can
+/// replace a bare byte offset. Tracking costs a little bookkeeping per container element,
+/// which is why it is opt-in.
+///
+/// # Errors
+///
+/// Returns a [`TracedError`] wrapping whatever error [`from_slice`] would return. Failures
+/// outside any container, such as trailing bytes, have an empty path.
+pub fn from_slice_traced<'de, T: Deserialize<'de>>(
+    bytes: &'de [u8],
+    limits: DecodeLimits,
+) -> Result<T, TracedError<'de>> {
+    let mut decoder = Decoder::<true>::new_checked(bytes, limits)?;
+    let trace = RefCell::new(Trace::default());
+    let res = T::deserialize(DecoderDeserializer {
+        decoder: &mut decoder,
+        tracer: &trace,
+    });
+    let trace = trace.into_inner();
+    match res {
+        Ok(_) if decoder.position() != bytes.len() => {
+            Err(CborError::new(ErrorCode::TrailingBytes, decoder.position()).into())
+        }
+        Ok(value) => Ok(value),
+        Err(err) => {
+            let mut path = trace.path;
+            if trace.error != Some(err) {
+                path.clear();
+            }
+            path.reverse();
+            Err(TracedError {
+                error: err.into_cbor_error(),
+                path,
+            })
+        }
+    }
+}
+
+/// Path segments collected innermost-first while an error unwinds to [`from_slice_traced`].
+#[derive(Default)]
+struct Trace<'de> {
+    error: Option<DeError>,
+    path: Vec<PathElem<'de>>,
+}
+
+/// Records the path of a failing element as errors unwind through containers.
+trait Tracer<'de>: Copy {
+    /// Pass `res` through, recording `elem` if it failed.
+    fn traced<T>(self, res: Result<T, DeError>, elem: PathElem<'de>) -> Result<T, DeError>;
+}
+
+/// The tracer behind plain `&mut Decoder` deserialization: records nothing.
+#[derive(Clone, Copy)]
+struct NoTrace;
+
+impl<'de> Tracer<'de> for NoTrace {
+    #[inline]
+    fn traced<T>(self, res: Result<T, DeError>, _elem: PathElem<'de>) -> Result<T, DeError> {
+        res
+    }
+}
+
+impl<'de> Tracer<'de> for &RefCell<Trace<'de>> {
+    fn traced<T>(self, res: Result<T, DeError>, elem: PathElem<'de>) -> Result<T, DeError> {
+        let mut trace = self.borrow_mut();
+        match &res {
+            // A visitor recovered from an error below this element; forget its path.
+            Ok(_) if trace.error.is_some() => {
+                trace.error = None;
+                trace.path.clear();
+            }
+            Ok(_) => {}
+            Err(err) => {
+                if trace.error != Some(*err) {
+                    trace.error = Some(*err);
+                    trace.path.clear();
+                }
+                // The trace is best-effort: on allocation failure the path is cut short.
+                if trace.path.try_reserve(1).is_ok() {
+                    trace.path.push(elem);
+                }
+            }
+        }
+        res
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct SerdeError {
     code: ErrorCode,
@@ -149,17 +361,18 @@ impl serde::ser::Error for SerdeError {
 
 impl From<CborError> for SerdeError {
     fn from(err: CborError) -> Self {
-        Self::with_code(err.code)
+        Self::with_code(err.code())
     }
 }
 
 struct EncoderSerializer<'a> {
     enc: &'a mut Encoder,
+    keys: MapKeyPolicy,
 }
 
 impl<'a> EncoderSerializer<'a> {
-    fn new(enc: &'a mut Encoder) -> Self {
-        Self { enc }
+    fn new(enc: &'a mut Encoder, keys: MapKeyPolicy) -> Self {
+        Self { enc, keys }
     }
 
     #[inline]
@@ -325,7 +538,7 @@ impl<'a> ser::Serializer for EncoderSerializer<'a> {
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
         let map = start_enum_map(self.enc, variant)?;
-        if let Err(err) = value.serialize(EncoderSerializer::new(self.enc)) {
+        if let Err(err) = value.serialize(EncoderSerializer::new(self.enc, self.keys)) {
             self.enc.truncate(map.start);
             self.enc.abort_container();
             return Err(err);
@@ -337,12 +550,12 @@ impl<'a> ser::Serializer for EncoderSerializer<'a> {
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let len = len.ok_or_else(|| SerdeError::with_code(ErrorCode::IndefiniteLengthForbidden))?;
         let root = self.enc.array_header(len).map_err(SerdeError::from)?;
-        Ok(SeqSerializer::new(self.enc, len, root))
+        Ok(SeqSerializer::new(self.enc, len, root, self.keys))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
         let root = self.enc.array_header(len).map_err(SerdeError::from)?;
-        Ok(SeqSerializer::new(self.enc, len, root))
+        Ok(SeqSerializer::new(self.enc, len, root, self.keys))
     }
 
     fn serialize_tuple_struct(
@@ -351,7 +564,7 @@ impl<'a> ser::Serializer for EncoderSerializer<'a> {
         len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
         let root = self.enc.array_header(len).map_err(SerdeError::from)?;
-        Ok(SeqSerializer::new(self.enc, len, root))
+        Ok(SeqSerializer::new(self.enc, len, root, self.keys))
     }
 
     fn serialize_tuple_variant(
@@ -367,13 +580,18 @@ impl<'a> ser::Serializer for EncoderSerializer<'a> {
             self.enc.abort_container();
             return Err(SerdeError::from(err));
         }
-        Ok(TupleVariantSerializer::new(self.enc, len, map))
+        Ok(TupleVariantSerializer::new(self.enc, len, map, self.keys))
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         let len = len.ok_or_else(|| SerdeError::with_code(ErrorCode::IndefiniteLengthForbidden))?;
+        let spans = if self.keys == MapKeyPolicy::IntegersAsDecimalText {
+            Some(try_vec_with_capacity(len, self.enc.buf_len())?)
+        } else {
+            None
+        };
         let root = self.enc.map_header(len).map_err(SerdeError::from)?;
-        Ok(MapSerializer::new(self.enc, len, root))
+        Ok(MapSerializer::new(self.enc, len, root, self.keys, spans))
     }
 
     fn serialize_struct(
@@ -382,7 +600,7 @@ impl<'a> ser::Serializer for EncoderSerializer<'a> {
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
         let root = self.enc.map_header(len).map_err(SerdeError::from)?;
-        Ok(StructSerializer::new(self.enc, len, root))
+        Ok(StructSerializer::new(self.enc, len, root, self.keys))
     }
 
     fn serialize_struct_variant(
@@ -398,7 +616,7 @@ impl<'a> ser::Serializer for EncoderSerializer<'a> {
             self.enc.abort_container();
             return Err(SerdeError::from(err));
         }
-        Ok(StructVariantSerializer::new(self.enc, len, map))
+        Ok(StructVariantSerializer::new(self.enc, len, map, self.keys))
     }
 }
 
@@ -427,15 +645,17 @@ fn start_enum_map(enc: &mut Encoder, variant: &str) -> Result<EnumMapState, Serd
 
 struct SeqSerializer<'a> {
     enc: &'a mut Encoder,
+    keys: MapKeyPolicy,
     remaining: usize,
     root: bool,
     finished: bool,
 }
 
 impl<'a> SeqSerializer<'a> {
-    fn new(enc: &'a mut Encoder, remaining: usize, root: bool) -> Self {
+    fn new(enc: &'a mut Encoder, remaining: usize, root: bool, keys: MapKeyPolicy) -> Self {
         Self {
             enc,
+            keys,
             remaining,
             root,
             finished: false,
@@ -451,7 +671,7 @@ impl SerializeSeq for SeqSerializer<'_> {
         if self.remaining == 0 {
             return Err(SerdeError::with_code(ErrorCode::ArrayLenMismatch));
         }
-        value.serialize(EncoderSerializer::new(self.enc))?;
+        value.serialize(EncoderSerializer::new(self.enc, self.keys))?;
         self.remaining -= 1;
         Ok(())
     }
@@ -503,6 +723,7 @@ impl ser::SerializeTupleStruct for SeqSerializer<'_> {
 
 struct TupleVariantSerializer<'a> {
     enc: &'a mut Encoder,
+    keys: MapKeyPolicy,
     remaining: usize,
     map_start: usize,
     map_root: bool,
@@ -510,9 +731,10 @@ struct TupleVariantSerializer<'a> {
 }
 
 impl<'a> TupleVariantSerializer<'a> {
-    fn new(enc: &'a mut Encoder, remaining: usize, map: EnumMapState) -> Self {
+    fn new(enc: &'a mut Encoder, remaining: usize, map: EnumMapState, keys: MapKeyPolicy) -> Self {
         Self {
             enc,
+            keys,
             remaining,
             map_start: map.start,
             map_root: map.root,
@@ -529,7 +751,7 @@ impl ser::SerializeTupleVariant for TupleVariantSerializer<'_> {
         if self.remaining == 0 {
             return Err(SerdeError::with_code(ErrorCode::ArrayLenMismatch));
         }
-        value.serialize(EncoderSerializer::new(self.enc))?;
+        value.serialize(EncoderSerializer::new(self.enc, self.keys))?;
         self.remaining -= 1;
         Ok(())
     }
@@ -564,20 +786,31 @@ struct PendingKey {
 
 struct MapSerializer<'a> {
     enc: &'a mut Encoder,
+    keys: MapKeyPolicy,
     remaining: usize,
     prev_key_range: Option<(usize, usize)>,
     pending: Option<PendingKey>,
+    /// Entry ranges to sort on `end`, recorded when coerced keys may arrive out of order.
+    spans: Option<Vec<EntrySpan>>,
     root: bool,
     finished: bool,
 }
 
 impl<'a> MapSerializer<'a> {
-    fn new(enc: &'a mut Encoder, remaining: usize, root: bool) -> Self {
+    fn new(
+        enc: &'a mut Encoder,
+        remaining: usize,
+        root: bool,
+        keys: MapKeyPolicy,
+        spans: Option<Vec<EntrySpan>>,
+    ) -> Self {
         Self {
             enc,
+            keys,
             remaining,
             prev_key_range: None,
             pending: None,
+            spans,
             root,
             finished: false,
         }
@@ -588,15 +821,18 @@ impl<'a> MapSerializer<'a> {
         key: &T,
     ) -> Result<PendingKey, SerdeError> {
         let entry_start = self.enc.buf_len();
-        let (key_start, key_end) = key.serialize(MapKeySerializer::new(self.enc, entry_start))?;
-
-        check_map_key_order(
-            self.enc,
-            self.prev_key_range,
-            key_start,
-            key_end,
-            entry_start,
-        )?;
+        let (key_start, key_end) =
+            key.serialize(MapKeySerializer::new(self.enc, entry_start, self.keys))?;
+
+        if self.spans.is_none() {
+            check_map_key_order(
+                self.enc,
+                self.prev_key_range,
+                key_start,
+                key_end,
+                entry_start,
+            )?;
+        }
 
         Ok(PendingKey {
             entry_start,
@@ -604,6 +840,19 @@ impl<'a> MapSerializer<'a> {
             key_end,
         })
     }
+
+    fn finish_entry(&mut self, pending: &PendingKey) {
+        if let Some(spans) = &mut self.spans {
+            // Capacity for `remaining` entries was reserved up front.
+            spans.push(EntrySpan {
+                key_start: pending.key_start,
+                key_end: pending.key_end,
+                end: self.enc.buf_len(),
+            });
+        }
+        self.prev_key_range = Some((pending.key_start, pending.key_end));
+        self.remaining -= 1;
+    }
 }
 
 impl SerializeMap for MapSerializer<'_> {
@@ -628,13 +877,12 @@ impl SerializeMap for MapSerializer<'_> {
             .take()
             .ok_or_else(|| SerdeError::with_code(ErrorCode::SerdeError))?;
 
-        if let Err(err) = value.serialize(EncoderSerializer::new(self.enc)) {
+        if let Err(err) = value.serialize(EncoderSerializer::new(self.enc, self.keys)) {
             self.enc.truncate(pending.entry_start);
             return Err(err);
         }
 
-        self.prev_key_range = Some((pending.key_start, pending.key_end));
-        self.remaining -= 1;
+        self.finish_entry(&pending);
         Ok(())
     }
 
@@ -647,12 +895,11 @@ impl SerializeMap for MapSerializer<'_> {
             return Err(SerdeError::with_code(ErrorCode::MapLenMismatch));
         }
         let pending = self.write_pending_key(key)?;
-        if let Err(err) = value.serialize(EncoderSerializer::new(self.enc)) {
+        if let Err(err) = value.serialize(EncoderSerializer::new(self.enc, self.keys)) {
             self.enc.truncate(pending.entry_start);
             return Err(err);
         }
-        self.prev_key_range = Some((pending.key_start, pending.key_end));
-        self.remaining -= 1;
+        self.finish_entry(&pending);
         Ok(())
     }
 
@@ -664,6 +911,9 @@ impl SerializeMap for MapSerializer<'_> {
             return Err(SerdeError::with_code(ErrorCode::MapLenMismatch));
         }
         let mut this = self;
+        if let Some(spans) = this.spans.take() {
+            this.enc.reorder_entries(spans)?;
+        }
         this.enc.finish_container(this.root);
         this.finished = true;
         Ok(())
@@ -680,6 +930,7 @@ impl Drop for MapSerializer<'_> {
 
 struct StructSerializer<'a> {
     enc: &'a mut Encoder,
+    keys: MapKeyPolicy,
     remaining: usize,
     prev_key_range: Option<(usize, usize)>,
     root: bool,
@@ -687,9 +938,10 @@ struct StructSerializer<'a> {
 }
 
 impl<'a> StructSerializer<'a> {
-    fn new(enc: &'a mut Encoder, remaining: usize, root: bool) -> Self {
+    fn new(enc: &'a mut Encoder, remaining: usize, root: bool, keys: MapKeyPolicy) -> Self {
         Self {
             enc,
+            keys,
             remaining,
             prev_key_range: None,
             root,
@@ -709,6 +961,7 @@ impl ser::SerializeStruct for StructSerializer<'_> {
     ) -> Result<(), SerdeError> {
         write_struct_field(
             self.enc,
+            self.keys,
             key,
             value,
             &mut self.remaining,
@@ -734,6 +987,7 @@ impl Drop for StructSerializer<'_> {
 
 struct StructVariantSerializer<'a> {
     enc: &'a mut Encoder,
+    keys: MapKeyPolicy,
     remaining: usize,
     prev_key_range: Option<(usize, usize)>,
     map_start: usize,
@@ -742,9 +996,10 @@ struct StructVariantSerializer<'a> {
 }
 
 impl<'a> StructVariantSerializer<'a> {
-    fn new(enc: &'a mut Encoder, remaining: usize, map: EnumMapState) -> Self {
+    fn new(enc: &'a mut Encoder, remaining: usize, map: EnumMapState, keys: MapKeyPolicy) -> Self {
         Self {
             enc,
+            keys,
             remaining,
             prev_key_range: None,
             map_start: map.start,
@@ -765,6 +1020,7 @@ impl ser::SerializeStructVariant for StructVariantSerializer<'_> {
     ) -> Result<(), SerdeError> {
         write_struct_field(
             self.enc,
+            self.keys,
             key,
             value,
             &mut self.remaining,
@@ -793,11 +1049,51 @@ impl Drop for StructVariantSerializer<'_> {
 struct MapKeySerializer<'a> {
     enc: &'a mut Encoder,
     entry_start: usize,
+    keys: MapKeyPolicy,
 }
 
 impl<'a> MapKeySerializer<'a> {
-    fn new(enc: &'a mut Encoder, entry_start: usize) -> Self {
-        Self { enc, entry_start }
+    fn new(enc: &'a mut Encoder, entry_start: usize, keys: MapKeyPolicy) -> Self {
+        Self {
+            enc,
+            entry_start,
+            keys,
+        }
+    }
+
+    /// Write an integer key as decimal text if the policy allows it.
+    fn integer_key(self, v: impl fmt::Display) -> Result<(usize, usize), SerdeError> {
+        if self.keys != MapKeyPolicy::IntegersAsDecimalText {
+            return Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText));
+        }
+        let mut buf = DecimalBuf {
+            bytes: [0; 40],
+            len: 0,
+        };
+        fmt::write(&mut buf, format_args!("{v}"))
+            .map_err(|_| SerdeError::with_code(ErrorCode::SerdeError))?;
+        // Only ASCII digits and `-` were written.
+        let text = core::str::from_utf8(&buf.bytes[..buf.len])
+            .map_err(|_| SerdeError::with_code(ErrorCode::SerdeError))?;
+        ser::Serializer::serialize_str(self, text)
+    }
+}
+
+/// Stack buffer for the decimal form of an integer key (an `i128` needs at most 40 bytes).
+struct DecimalBuf {
+    bytes: [u8; 40],
+    len: usize,
+}
+
+impl fmt::Write for DecimalBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len.checked_add(s.len()).ok_or(fmt::Error)?;
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
     }
 }
 
@@ -833,44 +1129,44 @@ impl ser::Serializer for MapKeySerializer<'_> {
         Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
     }
 
-    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_i128(self, _v: i128) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
-    fn serialize_u128(self, _v: u128) -> Result<Self::Ok, Self::Error> {
-        Err(SerdeError::with_code(ErrorCode::MapKeyMustBeText))
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.integer_key(v)
     }
 
     fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
@@ -985,6 +1281,8 @@ pub struct DeError {
     pub code: ErrorCode,
     /// Byte offset within the input where the error was detected.
     pub offset: usize,
+    /// Secondary offset, as in [`CborError::detail`].
+    pub detail: Option<u32>,
 }
 
 impl DeError {
@@ -992,27 +1290,34 @@ impl DeError {
     #[must_use]
     /// Construct a new serde error with a code and offset.
     pub const fn new(code: ErrorCode, offset: usize) -> Self {
-        Self { code, offset }
+        Self {
+            code,
+            offset,
+            detail: None,
+        }
     }
 
     #[inline]
     #[must_use]
     /// Convert into the crate's [`CborError`].
     pub const fn into_cbor_error(self) -> CborError {
-        CborError::new(self.code, self.offset)
+        CborError::new(self.code, self.offset).with_detail_u32(self.detail)
     }
 }
 
 impl fmt::Display for DeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let e = CborError::new(self.code, self.offset);
-        fmt::Display::fmt(&e, f)
+        fmt::Display::fmt(&self.into_cbor_error(), f)
     }
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for DeError {}
 
+/// `no_std` only, as for [`CborError`].
+#[cfg(all(feature = "core_error", not(feature = "std")))]
+impl core::error::Error for DeError {}
+
 impl serde::de::Error for DeError {
     fn custom<T: fmt::Display>(_msg: T) -> Self {
         Self::new(ErrorCode::SerdeError, 0)
@@ -1021,7 +1326,11 @@ impl serde::de::Error for DeError {
 
 impl From<CborError> for DeError {
     fn from(e: CborError) -> Self {
-        Self::new(e.code, e.offset)
+        Self {
+            code: e.code(),
+            offset: e.offset(),
+            detail: e.detail(),
+        }
     }
 }
 
@@ -1030,21 +1339,37 @@ impl crate::wire::DecodeError for DeError {
     fn new(code: ErrorCode, offset: usize) -> Self {
         Self::new(code, offset)
     }
+
+    #[inline]
+    fn with_prev_key(self, offset: usize) -> Self {
+        Self {
+            detail: u32::try_from(offset).ok(),
+            ..self
+        }
+    }
 }
 
-struct ArrayAccess<'a, 'de, const CHECKED: bool> {
+struct ArrayAccess<'a, 'de, const CHECKED: bool, R> {
     array: ArrayDecoder<'a, 'de, CHECKED>,
+    index: usize,
+    tracer: R,
 }
 
-impl<'de, const CHECKED: bool> SeqAccess<'de> for ArrayAccess<'_, 'de, CHECKED> {
+impl<'de, const CHECKED: bool, R: Tracer<'de>> SeqAccess<'de> for ArrayAccess<'_, 'de, CHECKED, R> {
     type Error = DeError;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, DeError>
     where
         T: DeserializeSeed<'de>,
     {
+        let elem = PathElem::Index(self.index);
+        self.index += 1;
+        let tracer = self.tracer;
         self.array
-            .decode_next(|decoder| seed.deserialize(decoder).map_err(DeError::into_cbor_error))
+            .decode_next(|decoder| {
+                let res = seed.deserialize(DecoderDeserializer { decoder, tracer });
+                tracer.traced(res, elem).map_err(DeError::into_cbor_error)
+            })
             .map_err(DeError::from)
     }
 
@@ -1053,11 +1378,15 @@ impl<'de, const CHECKED: bool> SeqAccess<'de> for ArrayAccess<'_, 'de, CHECKED>
     }
 }
 
-struct MapAccessImpl<'a, 'de, const CHECKED: bool> {
+struct MapAccessImpl<'a, 'de, const CHECKED: bool, R> {
     map: MapDecoder<'a, 'de, CHECKED>,
+    key: &'de str,
+    tracer: R,
 }
 
-impl<'de, const CHECKED: bool> MapAccess<'de> for MapAccessImpl<'_, 'de, CHECKED> {
+impl<'de, const CHECKED: bool, R: Tracer<'de>> MapAccess<'de>
+    for MapAccessImpl<'_, 'de, CHECKED, R>
+{
     type Error = DeError;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, DeError>
@@ -1067,6 +1396,7 @@ impl<'de, const CHECKED: bool> MapAccess<'de> for MapAccessImpl<'_, 'de, CHECKED
         let Some(key) = self.map.next_key().map_err(DeError::from)? else {
             return Ok(None);
         };
+        self.key = key;
         seed.deserialize(<&'de str as IntoDeserializer<'de, DeError>>::into_deserializer(key))
             .map(Some)
     }
@@ -1075,8 +1405,13 @@ impl<'de, const CHECKED: bool> MapAccess<'de> for MapAccessImpl<'_, 'de, CHECKED
     where
         V: DeserializeSeed<'de>,
     {
+        let elem = PathElem::Key(self.key);
+        let tracer = self.tracer;
         self.map
-            .decode_value(|decoder| seed.deserialize(decoder).map_err(DeError::into_cbor_error))
+            .decode_value(|decoder| {
+                let res = seed.deserialize(DecoderDeserializer { decoder, tracer });
+                tracer.traced(res, elem).map_err(DeError::into_cbor_error)
+            })
             .map_err(DeError::from)
     }
 
@@ -1085,15 +1420,18 @@ impl<'de, const CHECKED: bool> MapAccess<'de> for MapAccessImpl<'_, 'de, CHECKED
     }
 }
 
-struct EnumAccessImpl<'a, 'de, const CHECKED: bool> {
+struct EnumAccessImpl<'a, 'de, const CHECKED: bool, R> {
     key: &'de str,
     map: MapDecoder<'a, 'de, CHECKED>,
+    tracer: R,
 }
 
 #[allow(clippy::elidable_lifetime_names)]
-impl<'a, 'de, const CHECKED: bool> EnumAccess<'de> for EnumAccessImpl<'a, 'de, CHECKED> {
+impl<'a, 'de, const CHECKED: bool, R: Tracer<'de>> EnumAccess<'de>
+    for EnumAccessImpl<'a, 'de, CHECKED, R>
+{
     type Error = DeError;
-    type Variant = VariantAccessImpl<'a, 'de, CHECKED>;
+    type Variant = VariantAccessImpl<'a, 'de, CHECKED, R>;
 
     fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), DeError>
     where
@@ -1102,88 +1440,176 @@ impl<'a, 'de, const CHECKED: bool> EnumAccess<'de> for EnumAccessImpl<'a, 'de, C
         let variant = seed.deserialize(
             <&'de str as IntoDeserializer<'de, DeError>>::into_deserializer(self.key),
         )?;
-        Ok((variant, VariantAccessImpl { map: self.map }))
+        Ok((
+            variant,
+            VariantAccessImpl {
+                key: self.key,
+                map: self.map,
+                tracer: self.tracer,
+            },
+        ))
     }
 }
 
-struct VariantAccessImpl<'a, 'de, const CHECKED: bool> {
+struct VariantAccessImpl<'a, 'de, const CHECKED: bool, R> {
+    key: &'de str,
     map: MapDecoder<'a, 'de, CHECKED>,
+    tracer: R,
 }
 
-impl<'de, const CHECKED: bool> VariantAccess<'de> for VariantAccessImpl<'_, 'de, CHECKED> {
+impl<'de, const CHECKED: bool, R: Tracer<'de>> VariantAccess<'de>
+    for VariantAccessImpl<'_, 'de, CHECKED, R>
+{
     type Error = DeError;
 
-    fn unit_variant(mut self) -> Result<(), DeError> {
-        self.map
-            .decode_value(|decoder| <()>::deserialize(decoder).map_err(DeError::into_cbor_error))
-            .map_err(DeError::from)
+    fn unit_variant(self) -> Result<(), DeError> {
+        self.payload(|decoder| <()>::deserialize(decoder))
     }
 
-    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value, DeError>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, DeError>
     where
         T: DeserializeSeed<'de>,
     {
-        self.map
-            .decode_value(|decoder| seed.deserialize(decoder).map_err(DeError::into_cbor_error))
-            .map_err(DeError::from)
+        self.payload(|decoder| seed.deserialize(decoder))
     }
 
-    fn tuple_variant<V>(mut self, len: usize, visitor: V) -> Result<V::Value, DeError>
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, DeError>
     where
         V: Visitor<'de>,
     {
-        self.map
-            .decode_value(|decoder| {
-                decoder
-                    .deserialize_tuple(len, visitor)
-                    .map_err(DeError::into_cbor_error)
-            })
-            .map_err(DeError::from)
+        self.payload(|decoder| decoder.deserialize_tuple(len, visitor))
     }
 
     fn struct_variant<V>(
-        mut self,
+        self,
         fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, DeError>
     where
         V: Visitor<'de>,
     {
+        self.payload(|decoder| decoder.deserialize_struct("", fields, visitor))
+    }
+}
+
+impl<'de, const CHECKED: bool, R: Tracer<'de>> VariantAccessImpl<'_, 'de, CHECKED, R> {
+    fn payload<T>(
+        mut self,
+        f: impl FnOnce(DecoderDeserializer<'_, 'de, CHECKED, R>) -> Result<T, DeError>,
+    ) -> Result<T, DeError> {
+        let elem = PathElem::Key(self.key);
+        let tracer = self.tracer;
         self.map
             .decode_value(|decoder| {
-                decoder
-                    .deserialize_struct("", fields, visitor)
-                    .map_err(DeError::into_cbor_error)
+                let res = f(DecoderDeserializer { decoder, tracer });
+                tracer.traced(res, elem).map_err(DeError::into_cbor_error)
             })
             .map_err(DeError::from)
     }
 }
 
+/// `serde` deserializer over a [`Decoder`], generic over how failing paths are traced.
+struct DecoderDeserializer<'a, 'de, const CHECKED: bool, R> {
+    decoder: &'a mut Decoder<'de, CHECKED>,
+    tracer: R,
+}
+
+macro_rules! forward_untraced {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {$(
+        #[inline]
+        fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, DeError>
+        where
+            V: Visitor<'de>,
+        {
+            DecoderDeserializer {
+                decoder: self,
+                tracer: NoTrace,
+            }
+            .$method($($arg,)* visitor)
+        }
+    )*};
+}
+
 impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECKED> {
     type Error = DeError;
 
+    forward_untraced! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+}
+
+impl<'de, const CHECKED: bool, R: Tracer<'de>> de::Deserializer<'de>
+    for DecoderDeserializer<'_, 'de, CHECKED, R>
+{
+    type Error = DeError;
+
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, DeError>
     where
         V: Visitor<'de>,
     {
-        match self.peek_kind().map_err(DeError::from)? {
+        match self.decoder.peek_kind().map_err(DeError::from)? {
             CborKind::Null => self.deserialize_unit(visitor),
             CborKind::Bool => self.deserialize_bool(visitor),
             CborKind::Integer => {
-                let value: i128 = CborDecode::decode(self).map_err(DeError::from)?;
-                visitor.visit_i128(value)
+                // Prefer the 64-bit visitor methods: serde's buffered `Content` (used by
+                // `#[serde(flatten)]` and untagged enums) has no 128-bit variants.
+                let value: i128 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
+                if let Ok(v) = i64::try_from(value) {
+                    visitor.visit_i64(v)
+                } else if let Ok(v) = u64::try_from(value) {
+                    visitor.visit_u64(v)
+                } else {
+                    visitor.visit_i128(value)
+                }
             }
             CborKind::Float => self.deserialize_f64(visitor),
             CborKind::Bytes => {
-                let value: &'de [u8] = CborDecode::decode(self).map_err(DeError::from)?;
+                let value: &'de [u8] =
+                    CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
                 visitor.visit_borrowed_bytes(value)
             }
             CborKind::Text => {
-                let value: &'de str = CborDecode::decode(self).map_err(DeError::from)?;
+                let value: &'de str =
+                    CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
                 visitor.visit_borrowed_str(value)
             }
             CborKind::Array => self.deserialize_seq(visitor),
             CborKind::Map => self.deserialize_map(visitor),
+            // The serde data model has no tags; only `IgnoredAny`, which skips them, accepts one.
+            CborKind::Tag => Err(DeError::from(CborError::new(
+                ErrorCode::ForbiddenOrMalformedTag,
+                self.decoder.position(),
+            ))),
         }
     }
 
@@ -1191,7 +1617,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: bool = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: bool = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_bool(value)
     }
 
@@ -1199,7 +1625,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: i8 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: i8 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_i8(value)
     }
 
@@ -1207,7 +1633,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: i16 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: i16 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_i16(value)
     }
 
@@ -1215,7 +1641,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: i32 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: i32 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_i32(value)
     }
 
@@ -1223,7 +1649,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: i64 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: i64 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_i64(value)
     }
 
@@ -1231,7 +1657,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: i128 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: i128 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_i128(value)
     }
 
@@ -1239,7 +1665,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: u8 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: u8 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_u8(value)
     }
 
@@ -1247,7 +1673,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: u16 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: u16 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_u16(value)
     }
 
@@ -1255,7 +1681,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: u32 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: u32 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_u32(value)
     }
 
@@ -1263,7 +1689,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: u64 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: u64 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_u64(value)
     }
 
@@ -1271,7 +1697,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: u128 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: u128 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_u128(value)
     }
 
@@ -1279,7 +1705,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: f32 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: f32 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_f32(value)
     }
 
@@ -1287,7 +1713,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: f64 = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: f64 = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_f64(value)
     }
 
@@ -1295,13 +1721,16 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: &'de str = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: &'de str = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         let mut chars = value.chars();
         let ch = chars
             .next()
-            .ok_or_else(|| DeError::new(ErrorCode::ExpectedText, self.position()))?;
+            .ok_or_else(|| DeError::new(ErrorCode::ExpectedText, self.decoder.position()))?;
         if chars.next().is_some() {
-            return Err(DeError::new(ErrorCode::ExpectedText, self.position()));
+            return Err(DeError::new(
+                ErrorCode::ExpectedText,
+                self.decoder.position(),
+            ));
         }
         visitor.visit_char(ch)
     }
@@ -1310,7 +1739,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: &'de str = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: &'de str = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_borrowed_str(value)
     }
 
@@ -1318,7 +1747,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: String = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: String = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_string(value)
     }
 
@@ -1326,7 +1755,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: &'de [u8] = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: &'de [u8] = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_borrowed_bytes(value)
     }
 
@@ -1334,7 +1763,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let value: Vec<u8> = CborDecode::decode(self).map_err(DeError::from)?;
+        let value: Vec<u8> = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_byte_buf(value)
     }
 
@@ -1342,8 +1771,11 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        if matches!(self.peek_kind().map_err(DeError::from)?, CborKind::Null) {
-            let _: () = CborDecode::decode(self).map_err(DeError::from)?;
+        if matches!(
+            self.decoder.peek_kind().map_err(DeError::from)?,
+            CborKind::Null
+        ) {
+            let _: () = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
             visitor.visit_none()
         } else {
             visitor.visit_some(self)
@@ -1354,7 +1786,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let _: () = CborDecode::decode(self).map_err(DeError::from)?;
+        let _: () = CborDecode::decode(&mut *self.decoder).map_err(DeError::from)?;
         visitor.visit_unit()
     }
 
@@ -1378,10 +1810,10 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
         V: Visitor<'de>,
     {
         if name == RAW_VALUE_MARKER {
-            let start = self.position();
-            self.skip_value().map_err(DeError::from)?;
-            let end = self.position();
-            let raw = &self.data()[start..end];
+            let start = self.decoder.position();
+            self.decoder.skip_value().map_err(DeError::from)?;
+            let end = self.decoder.position();
+            let raw = &self.decoder.data()[start..end];
             return visitor.visit_borrowed_bytes(raw);
         }
         visitor.visit_newtype_struct(self)
@@ -1391,20 +1823,28 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let array = self.array().map_err(DeError::from)?;
-        visitor.visit_seq(ArrayAccess { array })
+        let array = self.decoder.array().map_err(DeError::from)?;
+        visitor.visit_seq(ArrayAccess {
+            array,
+            index: 0,
+            tracer: self.tracer,
+        })
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, DeError>
     where
         V: Visitor<'de>,
     {
-        let off = self.position();
-        let array = self.array().map_err(DeError::from)?;
+        let off = self.decoder.position();
+        let array = self.decoder.array().map_err(DeError::from)?;
         if array.remaining() != len {
             return Err(DeError::new(ErrorCode::ArrayLenMismatch, off));
         }
-        visitor.visit_seq(ArrayAccess { array })
+        visitor.visit_seq(ArrayAccess {
+            array,
+            index: 0,
+            tracer: self.tracer,
+        })
     }
 
     fn deserialize_tuple_struct<V>(
@@ -1423,8 +1863,12 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let map = self.map().map_err(DeError::from)?;
-        visitor.visit_map(MapAccessImpl { map })
+        let map = self.decoder.map().map_err(DeError::from)?;
+        visitor.visit_map(MapAccessImpl {
+            map,
+            key: "",
+            tracer: self.tracer,
+        })
     }
 
     fn deserialize_struct<V>(
@@ -1448,15 +1892,19 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        let off = self.position();
-        let mut map = self.map().map_err(DeError::from)?;
+        let off = self.decoder.position();
+        let mut map = self.decoder.map().map_err(DeError::from)?;
         if map.remaining() != 1 {
             return Err(DeError::new(ErrorCode::MapLenMismatch, off));
         }
         let Some(key) = map.next_key().map_err(DeError::from)? else {
             return Err(DeError::new(ErrorCode::MapLenMismatch, off));
         };
-        visitor.visit_enum(EnumAccessImpl { key, map })
+        visitor.visit_enum(EnumAccessImpl {
+            key,
+            map,
+            tracer: self.tracer,
+        })
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, DeError>
@@ -1470,7 +1918,7 @@ impl<'de, const CHECKED: bool> de::Deserializer<'de> for &mut Decoder<'de, CHECK
     where
         V: Visitor<'de>,
     {
-        self.skip_value().map_err(DeError::from)?;
+        self.decoder.skip_value().map_err(DeError::from)?;
         visitor.visit_unit()
     }
 }
@@ -0,0 +1,162 @@
+//! Value logic shared by the streaming encoders that write through a [`Sink`]
+//! ([`crate::ArrayEncoderFixed`] and `WriterEncoder`).
+//!
+//! The encoders differ only in what happens when a value fails midway: a buffer sink can
+//! take its bytes back, while an I/O sink cannot and poisons itself instead. That policy
+//! lives in [`StreamSink`]; everything else is implemented once on [`StreamEncoder`].
+
+use crate::profile::validate_int_safe_i64;
+use crate::scalar::F64Bits;
+use crate::wire::{encode_bytes, encode_float64, encode_int, encode_major_len, encode_text, Sink};
+use crate::{CborError, ErrorCode};
+
+/// A [`Sink`] that knows how to recover from a value that failed partway through.
+pub trait StreamSink: Sink {
+    /// Return the error that left the sink unusable, if any.
+    fn check(&self) -> Result<(), CborError>;
+
+    /// Handle `err`, raised by a value or map entry that started at `start`.
+    fn recover(&mut self, start: usize, err: CborError);
+}
+
+/// Sink plus nesting bookkeeping for one streaming encoder.
+pub struct StreamCore<S> {
+    pub sink: S,
+    depth: usize,
+    items: usize,
+    root_done: bool,
+}
+
+impl<S: StreamSink> StreamCore<S> {
+    pub const fn new(sink: S) -> Self {
+        Self {
+            sink,
+            depth: 0,
+            items: 0,
+            root_done: false,
+        }
+    }
+
+    /// Returns `true` once a complete root value has been written.
+    pub const fn root_done(&self) -> bool {
+        self.root_done
+    }
+}
+
+/// Encoding methods shared by the streaming encoders; each public method delegates here.
+pub trait StreamEncoder: Sized {
+    type Sink: StreamSink;
+
+    fn core(&mut self) -> &mut StreamCore<Self::Sink>;
+
+    fn err_here(&mut self, code: ErrorCode) -> CborError {
+        CborError::new(code, self.core().sink.position())
+    }
+
+    fn write_value<F>(&mut self, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), CborError>,
+    {
+        let core = self.core();
+        core.sink.check()?;
+        if core.depth == 0 && core.root_done {
+            return Err(self.err_here(ErrorCode::TrailingBytes));
+        }
+        let start = core.sink.position();
+        if let Err(err) = f(self) {
+            self.core().sink.recover(start, err);
+            return Err(err);
+        }
+        let core = self.core();
+        if core.depth == 0 {
+            core.root_done = true;
+        } else {
+            core.items += 1;
+        }
+        Ok(())
+    }
+
+    /// Run `f` one level deeper and return how many values it wrote at that level.
+    fn nested<F>(&mut self, f: F) -> Result<usize, CborError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), CborError>,
+    {
+        let core = self.core();
+        let outer = core::mem::replace(&mut core.items, 0);
+        core.depth += 1;
+        let res = f(self);
+        let core = self.core();
+        core.depth -= 1;
+        let written = core::mem::replace(&mut core.items, outer);
+        res.map(|()| written)
+    }
+
+    fn write_null(&mut self) -> Result<(), CborError> {
+        self.write_value(|e| e.core().sink.write_u8(0xf6))
+    }
+
+    fn write_bool(&mut self, v: bool) -> Result<(), CborError> {
+        self.write_value(|e| e.core().sink.write_u8(if v { 0xf5 } else { 0xf4 }))
+    }
+
+    fn write_int(&mut self, v: i64) -> Result<(), CborError> {
+        self.write_value(|e| {
+            validate_int_safe_i64(v).map_err(|code| e.err_here(code))?;
+            encode_int(&mut e.core().sink, v)
+        })
+    }
+
+    fn write_bytes(&mut self, b: &[u8]) -> Result<(), CborError> {
+        self.write_value(|e| encode_bytes(&mut e.core().sink, b))
+    }
+
+    fn write_text(&mut self, s: &str) -> Result<(), CborError> {
+        self.write_value(|e| encode_text(&mut e.core().sink, s))
+    }
+
+    fn write_float(&mut self, bits: F64Bits) -> Result<(), CborError> {
+        self.write_value(|e| encode_float64(&mut e.core().sink, bits))
+    }
+
+    fn write_array<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), CborError>,
+    {
+        self.write_value(|e| {
+            encode_major_len(&mut e.core().sink, 4, len)?;
+            let written = e.nested(f)?;
+            if written != len {
+                return Err(e.err_here(ErrorCode::ArrayLenMismatch));
+            }
+            Ok(())
+        })
+    }
+
+    /// Write a map header, then run `f`, which returns how many declared entries it left
+    /// unwritten.
+    fn write_map<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut Self) -> Result<usize, CborError>,
+    {
+        self.write_value(|e| {
+            encode_major_len(&mut e.core().sink, 5, len)?;
+            if f(e)? != 0 {
+                return Err(e.err_here(ErrorCode::MapLenMismatch));
+            }
+            Ok(())
+        })
+    }
+
+    /// Write the value of a map entry whose key is already written.
+    ///
+    /// The value is counted like an array item so `f` cannot write zero or several.
+    fn write_entry_value<F>(&mut self, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), CborError>,
+    {
+        if self.nested(f)? != 1 {
+            return Err(self.err_here(ErrorCode::MapLenMismatch));
+        }
+        Ok(())
+    }
+}
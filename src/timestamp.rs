@@ -0,0 +1,76 @@
+//! Timestamps as safe-integer milliseconds since the Unix epoch (`chrono` / `time` features).
+//!
+//! Both types encode as a single integer: milliseconds since 1970-01-01T00:00:00Z. Sub-millisecond
+//! precision is truncated toward negative infinity, so an instant and its floor to the millisecond
+//! encode identically. Instants outside the safe-integer window (about ±285,000 years, reachable
+//! only with `time`'s `large-dates`) fail to encode with `IntegerOutsideSafeRange`, and decoding
+//! rejects such integers with the same code before any conversion.
+
+#[cfg(feature = "alloc")]
+use crate::codec::{CborArrayElem, CborEncode};
+use crate::codec::{CborDecode, Decoder};
+#[cfg(feature = "alloc")]
+use crate::encode::Encoder;
+use crate::{CborError, ErrorCode};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+#[cfg(feature = "time")]
+use time::OffsetDateTime;
+
+/// Decode epoch milliseconds, reporting `ValueOutOfRange` if `convert` rejects them.
+fn decode_millis<T, const CHECKED: bool>(
+    decoder: &mut Decoder<'_, CHECKED>,
+    convert: impl FnOnce(i64) -> Option<T>,
+) -> Result<T, CborError> {
+    let off = decoder.position();
+    let millis = i64::decode(decoder)?;
+    convert(millis).ok_or_else(|| CborError::new(ErrorCode::ValueOutOfRange, off))
+}
+
+/// Encodes as integer milliseconds since the Unix epoch.
+#[cfg(all(feature = "chrono", feature = "alloc"))]
+impl CborEncode for DateTime<Utc> {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        enc.int(self.timestamp_millis())
+    }
+}
+
+/// Decodes integer milliseconds since the Unix epoch.
+///
+/// Values outside the range `DateTime<Utc>` can represent fail with `ValueOutOfRange`.
+#[cfg(feature = "chrono")]
+impl<'de> CborDecode<'de> for DateTime<Utc> {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        decode_millis(decoder, Self::from_timestamp_millis)
+    }
+}
+
+#[cfg(all(feature = "chrono", feature = "alloc"))]
+impl CborArrayElem for DateTime<Utc> {}
+
+/// Encodes as integer milliseconds since the Unix epoch. The UTC offset is not encoded.
+#[cfg(all(feature = "time", feature = "alloc"))]
+impl CborEncode for OffsetDateTime {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        let millis = self.unix_timestamp_nanos().div_euclid(1_000_000);
+        let millis = i64::try_from(millis)
+            .map_err(|_| CborError::new(ErrorCode::IntegerOutsideSafeRange, enc.len()))?;
+        enc.int(millis)
+    }
+}
+
+/// Decodes integer milliseconds since the Unix epoch into a UTC `OffsetDateTime`.
+///
+/// Values outside the range `OffsetDateTime` can represent fail with `ValueOutOfRange`.
+#[cfg(feature = "time")]
+impl<'de> CborDecode<'de> for OffsetDateTime {
+    fn decode<const CHECKED: bool>(decoder: &mut Decoder<'de, CHECKED>) -> Result<Self, CborError> {
+        decode_millis(decoder, |millis| {
+            Self::from_unix_timestamp_nanos(i128::from(millis) * 1_000_000).ok()
+        })
+    }
+}
+
+#[cfg(all(feature = "time", feature = "alloc"))]
+impl CborArrayElem for OffsetDateTime {}
@@ -1,7 +1,14 @@
 use alloc::vec::Vec;
+use core::cmp::Ordering;
 
-use crate::profile::{validate_bignum_bytes, validate_int_safe_i64};
-use crate::CborError;
+use crate::int::{
+    add_be, cmp_big_endian, decrement_be, increment_be, mag_to_u128, magnitude_from_u128, sub_be,
+    trim_be,
+};
+use crate::profile::{
+    validate_bignum_bytes, validate_int_safe_i64, MAX_SAFE_INTEGER_I64, MIN_SAFE_INTEGER,
+};
+use crate::{CborError, ErrorCode};
 
 /// A tagged bignum integer (CBOR tag 2 or 3).
 ///
@@ -105,6 +112,17 @@ impl CborInteger {
         }
     }
 
+    /// Returns the heap bytes owned by this integer: zero for safe integers, the magnitude
+    /// buffer's capacity for bignums.
+    #[inline]
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        match &self.0 {
+            IntegerRepr::Safe(_) => 0,
+            IntegerRepr::Big(b) => b.magnitude.capacity(),
+        }
+    }
+
     /// Return the underlying bignum if available.
     #[inline]
     #[must_use]
@@ -116,6 +134,171 @@ impl CborInteger {
     }
 }
 
+impl CborInteger {
+    /// Add two integers, promoting to a bignum when the sum leaves the safe range and
+    /// demoting back to a safe integer when it returns.
+    ///
+    /// Operands of any size are added exactly, so a large negative bignum plus a large
+    /// positive one yields their (possibly small) difference. Returns `None` only if the
+    /// result cannot be allocated.
+    #[must_use]
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        if let (IntegerRepr::Safe(a), IntegerRepr::Safe(b)) = (&self.0, &rhs.0) {
+            return Self::from_i128(i128::from(*a) + i128::from(*b)).ok();
+        }
+        let (a_neg, a) = self.sign_abs().ok()?;
+        let (b_neg, b) = rhs.sign_abs().ok()?;
+        let (negative, abs) = if a_neg == b_neg {
+            (a_neg, add_be(&a, &b).ok()?)
+        } else {
+            match cmp_big_endian(&a, &b) {
+                Ordering::Greater => (a_neg, sub_be(&a, &b).ok()?),
+                Ordering::Less => (b_neg, sub_be(&b, &a).ok()?),
+                Ordering::Equal => return Some(Self(IntegerRepr::Safe(0))),
+            }
+        };
+        Self::from_sign_abs(negative, abs).ok()
+    }
+
+    /// Sign and big-endian absolute value (not the CBOR bignum magnitude, which is one less
+    /// for negative values).
+    fn sign_abs(&self) -> Result<(bool, Vec<u8>), ErrorCode> {
+        match &self.0 {
+            IntegerRepr::Safe(v) => {
+                let abs = v.unsigned_abs().to_be_bytes();
+                Ok((*v < 0, trim_be(&abs).to_vec()))
+            }
+            IntegerRepr::Big(b) => {
+                let mut abs = crate::alloc_util::try_vec_u8_from_slice(&b.magnitude, 0)
+                    .map_err(|err| err.code())?;
+                if b.negative {
+                    increment_be(&mut abs);
+                }
+                Ok((b.negative, abs))
+            }
+        }
+    }
+
+    /// Inverse of [`CborInteger::sign_abs`] for a nonzero, trimmed absolute value.
+    fn from_sign_abs(negative: bool, mut abs: Vec<u8>) -> Result<Self, CborError> {
+        if let Some(n) = mag_to_u128(&abs).and_then(|n| i128::try_from(n).ok()) {
+            return Self::from_i128(if negative { -n } else { n });
+        }
+        if negative {
+            decrement_be(&mut abs);
+            let lead = abs.len() - trim_be(&abs).len();
+            abs.drain(..lead);
+        }
+        Ok(Self(IntegerRepr::Big(BigInt {
+            negative,
+            magnitude: abs,
+        })))
+    }
+
+    fn from_i128(v: i128) -> Result<Self, CborError> {
+        if let Ok(safe) = i64::try_from(v) {
+            if (MIN_SAFE_INTEGER..=MAX_SAFE_INTEGER_I64).contains(&safe) {
+                return Ok(Self(IntegerRepr::Safe(safe)));
+            }
+        }
+        let (negative, n) = if v < 0 {
+            (true, (-1 - v).unsigned_abs())
+        } else {
+            (false, v.unsigned_abs())
+        };
+        let magnitude = magnitude_from_u128(n).map_err(|code| CborError::new(code, 0))?;
+        Ok(Self(IntegerRepr::Big(BigInt {
+            negative,
+            magnitude,
+        })))
+    }
+}
+
+/// Orders by numeric value, not by encoding.
+impl Ord for CborInteger {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Bignums lie strictly outside the safe range, so their sign alone orders them
+        // against safe integers.
+        match (&self.0, &other.0) {
+            (IntegerRepr::Safe(a), IntegerRepr::Safe(b)) => a.cmp(b),
+            (IntegerRepr::Safe(_), IntegerRepr::Big(b)) => {
+                if b.negative {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            }
+            (IntegerRepr::Big(a), IntegerRepr::Safe(_)) => {
+                if a.negative {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+            (IntegerRepr::Big(a), IntegerRepr::Big(b)) => match (a.negative, b.negative) {
+                (false, false) => cmp_big_endian(&a.magnitude, &b.magnitude),
+                // A larger magnitude is a more negative value.
+                (true, true) => cmp_big_endian(&b.magnitude, &a.magnitude),
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+            },
+        }
+    }
+}
+
+impl PartialOrd for CborInteger {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Fails with `ExpectedInteger` if the value does not fit in `i128`.
+impl TryFrom<&CborInteger> for i128 {
+    type Error = CborError;
+
+    fn try_from(value: &CborInteger) -> Result<Self, Self::Error> {
+        match &value.0 {
+            IntegerRepr::Safe(v) => Ok(Self::from(*v)),
+            IntegerRepr::Big(b) => {
+                let n = mag_to_u128(&b.magnitude)
+                    .and_then(|n| Self::try_from(n).ok())
+                    .ok_or_else(|| CborError::new(ErrorCode::ExpectedInteger, 0))?;
+                Ok(if b.negative { -1 - n } else { n })
+            }
+        }
+    }
+}
+
+/// Fails with `ExpectedInteger` if the value is negative or does not fit in `u128`.
+impl TryFrom<&CborInteger> for u128 {
+    type Error = CborError;
+
+    fn try_from(value: &CborInteger) -> Result<Self, Self::Error> {
+        let n = match &value.0 {
+            IntegerRepr::Safe(v) => Self::try_from(*v).ok(),
+            IntegerRepr::Big(b) if !b.negative => mag_to_u128(&b.magnitude),
+            IntegerRepr::Big(_) => None,
+        };
+        n.ok_or_else(|| CborError::new(ErrorCode::ExpectedInteger, 0))
+    }
+}
+
+impl TryFrom<CborInteger> for i128 {
+    type Error = CborError;
+
+    fn try_from(value: CborInteger) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<CborInteger> for u128 {
+    type Error = CborError;
+
+    fn try_from(value: CborInteger) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
 impl From<BigInt> for CborInteger {
     fn from(value: BigInt) -> Self {
         Self(IntegerRepr::Big(value))
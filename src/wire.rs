@@ -8,11 +8,15 @@ use crate::limits::DEFAULT_MAX_DEPTH;
 use crate::profile::{
     check_encoded_key_order, validate_bignum_bytes, validate_f64_bits, MAX_SAFE_INTEGER,
 };
+use crate::scalar::F64Bits;
 use crate::utf8;
-use crate::{CborError, DecodeLimits, ErrorCode};
+use crate::{CborError, DecodeLimits, ErrorCode, Utf8Mode};
 
 pub trait DecodeError: Sized {
     fn new(code: ErrorCode, offset: usize) -> Self;
+
+    /// Record where the previous map key starts for an ordering error.
+    fn with_prev_key(self, offset: usize) -> Self;
 }
 
 impl DecodeError for CborError {
@@ -20,6 +24,11 @@ impl DecodeError for CborError {
     fn new(code: ErrorCode, offset: usize) -> Self {
         Self::new(code, offset)
     }
+
+    #[inline]
+    fn with_prev_key(self, offset: usize) -> Self {
+        self.with_detail(offset)
+    }
 }
 
 pub struct Cursor<'a, E: DecodeError> {
@@ -203,21 +212,66 @@ pub fn read_len_trusted(
     read_len_at::<false, CborError>(data, pos, ai, off)
 }
 
+#[inline]
+fn check_key_len<E: DecodeError>(
+    limits: Option<&DecodeLimits>,
+    len: usize,
+    off: usize,
+) -> Result<(), E> {
+    if let Some(limits) = limits {
+        if len > limits.max_key_len {
+            return Err(E::new(ErrorCode::KeyLenLimitExceeded, off));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a map key, enforcing `max_key_len` before the general text limits.
+///
+/// Trusted input skips the key cap: it was validated under limits this call cannot see.
+#[inline]
+pub fn parse_text_key<'a, const CHECKED: bool, E: DecodeError>(
+    cursor: &mut Cursor<'a, E>,
+    limits: Option<&DecodeLimits>,
+    utf8_mode: Utf8Mode,
+    off: usize,
+    ai: u8,
+) -> Result<&'a str, E> {
+    let len = read_len::<CHECKED, E>(cursor, ai, off)?;
+    if CHECKED {
+        check_key_len::<E>(limits, len, off)?;
+    }
+    parse_text_body::<CHECKED, E>(cursor, limits, utf8_mode, off, len)
+}
+
 #[inline]
 pub fn parse_text_from_header<'a, const CHECKED: bool, E: DecodeError>(
     cursor: &mut Cursor<'a, E>,
     limits: Option<&DecodeLimits>,
+    utf8_mode: Utf8Mode,
     off: usize,
     ai: u8,
 ) -> Result<&'a str, E> {
     let len = read_len::<CHECKED, E>(cursor, ai, off)?;
+    parse_text_body::<CHECKED, E>(cursor, limits, utf8_mode, off, len)
+}
+
+#[inline]
+fn parse_text_body<'a, const CHECKED: bool, E: DecodeError>(
+    cursor: &mut Cursor<'a, E>,
+    limits: Option<&DecodeLimits>,
+    utf8_mode: Utf8Mode,
+    off: usize,
+    len: usize,
+) -> Result<&'a str, E> {
     if let Some(limits) = limits {
         if len > limits.max_text_len {
             return Err(E::new(ErrorCode::TextLenLimitExceeded, off));
         }
     }
     let bytes = cursor.read_exact(len)?;
-    let s = if CHECKED {
+    let strict = CHECKED || utf8_mode == Utf8Mode::Strict;
+    let s = if strict {
         utf8::validate(bytes).map_err(|()| E::new(ErrorCode::Utf8Invalid, off))?
     } else {
         utf8::trusted(bytes).map_err(|()| E::new(ErrorCode::Utf8Invalid, off))?
@@ -238,7 +292,14 @@ pub fn parse_bignum<'a, const CHECKED: bool, E: DecodeError>(
         3 => true,
         _ => return Err(E::new(ErrorCode::ForbiddenOrMalformedTag, off)),
     };
+    parse_bignum_content::<CHECKED, E>(cursor, limits, negative)
+}
 
+fn parse_bignum_content<'a, const CHECKED: bool, E: DecodeError>(
+    cursor: &mut Cursor<'a, E>,
+    limits: Option<&DecodeLimits>,
+    negative: bool,
+) -> Result<(bool, &'a [u8]), E> {
     let m_off = cursor.position();
     let first = cursor.read_u8()?;
     let m_major = first >> 5;
@@ -273,7 +334,7 @@ pub fn check_map_key_order<E: DecodeError>(
         let prev = &data[ps..pe];
         let curr = &data[key_start..key_end];
         if let Err(code) = check_encoded_key_order(prev, curr) {
-            return Err(E::new(code, key_start));
+            return Err(E::new(code, key_start).with_prev_key(ps));
         }
     }
     *prev_key_range = Some((key_start, key_end));
@@ -458,8 +519,7 @@ fn try_reserve_vec<T, E: DecodeError>(
         return Ok(());
     }
     let elem_size = core::mem::size_of::<T>();
-    if elem_size != 0 {
-        let max = (isize::MAX as usize) / elem_size;
+    if let Some(max) = (isize::MAX as usize).checked_div(elem_size) {
         if needed > max {
             return Err(E::new(ErrorCode::LengthOverflow, offset));
         }
@@ -646,6 +706,11 @@ fn consume_value<E: DecodeError>(frame: &mut Frame, off: usize) -> Result<(), E>
     Ok(())
 }
 
+#[inline]
+fn full_int_range(limits: Option<&DecodeLimits>) -> bool {
+    limits.is_some_and(|l| l.allow_full_int_range)
+}
+
 #[allow(clippy::too_many_lines)]
 #[inline]
 fn skip_primitive<const CHECKED: bool, E: DecodeError>(
@@ -660,14 +725,14 @@ fn skip_primitive<const CHECKED: bool, E: DecodeError>(
     match major {
         0 => {
             let v = read_uint_arg::<CHECKED, E>(cursor, ai, off)?;
-            if CHECKED && v > MAX_SAFE_INTEGER {
+            if CHECKED && v > MAX_SAFE_INTEGER && !full_int_range(limits) {
                 return Err(E::new(ErrorCode::IntegerOutsideSafeRange, off));
             }
             Ok(None)
         }
         1 => {
             let n = read_uint_arg::<CHECKED, E>(cursor, ai, off)?;
-            if CHECKED && n >= MAX_SAFE_INTEGER {
+            if CHECKED && n >= MAX_SAFE_INTEGER && !full_int_range(limits) {
                 return Err(E::new(ErrorCode::IntegerOutsideSafeRange, off));
             }
             Ok(None)
@@ -733,8 +798,20 @@ fn skip_primitive<const CHECKED: bool, E: DecodeError>(
             }
         }
         6 => {
-            let _ = parse_bignum::<CHECKED, E>(cursor, limits, off, ai)?;
-            Ok(None)
+            let tag = read_uint_arg::<CHECKED, E>(cursor, ai, off)?;
+            match tag {
+                2 | 3 => {
+                    let _ = parse_bignum_content::<CHECKED, E>(cursor, limits, tag == 3)?;
+                    Ok(None)
+                }
+                // Trusted input has already been validated, so any other tag there was
+                // admitted by an allowlist.
+                _ if !CHECKED || limits.is_some_and(|l| l.extra_allowed_tags.contains(&tag)) => {
+                    ensure_depth::<E>(limits, next_depth, off)?;
+                    Ok(Some(Frame::Array { remaining: 1 }))
+                }
+                _ => Err(E::new(ErrorCode::ForbiddenOrMalformedTag, off)),
+            }
         }
         7 => {
             match ai {
@@ -811,7 +888,8 @@ fn skip_one_value_inner<const CHECKED: bool, E: DecodeError, S: StackOps>(
                 return Err(E::new(ErrorCode::MapKeyMustBeText, key_start));
             }
             if CHECKED {
-                let _ = parse_text_from_header::<CHECKED, E>(cursor, limits, key_start, ai)?;
+                let _ =
+                    parse_text_key::<CHECKED, E>(cursor, limits, Utf8Mode::Strict, key_start, ai)?;
             } else {
                 let len = read_len::<CHECKED, E>(cursor, ai, key_start)?;
                 if let Some(limits) = limits {
@@ -854,6 +932,10 @@ fn skip_one_value_inner<const CHECKED: bool, E: DecodeError, S: StackOps>(
     }
 }
 
+/// Skip one complete data item.
+///
+/// Nesting is tracked on an explicit frame stack rather than by recursion, so native stack use is
+/// constant and `max_depth` is the only bound on how deep input may nest.
 #[allow(clippy::too_many_lines)]
 pub fn skip_one_value<const CHECKED: bool, E: DecodeError>(
     cursor: &mut Cursor<'_, E>,
@@ -884,3 +966,92 @@ pub fn skip_one_value_with_scratch<const CHECKED: bool, E: DecodeError>(
         &mut scratch.stack,
     )
 }
+
+// Canonical header and scalar writers shared by `Encoder` and `ArrayEncoderFixed`.
+
+pub trait Sink {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), CborError>;
+
+    fn write_u8(&mut self, byte: u8) -> Result<(), CborError> {
+        self.write(&[byte])
+    }
+
+    fn position(&self) -> usize;
+}
+
+fn err_at<S: Sink>(sink: &S, code: ErrorCode) -> CborError {
+    CborError::new(code, sink.position())
+}
+
+pub fn encode_int<S: Sink>(sink: &mut S, v: i64) -> Result<(), CborError> {
+    if v >= 0 {
+        let u = u64::try_from(v).map_err(|_| err_at(sink, ErrorCode::LengthOverflow))?;
+        encode_major_uint(sink, 0, u)
+    } else {
+        let n_i128 = -1_i128 - i128::from(v);
+        let n_u64 = u64::try_from(n_i128).map_err(|_| err_at(sink, ErrorCode::LengthOverflow))?;
+        encode_major_uint(sink, 1, n_u64)
+    }
+}
+
+pub fn encode_bytes<S: Sink>(sink: &mut S, bytes: &[u8]) -> Result<(), CborError> {
+    encode_major_len(sink, 2, bytes.len())?;
+    sink.write(bytes)
+}
+
+pub fn encode_text<S: Sink>(sink: &mut S, s: &str) -> Result<(), CborError> {
+    // `str` guarantees valid UTF-8.
+    let b = s.as_bytes();
+    encode_major_len(sink, 3, b.len())?;
+    sink.write(b)
+}
+
+pub fn encode_float64<S: Sink>(sink: &mut S, bits: F64Bits) -> Result<(), CborError> {
+    let raw = bits.bits();
+    let mut buf = [0u8; 9];
+    buf[0] = 0xfb;
+    buf[1..9].copy_from_slice(&raw.to_be_bytes());
+    sink.write(&buf)
+}
+
+/// Returns the size of a canonical header whose argument is `len`.
+#[cfg(feature = "alloc")]
+pub const fn head_len(len: usize) -> usize {
+    match len as u64 {
+        0..=23 => 1,
+        24..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+pub fn encode_major_len<S: Sink>(sink: &mut S, major: u8, len: usize) -> Result<(), CborError> {
+    let len_u64 = u64::try_from(len).map_err(|_| err_at(sink, ErrorCode::LengthOverflow))?;
+    encode_major_uint(sink, major, len_u64)
+}
+
+pub fn encode_major_uint<S: Sink>(sink: &mut S, major: u8, value: u64) -> Result<(), CborError> {
+    debug_assert!(major <= 7);
+    if value < 24 {
+        let v = u8::try_from(value).unwrap();
+        return sink.write_u8((major << 5) | v);
+    }
+    if value <= 0xff {
+        let v = u8::try_from(value).unwrap();
+        sink.write_u8((major << 5) | 0x18)?;
+        return sink.write_u8(v);
+    }
+    if value <= 0xffff {
+        let v = u16::try_from(value).unwrap();
+        sink.write_u8((major << 5) | 0x19)?;
+        return sink.write(&v.to_be_bytes());
+    }
+    if value <= 0xffff_ffff {
+        let v = u32::try_from(value).unwrap();
+        sink.write_u8((major << 5) | 0x1a)?;
+        return sink.write(&v.to_be_bytes());
+    }
+    sink.write_u8((major << 5) | 0x1b)?;
+    sink.write(&value.to_be_bytes())
+}
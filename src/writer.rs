@@ -0,0 +1,265 @@
+//! Canonical encoding straight into an `io::Write` sink.
+
+use alloc::string::String;
+use core::cmp::Ordering;
+use std::io::Write;
+
+use crate::alloc_util::try_reserve_exact_str;
+use crate::profile::{cmp_text_keys_canonical, validate_bignum_bytes};
+use crate::scalar::F64Bits;
+use crate::stream::{StreamCore, StreamEncoder, StreamSink};
+use crate::wire::{encode_bytes, encode_major_uint, encode_text, Sink};
+use crate::{CborError, ErrorCode};
+
+pub struct IoSink<W> {
+    w: W,
+    pos: usize,
+    poison: Option<CborError>,
+}
+
+impl<W: Write> Sink for IoSink<W> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), CborError> {
+        self.w
+            .write_all(bytes)
+            .map_err(|_| CborError::new(ErrorCode::IoError, self.pos))?;
+        self.pos += bytes.len();
+        Ok(())
+    }
+
+    fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<W: Write> StreamSink for IoSink<W> {
+    fn check(&self) -> Result<(), CborError> {
+        self.poison.map_or(Ok(()), Err)
+    }
+
+    fn recover(&mut self, start: usize, err: CborError) {
+        // Written bytes cannot be taken back, and a failed `write_all` may have written
+        // part of its buffer.
+        if self.pos != start || err.code() == ErrorCode::IoError {
+            self.poison.get_or_insert(err);
+        }
+    }
+}
+
+/// Canonical encoder that writes headers and data straight to an [`std::io::Write`].
+///
+/// The method set mirrors the core of [`crate::Encoder`], but nothing is buffered except the
+/// previous key of each open map, which is needed for the canonical key-order check. Because
+/// bytes are gone once written there is no `truncate`, `rollback`, or `map_sorted`: keys must
+/// be inserted in canonical order, and any error raised after bytes reached the writer
+/// poisons the encoder so the partial output cannot be mistaken for a finished item.
+/// Wrap unbuffered writers in [`std::io::BufWriter`]; every header is a separate write.
+pub struct WriterEncoder<W: Write> {
+    core: StreamCore<IoSink<W>>,
+}
+
+impl<W: Write> StreamEncoder for WriterEncoder<W> {
+    type Sink = IoSink<W>;
+
+    fn core(&mut self) -> &mut StreamCore<IoSink<W>> {
+        &mut self.core
+    }
+}
+
+impl<W: Write> WriterEncoder<W> {
+    /// Create an encoder that writes to `w`.
+    #[must_use]
+    pub const fn new(w: W) -> Self {
+        Self {
+            core: StreamCore::new(IoSink {
+                w,
+                pos: 0,
+                poison: None,
+            }),
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.core.sink.pos
+    }
+
+    /// Flush the writer and return it once exactly one complete item has been written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that poisoned the encoder, `UnexpectedEof` if no complete value has
+    /// been written, or `IoError` if flushing fails.
+    pub fn finish(self) -> Result<W, CborError> {
+        let root_done = self.core.root_done();
+        let mut sink = self.core.sink;
+        sink.check()?;
+        if !root_done {
+            return Err(CborError::new(ErrorCode::UnexpectedEof, sink.pos));
+        }
+        sink.w
+            .flush()
+            .map_err(|_| CborError::new(ErrorCode::IoError, sink.pos))?;
+        Ok(sink.w)
+    }
+
+    /// Encode CBOR null.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the write fails.
+    pub fn null(&mut self) -> Result<(), CborError> {
+        self.write_null()
+    }
+
+    /// Encode a CBOR boolean.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the write fails.
+    pub fn bool(&mut self, v: bool) -> Result<(), CborError> {
+        self.write_bool(v)
+    }
+
+    /// Encode a safe-range integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IntegerOutsideSafeRange` if `v` is outside the safe range, or `IoError` if
+    /// the write fails.
+    pub fn int(&mut self, v: i64) -> Result<(), CborError> {
+        self.write_int(v)
+    }
+
+    /// Encode a CBOR bignum (tag 2/3 + byte string magnitude).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the magnitude is not canonical, or `IoError` if the write fails.
+    pub fn bignum(&mut self, negative: bool, magnitude: &[u8]) -> Result<(), CborError> {
+        self.write_value(|e| {
+            validate_bignum_bytes(negative, magnitude).map_err(|code| e.err_here(code))?;
+            encode_major_uint(&mut e.core.sink, 6, if negative { 3 } else { 2 })?;
+            encode_bytes(&mut e.core.sink, magnitude)
+        })
+    }
+
+    /// Encode a byte string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the write fails.
+    pub fn bytes(&mut self, b: &[u8]) -> Result<(), CborError> {
+        self.write_bytes(b)
+    }
+
+    /// Encode a text string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the write fails.
+    pub fn text(&mut self, s: &str) -> Result<(), CborError> {
+        self.write_text(s)
+    }
+
+    /// Encode a float64 value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the write fails.
+    pub fn float(&mut self, bits: F64Bits) -> Result<(), CborError> {
+        self.write_float(bits)
+    }
+
+    /// Encode a definite-length array whose items are written by `f`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArrayLenMismatch` if `f` writes a different number of items, or the first
+    /// error raised while writing them.
+    pub fn array<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), CborError>,
+    {
+        self.write_array(len, f)
+    }
+
+    /// Encode a definite-length map whose entries are written by `f`.
+    ///
+    /// Entries must be inserted in canonical key order; there is no buffer to sort into.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MapLenMismatch` if `f` writes a different number of entries, or the first
+    /// error raised while writing them.
+    pub fn map<F>(&mut self, len: usize, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut MapWriterEncoder<'_, W>) -> Result<(), CborError>,
+    {
+        self.write_map(len, |e| {
+            let mut m = MapWriterEncoder {
+                enc: e,
+                remaining: len,
+                prev_key: String::new(),
+                has_prev: false,
+            };
+            f(&mut m)?;
+            Ok(m.remaining)
+        })
+    }
+}
+
+/// Builder for writing map entries through a [`WriterEncoder`].
+pub struct MapWriterEncoder<'a, W: Write> {
+    enc: &'a mut WriterEncoder<W>,
+    remaining: usize,
+    /// Rolling copy of the last key written, for the canonical order check.
+    prev_key: String,
+    has_prev: bool,
+}
+
+impl<W: Write> MapWriterEncoder<'_, W> {
+    /// Insert a map entry whose value is written by `f`. Keys must be in canonical order;
+    /// duplicates are rejected before anything is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NonCanonicalMapOrder`/`DuplicateMapKey` for misordered keys,
+    /// `MapLenMismatch` if the map is already full or `f` does not write exactly one value,
+    /// or the first error raised while writing.
+    pub fn entry<F>(&mut self, key: &str, f: F) -> Result<(), CborError>
+    where
+        F: FnOnce(&mut WriterEncoder<W>) -> Result<(), CborError>,
+    {
+        let enc = &mut *self.enc;
+        enc.core.sink.check()?;
+        let start = enc.core.sink.pos;
+        if self.remaining == 0 {
+            return Err(CborError::new(ErrorCode::MapLenMismatch, start));
+        }
+        if self.has_prev {
+            match cmp_text_keys_canonical(&self.prev_key, key) {
+                Ordering::Less => {}
+                Ordering::Equal => return Err(CborError::new(ErrorCode::DuplicateMapKey, start)),
+                Ordering::Greater => {
+                    return Err(CborError::new(ErrorCode::NonCanonicalMapOrder, start))
+                }
+            }
+        }
+        // Reserve before writing so a failed allocation cannot strand a written entry.
+        let additional = key.len().saturating_sub(self.prev_key.len());
+        try_reserve_exact_str(&mut self.prev_key, additional, start)?;
+
+        let res = encode_text(&mut enc.core.sink, key).and_then(|()| enc.write_entry_value(f));
+        if let Err(err) = res {
+            enc.core.sink.recover(start, err);
+            return Err(err);
+        }
+
+        self.prev_key.clear();
+        self.prev_key.push_str(key);
+        self.has_prev = true;
+        self.remaining -= 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use sacp_cbor::{validate_canonical, CanonicalCbor, CborKind, DecodeLimits};
+
+#[test]
+fn generated_items_are_canonical_and_cover_containers() {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut buf = vec![0u8; 512];
+    let (mut arrays, mut maps) = (0, 0);
+    for _ in 0..500 {
+        for byte in &mut buf {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            *byte = (state >> 56) as u8;
+        }
+        let mut u = Unstructured::new(&buf);
+        let item = CanonicalCbor::arbitrary(&mut u).unwrap();
+        let checked = validate_canonical(item.as_bytes(), DecodeLimits::unlimited()).unwrap();
+        assert_eq!(checked.as_bytes(), item.as_bytes());
+        match item.root().kind().unwrap() {
+            CborKind::Array => arrays += 1,
+            CborKind::Map => maps += 1,
+            _ => {}
+        }
+    }
+    assert!(arrays > 0 && maps > 0);
+}
+
+#[test]
+fn empty_input_still_yields_a_valid_item() {
+    let mut u = Unstructured::new(&[]);
+    let item = CanonicalCbor::arbitrary(&mut u).unwrap();
+    assert_eq!(item.as_bytes(), &[0xf6]);
+}
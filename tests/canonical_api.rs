@@ -1,6 +1,10 @@
 #![cfg(feature = "alloc")]
 
-use sacp_cbor::{validate_canonical, CanonicalCbor, DecodeLimits, ErrorCode};
+use sacp_cbor::{
+    validate_canonical, validate_relaxed, CanonicalCbor, CborInteger, DecodeLimits, ErrorCode,
+    Interner,
+};
+use std::sync::Arc;
 
 #[test]
 fn canonical_from_slice_accepts_and_to_owned_roundtrips() {
@@ -13,13 +17,32 @@ fn canonical_from_slice_accepts_and_to_owned_roundtrips() {
     let borrowed = validate_canonical(&bytes, limits).unwrap();
     let owned2 = borrowed.to_owned().unwrap();
     assert_eq!(owned2.as_bytes(), bytes);
+
+    let copy = owned.try_clone().unwrap();
+    assert_eq!(copy, owned);
+    assert_ne!(copy.as_bytes().as_ptr(), owned.as_bytes().as_ptr());
+}
+
+#[test]
+fn heap_size_counts_owned_buffers() {
+    let empty_map = CanonicalCbor::from_vec(vec![0xa0], DecodeLimits::for_bytes(1)).unwrap();
+    assert_eq!(empty_map.heap_size(), 1);
+
+    let mut spare = Vec::with_capacity(64);
+    spare.extend_from_slice(&[0x82, 0x01, 0x02]);
+    let array = CanonicalCbor::from_vec(spare, DecodeLimits::for_bytes(3)).unwrap();
+    assert_eq!(array.heap_size(), 64);
+
+    assert_eq!(CborInteger::safe(7).unwrap().heap_size(), 0);
+    let big = CborInteger::big(false, vec![0x20, 0, 0, 0, 0, 0, 0]).unwrap();
+    assert_eq!(big.heap_size(), 7);
 }
 
 #[test]
 fn canonical_from_slice_rejects_invalid() {
     let bytes = [0x18];
     let err = CanonicalCbor::from_slice(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::UnexpectedEof);
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
 }
 
 #[cfg(feature = "sha2")]
@@ -41,3 +64,128 @@ fn canonical_sha256_matches_manual_hash() {
 
     assert_eq!(h1, h2);
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn from_reader_stops_after_one_item() {
+    // [1, {"a": h'00'}] followed by a second item
+    let stream = [0x82, 0x01, 0xa1, 0x61, b'a', 0x41, 0x00, 0xf5];
+    let mut reader = &stream[..];
+    let first = CanonicalCbor::from_reader(&mut reader, DecodeLimits::for_bytes(64)).unwrap();
+    assert_eq!(first.as_bytes(), &stream[..7]);
+    assert_eq!(reader, &[0xf5]);
+    let second = CanonicalCbor::from_reader(&mut reader, DecodeLimits::for_bytes(64)).unwrap();
+    assert_eq!(second.as_bytes(), &[0xf5]);
+
+    let err = CanonicalCbor::from_reader(&stream[..4], DecodeLimits::for_bytes(64)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+
+    let err = CanonicalCbor::from_reader(&stream[..], DecodeLimits::for_bytes(4)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MessageLenLimitExceeded);
+
+    // A huge declared length is rejected before reading or allocating.
+    let huge = [0x5b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    let err = CanonicalCbor::from_reader(&huge[..], DecodeLimits::for_bytes(64)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MessageLenLimitExceeded);
+}
+
+fn check(bytes: &[u8]) -> Result<sacp_cbor::CanonicalCborRef<'_>, sacp_cbor::CborError> {
+    validate_canonical(bytes, DecodeLimits::for_bytes(bytes.len()))
+}
+
+#[test]
+fn interner_shares_equal_payloads() {
+    let a = [0xa1, 0x61, 0x61, 0x01];
+    let b = [0x82, 0x01, 0x02];
+    let a2 = a.to_vec();
+
+    let mut pool = Interner::new();
+    let first = pool.intern(check(&a).unwrap()).unwrap();
+    let other = pool.intern(check(&b).unwrap()).unwrap();
+    let again = pool.intern(check(&a2).unwrap()).unwrap();
+
+    assert!(Arc::ptr_eq(&first, &again));
+    assert!(!Arc::ptr_eq(&first, &other));
+    assert_eq!(again.as_bytes(), a);
+    assert_eq!(pool.len(), 2);
+
+    pool.clear();
+    assert!(pool.is_empty());
+    let fresh = pool.intern(check(&a).unwrap()).unwrap();
+    assert!(!Arc::ptr_eq(&first, &fresh));
+    assert_eq!(first.as_bytes(), fresh.as_bytes());
+}
+
+#[test]
+fn is_canonical_rechecks_against_limits() {
+    let bytes = [0x81, 0x81, 0x01];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    assert!(canon.is_canonical(DecodeLimits::for_bytes(bytes.len())));
+    canon.debug_assert_canonical();
+
+    let mut shallow = DecodeLimits::for_bytes(bytes.len());
+    shallow.max_depth = 1;
+    assert!(!canon.is_canonical(shallow));
+}
+
+#[test]
+fn relaxed_documents_stay_out_of_canonical_refs() {
+    const TAGS: &[u64] = &[1];
+    let mut relaxed = DecodeLimits::unlimited();
+    relaxed.allow_full_int_range = true;
+    relaxed.extra_allowed_tags = TAGS;
+
+    let wide = [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    let tagged = [0x82, 0xc1, 0x01, 0xf5];
+    let mut long_key = vec![0xa1, 0x79, 0x01, 0x2c];
+    long_key.extend_from_slice(&[b'k'; 300]);
+    long_key.push(0xf6);
+    let mut deep = vec![0x81; 300];
+    deep.push(0x01);
+
+    for (bytes, code) in [
+        (&wide[..], ErrorCode::IntegerOutsideSafeRange),
+        (&tagged, ErrorCode::ForbiddenOrMalformedTag),
+    ] {
+        let err = validate_canonical(bytes, relaxed).unwrap_err();
+        assert_eq!(err.code(), code);
+        let doc = validate_relaxed(bytes, relaxed).unwrap();
+        assert_eq!(doc.as_bytes(), bytes);
+    }
+    let doc = validate_relaxed(&wide, relaxed).unwrap();
+    assert_eq!(doc.root().decode_as::<u64>().unwrap(), u64::MAX);
+
+    // Limits beyond the strict defaults are not relaxations.
+    for bytes in [&long_key[..], &deep] {
+        let canon = validate_canonical(bytes, relaxed).unwrap();
+        canon.debug_assert_canonical();
+    }
+}
+
+#[cfg(all(feature = "unsafe", debug_assertions))]
+#[test]
+#[should_panic(expected = "does not hold canonical")]
+fn decode_canonical_asserts_in_debug_builds() {
+    // A non-minimal integer smuggled in through the unsafe constructor.
+    let bytes = [0x18, 0x01];
+    let canon = unsafe { sacp_cbor::CanonicalCborRef::from_canonical(&bytes) };
+    assert!(!canon.is_canonical(DecodeLimits::for_bytes(bytes.len())));
+    let _ = sacp_cbor::decode_canonical::<u64>(canon);
+}
+
+#[cfg(feature = "unsafe")]
+#[test]
+fn strict_utf8_mode_validates_trusted_text() {
+    use sacp_cbor::{CborDecode, DecodeOptions, Decoder, Utf8Mode};
+
+    // Invalid UTF-8 smuggled in through the unsafe constructor.
+    let bytes = [0x62, 0xc3, 0x28];
+    let canon = unsafe { sacp_cbor::CanonicalCborRef::from_canonical(&bytes) };
+    let limits = DecodeLimits::for_bytes(bytes.len());
+    assert_eq!(DecodeOptions::default().utf8_mode, Utf8Mode::TrustedSkip);
+    let options = DecodeOptions::new().with_utf8_mode(Utf8Mode::Strict);
+
+    let mut decoder = Decoder::new_trusted_with(canon, limits, options).unwrap();
+    let err = <&str>::decode(&mut decoder).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Utf8Invalid);
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "alloc")]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use sacp_cbor::{
+    canonicalize, canonicalize_with, cbor_bytes, validate_canonical, CanonicalizeOptions,
+    DecodeLimits, Encoder, ErrorCode, FloatPolicy,
+};
+
+fn canon(input: &[u8]) -> Vec<u8> {
+    let limits = DecodeLimits::for_bytes(input.len());
+    canonicalize(input, limits).unwrap().as_bytes().to_vec()
+}
+
+fn canon_err(input: &[u8]) -> (ErrorCode, usize) {
+    let limits = DecodeLimits::for_bytes(input.len());
+    let err = canonicalize(input, limits).unwrap_err();
+    (err.code(), err.offset())
+}
+
+#[test]
+fn minimizes_integers_and_normalizes_bignums() {
+    // 1 encoded as a uint64, -1 as a uint16 argument.
+    assert_eq!(canon(&[0x1b, 0, 0, 0, 0, 0, 0, 0, 1]), [0x01]);
+    assert_eq!(canon(&[0x39, 0x00, 0x00]), [0x20]);
+
+    // 2^53 is outside the safe range and becomes a bignum.
+    let mut enc = Encoder::new();
+    enc.bignum(false, &[0x20, 0, 0, 0, 0, 0, 0]).unwrap();
+    assert_eq!(canon(&[0x1b, 0x00, 0x20, 0, 0, 0, 0, 0, 0]), enc.as_bytes());
+
+    // A padded bignum that fits the safe range collapses to an integer.
+    assert_eq!(canon(&[0xc3, 0x42, 0x00, 0x05]), [0x25]);
+}
+
+#[test]
+fn sorts_keys_and_collapses_indefinite_items() {
+    // {_ "bb": [_ 1, 2], "a": (_ "x", "y") }
+    let input = [
+        0xbf, 0x62, b'b', b'b', 0x9f, 0x01, 0x02, 0xff, 0x61, b'a', 0x7f, 0x61, b'x', 0x61, b'y',
+        0xff, 0xff,
+    ];
+    let expected = cbor_bytes!({ a: "xy", bb: [1, 2] }).unwrap();
+    assert_eq!(canon(&input), expected.as_bytes());
+
+    // Indefinite byte string with chunks.
+    let input = [0x5f, 0x41, 0x01, 0x42, 0x02, 0x03, 0xff];
+    assert_eq!(canon(&input), [0x43, 0x01, 0x02, 0x03]);
+}
+
+#[test]
+fn float_policy_normalizes_negative_zero() {
+    let input = [0xfb, 0x80, 0, 0, 0, 0, 0, 0, 0];
+    let limits = DecodeLimits::for_bytes(input.len());
+    assert_eq!(
+        canonicalize(&input, limits).unwrap_err().code(),
+        ErrorCode::NegativeZeroForbidden
+    );
+
+    let options = CanonicalizeOptions::new().with_float_policy(FloatPolicy::NormalizeNegativeZero);
+    let out = canonicalize_with(&input, limits, options).unwrap();
+    assert_eq!(out.as_bytes(), [0xfb, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let out = canonicalize_with(&[0xf9, 0x80, 0x00], limits, options).unwrap();
+    assert_eq!(out.as_bytes(), [0xfb, 0, 0, 0, 0, 0, 0, 0, 0]);
+    // NaN payloads are canonicalized under either policy.
+    let nan = [0xfb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 1];
+    let out = canonicalize_with(&nan, limits, options).unwrap();
+    assert_eq!(out.as_bytes(), [0xfb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        canonicalize(&nan, limits).unwrap().as_bytes(),
+        out.as_bytes()
+    );
+
+    // Validation stays strict regardless of the policy.
+    assert_eq!(
+        validate_canonical(&input, limits).unwrap_err().code(),
+        ErrorCode::NegativeZeroForbidden
+    );
+}
+
+#[test]
+fn widens_floats() {
+    // 1.5 as float16 and float32.
+    let expected = [0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0];
+    assert_eq!(canon(&[0xf9, 0x3e, 0x00]), expected);
+    assert_eq!(canon(&[0xfa, 0x3f, 0xc0, 0x00, 0x00]), expected);
+
+    // Smallest float16 subnormal and a float16 NaN payload.
+    assert_eq!(
+        canon(&[0xf9, 0x00, 0x01]),
+        cbor_bytes!(5.960_464_477_539_063e-8).unwrap().as_bytes()
+    );
+    assert_eq!(
+        canon(&[0xf9, 0x7e, 0x01]),
+        [0xfb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0]
+    );
+
+    assert_eq!(
+        canon_err(&[0xf9, 0x80, 0x00]),
+        (ErrorCode::NegativeZeroForbidden, 0)
+    );
+}
+
+#[test]
+fn rejects_values_outside_the_data_model() {
+    assert_eq!(
+        canon_err(&[0x82, 0x01, 0xf7]),
+        (ErrorCode::UnsupportedSimpleValue, 2)
+    );
+    assert_eq!(
+        canon_err(&[0xc1, 0x01]),
+        (ErrorCode::ForbiddenOrMalformedTag, 0)
+    );
+    assert_eq!(
+        canon_err(&[0xa1, 0x01, 0x01]),
+        (ErrorCode::MapKeyMustBeText, 1)
+    );
+    assert_eq!(
+        canon_err(&[0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02]),
+        (ErrorCode::DuplicateMapKey, 4)
+    );
+    assert_eq!(
+        canon_err(&[0x7f, 0x41, 0x00, 0xff]),
+        (ErrorCode::ExpectedText, 1)
+    );
+    assert_eq!(
+        canon_err(&[0x5f, 0x5f, 0xff, 0xff]),
+        (ErrorCode::IndefiniteLengthForbidden, 1)
+    );
+    assert_eq!(canon_err(&[0xff]), (ErrorCode::ReservedAdditionalInfo, 0));
+    assert_eq!(canon_err(&[0x01, 0x02]), (ErrorCode::TrailingBytes, 1));
+}
+
+#[test]
+fn enforces_limits() {
+    let input = [0x9f, 0x9f, 0x9f, 0xff, 0xff, 0xff];
+    let mut limits = DecodeLimits::for_bytes(input.len());
+    limits.max_depth = 2;
+    let err = canonicalize(&input, limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DepthLimitExceeded);
+    assert_eq!(err.offset(), 2);
+
+    let limits = DecodeLimits::for_bytes(1);
+    let err = canonicalize(&[0x18, 0x18], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MessageLenLimitExceeded);
+}
@@ -51,13 +51,13 @@ fn cbor_bytes_map_order_sorted() {
 #[test]
 fn cbor_bytes_duplicate_keys_error() {
     let err = cbor_bytes!({ a: 1, a: 2 }).unwrap_err();
-    assert_eq!(err.code, ErrorCode::DuplicateMapKey);
+    assert_eq!(err.code(), ErrorCode::DuplicateMapKey);
 }
 
 #[test]
 fn cbor_bytes_negative_zero_rejected() {
     let err = cbor_bytes!(-0.0f64).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NegativeZeroForbidden);
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
 }
 
 #[test]
@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
 use sacp_cbor::{
-    decode, decode_canonical, encode_to_canonical, encode_to_vec, BigInt, DecodeLimits, ErrorCode,
+    decode, decode_canonical, encode_to_canonical, encode_to_vec, encodes_to, validate_canonical,
+    validate_relaxed, BigInt, CborEncode, CborError, CborInteger, CborIntegerRef, CborKind,
+    DecodeLimits, Decoder, Encoder, ErrorCode, MAX_SAFE_INTEGER_I64, MIN_SAFE_INTEGER,
 };
 
 #[test]
@@ -8,13 +12,133 @@ fn empty_array_counts_depth() {
     let mut limits = DecodeLimits::for_bytes(bytes.len());
     limits.max_depth = 0;
     let err = decode::<Vec<bool>>(&bytes, limits).unwrap_err();
-    assert_eq!(err.code, ErrorCode::DepthLimitExceeded);
+    assert_eq!(err.code(), ErrorCode::DepthLimitExceeded);
 }
 
 #[test]
 fn u64_outside_safe_range_errors() {
     let err = encode_to_vec(&u64::MAX).unwrap_err();
-    assert_eq!(err.code, ErrorCode::IntegerOutsideSafeRange);
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+}
+
+#[test]
+fn full_int_range_is_opt_in() {
+    let mut enc = Encoder::new();
+    enc.int_exact(i128::from(u64::MAX)).unwrap();
+    let big = enc.into_vec();
+    assert_eq!(big, [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+    let mut enc = Encoder::new();
+    enc.int_exact(i128::from(i64::MIN)).unwrap();
+    let min = enc.into_vec();
+    assert_eq!(min, [0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+    let err = Encoder::new().int_exact(1i128 << 64).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+
+    let strict = DecodeLimits::for_bytes(16);
+    let err = decode::<u64>(&big, strict).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+    assert!(validate_canonical(&big, strict).is_err());
+
+    let mut relaxed = strict;
+    relaxed.allow_full_int_range = true;
+    assert_eq!(decode::<u64>(&big, relaxed).unwrap(), u64::MAX);
+    assert_eq!(decode::<i128>(&big, relaxed).unwrap(), i128::from(u64::MAX));
+    assert_eq!(decode::<i64>(&min, relaxed).unwrap(), i64::MIN);
+    assert_eq!(
+        decode::<i64>(&big, relaxed).unwrap_err().code(),
+        ErrorCode::ExpectedInteger
+    );
+    let root = validate_relaxed(&big, relaxed).unwrap().root();
+    assert_eq!(
+        root.integer().unwrap(),
+        CborIntegerRef::Wide(i128::from(u64::MAX))
+    );
+    assert_eq!(root.integer().unwrap().as_i64(), None);
+    let neg = [0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    let root = validate_relaxed(&neg, relaxed).unwrap().root();
+    assert_eq!(
+        root.integer().unwrap().as_i128(),
+        Some(-1 - i128::from(u64::MAX))
+    );
+    let root = validate_relaxed(&min, relaxed).unwrap().root();
+    assert_eq!(
+        root.integer().unwrap(),
+        CborIntegerRef::Wide(i128::from(i64::MIN))
+    );
+    let safe = validate_relaxed(&[0x01], relaxed).unwrap().root();
+    assert_eq!(safe.integer().unwrap(), CborIntegerRef::Safe(1));
+
+    // Only `validate_relaxed` honors the relaxation; canonical validation stays strict.
+    let err = validate_canonical(&big, relaxed).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+
+    // The encoder never certifies out-of-profile integers as canonical.
+    let mut enc = Encoder::new();
+    enc.int_exact(i128::from(u64::MAX)).unwrap();
+    let err = enc.into_canonical().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+    let mut enc = Encoder::new();
+    enc.array(2, |a| {
+        a.int(1)?;
+        a.int(2)
+    })
+    .unwrap();
+    assert!(enc.into_canonical().is_ok());
+    let mut enc = Encoder::new();
+    let cp = enc.checkpoint();
+    enc.int_exact(-(1i128 << 60)).unwrap();
+    enc.rollback(cp);
+    enc.int_exact(MAX_SAFE_INTEGER_I64.into()).unwrap();
+    assert!(enc.into_canonical().is_ok());
+
+    // A failed container discards the integer along with its bytes.
+    let mut enc = Encoder::new();
+    let err = enc
+        .map(1, |m| {
+            m.entry("a", |e| e.int_exact(i128::from(u64::MAX)))?;
+            m.entry("b", |e| e.int(2))
+        })
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MapLenMismatch);
+    enc.int(1).unwrap();
+    assert_eq!(enc.into_canonical().unwrap().as_bytes(), [0x01]);
+}
+
+#[test]
+fn extra_tags_are_opt_in() {
+    // { "t": 1(1700000000) }
+    let bytes = [0xa1, 0x61, b't', 0xc1, 0x1a, 0x65, 0x53, 0xf1, 0x00];
+    let strict = DecodeLimits::for_bytes(16);
+    let err = validate_canonical(&bytes, strict).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ForbiddenOrMalformedTag);
+    assert_eq!(err.offset(), 3);
+
+    let mut relaxed = strict;
+    relaxed.extra_allowed_tags = &[1];
+    let err = validate_canonical(&bytes, relaxed).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ForbiddenOrMalformedTag);
+    let canon = validate_relaxed(&bytes, relaxed).unwrap();
+    let value = canon.root().get_key("t").unwrap().unwrap();
+    assert_eq!(value.kind().unwrap(), CborKind::Tag);
+    let (tag, content) = value.tag().unwrap().unwrap();
+    assert_eq!(tag, 1);
+    assert_eq!(content.as_bytes(), &bytes[4..]);
+    assert_eq!(content.integer().unwrap().as_i64(), Some(1_700_000_000));
+    assert!(canon.root().tag().unwrap().is_none());
+
+    // Tagged content is validated and counts toward depth.
+    let bad = [0xc1, 0x1b, 0, 0, 0, 0, 0, 0, 0, 1];
+    let err = validate_relaxed(&bad, relaxed).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
+    let mut shallow = relaxed;
+    shallow.max_depth = 0;
+    let err = validate_relaxed(&bytes[3..], shallow).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DepthLimitExceeded);
+    let other = [0xc0, 0x60];
+    let err = validate_relaxed(&other, relaxed).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ForbiddenOrMalformedTag);
 }
 
 #[test]
@@ -24,3 +148,374 @@ fn bigint_roundtrip() {
     let decoded: BigInt = decode_canonical(canon.as_ref()).unwrap();
     assert_eq!(decoded, big);
 }
+
+#[test]
+fn cbor_integer_numeric_order_and_add() {
+    let neg_big = CborInteger::big(true, vec![0x20, 0, 0, 0, 0, 0, 0]).unwrap();
+    let min = CborInteger::safe(MIN_SAFE_INTEGER).unwrap();
+    let one = CborInteger::safe(1).unwrap();
+    let max = CborInteger::safe(MAX_SAFE_INTEGER_I64).unwrap();
+    let pos_big = CborInteger::big(false, vec![0x20, 0, 0, 0, 0, 0, 0]).unwrap();
+    let huge = CborInteger::big(false, vec![0x01; 9]).unwrap();
+    let mut sorted = vec![
+        huge.clone(),
+        one.clone(),
+        pos_big.clone(),
+        neg_big.clone(),
+        max.clone(),
+        min.clone(),
+    ];
+    sorted.sort();
+    assert_eq!(
+        sorted,
+        [
+            neg_big.clone(),
+            min,
+            one.clone(),
+            max.clone(),
+            pos_big.clone(),
+            huge
+        ]
+    );
+    assert!(CborInteger::big(true, vec![0x01; 9]).unwrap() < neg_big);
+
+    // Overflow out of the safe range promotes to a bignum and back.
+    let sum = max.checked_add(&one).unwrap();
+    assert_eq!(sum, pos_big);
+    assert_eq!(i128::try_from(&sum).unwrap(), 1i128 << 53);
+    assert_eq!(sum.checked_add(&CborInteger::safe(-1).unwrap()), Some(max));
+    assert_eq!(
+        i128::try_from(neg_big.checked_add(&neg_big).unwrap()).unwrap(),
+        -2 - (1i128 << 54)
+    );
+
+    let top = CborInteger::big(false, vec![0xff; 16]).unwrap();
+    assert_eq!(u128::try_from(&top).unwrap(), u128::MAX);
+    assert_eq!(
+        i128::try_from(&top).unwrap_err().code(),
+        ErrorCode::ExpectedInteger
+    );
+    assert!(u128::try_from(&neg_big).is_err());
+
+    // Operands beyond i128 still add exactly.
+    let mut carried = vec![0x01];
+    carried.extend([0x00; 16]);
+    assert_eq!(
+        top.checked_add(&one).unwrap(),
+        CborInteger::big(false, carried).unwrap()
+    );
+    let pos_huge = CborInteger::big(false, vec![0xff; 17]).unwrap();
+    let neg_huge = CborInteger::big(true, vec![0xff; 17]).unwrap();
+    // -(m + 1) + m == -1
+    assert_eq!(
+        neg_huge.checked_add(&pos_huge).unwrap(),
+        CborInteger::safe(-1).unwrap()
+    );
+    assert_eq!(
+        pos_huge.checked_add(&neg_huge).unwrap(),
+        CborInteger::safe(-1).unwrap()
+    );
+    // -(m + 1) + (2^128 - 1) stays a negative bignum.
+    let mut rest = vec![0xff];
+    rest.extend([0x00; 16]);
+    assert_eq!(
+        neg_huge.checked_add(&top).unwrap(),
+        CborInteger::big(true, rest).unwrap()
+    );
+    let mut doubled = vec![0x01];
+    doubled.extend([0xff; 17]);
+    assert_eq!(
+        neg_huge.checked_add(&neg_huge).unwrap(),
+        CborInteger::big(true, doubled).unwrap()
+    );
+    // Cancelling to a safe value demotes, including to zero.
+    let mut minus_m = vec![0xff; 16];
+    minus_m.push(0xfe);
+    assert_eq!(
+        pos_huge
+            .checked_add(&CborInteger::big(true, minus_m).unwrap())
+            .unwrap(),
+        CborInteger::safe(0).unwrap()
+    );
+}
+
+#[test]
+fn map_decoder_seek_key_stops_early() {
+    // { "a": 1, "c": [2], "dd": 3 }
+    let bytes = [
+        0xa3, 0x61, b'a', 0x01, 0x61, b'c', 0x81, 0x02, 0x62, b'd', b'd', 0x03,
+    ];
+    let limits = DecodeLimits::for_bytes(bytes.len());
+
+    let mut dec = Decoder::new_checked(&bytes, limits).unwrap();
+    let mut map = dec.map().unwrap();
+    assert!(map.seek_key("c").unwrap());
+    assert_eq!(map.next_value::<Vec<i64>>().unwrap(), vec![2]);
+    assert!(!map.seek_key("d").unwrap());
+    assert_eq!(map.next_entry::<i64>().unwrap(), Some(("dd", 3)));
+    assert!(!map.seek_key("zz").unwrap());
+    drop(map);
+
+    let mut dec = Decoder::new_checked(&bytes, limits).unwrap();
+    let mut map = dec.map().unwrap();
+    assert!(!map.seek_key("b").unwrap());
+    assert!(map.seek_key("dd").unwrap());
+    assert_eq!(map.next_value::<i64>().unwrap(), 3);
+}
+
+#[test]
+fn map_decoder_expect_keys() {
+    // { "a": 1, "c": [2], "dd": 3 }
+    let bytes = [
+        0xa3, 0x61, b'a', 0x01, 0x61, b'c', 0x81, 0x02, 0x62, b'd', b'd', 0x03,
+    ];
+    let limits = DecodeLimits::for_bytes(bytes.len());
+
+    let mut dec = Decoder::new_checked(&bytes, limits).unwrap();
+    let mut map = dec.map().unwrap();
+    map.expect_keys(&["dd", "a", "c"], false).unwrap();
+    map.expect_keys(&["a"], true).unwrap();
+    assert_eq!(map.next_entry::<i64>().unwrap(), Some(("a", 1)));
+    let err = map.expect_keys(&["a"], true).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MissingKey);
+    assert_eq!(err.context(), Some("a"));
+    map.skip_remaining().unwrap();
+    drop(map);
+
+    let mut dec = Decoder::new_checked(&bytes, limits).unwrap();
+    let mut map = dec.map().unwrap();
+    let err = map.expect_keys(&["a", "b", "c"], true).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MissingKey);
+    assert_eq!(err.context(), Some("b"));
+    let err = map.expect_keys(&["a", "c"], false).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownKey);
+    assert_eq!(err.offset(), 8);
+    assert_eq!(map.next_entry::<i64>().unwrap(), Some(("a", 1)));
+}
+
+#[test]
+fn decoder_exact_length_guards() {
+    // [{ "a": 1 }, [1, 2]]
+    let bytes = [0x82, 0xa1, 0x61, b'a', 0x01, 0x82, 0x01, 0x02];
+    let limits = DecodeLimits::for_bytes(bytes.len());
+
+    let mut dec = Decoder::new_checked(&bytes, limits).unwrap();
+    let mut outer = dec.array_exact(2).unwrap();
+    assert_eq!(
+        outer
+            .next_value::<BTreeMap<String, i64>>()
+            .unwrap()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(outer.next_value::<Vec<i64>>().unwrap().unwrap(), [1, 2]);
+    drop(outer);
+
+    let mut dec = Decoder::new_checked(&bytes, limits).unwrap();
+    let err = dec.array_exact(3).map(|_| ()).unwrap_err();
+    assert_eq!((err.code(), err.offset()), (ErrorCode::ArrayLenMismatch, 0));
+
+    let mut dec = Decoder::new_checked(&bytes[1..5], limits).unwrap();
+    let err = dec.map_exact(2).map(|_| ()).unwrap_err();
+    assert_eq!((err.code(), err.offset()), (ErrorCode::MapLenMismatch, 0));
+    let mut dec = Decoder::new_checked(&bytes[1..5], limits).unwrap();
+    let err = dec.array_exact(1).map(|_| ()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedArray);
+}
+
+#[test]
+fn map_decoder_seek_key_checks_order() {
+    // { "b": 1, "a": 2 } (non-canonical order)
+    let bytes = [0xa2, 0x61, b'b', 0x01, 0x61, b'a', 0x02];
+    let mut dec = Decoder::new_checked(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let mut map = dec.map().unwrap();
+    let err = map.seek_key("c").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalMapOrder);
+}
+
+#[test]
+fn decoder_parse_number_accepts_int_and_float() {
+    let limits = DecodeLimits::for_bytes(16);
+
+    let int = [0x03];
+    let mut dec = Decoder::new_checked(&int, limits).unwrap();
+    assert_eq!(dec.parse_number().unwrap(), 3.0);
+
+    let float = encode_to_vec(&3.0f64).unwrap();
+    let mut dec = Decoder::new_checked(&float, limits).unwrap();
+    assert_eq!(dec.parse_number().unwrap(), 3.0);
+
+    let neg = [0x38, 0x63];
+    let mut dec = Decoder::new_checked(&neg, limits).unwrap();
+    assert_eq!(dec.parse_number().unwrap(), -100.0);
+
+    // 2^53 as a bignum
+    let big = [0xc2, 0x47, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut dec = Decoder::new_checked(&big, limits).unwrap();
+    assert_eq!(dec.parse_number().unwrap(), 9_007_199_254_740_992.0);
+
+    let text = [0x61, b'x'];
+    let mut dec = Decoder::new_checked(&text, limits).unwrap();
+    assert_eq!(
+        dec.parse_number().unwrap_err().code(),
+        ErrorCode::ExpectedFloat
+    );
+}
+
+#[test]
+fn decoder_array_stream_yields_elements_lazily() {
+    // [1, 2, 3]
+    let bytes = [0x83, 0x01, 0x02, 0x03];
+    let limits = DecodeLimits::for_bytes(bytes.len());
+
+    let mut dec = Decoder::new_checked(&bytes, limits).unwrap();
+    let items: Result<Vec<i64>, _> = dec.array_stream::<i64>().unwrap().collect();
+    assert_eq!(items.unwrap(), vec![1, 2, 3]);
+
+    // Dropping the stream early poisons the decoder.
+    let mut dec = Decoder::new_checked(&bytes, limits).unwrap();
+    let mut stream = dec.array_stream::<i64>().unwrap();
+    assert_eq!(stream.next().unwrap().unwrap(), 1);
+    drop(stream);
+    assert_eq!(
+        dec.skip_value().unwrap_err().code(),
+        ErrorCode::MalformedCanonical
+    );
+
+    // A bad element ends the stream after one error.
+    let bad = [0x82, 0x01, 0x61, b'x'];
+    let mut dec = Decoder::new_checked(&bad, DecodeLimits::for_bytes(bad.len())).unwrap();
+    let mut stream = dec.array_stream::<i64>().unwrap();
+    assert_eq!(stream.next().unwrap().unwrap(), 1);
+    assert_eq!(
+        stream.next().unwrap().unwrap_err().code(),
+        ErrorCode::ExpectedInteger
+    );
+    assert!(stream.next().is_none());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn std_maps_roundtrip_in_canonical_order() {
+    use std::collections::HashMap;
+
+    let mut btree = BTreeMap::new();
+    btree.insert("bb".to_string(), 1i64);
+    btree.insert("c".to_string(), 2);
+    btree.insert("a".to_string(), 3);
+    let bytes = encode_to_vec(&btree).unwrap();
+    // Canonical order is length-first: "a", "c", "bb".
+    assert_eq!(
+        bytes,
+        [0xa3, 0x61, b'a', 0x03, 0x61, b'c', 0x02, 0x62, b'b', b'b', 0x01]
+    );
+
+    let hash: HashMap<String, i64> = btree.clone().into_iter().collect();
+    assert_eq!(encode_to_vec(&hash).unwrap(), bytes);
+
+    let limits = DecodeLimits::for_bytes(bytes.len());
+    assert_eq!(
+        decode::<BTreeMap<String, i64>>(&bytes, limits).unwrap(),
+        btree
+    );
+    assert_eq!(
+        decode::<HashMap<String, i64>>(&bytes, limits).unwrap(),
+        hash
+    );
+
+    // { "a": 1, "a": 2 }
+    let dup = [0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02];
+    let limits = DecodeLimits::for_bytes(dup.len());
+    let err = decode::<BTreeMap<String, i64>>(&dup, limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DuplicateMapKey);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn ip_and_socket_addrs_roundtrip_as_byte_strings() {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+    let v4 = Ipv4Addr::new(192, 0, 2, 1);
+    let mapped = v4.to_ipv6_mapped();
+    let bytes = encode_to_vec(&IpAddr::V4(v4)).unwrap();
+    assert_eq!(bytes, [0x44, 192, 0, 2, 1]);
+
+    let mapped_bytes = encode_to_vec(&IpAddr::V6(mapped)).unwrap();
+    assert_eq!(mapped_bytes[0], 0x50);
+    assert_eq!(&mapped_bytes[1..], &mapped.octets());
+
+    // The mapped form keeps its width and never decodes as V4.
+    let limits = DecodeLimits::for_bytes(64);
+    assert_eq!(
+        decode::<IpAddr>(&mapped_bytes, limits).unwrap(),
+        IpAddr::V6(mapped)
+    );
+    assert_eq!(decode::<IpAddr>(&bytes, limits).unwrap(), IpAddr::V4(v4));
+    assert_eq!(decode::<Ipv6Addr>(&mapped_bytes, limits).unwrap(), mapped);
+    let err = decode::<Ipv6Addr>(&bytes, limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::BytesLenMismatch);
+    let err = decode::<IpAddr>(&[0x43, 1, 2, 3], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::BytesLenMismatch);
+    // A value of the wrong type still reports the type.
+    let err = decode::<IpAddr>(&[0x01], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedBytes);
+
+    let sock = SocketAddr::from((v4, 8080));
+    let bytes = encode_to_vec(&sock).unwrap();
+    assert_eq!(bytes, [0x82, 0x44, 192, 0, 2, 1, 0x19, 0x1f, 0x90]);
+    assert_eq!(decode::<SocketAddr>(&bytes, limits).unwrap(), sock);
+
+    // Flow info and scope id are not part of the encoding.
+    let scoped = SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 443, 7, 3));
+    let bytes = encode_to_vec(&scoped).unwrap();
+    assert_eq!(
+        decode::<SocketAddr>(&bytes, limits).unwrap(),
+        SocketAddr::from((Ipv6Addr::LOCALHOST, 443))
+    );
+
+    let addrs = vec![
+        IpAddr::V4(v4),
+        IpAddr::V6(mapped),
+        IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    ];
+    let bytes = encode_to_vec(&addrs).unwrap();
+    assert_eq!(decode::<Vec<IpAddr>>(&bytes, limits).unwrap(), addrs);
+}
+
+#[test]
+fn encodes_to_compares_against_canonical_bytes() {
+    let mut map = BTreeMap::new();
+    map.insert("bb".to_string(), 1i64);
+    map.insert("a".to_string(), 2);
+    let canon = encode_to_canonical(&map).unwrap();
+
+    assert!(encodes_to(&map, canon.as_ref()).unwrap());
+    map.insert("a".to_string(), 3);
+    assert!(!encodes_to(&map, canon.as_ref()).unwrap());
+    assert!(!encodes_to(&(), canon.as_ref()).unwrap());
+    assert!(encodes_to("hi", encode_to_canonical(&"hi").unwrap().as_ref()).unwrap());
+
+    let err = encodes_to(&u64::MAX, canon.as_ref()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+
+    // Out-of-profile and poisoned output never compares equal, even byte-for-byte.
+    struct Wide;
+    impl CborEncode for Wide {
+        fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+            enc.int_exact(1 << 60)
+        }
+    }
+    let err = encodes_to(&Wide, canon.as_ref()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+
+    struct Underfilled;
+    impl CborEncode for Underfilled {
+        fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+            drop(enc.open_array(2)?);
+            Ok(())
+        }
+    }
+    let err = encodes_to(&Underfilled, canon.as_ref()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ArrayLenMismatch);
+}
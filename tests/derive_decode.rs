@@ -0,0 +1,566 @@
+#![cfg(feature = "alloc")]
+
+use sacp_cbor::{
+    cbor_bytes, decode, encode_to_vec, path, CborDecode, CborEncode, CborValueRef, DecodeLimits,
+    ErrorCode,
+};
+
+#[derive(Debug, PartialEq, CborDecode)]
+struct Inner {
+    ts: i64,
+}
+
+#[derive(Debug, PartialEq, CborDecode)]
+struct Outer {
+    inner: Inner,
+    name: String,
+}
+
+#[test]
+fn derive_decode_errors_carry_field_context() {
+    let missing = cbor_bytes!({ inner: { ts: 1 } }).unwrap();
+    let err = decode::<Outer>(missing.as_bytes(), DecodeLimits::for_bytes(64)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MissingKey);
+    assert_eq!(err.context(), Some("name"));
+    assert!(err.to_string().ends_with(" (at name)"));
+
+    let nested = cbor_bytes!({ name: "x", inner: { ts: "late" } }).unwrap();
+    let err = decode::<Outer>(nested.as_bytes(), DecodeLimits::for_bytes(64)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedInteger);
+    assert_eq!(err.context(), Some("ts"));
+
+    let ok = cbor_bytes!({ name: "x", inner: { ts: 5 } }).unwrap();
+    let v = decode::<Outer>(ok.as_bytes(), DecodeLimits::for_bytes(64)).unwrap();
+    assert_eq!(v.inner, Inner { ts: 5 });
+}
+
+#[test]
+fn value_ref_decode_as_derived_struct() {
+    let doc = cbor_bytes!({ meta: { outer: { name: "x", inner: { ts: 7 } } } }).unwrap();
+    let outer: Outer = doc
+        .at(path!("meta", "outer"))
+        .unwrap()
+        .unwrap()
+        .decode_as()
+        .unwrap();
+    assert_eq!(outer.name, "x");
+    assert_eq!(outer.inner, Inner { ts: 7 });
+
+    let value = doc.at(path!("meta")).unwrap().unwrap();
+    let err = value.decode_as::<Inner>().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MissingKey);
+    assert!(err.offset() >= value.offset());
+}
+
+#[test]
+fn container_refs_collect_typed_values() {
+    use sacp_cbor::MapEntries;
+
+    let doc = cbor_bytes!({ items: [3, 1, 2], by_name: { b: { ts: 2 }, a: { ts: 1 } } }).unwrap();
+    let items = doc
+        .at(path!("items"))
+        .unwrap()
+        .unwrap()
+        .array()
+        .unwrap()
+        .to_vec::<i64>()
+        .unwrap();
+    assert_eq!(items, vec![3, 1, 2]);
+
+    let by_name: MapEntries<String, Inner> = doc
+        .at(path!("by_name"))
+        .unwrap()
+        .unwrap()
+        .map()
+        .unwrap()
+        .to_map_entries()
+        .unwrap();
+    assert_eq!(
+        by_name.0,
+        vec![
+            ("a".to_string(), Inner { ts: 1 }),
+            ("b".to_string(), Inner { ts: 2 })
+        ]
+    );
+
+    let err = doc
+        .at(path!("items"))
+        .unwrap()
+        .unwrap()
+        .array()
+        .unwrap()
+        .to_vec::<String>()
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedText);
+}
+
+#[derive(Debug, PartialEq)]
+struct Label(String);
+
+impl<'de> CborDecode<'de> for Label {
+    fn decode<const CHECKED: bool>(
+        decoder: &mut sacp_cbor::Decoder<'de, CHECKED>,
+    ) -> Result<Self, sacp_cbor::CborError> {
+        String::decode(decoder).map(Self)
+    }
+}
+
+#[derive(Debug, PartialEq, CborDecode)]
+#[cbor(untagged)]
+enum Field {
+    Named(#[cbor(kind = "text")] Label),
+    Id(u32),
+}
+
+#[test]
+fn untagged_variant_kind_override() {
+    let text = cbor_bytes!("x").unwrap();
+    let v = decode::<Field>(text.as_bytes(), DecodeLimits::for_bytes(16)).unwrap();
+    assert_eq!(v, Field::Named(Label("x".to_string())));
+
+    let int = cbor_bytes!(7).unwrap();
+    let v = decode::<Field>(int.as_bytes(), DecodeLimits::for_bytes(16)).unwrap();
+    assert_eq!(v, Field::Id(7));
+}
+
+/// Has no `CborEncode`/`CborDecode` impls; only reachable through `cbor(with)`.
+#[derive(Debug, PartialEq)]
+struct Epoch(u32);
+
+mod epoch {
+    use sacp_cbor::{CborDecode, CborError, Decoder, Encoder};
+
+    pub fn encode(v: &super::Epoch, enc: &mut Encoder) -> Result<(), CborError> {
+        enc.int(i64::from(v.0))
+    }
+
+    pub fn decode<const C: bool>(d: &mut Decoder<'_, C>) -> Result<super::Epoch, CborError> {
+        u32::decode(d).map(super::Epoch)
+    }
+}
+
+#[derive(Debug, PartialEq, CborEncode, CborDecode)]
+struct Event {
+    #[cbor(with = "epoch")]
+    at: Epoch,
+    name: String,
+}
+
+#[test]
+fn field_with_custom_codec() {
+    let ev = Event {
+        at: Epoch(1_700_000_000),
+        name: "boot".to_string(),
+    };
+    let bytes = encode_to_vec(&ev).unwrap();
+    let expected = cbor_bytes!({ at: 1_700_000_000, name: "boot" }).unwrap();
+    assert_eq!(bytes, expected.as_bytes());
+
+    let back = decode::<Event>(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    assert_eq!(back, ev);
+
+    let bad = cbor_bytes!({ at: "x", name: "boot" }).unwrap();
+    let err = decode::<Event>(bad.as_bytes(), DecodeLimits::for_bytes(64)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedInteger);
+    assert_eq!(err.context(), Some("at"));
+}
+
+#[derive(Debug, CborDecode)]
+struct Signed<'a> {
+    body: sacp_cbor::RawCbor<'a>,
+    sig: &'a [u8],
+}
+
+#[test]
+fn raw_cbor_field_rejects_relaxed_content() {
+    let mut enc = sacp_cbor::Encoder::new();
+    enc.map(2, |m| {
+        m.entry("sig", |e| e.bytes(&[0xaa]))?;
+        m.entry("body", |e| e.int_exact(i128::from(u64::MAX)))
+    })
+    .unwrap();
+    let msg = enc.into_vec();
+
+    let relaxed = DecodeLimits::for_bytes(64).with_allow_full_int_range(true);
+    let err = decode::<Signed<'_>>(&msg, relaxed).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+    assert_eq!(err.offset(), 12);
+}
+
+#[test]
+fn raw_cbor_field_captures_exact_bytes() {
+    let body = cbor_bytes!({ b: [1, 2], a: "x" }).unwrap();
+    let mut enc = sacp_cbor::Encoder::new();
+    enc.map(2, |m| {
+        m.entry("sig", |e| e.bytes(&[0xaa]))?;
+        m.entry("body", |e| e.raw_cbor(body.as_ref()))
+    })
+    .unwrap();
+    let msg = enc.into_canonical().unwrap();
+
+    let signed: Signed<'_> = decode(msg.as_bytes(), DecodeLimits::for_bytes(64)).unwrap();
+    assert_eq!(signed.sig, &[0xaa]);
+    assert_eq!(signed.body.as_bytes(), body.as_bytes());
+    assert_eq!(signed.body.as_canonical(), body.as_ref());
+    assert_eq!(signed.body.offset(), 12);
+    assert_eq!(
+        signed
+            .body
+            .value()
+            .get_key("a")
+            .unwrap()
+            .unwrap()
+            .text()
+            .unwrap(),
+        "x"
+    );
+    assert_eq!(encode_to_vec(&signed.body).unwrap(), body.as_bytes());
+}
+
+#[derive(Debug, PartialEq, CborDecode)]
+#[cbor(deny_unknown_keys)]
+struct Strict {
+    op: i64,
+    #[cbor(default)]
+    arg: Option<String>,
+}
+
+#[test]
+fn deny_unknown_keys_rejects_extras() {
+    let limits = DecodeLimits::for_bytes(64);
+    let ok = cbor_bytes!({ op: 1 }).unwrap();
+    assert_eq!(
+        decode::<Strict>(ok.as_bytes(), limits).unwrap(),
+        Strict { op: 1, arg: None }
+    );
+
+    // { "op": 1, "zz": 2 }: the unknown key starts at offset 5.
+    let extra = cbor_bytes!({ op: 1, zz: 2 }).unwrap();
+    let err = decode::<Strict>(extra.as_bytes(), limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownKey);
+    assert_eq!(err.offset(), 5);
+
+    // Without the attribute, extras are still skipped.
+    assert_eq!(
+        decode::<Inner>(cbor_bytes!({ ts: 3, zz: 2 }).unwrap().as_bytes(), limits).unwrap(),
+        Inner { ts: 3 }
+    );
+}
+
+#[derive(Debug, PartialEq, CborDecode)]
+#[cbor(deny_unknown_keys)]
+enum StrictCmd {
+    Set { key: String },
+    Ping,
+}
+
+#[derive(Debug, PartialEq, CborDecode)]
+#[cbor(untagged, deny_unknown_keys)]
+enum StrictUntagged {
+    Named { op: i64 },
+    Flag(bool),
+}
+
+#[test]
+fn deny_unknown_keys_applies_to_enum_struct_variants() {
+    let limits = DecodeLimits::for_bytes(64);
+    let ok = cbor_bytes!({ Set: { key: "a" } }).unwrap();
+    assert_eq!(
+        decode::<StrictCmd>(ok.as_bytes(), limits).unwrap(),
+        StrictCmd::Set {
+            key: "a".to_string()
+        }
+    );
+    let ping = cbor_bytes!({ Ping: null }).unwrap();
+    assert_eq!(
+        decode::<StrictCmd>(ping.as_bytes(), limits).unwrap(),
+        StrictCmd::Ping
+    );
+
+    let extra = cbor_bytes!({ Set: { key: "a", zz: 1 } }).unwrap();
+    let err = decode::<StrictCmd>(extra.as_bytes(), limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownKey);
+
+    let extra = cbor_bytes!({ op: 1, zz: 2 }).unwrap();
+    let err = decode::<StrictUntagged>(extra.as_bytes(), limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownKey);
+    assert_eq!(
+        decode::<StrictUntagged>(cbor_bytes!({ op: 1 }).unwrap().as_bytes(), limits).unwrap(),
+        StrictUntagged::Named { op: 1 }
+    );
+}
+
+#[test]
+fn result_encodes_as_ok_or_err_map() {
+    let ok: Result<u32, String> = Ok(7);
+    let err: Result<u32, String> = Err("denied".to_string());
+    let ok_bytes = encode_to_vec(&ok).unwrap();
+    let err_bytes = encode_to_vec(&err).unwrap();
+    assert_eq!(ok_bytes, cbor_bytes!({ Ok: 7 }).unwrap().as_bytes());
+    assert_eq!(
+        err_bytes,
+        cbor_bytes!({ Err: "denied" }).unwrap().as_bytes()
+    );
+
+    let limits = DecodeLimits::for_bytes(64);
+    assert_eq!(
+        decode::<Result<u32, String>>(&ok_bytes, limits).unwrap(),
+        ok
+    );
+    assert_eq!(
+        decode::<Result<u32, String>>(&err_bytes, limits).unwrap(),
+        err
+    );
+
+    let other = cbor_bytes!({ ok: 7 }).unwrap();
+    let e = decode::<Result<u32, String>>(other.as_bytes(), limits).unwrap_err();
+    assert_eq!(e.code(), ErrorCode::UnknownEnumVariant);
+    let two = cbor_bytes!({ Ok: 7, Err: "x" }).unwrap();
+    let e = decode::<Result<u32, String>>(two.as_bytes(), limits).unwrap_err();
+    assert_eq!(e.code(), ErrorCode::MapLenMismatch);
+}
+
+#[test]
+fn tuples_roundtrip_as_fixed_arrays() {
+    let bytes = encode_to_vec(&(7u64, "seven", true)).unwrap();
+    assert_eq!(bytes, cbor_bytes!([7, "seven", true]).unwrap().as_bytes());
+    let limits = DecodeLimits::for_bytes(64);
+    let back: (u64, &str, bool) = decode(&bytes, limits).unwrap();
+    assert_eq!(back, (7, "seven", true));
+
+    let pairs = vec![("a".to_string(), 1i64), ("b".to_string(), -2)];
+    let bytes = encode_to_vec(&pairs).unwrap();
+    assert_eq!(decode::<Vec<(String, i64)>>(&bytes, limits).unwrap(), pairs);
+
+    let short = cbor_bytes!([7, "seven"]).unwrap();
+    let e = decode::<(u64, &str, bool)>(short.as_bytes(), limits).unwrap_err();
+    assert_eq!(e.code(), ErrorCode::ArrayLenMismatch);
+    assert_eq!(e.offset(), 0);
+}
+
+#[derive(Debug, PartialEq, CborDecode)]
+#[cbor(tag_hint)]
+enum Command {
+    Stop,
+    Move { x: i64 },
+}
+
+#[derive(Debug, PartialEq, CborDecode)]
+struct Envelope {
+    cmd: Command,
+}
+
+#[test]
+fn tag_hint_rejects_non_map_at_enum_offset() {
+    let limits = DecodeLimits::for_bytes(64);
+    let ok = cbor_bytes!({ cmd: { Move: { x: 3 } } }).unwrap();
+    let v = decode::<Envelope>(ok.as_bytes(), limits).unwrap();
+    assert_eq!(v.cmd, Command::Move { x: 3 });
+    let stop = cbor_bytes!({ cmd: { Stop: null } }).unwrap();
+    assert_eq!(
+        decode::<Envelope>(stop.as_bytes(), limits).unwrap().cmd,
+        Command::Stop
+    );
+
+    let bad = cbor_bytes!({ cmd: ["Stop"] }).unwrap();
+    let e = decode::<Envelope>(bad.as_bytes(), limits).unwrap_err();
+    assert_eq!(e.code(), ErrorCode::ExpectedEnum);
+    assert_eq!(e.offset(), 5);
+    assert_eq!(e.context(), Some("cmd"));
+}
+
+#[derive(Debug, PartialEq, CborEncode, CborDecode)]
+struct Shared {
+    boxed: Box<i64>,
+    inner: std::sync::Arc<Inner2>,
+    name: std::rc::Rc<String>,
+}
+
+#[derive(Debug, PartialEq, CborEncode, CborDecode)]
+struct Inner2 {
+    ts: i64,
+}
+
+#[test]
+fn pointers_encode_and_decode_as_their_target() {
+    let value = Shared {
+        boxed: Box::new(5),
+        inner: std::sync::Arc::new(Inner2 { ts: 9 }),
+        name: std::rc::Rc::new("x".to_string()),
+    };
+    let bytes = encode_to_vec(&value).unwrap();
+    assert_eq!(
+        bytes,
+        cbor_bytes!({ name: "x", boxed: 5, inner: { ts: 9 } })
+            .unwrap()
+            .as_bytes()
+    );
+    let back: Shared = decode(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    assert_eq!(back, value);
+
+    let items: Vec<Box<str>> = vec!["a".into(), "b".into()];
+    let by_ref: Vec<&&str> = vec![&"a", &"b"];
+    assert_eq!(
+        encode_to_vec(&items).unwrap(),
+        encode_to_vec(&by_ref).unwrap()
+    );
+}
+
+#[derive(Debug, PartialEq, CborEncode, CborDecode)]
+#[cbor(transparent)]
+struct UserId(String);
+
+#[derive(Debug, PartialEq, CborEncode, CborDecode)]
+#[cbor(transparent)]
+struct Score {
+    points: i64,
+}
+
+#[derive(Debug, PartialEq, CborEncode, CborDecode)]
+struct Player {
+    id: UserId,
+    score: Score,
+}
+
+#[test]
+fn transparent_structs_use_the_inner_encoding() {
+    let id = UserId("u1".to_string());
+    assert_eq!(
+        encode_to_vec(&id).unwrap(),
+        cbor_bytes!("u1").unwrap().as_bytes()
+    );
+
+    let player = Player {
+        id,
+        score: Score { points: -3 },
+    };
+    let bytes = encode_to_vec(&player).unwrap();
+    assert_eq!(
+        bytes,
+        cbor_bytes!({ id: "u1", score: -3 }).unwrap().as_bytes()
+    );
+    let back: Player = decode(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    assert_eq!(back, player);
+
+    let err = decode::<Score>(
+        cbor_bytes!("x").unwrap().as_bytes(),
+        DecodeLimits::for_bytes(8),
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedInteger);
+}
+
+#[derive(Debug, PartialEq, CborEncode, CborDecode)]
+enum Signal<'a> {
+    Ping,
+    Resize(u32),
+    #[cbor(other)]
+    Unknown(&'a str, CborValueRef<'a>),
+}
+
+#[derive(Debug, PartialEq, CborEncode, CborDecode)]
+enum Level {
+    Low,
+    #[cbor(other)]
+    Unrecognized,
+}
+
+#[test]
+fn other_variant_catches_unknown_names() {
+    let limits = DecodeLimits::for_bytes(64);
+    let known = cbor_bytes!({ Resize: [4] }).unwrap();
+    assert_eq!(
+        decode::<Signal>(known.as_bytes(), limits).unwrap(),
+        Signal::Resize(4)
+    );
+
+    let future = cbor_bytes!({ Scroll: [1, 2] }).unwrap();
+    let unknown = decode::<Signal>(future.as_bytes(), limits).unwrap();
+    let Signal::Unknown(name, raw) = unknown else {
+        panic!("expected the catch-all variant");
+    };
+    assert_eq!(name, "Scroll");
+    assert_eq!(raw.as_bytes(), cbor_bytes!([1, 2]).unwrap().as_bytes());
+    // A captured entry re-encodes under its original name.
+    assert_eq!(encode_to_vec(&unknown).unwrap(), future.as_bytes());
+
+    // The catch-all's own name is just another unknown name.
+    let own = cbor_bytes!({ Unknown: 1 }).unwrap();
+    assert!(matches!(
+        decode::<Signal>(own.as_bytes(), limits).unwrap(),
+        Signal::Unknown("Unknown", _)
+    ));
+
+    // A unit catch-all discarded the name, so it cannot be encoded. The enum must still be
+    // a one-entry map.
+    let err = encode_to_vec(&Level::Unrecognized).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnknownEnumVariant);
+    let own = cbor_bytes!({ Unrecognized: null }).unwrap();
+    assert_eq!(
+        decode::<Level>(own.as_bytes(), limits).unwrap(),
+        Level::Unrecognized
+    );
+    let future = cbor_bytes!({ High: { boost: true } }).unwrap();
+    assert_eq!(
+        decode::<Level>(future.as_bytes(), limits).unwrap(),
+        Level::Unrecognized
+    );
+    let two = cbor_bytes!({ Low: 0, Mid: 0 }).unwrap();
+    let e = decode::<Level>(two.as_bytes(), limits).unwrap_err();
+    assert_eq!(e.code(), ErrorCode::MapLenMismatch);
+}
+
+#[test]
+fn phantom_data_fields_are_absent_on_the_wire() {
+    use std::marker::PhantomData;
+
+    // Implements neither `CborEncode`, `CborDecode`, nor `Default`.
+    #[derive(Debug, PartialEq)]
+    struct V2;
+
+    #[derive(Debug, PartialEq, CborEncode, CborDecode)]
+    struct Envelope<Version> {
+        id: u32,
+        version: PhantomData<Version>,
+    }
+
+    let env = Envelope::<V2> {
+        id: 9,
+        version: PhantomData,
+    };
+    let bytes = encode_to_vec(&env).unwrap();
+    assert_eq!(bytes, cbor_bytes!({ id: 9 }).unwrap().as_bytes());
+    assert_eq!(
+        decode::<Envelope<V2>>(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap(),
+        env
+    );
+
+    // Tuple structs and tuple variants skip markers too.
+    #[derive(Debug, PartialEq, CborEncode, CborDecode)]
+    struct Tagged<Version>(u32, PhantomData<Version>, bool);
+
+    #[derive(Debug, PartialEq, CborEncode, CborDecode)]
+    enum Msg<Version> {
+        Ping(u32, PhantomData<Version>),
+    }
+
+    let tagged = Tagged::<V2>(9, PhantomData, true);
+    let bytes = encode_to_vec(&tagged).unwrap();
+    assert_eq!(bytes, cbor_bytes!([9, true]).unwrap().as_bytes());
+    assert_eq!(
+        decode::<Tagged<V2>>(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap(),
+        tagged
+    );
+    let msg = Msg::<V2>::Ping(9, PhantomData);
+    let bytes = encode_to_vec(&msg).unwrap();
+    assert_eq!(bytes, cbor_bytes!({ Ping: [9] }).unwrap().as_bytes());
+    assert_eq!(
+        decode::<Msg<V2>>(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap(),
+        msg
+    );
+
+    // Standalone markers encode as `null`, like `()`.
+    let bytes = encode_to_vec(&PhantomData::<V2>).unwrap();
+    assert_eq!(bytes, [0xf6]);
+    decode::<PhantomData<V2>>(&bytes, DecodeLimits::for_bytes(1)).unwrap();
+}
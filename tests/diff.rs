@@ -0,0 +1,134 @@
+#![cfg(feature = "alloc")]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use proptest::prelude::*;
+
+use sacp_cbor::{cbor_bytes, diff, CanonicalCbor, CborError, Encoder};
+
+fn apply(old: &CanonicalCbor, patch: &CanonicalCbor) -> Result<CanonicalCbor, CborError> {
+    old.edit(|e| e.apply_patch(patch.as_ref()))
+}
+
+fn roundtrip(old: &CanonicalCbor, new: &CanonicalCbor) {
+    let patch = diff(old.as_ref(), new.as_ref()).unwrap();
+    let out = apply(old, &patch).unwrap();
+    assert_eq!(out.as_bytes(), new.as_bytes());
+}
+
+#[test]
+fn diff_identical_is_empty() {
+    let doc = cbor_bytes!({ a: 1, b: [true, null] }).unwrap();
+    let patch = diff(doc.as_ref(), doc.as_ref()).unwrap();
+    assert_eq!(patch.as_bytes(), &[0x80]);
+}
+
+#[test]
+fn diff_map_changes() {
+    let old = cbor_bytes!({ a: 1, b: { c: "x", d: 2 }, gone: null }).unwrap();
+    let new = cbor_bytes!({ a: 1, b: { c: "y", d: 2 }, added: [1] }).unwrap();
+
+    let patch = diff(old.as_ref(), new.as_ref()).unwrap();
+    let expected = cbor_bytes!([
+        { op: "set", path: ["b", "c"], value: "y" },
+        { op: "delete", path: ["gone"] },
+        { op: "set", path: ["added"], value: [1] },
+    ])
+    .unwrap();
+    assert_eq!(patch.as_bytes(), expected.as_bytes());
+    roundtrip(&old, &new);
+}
+
+#[test]
+fn diff_array_splices() {
+    let old = cbor_bytes!([1, 2, 3, 4, 5]).unwrap();
+    let new = cbor_bytes!([1, 9, 5]).unwrap();
+    let patch = diff(old.as_ref(), new.as_ref()).unwrap();
+    let expected = cbor_bytes!([
+        { op: "set", path: [1], value: 9 },
+        { op: "splice", pos: 2, path: [], delete: 2, insert: [] },
+    ])
+    .unwrap();
+    assert_eq!(patch.as_bytes(), expected.as_bytes());
+    roundtrip(&old, &new);
+
+    roundtrip(&new, &old);
+    roundtrip(
+        &cbor_bytes!([{ a: 1 }, [2]]).unwrap(),
+        &cbor_bytes!([{ a: 2 }, [2, 3], "tail"]).unwrap(),
+    );
+}
+
+#[test]
+fn diff_root_replacement() {
+    let old = cbor_bytes!({ a: 1 }).unwrap();
+    let new = cbor_bytes!([1]).unwrap();
+    let patch = diff(old.as_ref(), new.as_ref()).unwrap();
+    let expected = cbor_bytes!([{ op: "set", path: [], value: [1] }]).unwrap();
+    assert_eq!(patch.as_bytes(), expected.as_bytes());
+    roundtrip(&old, &new);
+}
+
+#[derive(Debug, Clone)]
+enum Doc {
+    Null,
+    Int(i64),
+    Text(String),
+    Array(Vec<Doc>),
+    Map(Vec<(String, Doc)>),
+}
+
+fn encode_doc(enc: &mut Encoder, doc: &Doc) -> Result<(), CborError> {
+    match doc {
+        Doc::Null => enc.null(),
+        Doc::Int(v) => enc.int(*v),
+        Doc::Text(s) => enc.text(s),
+        Doc::Array(items) => enc.array(items.len(), |a| {
+            for item in items {
+                a.raw_cbor(to_canonical(item).as_ref())?;
+            }
+            Ok(())
+        }),
+        Doc::Map(entries) => {
+            let mut entries = entries.clone();
+            entries.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+            entries.dedup_by(|(a, _), (b, _)| a == b);
+            enc.map(entries.len(), |m| {
+                for (k, v) in &entries {
+                    m.entry(k, |e| encode_doc(e, v))?;
+                }
+                Ok(())
+            })
+        }
+    }
+}
+
+fn to_canonical(doc: &Doc) -> CanonicalCbor {
+    let mut enc = Encoder::new();
+    encode_doc(&mut enc, doc).unwrap();
+    enc.into_canonical().unwrap()
+}
+
+fn doc_strategy() -> impl Strategy<Value = Doc> {
+    let leaf = prop_oneof![
+        Just(Doc::Null),
+        (-3i64..3).prop_map(Doc::Int),
+        "[a-c]{0,2}".prop_map(Doc::Text),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            proptest::collection::vec(inner.clone(), 0..4).prop_map(Doc::Array),
+            proptest::collection::vec(("[a-d]{1,2}", inner), 0..4).prop_map(Doc::Map),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn diff_then_apply_reproduces_new(old in doc_strategy(), new in doc_strategy()) {
+        let old = to_canonical(&old);
+        let new = to_canonical(&new);
+        let patch = diff(old.as_ref(), new.as_ref()).unwrap();
+        let out = apply(&old, &patch).unwrap();
+        prop_assert_eq!(out.as_bytes(), new.as_bytes());
+    }
+}
@@ -1,6 +1,6 @@
 #![cfg(feature = "alloc")]
 
-use sacp_cbor::{cbor_bytes, path, ErrorCode};
+use sacp_cbor::{cbor_bytes, path, ArrayPos, ErrorCode};
 
 #[test]
 fn edit_noop_preserves_bytes() {
@@ -51,7 +51,7 @@ fn edit_conflicts_are_rejected() {
         })
         .unwrap_err();
 
-    assert_eq!(err.code, ErrorCode::PatchConflict);
+    assert_eq!(err.code(), ErrorCode::PatchConflict);
 }
 
 #[test]
@@ -78,7 +78,7 @@ fn edit_array_index_out_of_bounds() {
         })
         .unwrap_err();
 
-    assert_eq!(err.code, ErrorCode::IndexOutOfBounds);
+    assert_eq!(err.code(), ErrorCode::IndexOutOfBounds);
 }
 
 #[test]
@@ -109,6 +109,27 @@ fn edit_array_insert_before_index() {
     assert_eq!(out.as_bytes(), expected.as_bytes());
 }
 
+#[test]
+fn edit_splice_inserts_many_values() {
+    let bytes = cbor_bytes!({ src: ["x", "y"], xs: [1, 2, 3, 4] }).unwrap();
+    let src = bytes.at(path!["src"]).unwrap().unwrap().array().unwrap();
+    let raws: Vec<_> = src.iter().map(Result::unwrap).collect();
+    let out = bytes
+        .edit(|e| {
+            e.splice(path!["xs"], ArrayPos::At(1), 2)?
+                .insert_many([7i64, 8, 9])?
+                .insert_raw_many(&raws)?
+                .finish()?;
+            e.splice(path!["src"], ArrayPos::End, 0)?
+                .insert_many(Vec::<i64>::new())?
+                .finish()
+        })
+        .unwrap();
+
+    let expected = cbor_bytes!({ src: ["x", "y"], xs: [1, 7, 8, 9, "x", "y", 4] }).unwrap();
+    assert_eq!(out.as_bytes(), expected.as_bytes());
+}
+
 #[test]
 fn edit_set_raw_reuses_value_bytes() {
     let bytes = cbor_bytes!({ a: [1, 2], b: 0 }).unwrap();
@@ -138,3 +159,119 @@ fn edit_can_create_missing_maps_when_enabled() {
     let expected = cbor_bytes!({ a: { b: 1 } }).unwrap();
     assert_eq!(out.as_bytes(), expected.as_bytes());
 }
+
+#[test]
+fn apply_patch_records_ops() {
+    let bytes = cbor_bytes!({ a: 1, b: [1, 2, 3], c: "x" }).unwrap();
+    let patch = cbor_bytes!([
+        { op: "set", path: ["a"], value: { n: 2 } },
+        { op: "delete", path: ["c"] },
+        { op: "splice", pos: 1, path: ["b"], delete: 1, insert: [7, 8] },
+    ])
+    .unwrap();
+
+    let out = bytes.edit(|e| e.apply_patch(patch.as_ref())).unwrap();
+    let expected = cbor_bytes!({ a: { n: 2 }, b: [1, 7, 8, 3] }).unwrap();
+    assert_eq!(out.as_bytes(), expected.as_bytes());
+}
+
+#[test]
+fn apply_patch_rejects_bad_shapes_and_conflicts() {
+    let bytes = cbor_bytes!({ a: 1 }).unwrap();
+    for patch in [
+        cbor_bytes!({ op: "set" }).unwrap(),
+        cbor_bytes!([{ op: "move", path: ["a"] }]).unwrap(),
+        cbor_bytes!([{ op: "delete", path: [true] }]).unwrap(),
+        cbor_bytes!([{ op: "delete", path: ["a"], extra: 1 }]).unwrap(),
+        cbor_bytes!([{ op: "set", path: ["a"] }]).unwrap(),
+    ] {
+        let err = bytes.edit(|e| e.apply_patch(patch.as_ref())).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidQuery);
+    }
+
+    let patch = cbor_bytes!([
+        { op: "set", path: ["a"], value: 2 },
+        { op: "delete", path: ["a"] },
+    ])
+    .unwrap();
+    let err = bytes.edit(|e| e.apply_patch(patch.as_ref())).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::PatchConflict);
+}
+
+#[test]
+fn validate_reports_what_apply_would() {
+    type Edit = fn(&mut sacp_cbor::Editor<'_>) -> Result<(), sacp_cbor::CborError>;
+    let bytes = cbor_bytes!({ a: { b: [1, 2, 3] }, c: 0 }).unwrap();
+    let cases: [(Edit, Option<ErrorCode>); 6] = [
+        (|e| e.set(path!["a", "b", 1], 9i64), None),
+        (
+            |e| e.replace(path!["a", "x"], 9i64),
+            Some(ErrorCode::MissingKey),
+        ),
+        (
+            |e| e.set(path!["a", "b", 7], 9i64),
+            Some(ErrorCode::IndexOutOfBounds),
+        ),
+        (
+            |e| e.set(path!["d", "e"], 1i64),
+            Some(ErrorCode::MissingKey),
+        ),
+        (
+            |e| {
+                e.options_mut().create_missing_maps = true;
+                e.set(path!["d", "e"], 1i64)
+            },
+            None,
+        ),
+        (
+            |e| e.insert(path!["c"], 1i64),
+            Some(ErrorCode::InvalidQuery),
+        ),
+    ];
+
+    for (edit, expected) in cases {
+        let mut editor = bytes.editor();
+        edit(&mut editor).unwrap();
+        assert_eq!(editor.validate().err().map(|e| e.code()), expected);
+        assert_eq!(editor.apply().err().map(|e| e.code()), expected);
+    }
+}
+
+#[test]
+fn estimated_len_matches_apply_across_header_widths() {
+    fn check(bytes: &sacp_cbor::CanonicalCbor, edit: impl FnOnce(&mut sacp_cbor::Editor<'_>)) {
+        let mut editor = bytes.editor();
+        edit(&mut editor);
+        let estimate = editor.estimated_len().unwrap();
+        assert_eq!(estimate, editor.apply().unwrap().as_bytes().len());
+    }
+
+    for n in [23usize, 24, 255, 256, 65535, 65536] {
+        let mut enc = sacp_cbor::Encoder::new();
+        enc.map(1, |m| {
+            m.entry("xs", |e| {
+                e.array(n, |a| {
+                    (0..n).try_for_each(|i| a.int(i64::try_from(i % 30).unwrap()))
+                })
+            })
+        })
+        .unwrap();
+        let bytes = enc.into_canonical().unwrap();
+
+        check(&bytes, |e| e.push(path!["xs"], "tail").unwrap());
+        check(&bytes, |e| e.delete(path!["xs", 0]).unwrap());
+        check(&bytes, |e| {
+            e.set(path!["xs", 3], 1_000_000i64).unwrap();
+            e.insert(path!["xs", 5], [7u8; 300].as_slice()).unwrap();
+        });
+    }
+
+    let bytes = cbor_bytes!({ a: { b: [1, 2, 3] }, c: "long value" }).unwrap();
+    check(&bytes, |e| e.delete(path!["c"]).unwrap());
+    check(&bytes, |e| e.set(path!["a", "b", 0], "x").unwrap());
+    check(&bytes, |e| {
+        e.options_mut().create_missing_maps = true;
+        e.set(path!["d", "e", "f"], 1i64).unwrap();
+        e.delete_if_present(path!["zz"]).unwrap();
+    });
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "json")]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use sacp_cbor::{
+    cbor_bytes, from_editable_text, to_editable_text, CanonicalCbor, Encoder, ErrorCode, F64Bits,
+};
+
+fn roundtrip(doc: &CanonicalCbor) -> String {
+    let text = to_editable_text(doc.as_ref()).unwrap();
+    let back = from_editable_text(&text).unwrap();
+    assert_eq!(back.as_bytes(), doc.as_bytes(), "text was:\n{text}");
+    text
+}
+
+#[test]
+fn export_is_pretty_and_round_trips() {
+    let doc = cbor_bytes!({
+        name: "svc",
+        port: 8080,
+        tags: ["a", "0xbeef", "12", "'q"],
+        ratio: 0.5,
+        empty: {},
+        none: [],
+        flag: null
+    })
+    .unwrap();
+    let text = roundtrip(&doc);
+    assert_eq!(
+        text,
+        r#"{
+  "flag": null,
+  "name": "svc",
+  "none": [],
+  "port": 8080,
+  "tags": [
+    "a",
+    "'0xbeef",
+    "'12",
+    "''q"
+  ],
+  "empty": {},
+  "ratio": 0.5
+}"#
+    );
+}
+
+#[test]
+fn scalars_round_trip() {
+    let mut enc = Encoder::new();
+    enc.array(10, |a| {
+        a.bytes(&[0x00, 0xff])?;
+        a.bignum(false, &[0x01, 0, 0, 0, 0, 0, 0, 0, 0])?;
+        a.bignum(true, &[0x01, 0, 0, 0, 0, 0, 0, 0, 0])?;
+        a.float(F64Bits::try_from_f64(1.0)?)?;
+        a.float(F64Bits::try_from_f64(1e300)?)?;
+        a.float(F64Bits::try_from_f64(f64::NAN)?)?;
+        a.float(F64Bits::try_from_f64(f64::NEG_INFINITY)?)?;
+        a.text("line\n\"quoted\"\u{1}")?;
+        a.int(-9_007_199_254_740_991)?;
+        a.bool(false)
+    })
+    .unwrap();
+    let doc = enc.into_canonical().unwrap();
+    let text = roundtrip(&doc);
+    assert!(text.contains(r#""0x00ff""#));
+    assert!(text.contains(r#""18446744073709551616""#));
+    assert!(text.contains(r#""-18446744073709551617""#));
+    assert!(text.contains("1e300"));
+    assert!(text.contains(r#""line\n\"quoted\"\u0001""#));
+}
+
+#[test]
+fn edited_text_is_recanonicalized() {
+    let doc = cbor_bytes!({ port: 80, host: "x" }).unwrap();
+    let text = to_editable_text(doc.as_ref()).unwrap();
+    // Edit two values and leave a trailing comma behind.
+    let edited = text.replace("80\n", "8080,\n").replace("\"x\"", "\"y\"");
+    let out = from_editable_text(&edited).unwrap();
+    assert_eq!(
+        out.as_bytes(),
+        cbor_bytes!({ host: "y", port: 8080 }).unwrap().as_bytes()
+    );
+
+    // Hand-written input: keys out of order, integers as strings, upper-case hex.
+    let out = from_editable_text(r#"{"b": "0xAB", "a": "5", "c": 9007199254740992}"#).unwrap();
+    let mut enc = Encoder::new();
+    enc.map(3, |m| {
+        m.entry("a", |e| e.int(5))?;
+        m.entry("b", |e| e.bytes(&[0xab]))?;
+        m.entry("c", |e| e.bignum(false, &[0x20, 0, 0, 0, 0, 0, 0]))
+    })
+    .unwrap();
+    assert_eq!(out.as_bytes(), enc.as_bytes());
+}
+
+#[test]
+fn import_errors() {
+    let err = from_editable_text(r#"{"a": 1, "a": 2}"#).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DuplicateMapKey);
+    assert_eq!(err.offset(), 9);
+
+    let err = from_editable_text("[1, 2").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidTextSyntax);
+    assert_eq!(err.offset(), 5);
+
+    let err = from_editable_text(r#""0xabc""#).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidTextSyntax);
+
+    let err = from_editable_text("-0.0").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
+
+    let err = from_editable_text("1 2").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TrailingBytes);
+
+    let deep = "[".repeat(300);
+    let err = from_editable_text(&deep).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DepthLimitExceeded);
+}
+
+#[cfg(feature = "unsafe")]
+#[test]
+fn export_rejects_passthrough_tags() {
+    // 1(1) smuggled in through the unsafe constructor; validation never admits it as canonical.
+    let bytes = [0x81, 0xc1, 0x01];
+    let canon = unsafe { sacp_cbor::CanonicalCborRef::from_canonical(&bytes) };
+    let err = to_editable_text(canon).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MalformedCanonical);
+    assert_eq!(err.offset(), 1);
+}
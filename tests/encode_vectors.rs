@@ -1,6 +1,9 @@
 #![cfg(feature = "alloc")]
 
-use sacp_cbor::{CborError, Encoder, ErrorCode, F64Bits, MAX_SAFE_INTEGER_I64, MIN_SAFE_INTEGER};
+use sacp_cbor::{
+    validate_canonical, ArrayEncoderFixed, CborError, DecodeLimits, EncodedTextKey, Encoder,
+    ErrorCode, F64Bits, MAX_SAFE_INTEGER_I64, MIN_SAFE_INTEGER,
+};
 
 fn encode_one(f: impl FnOnce(&mut Encoder) -> Result<(), CborError>) -> Vec<u8> {
     let mut enc = Encoder::new();
@@ -44,11 +47,36 @@ fn encode_nint_boundaries() {
 fn encode_rejects_int_outside_safe_range() {
     let too_big = MAX_SAFE_INTEGER_I64 + 1;
     let err = Encoder::new().int(too_big).unwrap_err();
-    assert_eq!(err.code, ErrorCode::IntegerOutsideSafeRange);
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
 
     let too_small = MIN_SAFE_INTEGER - 1;
     let err = Encoder::new().int(too_small).unwrap_err();
-    assert_eq!(err.code, ErrorCode::IntegerOutsideSafeRange);
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+}
+
+#[test]
+fn int_i128_switches_to_bignum_just_past_the_safe_range() {
+    let max = i128::from(MAX_SAFE_INTEGER_I64);
+    let cases: [(i128, &[u8]); 4] = [
+        (max, &[0x1b, 0x00, 0x1f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+        (
+            max + 1,
+            &[0xc2, 0x47, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ),
+        (
+            -max,
+            &[0x3b, 0x00, 0x1f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe],
+        ),
+        (
+            -max - 1,
+            &[0xc3, 0x47, 0x1f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ),
+    ];
+    for (n, expected) in cases {
+        let bytes = encode_one(|e| e.int_i128(n));
+        assert_eq!(bytes, expected, "n = {n}");
+        validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    }
 }
 
 #[test]
@@ -102,5 +130,498 @@ fn encode_float_nan_is_canonical() {
 #[test]
 fn encode_float_rejects_negative_zero() {
     let err = F64Bits::try_from_f64(-0.0).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NegativeZeroForbidden);
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
+}
+
+#[test]
+fn map_entry_opt_skips_none() {
+    let name: Option<String> = Some("x".to_string());
+    let nick: Option<String> = None;
+    let age: Option<i64> = Some(7);
+    let present = [name.is_some(), nick.is_some(), age.is_some()]
+        .iter()
+        .filter(|&&p| p)
+        .count();
+
+    let bytes = encode_one(|e| {
+        e.map(present, |m| {
+            assert!(m.entry_opt("age", &age)?);
+            assert!(m.entry_opt("name", &name)?);
+            assert!(!m.entry_opt("nick", &nick)?);
+            Ok(())
+        })
+    });
+
+    // { "age": 7, "name": "x" }
+    assert_eq!(
+        bytes,
+        vec![0xa2, 0x63, b'a', b'g', b'e', 0x07, 0x64, b'n', b'a', b'm', b'e', 0x61, b'x']
+    );
+}
+
+#[test]
+fn map_sorted_reorders_entries() {
+    let bytes = encode_one(|e| {
+        e.map_sorted(3, |m| {
+            m.entry("bb", |e| {
+                e.map_sorted(2, |m| {
+                    m.entry("z", |e| e.int(1))?;
+                    m.entry("y", |e| e.int(2))
+                })
+            })?;
+            m.entry("c", |e| e.text("long value"))?;
+            m.entry("a", |e| e.null())
+        })
+    });
+
+    let expected = encode_one(|e| {
+        e.map(3, |m| {
+            m.entry("a", |e| e.null())?;
+            m.entry("c", |e| e.text("long value"))?;
+            m.entry("bb", |e| {
+                e.map(2, |m| {
+                    m.entry("y", |e| e.int(2))?;
+                    m.entry("z", |e| e.int(1))
+                })
+            })
+        })
+    });
+    assert_eq!(bytes, expected);
+
+    let mut enc = Encoder::new();
+    let err = enc
+        .map_sorted(2, |m| {
+            m.entry("a", |e| e.int(1))?;
+            m.entry("a", |e| e.int(2))
+        })
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DuplicateMapKey);
+    assert!(enc.is_empty());
+}
+
+#[test]
+fn encoder_try_reserve_fails_gracefully() {
+    let mut enc = Encoder::new();
+    enc.try_reserve(64).unwrap();
+    enc.int(1).unwrap();
+
+    let err = enc.try_reserve(usize::MAX).unwrap_err();
+    assert!(matches!(
+        err.code(),
+        ErrorCode::LengthOverflow | ErrorCode::AllocationFailed
+    ));
+    let err = enc.try_reserve(isize::MAX as usize).unwrap_err();
+    assert!(matches!(
+        err.code(),
+        ErrorCode::LengthOverflow | ErrorCode::AllocationFailed
+    ));
+    assert_eq!(enc.as_bytes(), &[0x01]);
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn encoder_into_sha256_matches_canonical_digest() {
+    let mut enc = Encoder::new();
+    enc.array(3, |a| {
+        a.int(1)?;
+        a.text("two")?;
+        a.bytes(&[3; 64])
+    })
+    .unwrap();
+    let expected = enc.as_bytes().to_vec();
+    let (digest, bytes) = enc.into_sha256().unwrap();
+    assert_eq!(bytes, expected);
+    let limits = DecodeLimits::for_bytes(bytes.len());
+    assert_eq!(digest, validate_canonical(&bytes, limits).unwrap().sha256());
+
+    let err = Encoder::new().into_sha256().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+}
+
+#[test]
+fn open_container_guards() {
+    let mut enc = Encoder::new();
+    {
+        let mut a = enc.open_array(2).unwrap();
+        a.int(1).unwrap();
+        a.text("x").unwrap();
+        assert_eq!(a.remaining(), 0);
+    }
+    assert_eq!(enc.as_bytes(), &[0x82, 0x01, 0x61, b'x']);
+    assert_eq!(enc.into_canonical().unwrap().as_bytes().len(), 4);
+
+    let mut enc = Encoder::new();
+    let mut m = enc.open_map(1).unwrap();
+    m.entry("a", |e| e.null()).unwrap();
+    m.finish().unwrap();
+    assert_eq!(enc.as_bytes(), &[0xa1, 0x61, b'a', 0xf6]);
+
+    let mut enc = Encoder::new();
+    let mut a = enc.open_array(3).unwrap();
+    a.int(1).unwrap();
+    drop(a);
+    let err = enc.null().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ArrayLenMismatch);
+    let err = enc.into_canonical().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ArrayLenMismatch);
+
+    // Every checked exit reports the poison, not just `into_canonical`.
+    let mut enc = Encoder::new();
+    drop(enc.open_array(3).unwrap());
+    let err = enc.try_into_vec().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ArrayLenMismatch);
+    #[cfg(feature = "std")]
+    {
+        let mut enc = Encoder::new();
+        let mut a = enc.open_array(3).unwrap();
+        a.int(1).unwrap();
+        drop(a);
+        let mut out = Vec::new();
+        let err = enc.into_writer(&mut out).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let cause = err.get_ref().and_then(|e| e.downcast_ref::<CborError>());
+        assert_eq!(cause.map(|e| e.code()), Some(ErrorCode::ArrayLenMismatch));
+        assert!(out.is_empty());
+    }
+
+    let mut enc = Encoder::new();
+    let err = enc.open_map(2).unwrap().finish().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MapLenMismatch);
+    assert!(enc.into_canonical().is_err());
+
+    let mut enc = Encoder::new();
+    core::mem::forget(enc.open_array(1).unwrap());
+    let err = enc.into_canonical().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MalformedCanonical);
+}
+
+#[test]
+fn map_builder_backpatches_length() {
+    let mut enc = Encoder::new();
+    let mut m = enc.map_builder().unwrap();
+    m.entry("a", |e| e.int(1)).unwrap();
+    assert!(!m.entry_if(false, "b", |e| e.null()).unwrap());
+    assert!(m.entry_if(true, "c", |e| e.null()).unwrap());
+    assert_eq!(m.len(), 2);
+    m.finish().unwrap();
+    assert_eq!(
+        enc.into_canonical().unwrap().as_bytes(),
+        &[0xa2, 0x61, b'a', 0x01, 0x61, b'c', 0xf6]
+    );
+
+    // 24 entries need a two-byte header; nested inside a map and closed by drop.
+    let keys: Vec<String> = (b'a'..=b'x').map(|c| char::from(c).to_string()).collect();
+    let mut enc = Encoder::new();
+    enc.map(2, |outer| {
+        outer.entry("m", |e| {
+            let mut m = e.map_builder()?;
+            for k in &keys {
+                m.entry(k, |e| e.null())?;
+            }
+            Ok(())
+        })?;
+        outer.entry("n", |e| e.int(7))
+    })
+    .unwrap();
+    let bytes = enc.into_canonical().unwrap();
+    let expected = encode_one(|e| {
+        e.map(2, |outer| {
+            outer.entry("m", |e| {
+                e.map(24, |m| {
+                    for k in &keys {
+                        m.entry(k, |e| e.null())?;
+                    }
+                    Ok(())
+                })
+            })?;
+            outer.entry("n", |e| e.int(7))
+        })
+    });
+    assert_eq!(bytes.as_bytes(), expected.as_slice());
+    assert_eq!(&bytes.as_bytes()[3..5], &[0xb8, 24]);
+
+    let mut enc = Encoder::new();
+    let mut m = enc.map_builder().unwrap();
+    m.entry("b", |e| e.null()).unwrap();
+    let err = m.entry("a", |e| e.null()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalMapOrder);
+    m.finish().unwrap();
+    assert_eq!(enc.as_bytes(), &[0xa1, 0x61, b'b', 0xf6]);
+}
+
+struct Fallible(bool);
+
+impl sacp_cbor::CborEncode for Fallible {
+    fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+        let cp = enc.checkpoint();
+        let res = enc.map(2, |m| {
+            m.entry("a", |e| e.int(1))?;
+            if self.0 {
+                m.entry("b", |e| e.int(2))
+            } else {
+                Ok(())
+            }
+        });
+        if res.is_err() {
+            enc.rollback(cp);
+            return enc.text("fallback");
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn checkpoint_rollback_undoes_partial_writes() {
+    let mut enc = Encoder::new();
+    let cp = enc.checkpoint();
+    enc.int(7).unwrap();
+    enc.rollback(cp);
+    assert!(enc.is_empty());
+    enc.text("x").unwrap();
+    assert_eq!(enc.into_canonical().unwrap().as_bytes(), &[0x61, b'x']);
+
+    let mut enc = Encoder::new();
+    enc.array(2, |a| {
+        a.value(&Fallible(true))?;
+        a.value(&Fallible(false))
+    })
+    .unwrap();
+    let mut expected = vec![0x82, 0xa2, 0x61, b'a', 0x01, 0x61, b'b', 0x02, 0x68];
+    expected.extend_from_slice(b"fallback");
+    assert_eq!(enc.as_bytes(), expected.as_slice());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "checkpoint is past the end")]
+fn rollback_past_end_panics_in_debug() {
+    let mut enc = Encoder::new();
+    enc.int(1).unwrap();
+    let cp = enc.checkpoint();
+    enc.clear();
+    enc.rollback(cp);
+}
+
+#[test]
+fn fixed_buffer_encoder_matches_encoder() {
+    let expected = encode_one(|enc| {
+        enc.map(3, |m| {
+            m.entry("a", |e| e.int(-7))?;
+            m.entry("id", |e| e.bytes(&[1, 2]))?;
+            m.entry("xs", |e| {
+                e.array(4, |a| {
+                    a.null()?;
+                    a.bool(true)?;
+                    a.text("t")?;
+                    a.float(F64Bits::try_from_f64(0.5)?)
+                })
+            })
+        })
+    });
+
+    let mut buf = [0u8; 64];
+    let mut enc = ArrayEncoderFixed::new(&mut buf);
+    enc.map(3, |m| {
+        m.entry("a", |e| e.int(-7))?;
+        m.entry("id", |e| e.bytes(&[1, 2]))?;
+        m.entry("xs", |e| {
+            e.array(4, |a| {
+                a.null()?;
+                a.bool(true)?;
+                a.text("t")?;
+                a.float(F64Bits::try_from_f64(0.5)?)
+            })
+        })
+    })
+    .unwrap();
+    assert_eq!(enc.null().unwrap_err().code(), ErrorCode::TrailingBytes);
+    let canon = enc.into_canonical().unwrap();
+    assert_eq!(canon.as_bytes(), expected.as_slice());
+}
+
+#[test]
+fn fixed_buffer_encoder_errors_roll_back() {
+    let mut buf = [0u8; 8];
+    let mut enc = ArrayEncoderFixed::new(&mut buf);
+    let err = enc
+        .array(2, |a| {
+            a.int(1)?;
+            a.text("too long")
+        })
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::BufferTooSmall);
+    assert!(enc.is_empty());
+
+    let err = enc.array(2, |a| a.int(1)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ArrayLenMismatch);
+
+    let err = enc
+        .map(2, |m| {
+            m.entry("b", |e| e.null())?;
+            m.entry("a", |e| e.null())
+        })
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalMapOrder);
+
+    let err = enc.map(1, |m| m.entry("a", |_| Ok(()))).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MapLenMismatch);
+    assert!(enc.is_empty());
+
+    enc.array(1, |a| a.int(MAX_SAFE_INTEGER_I64)).unwrap_err();
+    enc.array(1, |a| a.int(23)).unwrap();
+    assert_eq!(enc.into_canonical().unwrap().as_bytes(), [0x81, 0x17]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn writer_encoder_streams_same_bytes_as_encoder() {
+    use sacp_cbor::WriterEncoder;
+
+    let mut enc = Encoder::new();
+    enc.map(2, |m| {
+        m.entry("a", |e| {
+            e.array(2, |a| {
+                a.int(-3)?;
+                a.bignum(false, &[0x20, 0, 0, 0, 0, 0, 0])
+            })
+        })?;
+        m.entry("bb", |e| e.text("x"))
+    })
+    .unwrap();
+    let expected = enc.into_vec();
+
+    let mut w = WriterEncoder::new(Vec::new());
+    w.map(2, |m| {
+        m.entry("a", |e| {
+            e.array(2, |a| {
+                a.int(-3)?;
+                a.bignum(false, &[0x20, 0, 0, 0, 0, 0, 0])
+            })
+        })?;
+        // Rejected before anything is written, so the map can still be completed.
+        let err = m.entry("a", |e| e.null()).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DuplicateMapKey);
+        m.entry("bb", |e| e.text("x"))
+    })
+    .unwrap();
+    assert_eq!(w.position(), expected.len());
+    assert_eq!(w.finish().unwrap(), expected);
+
+    let mut out = Vec::new();
+    let mut enc = Encoder::new();
+    enc.int(7).unwrap();
+    enc.into_writer(&mut out).unwrap();
+    assert_eq!(out, [0x07]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn writer_encoder_poisons_after_partial_output() {
+    use sacp_cbor::WriterEncoder;
+
+    let mut w = WriterEncoder::new(Vec::new());
+    let err = w.array(2, |a| a.int(1)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ArrayLenMismatch);
+    assert_eq!(w.null().unwrap_err(), err);
+    assert_eq!(w.finish().unwrap_err(), err);
+
+    let mut buf = [0u8; 2];
+    let mut w = WriterEncoder::new(&mut buf[..]);
+    let err = w.text("abc").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IoError);
+    assert_eq!(w.finish().unwrap_err(), err);
+}
+
+#[test]
+fn owned_encoded_keys_sort_canonically_and_stream() {
+    let mut keys: Vec<_> = ["aa", "b", "c"]
+        .iter()
+        .map(|k| EncodedTextKey::encode(k).unwrap())
+        .collect();
+    keys.sort();
+    assert_eq!(keys[0].as_bytes(), &[0x61, b'b']);
+    assert_eq!(keys[2].as_key().as_bytes(), &[0x62, b'a', b'a']);
+
+    let mut enc = Encoder::new();
+    enc.array(2, |a| {
+        for record in 0..2 {
+            a.map(3, |m| {
+                for key in &keys {
+                    m.entry_raw_key(key.as_key(), |e| e.int(record))?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    })
+    .unwrap();
+    let expected = encode_one(|e| {
+        e.array(2, |a| {
+            for record in 0..2 {
+                a.map(3, |m| {
+                    m.entry("b", |e| e.int(record))?;
+                    m.entry("c", |e| e.int(record))?;
+                    m.entry("aa", |e| e.int(record))
+                })?;
+            }
+            Ok(())
+        })
+    });
+    assert_eq!(enc.into_vec(), expected);
+}
+
+#[test]
+fn raw_cbor_checked_validates_fragments_before_splicing() {
+    let limits = DecodeLimits::for_bytes(16);
+    let bytes = encode_one(|enc| {
+        enc.array(2, |a| {
+            a.raw_cbor_checked(&[0x61, b'x'], limits)?;
+            a.int(1)
+        })
+    });
+    assert_eq!(bytes, [0x82, 0x61, b'x', 0x01]);
+
+    let mut enc = Encoder::new();
+    // Overlong integer encoding: well-formed CBOR, but not canonical.
+    let err = enc.raw_cbor_checked(&[0x18, 0x01], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
+    assert!(enc.is_empty());
+    enc.raw_cbor_checked(&[0xf6], limits).unwrap();
+    assert_eq!(enc.into_vec(), [0xf6]);
+
+    let mut enc = Encoder::new();
+    let err = enc
+        .array(1, |a| a.raw_cbor_checked(&[0x01, 0x02], limits))
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TrailingBytes);
+
+    // Profile relaxations in the limits do not let out-of-profile fragments through.
+    const TAGS: &[u64] = &[1];
+    let mut relaxed = limits;
+    relaxed.allow_full_int_range = true;
+    relaxed.extra_allowed_tags = TAGS;
+    let mut enc = Encoder::new();
+    let wide = [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    let err = enc.raw_cbor_checked(&wide, relaxed).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+    let err = enc
+        .array(1, |a| a.raw_cbor_checked(&[0xc1, 0x01], relaxed))
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ForbiddenOrMalformedTag);
+}
+
+#[test]
+fn bytes_from_chunks_writes_a_single_byte_string() {
+    let chunks: [&[u8]; 3] = [b"ab", b"", b"cde"];
+    let bytes = encode_one(|enc| enc.bytes_from_chunks(5, chunks));
+    assert_eq!(bytes, encode_one(|enc| enc.bytes(b"abcde")));
+
+    let mut enc = Encoder::new();
+    let err = enc.bytes_from_chunks(4, chunks).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::LengthOverflow);
+    let err = enc.bytes_from_chunks(6, chunks).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::LengthOverflow);
+    assert!(enc.is_empty());
+    enc.bytes_from_chunks(0, []).unwrap();
+    assert_eq!(enc.into_vec(), [0x40]);
 }
@@ -0,0 +1,275 @@
+use core::fmt::Write;
+
+use sacp_cbor::{CborError, ErrorCategory, ErrorCode};
+
+const ALL: &[ErrorCode] = &[
+    ErrorCode::InvalidLimits,
+    ErrorCode::UnexpectedEof,
+    ErrorCode::LengthOverflow,
+    ErrorCode::TrailingBytes,
+    ErrorCode::AllocationFailed,
+    ErrorCode::ArrayLenMismatch,
+    ErrorCode::MapLenMismatch,
+    ErrorCode::IoError,
+    ErrorCode::BufferTooSmall,
+    ErrorCode::DepthLimitExceeded,
+    ErrorCode::TotalItemsLimitExceeded,
+    ErrorCode::ArrayLenLimitExceeded,
+    ErrorCode::MapLenLimitExceeded,
+    ErrorCode::BytesLenLimitExceeded,
+    ErrorCode::TextLenLimitExceeded,
+    ErrorCode::MessageLenLimitExceeded,
+    ErrorCode::KeyLenLimitExceeded,
+    ErrorCode::ReservedAdditionalInfo,
+    ErrorCode::IndefiniteLengthForbidden,
+    ErrorCode::NonCanonicalEncoding,
+    ErrorCode::MapKeyMustBeText,
+    ErrorCode::DuplicateMapKey,
+    ErrorCode::NonCanonicalMapOrder,
+    ErrorCode::ForbiddenOrMalformedTag,
+    ErrorCode::BignumNotCanonical,
+    ErrorCode::BignumMustBeOutsideSafeRange,
+    ErrorCode::UnsupportedSimpleValue,
+    ErrorCode::IntegerOutsideSafeRange,
+    ErrorCode::Utf8Invalid,
+    ErrorCode::NegativeZeroForbidden,
+    ErrorCode::NonCanonicalNaN,
+    ErrorCode::SerdeError,
+    ErrorCode::ExpectedMap,
+    ErrorCode::ExpectedArray,
+    ErrorCode::ExpectedInteger,
+    ErrorCode::ExpectedText,
+    ErrorCode::ExpectedBytes,
+    ErrorCode::ExpectedBool,
+    ErrorCode::ExpectedNull,
+    ErrorCode::ExpectedFloat,
+    ErrorCode::ExpectedEnum,
+    ErrorCode::UnknownEnumVariant,
+    ErrorCode::ExpectedNumber,
+    ErrorCode::BytesLenMismatch,
+    ErrorCode::ValueOutOfRange,
+    ErrorCode::ExpectedTag,
+    ErrorCode::PatchConflict,
+    ErrorCode::IndexOutOfBounds,
+    ErrorCode::InvalidQuery,
+    ErrorCode::MissingKey,
+    ErrorCode::MalformedCanonical,
+    ErrorCode::WriteFailed,
+    ErrorCode::UnknownKey,
+    ErrorCode::InvalidTextSyntax,
+];
+
+#[test]
+fn error_code_numbers_roundtrip_and_are_unique() {
+    for &code in ALL {
+        assert_eq!(ErrorCode::from_u16(code.as_u16()), Some(code));
+    }
+
+    let assigned = (0..=u16::MAX)
+        .filter_map(ErrorCode::from_u16)
+        .inspect(|code| assert!(ALL.contains(code), "{code:?} missing from ALL"))
+        .count();
+    assert_eq!(assigned, ALL.len());
+
+    assert_eq!(ErrorCode::from_u16(0), None);
+}
+
+/// Wire numbers and labels are a compatibility contract: never edit an existing row.
+const PINNED: &[(ErrorCode, u16, &str)] = &[
+    (ErrorCode::InvalidLimits, 1, "INVALID_LIMITS"),
+    (ErrorCode::UnexpectedEof, 100, "UNEXPECTED_EOF"),
+    (ErrorCode::LengthOverflow, 101, "LENGTH_OVERFLOW"),
+    (ErrorCode::TrailingBytes, 102, "TRAILING_BYTES"),
+    (ErrorCode::AllocationFailed, 103, "ALLOCATION_FAILED"),
+    (ErrorCode::ArrayLenMismatch, 104, "ARRAY_LEN_MISMATCH"),
+    (ErrorCode::MapLenMismatch, 105, "MAP_LEN_MISMATCH"),
+    (ErrorCode::IoError, 106, "IO_ERROR"),
+    (ErrorCode::BufferTooSmall, 107, "BUFFER_TOO_SMALL"),
+    (ErrorCode::DepthLimitExceeded, 200, "DEPTH_LIMIT_EXCEEDED"),
+    (
+        ErrorCode::TotalItemsLimitExceeded,
+        201,
+        "TOTAL_ITEMS_LIMIT_EXCEEDED",
+    ),
+    (
+        ErrorCode::ArrayLenLimitExceeded,
+        202,
+        "ARRAY_LEN_LIMIT_EXCEEDED",
+    ),
+    (
+        ErrorCode::MapLenLimitExceeded,
+        203,
+        "MAP_LEN_LIMIT_EXCEEDED",
+    ),
+    (
+        ErrorCode::BytesLenLimitExceeded,
+        204,
+        "BYTES_LEN_LIMIT_EXCEEDED",
+    ),
+    (
+        ErrorCode::TextLenLimitExceeded,
+        205,
+        "TEXT_LEN_LIMIT_EXCEEDED",
+    ),
+    (
+        ErrorCode::MessageLenLimitExceeded,
+        206,
+        "MESSAGE_LEN_LIMIT_EXCEEDED",
+    ),
+    (
+        ErrorCode::KeyLenLimitExceeded,
+        207,
+        "KEY_LEN_LIMIT_EXCEEDED",
+    ),
+    (
+        ErrorCode::ReservedAdditionalInfo,
+        300,
+        "RESERVED_ADDITIONAL_INFO",
+    ),
+    (
+        ErrorCode::IndefiniteLengthForbidden,
+        301,
+        "INDEFINITE_LENGTH_FORBIDDEN",
+    ),
+    (
+        ErrorCode::NonCanonicalEncoding,
+        302,
+        "NON_CANONICAL_ENCODING",
+    ),
+    (ErrorCode::MapKeyMustBeText, 303, "MAP_KEY_MUST_BE_TEXT"),
+    (ErrorCode::DuplicateMapKey, 304, "DUPLICATE_MAP_KEY"),
+    (
+        ErrorCode::NonCanonicalMapOrder,
+        305,
+        "NON_CANONICAL_MAP_ORDER",
+    ),
+    (
+        ErrorCode::ForbiddenOrMalformedTag,
+        306,
+        "FORBIDDEN_OR_MALFORMED_TAG",
+    ),
+    (ErrorCode::BignumNotCanonical, 307, "BIGNUM_NOT_CANONICAL"),
+    (
+        ErrorCode::BignumMustBeOutsideSafeRange,
+        308,
+        "BIGNUM_MUST_BE_OUTSIDE_SAFE_RANGE",
+    ),
+    (
+        ErrorCode::UnsupportedSimpleValue,
+        309,
+        "UNSUPPORTED_SIMPLE_VALUE",
+    ),
+    (
+        ErrorCode::IntegerOutsideSafeRange,
+        310,
+        "INTEGER_OUTSIDE_SAFE_RANGE",
+    ),
+    (ErrorCode::Utf8Invalid, 311, "UTF8_INVALID"),
+    (
+        ErrorCode::NegativeZeroForbidden,
+        312,
+        "NEGATIVE_ZERO_FORBIDDEN",
+    ),
+    (ErrorCode::NonCanonicalNaN, 313, "NON_CANONICAL_NAN"),
+    (ErrorCode::SerdeError, 400, "SERDE_ERROR"),
+    (ErrorCode::ExpectedMap, 500, "EXPECTED_MAP"),
+    (ErrorCode::ExpectedArray, 501, "EXPECTED_ARRAY"),
+    (ErrorCode::ExpectedInteger, 502, "EXPECTED_INTEGER"),
+    (ErrorCode::ExpectedText, 503, "EXPECTED_TEXT"),
+    (ErrorCode::ExpectedBytes, 504, "EXPECTED_BYTES"),
+    (ErrorCode::ExpectedBool, 505, "EXPECTED_BOOL"),
+    (ErrorCode::ExpectedNull, 506, "EXPECTED_NULL"),
+    (ErrorCode::ExpectedFloat, 507, "EXPECTED_FLOAT"),
+    (ErrorCode::ExpectedEnum, 508, "EXPECTED_ENUM"),
+    (ErrorCode::UnknownEnumVariant, 509, "UNKNOWN_ENUM_VARIANT"),
+    (ErrorCode::ExpectedNumber, 510, "EXPECTED_NUMBER"),
+    (ErrorCode::BytesLenMismatch, 511, "BYTES_LEN_MISMATCH"),
+    (ErrorCode::ValueOutOfRange, 512, "VALUE_OUT_OF_RANGE"),
+    (ErrorCode::ExpectedTag, 513, "EXPECTED_TAG"),
+    (ErrorCode::PatchConflict, 600, "PATCH_CONFLICT"),
+    (ErrorCode::IndexOutOfBounds, 601, "INDEX_OUT_OF_BOUNDS"),
+    (ErrorCode::InvalidQuery, 602, "INVALID_QUERY"),
+    (ErrorCode::MissingKey, 603, "MISSING_KEY"),
+    (ErrorCode::MalformedCanonical, 604, "MALFORMED_CANONICAL"),
+    (ErrorCode::WriteFailed, 605, "WRITE_FAILED"),
+    (ErrorCode::UnknownKey, 606, "UNKNOWN_KEY"),
+    (ErrorCode::InvalidTextSyntax, 700, "INVALID_TEXT_SYNTAX"),
+];
+
+#[test]
+fn error_code_numbers_and_labels_are_pinned() {
+    assert_eq!(PINNED.len(), ALL.len());
+    for &(code, num, label) in PINNED {
+        assert_eq!(code.as_u16(), num, "{code:?}");
+        assert_eq!(code.as_str(), label, "{code:?}");
+    }
+}
+
+#[test]
+fn error_categories() {
+    assert_eq!(
+        ErrorCode::DepthLimitExceeded.category(),
+        ErrorCategory::Limit
+    );
+    assert_eq!(
+        ErrorCode::DuplicateMapKey.category(),
+        ErrorCategory::Profile
+    );
+    assert_eq!(
+        ErrorCode::UnexpectedEof.category(),
+        ErrorCategory::Structural
+    );
+    assert_eq!(ErrorCode::ExpectedMap.category(), ErrorCategory::Serde);
+    assert_eq!(ErrorCode::IoError.category(), ErrorCategory::Io);
+
+    for &code in ALL {
+        let err = CborError::new(code, 0);
+        assert!(!(err.is_limit_error() && err.is_malformed()), "{code:?}");
+    }
+    assert!(CborError::new(ErrorCode::TotalItemsLimitExceeded, 0).is_limit_error());
+    assert!(CborError::new(ErrorCode::MalformedCanonical, 0).is_malformed());
+    assert!(CborError::new(ErrorCode::NonCanonicalNaN, 0).is_malformed());
+    assert!(!CborError::new(ErrorCode::SerdeError, 0).is_malformed());
+}
+
+/// Fixed-capacity formatting target, so the check needs neither `std` nor `alloc`.
+struct StackBuf {
+    bytes: [u8; 160],
+    len: usize,
+}
+
+impl Write for StackBuf {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(core::fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[test]
+fn every_code_has_a_distinct_message() {
+    for (i, &code) in ALL.iter().enumerate() {
+        let msg = code.message();
+        assert!(!msg.is_empty(), "{code:?}");
+        assert!(
+            ALL[..i].iter().all(|other| other.message() != msg),
+            "{code:?} reuses a message"
+        );
+
+        let mut buf = StackBuf {
+            bytes: [0; 160],
+            len: 0,
+        };
+        write!(buf, "{}", CborError::new(code, 7)).unwrap();
+        let shown = core::str::from_utf8(&buf.bytes[..buf.len]).unwrap();
+        assert_eq!(
+            shown.strip_prefix("cbor error at 7: "),
+            Some(msg),
+            "{code:?}"
+        );
+    }
+}
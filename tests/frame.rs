@@ -0,0 +1,42 @@
+#![cfg(feature = "alloc")]
+
+use sacp_cbor::{cbor_bytes, read_frame, write_frame, DecodeLimits, ErrorCode};
+
+#[test]
+fn frames_roundtrip_in_sequence() {
+    let small = cbor_bytes!({ a: 1 }).unwrap();
+    let large = cbor_bytes!({ text: "a string long enough for a two-byte length prefix" }).unwrap();
+    assert!(large.as_bytes().len() >= 24);
+
+    let mut buf = Vec::new();
+    write_frame(small.as_ref(), &mut buf).unwrap();
+    write_frame(large.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf[0], u8::try_from(small.as_bytes().len()).unwrap());
+
+    let limits = DecodeLimits::for_bytes(buf.len());
+    let (first, used) = read_frame(&buf, limits).unwrap();
+    assert_eq!(first, small.as_ref());
+    let (second, used2) = read_frame(&buf[used..], limits).unwrap();
+    assert_eq!(second, large.as_ref());
+    assert_eq!(buf[used], 0x18);
+    assert_eq!(used + used2, buf.len());
+}
+
+#[test]
+fn read_frame_rejects_bad_prefix_and_body() {
+    let limits = DecodeLimits::for_bytes(64);
+
+    let err = read_frame(&[0x18, 0x01, 0xf6], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
+
+    let err = read_frame(&[0x61, b'x'], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedInteger);
+
+    let err = read_frame(&[0x03, 0x82, 0x01], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+
+    // Declared length 2 but the enclosed item is only one byte long.
+    let err = read_frame(&[0x02, 0x01, 0x02], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TrailingBytes);
+    assert_eq!(err.offset(), 2);
+}
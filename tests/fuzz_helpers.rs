@@ -0,0 +1,26 @@
+#![cfg(feature = "fuzz")]
+
+use sacp_cbor::{cbor_bytes, fuzz_decode, fuzz_roundtrip};
+
+#[test]
+fn fuzz_helpers_accept_valid_and_invalid_input() {
+    let doc = cbor_bytes!({
+        a: [1, -2, true, 1.5, null],
+        b: b"raw",
+        big: 18_446_744_073_709_551_615u128,
+        text: "x",
+    })
+    .unwrap();
+    fuzz_decode(doc.as_bytes());
+    fuzz_roundtrip(doc.as_bytes());
+
+    for bad in [
+        &[][..],
+        &[0x18],
+        &[0xa2, 0x61, 0x62, 0x01, 0x61, 0x61, 0x02],
+        &[0xff],
+    ] {
+        fuzz_decode(bad);
+        fuzz_roundtrip(bad);
+    }
+}
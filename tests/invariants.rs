@@ -5,13 +5,13 @@ use sacp_cbor::{ArrayPos, EncodedTextKey, Encoder, ErrorCode};
 #[test]
 fn encoded_text_key_parse_rejects_non_text() {
     let err = EncodedTextKey::parse(&[0x01]).unwrap_err();
-    assert_eq!(err.code, ErrorCode::MapKeyMustBeText);
+    assert_eq!(err.code(), ErrorCode::MapKeyMustBeText);
 }
 
 #[test]
 fn encoded_text_key_parse_rejects_noncanonical() {
     let err = EncodedTextKey::parse(&[0x78, 0x01, b'a']).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalEncoding);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
 }
 
 #[test]
@@ -23,6 +23,28 @@ fn map_encoder_entry_raw_key_accepts_valid_key() {
     assert_eq!(bytes.as_bytes(), &[0xa1, 0x61, b'a', 0xf6]);
 }
 
+#[test]
+fn map_encoder_entry_raw_splices_value_and_checks_order() {
+    let inner = sacp_cbor::cbor_bytes!({ x: [1, 2] }).unwrap();
+    let mut enc = Encoder::new();
+    enc.map(2, |m| {
+        m.entry_raw("a", inner.as_ref())?;
+        m.entry("b", |e| e.null())
+    })
+    .unwrap();
+    let expected = sacp_cbor::cbor_bytes!({ a: { x: [1, 2] }, b: null }).unwrap();
+    assert_eq!(enc.as_bytes(), expected.as_bytes());
+
+    let mut enc = Encoder::new();
+    let err = enc
+        .map(2, |m| {
+            m.entry("b", |e| e.null())?;
+            m.entry_raw("a", inner.as_ref())
+        })
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalMapOrder);
+}
+
 #[test]
 fn splice_insert_inside_delete_conflicts() {
     let bytes = sacp_cbor::cbor_bytes!([0, 1, 2, 3]).unwrap();
@@ -41,7 +63,7 @@ fn splice_insert_inside_delete_conflicts() {
         .finish()
         .unwrap_err();
 
-    assert_eq!(err.code, ErrorCode::PatchConflict);
+    assert_eq!(err.code(), ErrorCode::PatchConflict);
 }
 
 #[test]
@@ -64,5 +86,5 @@ fn splice_end_and_at_len_conflict_on_apply() {
         .unwrap();
 
     let err = editor.apply().unwrap_err();
-    assert_eq!(err.code, ErrorCode::PatchConflict);
+    assert_eq!(err.code(), ErrorCode::PatchConflict);
 }
@@ -0,0 +1,85 @@
+#![cfg(feature = "alloc")]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use sacp_cbor::{cbor_bytes, merge, CanonicalCbor, MergeStrategy};
+
+fn merged(base: &CanonicalCbor, overlay: &CanonicalCbor, strategy: MergeStrategy) -> Vec<u8> {
+    merge(base.as_ref(), overlay.as_ref(), strategy)
+        .unwrap()
+        .into_bytes()
+}
+
+#[test]
+fn nested_maps_merge_recursively() {
+    let defaults = cbor_bytes!({
+        db: { host: "localhost", pool: { max: 10, min: 1 } },
+        log: "info",
+    })
+    .unwrap();
+    let overrides = cbor_bytes!({
+        db: { pool: { max: 50 }, user: "app" },
+        tls: true,
+    })
+    .unwrap();
+
+    let expected = cbor_bytes!({
+        db: { host: "localhost", pool: { max: 50, min: 1 }, user: "app" },
+        log: "info",
+        tls: true,
+    })
+    .unwrap();
+    assert_eq!(
+        merged(&defaults, &overrides, MergeStrategy::Replace),
+        expected.as_bytes()
+    );
+}
+
+#[test]
+fn type_clashes_and_scalars_take_the_overlay() {
+    let base = cbor_bytes!({ a: { b: 1 }, c: 2, d: [1] }).unwrap();
+    let overlay = cbor_bytes!({ a: "flat", c: { nested: true }, d: null }).unwrap();
+    assert_eq!(
+        merged(&base, &overlay, MergeStrategy::Concat),
+        overlay.as_bytes()
+    );
+
+    let root = cbor_bytes!(7).unwrap();
+    assert_eq!(
+        merged(&base, &root, MergeStrategy::Replace),
+        root.as_bytes()
+    );
+}
+
+#[test]
+fn array_strategies() {
+    let base = cbor_bytes!({ tags: ["a", "b"] }).unwrap();
+    let overlay = cbor_bytes!({ tags: ["b", "c"] }).unwrap();
+
+    let cases = [
+        (MergeStrategy::Replace, cbor_bytes!({ tags: ["b", "c"] })),
+        (
+            MergeStrategy::Append,
+            cbor_bytes!({ tags: ["a", "b", "c"] }),
+        ),
+        (
+            MergeStrategy::Concat,
+            cbor_bytes!({ tags: ["a", "b", "b", "c"] }),
+        ),
+    ];
+    for (strategy, expected) in cases {
+        assert_eq!(
+            merged(&base, &overlay, strategy),
+            expected.unwrap().as_bytes()
+        );
+    }
+}
+
+#[test]
+fn append_keeps_repeated_overlay_items_once() {
+    let base = cbor_bytes!(["a", 1]).unwrap();
+    let overlay = cbor_bytes!(["c", "a", "c", 2, 1, 2, "d"]).unwrap();
+    assert_eq!(
+        merged(&base, &overlay, MergeStrategy::Append),
+        cbor_bytes!(["a", 1, "c", 2, "d"]).unwrap().as_bytes()
+    );
+}
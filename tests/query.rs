@@ -1,4 +1,4 @@
-use sacp_cbor::{validate_canonical, DecodeLimits, ErrorCode, PathElem};
+use sacp_cbor::{validate_canonical, CborKind, DecodeLimits, ErrorCode, PathElem, RawReader};
 
 #[test]
 fn map_get_single_int() {
@@ -14,6 +14,25 @@ fn map_get_single_int() {
     assert!(root.map().unwrap().get("missing").unwrap().is_none());
 }
 
+#[test]
+fn map_get_as_decodes_typed_values() {
+    // { "host": "h", "port": 443 }
+    let bytes = [
+        0xa2, 0x64, b'h', b'o', b's', b't', 0x61, b'h', 0x64, b'p', b'o', b'r', b't', 0x19, 0x01,
+        0xbb,
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let map = canon.root().map().unwrap();
+
+    assert_eq!(map.get_as::<u16>("port").unwrap(), Some(443));
+    assert_eq!(map.get_as::<&str>("host").unwrap(), Some("h"));
+    assert_eq!(map.get_as::<u16>("timeout").unwrap().unwrap_or(30), 30);
+
+    let err = map.get_as::<u8>("port").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedInteger);
+    assert_eq!(err.offset(), 13);
+}
+
 #[test]
 fn nested_path_key_key_index() {
     // { "a": { "b": [true, null] } }
@@ -72,7 +91,7 @@ fn type_mismatch_errors() {
 
     let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
     let err = canon.root().get_key("x").unwrap_err();
-    assert_eq!(err.code, ErrorCode::ExpectedMap);
+    assert_eq!(err.code(), ErrorCode::ExpectedMap);
 }
 
 #[test]
@@ -105,7 +124,7 @@ fn get_many_sorted_accepts_unsorted_and_rejects_duplicates() {
     assert_eq!(out[1].unwrap().integer().unwrap().as_i64().unwrap(), 1);
 
     let err = map.get_many_sorted(["a", "a"]).unwrap_err();
-    assert_eq!(err.code, ErrorCode::InvalidQuery);
+    assert_eq!(err.code(), ErrorCode::InvalidQuery);
 }
 
 #[test]
@@ -208,3 +227,607 @@ fn get_many_sorted_respects_input_order_not_canonical_order() {
     assert_eq!(out[0].unwrap().integer().unwrap().as_i64().unwrap(), 2);
     assert_eq!(out[1].unwrap().integer().unwrap().as_i64().unwrap(), 1);
 }
+
+#[test]
+fn content_hash_and_dedup_group_byte_equal_values() {
+    // [1, "a", 1, [1], "a", 2]
+    let bytes = [0x86, 0x01, 0x61, 0x61, 0x01, 0x81, 0x01, 0x61, 0x61, 0x02];
+
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let values: Vec<_> = canon
+        .root()
+        .array()
+        .unwrap()
+        .iter()
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(values[0].content_hash(), values[2].content_hash());
+    assert_ne!(values[0].content_hash(), values[5].content_hash());
+
+    let groups = sacp_cbor::dedup(&values).unwrap();
+    assert_eq!(groups, vec![0, 1, 0, 3, 1, 5]);
+    assert!(sacp_cbor::dedup(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn equal_values_hash_equal() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use std::hash::{Hash, Hasher};
+
+    // [{"k": 1}, {"k": 1}, {"k": 2}]
+    let bytes = [
+        0x83, 0xa1, 0x61, b'k', 0x01, 0xa1, 0x61, b'k', 0x01, 0xa1, 0x61, b'k', 0x02,
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let values: Vec<_> = canon
+        .root()
+        .array()
+        .unwrap()
+        .iter()
+        .map(Result::unwrap)
+        .collect();
+
+    let hash = |v: &sacp_cbor::CborValueRef<'_>| {
+        let mut h = DefaultHasher::new();
+        v.hash(&mut h);
+        h.finish()
+    };
+    assert_eq!(values[0], values[1]);
+    assert_eq!(hash(&values[0]), hash(&values[1]));
+    assert_eq!(values[0].content_hash(), values[1].content_hash());
+    assert_ne!(values[0].content_hash(), values[2].content_hash());
+
+    let mut counts = HashMap::new();
+    for v in &values {
+        *counts.entry(*v).or_insert(0) += 1;
+    }
+    assert_eq!(counts[&values[0]], 2);
+    assert_eq!(counts[&values[2]], 1);
+}
+
+#[test]
+fn ct_eq_bytes_compares_byte_strings() {
+    // [h'010203', "x"]
+    let bytes = [0x82, 0x43, 0x01, 0x02, 0x03, 0x61, 0x78];
+
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let arr = canon.root().array().unwrap();
+    let tag = arr.get(0).unwrap().unwrap();
+
+    assert!(tag.ct_eq_bytes(&[1, 2, 3]).unwrap());
+    assert!(!tag.ct_eq_bytes(&[1, 2, 4]).unwrap());
+    assert!(!tag.ct_eq_bytes(&[1, 2]).unwrap());
+
+    let err = arr.get(1).unwrap().unwrap().ct_eq_bytes(b"x").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedBytes);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn top_level_keys_lists_root_map_keys() {
+    // { "b": [1, 2], "aa": { "x": null } }
+    let bytes = [
+        0xa2, 0x61, b'b', 0x82, 0x01, 0x02, 0x62, b'a', b'a', 0xa1, 0x61, b'x', 0xf6,
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    assert_eq!(canon.top_level_keys().unwrap(), vec!["b", "aa"]);
+
+    let arr = [0x80];
+    let canon = validate_canonical(&arr, DecodeLimits::for_bytes(arr.len())).unwrap();
+    assert_eq!(
+        canon.top_level_keys().unwrap_err().code(),
+        ErrorCode::ExpectedMap
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn annotate_writes_structure_with_offsets() {
+    // { "id": 42, "tags": ["x", h'0102'] }
+    let bytes = [
+        0xa2, 0x62, b'i', b'd', 0x18, 0x2a, 0x64, b't', b'a', b'g', b's', 0x82, 0x61, b'x', 0x42,
+        0x01, 0x02,
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let mut out = String::new();
+    canon.annotate(&mut out).unwrap();
+    assert_eq!(
+        out,
+        "0000: map(2)\n  0001: \"id\" => int(42)\n  0006: \"tags\" => array(2)\n    000c: \"x\"\n    000e: bytes(2)\n"
+    );
+}
+
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+#[test]
+fn shape_digest_ignores_scalar_values() {
+    let a = sacp_cbor::cbor_bytes!({ id: 1, tags: ["x", "y"], ok: true }).unwrap();
+    let b = sacp_cbor::cbor_bytes!({ id: 99, tags: ["long", "z"], ok: false }).unwrap();
+    assert_eq!(
+        a.root().shape_digest().unwrap(),
+        b.root().shape_digest().unwrap()
+    );
+
+    let other_key = sacp_cbor::cbor_bytes!({ id: 1, tags: ["x", "y"], no: true }).unwrap();
+    let other_len = sacp_cbor::cbor_bytes!({ id: 1, tags: ["x"], ok: true }).unwrap();
+    let other_kind = sacp_cbor::cbor_bytes!({ id: "1", tags: ["x", "y"], ok: true }).unwrap();
+    for doc in [other_key, other_len, other_kind] {
+        assert_ne!(
+            a.root().shape_digest().unwrap(),
+            doc.root().shape_digest().unwrap()
+        );
+    }
+}
+
+/// `{ "a": 1({ "b": 7 }), "t": 1(1700000000) }` under `extra_allowed_tags = [1]`.
+const TAGGED: [u8; 16] = [
+    0xa2, 0x61, b'a', 0xc1, 0xa1, 0x61, b'b', 0x07, 0x61, b't', 0xc1, 0x1a, 0x65, 0x53, 0xf1, 0x00,
+];
+
+fn tagged_limits() -> DecodeLimits {
+    DecodeLimits::for_bytes(TAGGED.len()).with_extra_allowed_tags(&[1])
+}
+
+#[test]
+fn passthrough_tags_have_their_own_kind() {
+    let doc = sacp_cbor::validate_relaxed(&TAGGED, tagged_limits()).unwrap();
+    let t = doc.pointer("/t").unwrap().unwrap();
+    assert_eq!(t.kind().unwrap(), CborKind::Tag);
+    assert_eq!(t.offset(), 10);
+    assert!(t.expect(CborKind::Tag).is_ok());
+    let err = t.expect(CborKind::Integer).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedInteger);
+    assert_eq!(err.found_kind(), Some(CborKind::Tag));
+    let int = t.tag().unwrap().unwrap().1;
+    let err = int.expect(CborKind::Tag).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedTag);
+
+    // Pointers step through tags into their content.
+    let b = doc.pointer("/a/b").unwrap().unwrap();
+    assert_eq!(b.integer().unwrap().as_i64(), Some(7));
+    assert!(doc.pointer("/t/0").unwrap().is_none());
+
+    // A tagged value is not a number, even when its content is.
+    let err = t.numeric_cmp(int).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedNumber);
+    assert_eq!(err.offset(), 10);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn passthrough_tags_render() {
+    let doc = sacp_cbor::validate_relaxed(&TAGGED, tagged_limits()).unwrap();
+    assert_eq!(
+        doc.root().display_pretty(2).unwrap(),
+        "a: !1\n  b: 7\nt: !1 1700000000"
+    );
+    let mut out = String::new();
+    doc.annotate(&mut out).unwrap();
+    assert_eq!(
+        out,
+        "0000: map(2)\n  0001: \"a\" => tag(1)\n    0004: map(1)\n      0005: \"b\" => int(7)\n  0008: \"t\" => tag(1)\n    000b: int(1700000000)\n"
+    );
+}
+
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+#[test]
+fn shape_digest_covers_tag_numbers() {
+    let digest = |bytes: &[u8]| {
+        let limits = DecodeLimits::for_bytes(bytes.len()).with_extra_allowed_tags(&[0, 1]);
+        sacp_cbor::validate_relaxed(bytes, limits)
+            .unwrap()
+            .root()
+            .shape_digest()
+            .unwrap()
+    };
+    let base = digest(&[0xc1, 0x01]);
+    assert_eq!(base, digest(&[0xc1, 0x18, 0x2a]));
+    assert_ne!(base, digest(&[0xc0, 0x01]));
+    assert_ne!(base, digest(&[0x01]));
+    assert_ne!(base, digest(&[0xc1, 0x60]));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn typed_path_accessors() {
+    use sacp_cbor::path;
+
+    let doc = sacp_cbor::cbor_bytes!({
+        user: { name: "ada", age: 36, admin: true, key: b"\x01\x02", score: 1.5 },
+        big: 18_446_744_073_709_551_615u128,
+    })
+    .unwrap();
+    let root = doc.root();
+
+    assert_eq!(root.at_text(path!("user", "name")).unwrap(), Some("ada"));
+    assert_eq!(root.at_i64(path!("user", "age")).unwrap(), Some(36));
+    assert_eq!(root.at_bool(path!("user", "admin")).unwrap(), Some(true));
+    assert_eq!(
+        root.at_bytes(path!("user", "key")).unwrap(),
+        Some(&[1u8, 2][..])
+    );
+    assert_eq!(root.at_f64(path!("user", "score")).unwrap(), Some(1.5));
+    assert_eq!(root.at_text(path!("user", "missing")).unwrap(), None);
+
+    assert_eq!(
+        root.at_text(path!("user", "age")).unwrap_err().code(),
+        ErrorCode::ExpectedText
+    );
+    assert_eq!(
+        root.at_i64(path!("big")).unwrap_err().code(),
+        ErrorCode::IntegerOutsideSafeRange
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn numeric_cmp_orders_ints_bignums_and_floats() {
+    use core::cmp::Ordering;
+    use sacp_cbor::{Encoder, F64Bits};
+
+    let two_64 = [0x01, 0, 0, 0, 0, 0, 0, 0, 0];
+    let f = |v: f64| F64Bits::try_from_f64(v).unwrap();
+    let mut enc = Encoder::new();
+    // Listed in ascending numeric order.
+    enc.array(12, |a| {
+        a.float(f(f64::NEG_INFINITY))?;
+        a.bignum(true, &two_64)?; // -2^64 - 1
+        a.float(f(-18_446_744_073_709_551_616.0))?; // -2^64
+        a.int(-5)?;
+        a.int(3)?;
+        a.float(f(3.5))?;
+        a.int(9_007_199_254_740_991)?;
+        a.float(f(9_007_199_254_740_992.0))?;
+        a.float(f(18_446_744_073_709_551_616.0))?; // 2^64
+        a.bignum(false, &two_64)?; // 2^64
+        a.float(f(1e300))?;
+        a.float(f(f64::NAN))
+    })
+    .unwrap();
+    let doc = enc.into_canonical().unwrap();
+    let values: Vec<_> = doc
+        .root()
+        .array()
+        .unwrap()
+        .iter()
+        .map(Result::unwrap)
+        .collect();
+
+    for (i, a) in values.iter().enumerate() {
+        for (j, b) in values.iter().enumerate() {
+            let expected = if (i, j) == (8, 9) || (i, j) == (9, 8) {
+                Ordering::Equal
+            } else {
+                i.cmp(&j)
+            };
+            assert_eq!(a.numeric_cmp(*b).unwrap(), expected, "{i} vs {j}");
+        }
+    }
+
+    // "3"
+    let text = validate_canonical(&[0x61, b'3'], DecodeLimits::for_bytes(2)).unwrap();
+    let err = values[0].numeric_cmp(text.root()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedNumber);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn numeric_cmp_orders_wide_integers() {
+    use core::cmp::Ordering;
+    use sacp_cbor::{validate_relaxed, CborEncode, CborError, Encoder, F64Bits};
+
+    struct Wide(i128);
+    impl CborEncode for Wide {
+        fn encode(&self, enc: &mut Encoder) -> Result<(), CborError> {
+            enc.int_exact(self.0)
+        }
+    }
+
+    let two_64 = [0x01, 0, 0, 0, 0, 0, 0, 0, 0];
+    let f = |v: f64| F64Bits::try_from_f64(v).unwrap();
+    let mut enc = Encoder::new();
+    // Listed in ascending numeric order.
+    enc.array(14, |a| {
+        a.bignum(true, &two_64)?; // -2^64 - 1
+        a.bignum(true, &[0xff; 8])?; // -2^64
+        a.value(&Wide(-(1i128 << 64)))?;
+        a.float(f(-9_223_372_036_854_775_808.0))?; // -2^63
+        a.value(&Wide(1 - (1i128 << 63)))?;
+        a.value(&Wide(-(1i128 << 53)))?;
+        a.float(f(-9_007_199_254_740_991.0))?;
+        a.int(0)?;
+        a.value(&Wide(1i128 << 53))?;
+        a.float(f(9_007_199_254_740_992.0))?; // 2^53
+        a.float(f(9_007_199_254_740_994.0))?;
+        a.value(&Wide(i128::from(u64::MAX)))?;
+        a.float(f(18_446_744_073_709_551_616.0))?; // 2^64
+        a.bignum(false, &two_64)
+    })
+    .unwrap();
+    let bytes = enc.into_vec();
+    let limits = DecodeLimits::for_bytes(bytes.len()).with_allow_full_int_range(true);
+    let doc = validate_relaxed(&bytes, limits).unwrap();
+    let values: Vec<_> = doc
+        .root()
+        .array()
+        .unwrap()
+        .iter()
+        .map(Result::unwrap)
+        .collect();
+
+    let equal = [(1, 2), (8, 9), (12, 13)];
+    for (i, a) in values.iter().enumerate() {
+        for (j, b) in values.iter().enumerate() {
+            let expected = if equal.contains(&(i, j)) || equal.contains(&(j, i)) {
+                Ordering::Equal
+            } else {
+                i.cmp(&j)
+            };
+            assert_eq!(a.numeric_cmp(*b).unwrap(), expected, "{i} vs {j}");
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn map_positional_and_indexed_lookup() {
+    let doc = sacp_cbor::cbor_bytes!({ b: 2, a: 1, aa: [3], c: null }).unwrap();
+    let map = doc.root().map().unwrap();
+
+    let keys: Vec<&str> = (0..map.len())
+        .map(|i| map.index(i).unwrap().unwrap().0)
+        .collect();
+    assert_eq!(keys, ["a", "b", "c", "aa"]);
+    let (_, aa) = map.index(3).unwrap().unwrap();
+    assert_eq!(aa.as_bytes(), [0x81, 0x03]);
+    assert!(map.index(4).unwrap().is_none());
+
+    let index = map.build_index().unwrap();
+    assert_eq!(index.len(), 4);
+    for key in ["a", "b", "c", "aa"] {
+        assert_eq!(index.get(key), map.get(key).unwrap(), "key {key}");
+    }
+    assert!(index.get("bb").is_none());
+    assert!(index.get("").is_none());
+    assert_eq!(index.index(1).unwrap().0, "b");
+    assert!(index.index(4).is_none());
+}
+
+#[test]
+fn json_pointer_lookup() {
+    // { "a/b": [10, { "0": 2, "~": 1 }] }
+    let bytes = [
+        0xa1, 0x63, b'a', b'/', b'b', 0x82, 0x0a, 0xa2, 0x61, b'0', 0x02, 0x61, b'~', 0x01,
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let int_at = |ptr: &str| {
+        canon
+            .pointer(ptr)
+            .unwrap()
+            .map(|v| v.integer().unwrap().as_i64().unwrap())
+    };
+
+    assert_eq!(canon.pointer("").unwrap().unwrap().offset(), 0);
+    assert_eq!(int_at("/a~1b/0"), Some(10));
+    assert_eq!(int_at("/a~1b/1/~0"), Some(1));
+    // Numeric tokens are keys when the current value is a map.
+    assert_eq!(int_at("/a~1b/1/0"), Some(2));
+
+    assert_eq!(int_at("/a~1b/2"), None);
+    assert_eq!(int_at("/a~1b/01"), None);
+    assert_eq!(int_at("/a~1b/0/x"), None);
+    assert_eq!(int_at("/a/b"), None);
+    assert_eq!(int_at("/missing"), None);
+
+    let err = canon.pointer("a").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidTextSyntax);
+    assert_eq!(err.offset(), 0);
+    let err = canon.pointer("/missing/a~2").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidTextSyntax);
+    assert_eq!(err.offset(), 10);
+}
+
+#[test]
+fn array_chunks_are_queryable_sub_arrays() {
+    // [1, "two", [3], 4, 5, 6, 7]
+    let bytes = [
+        0x87, 0x01, 0x63, b't', b'w', b'o', 0x81, 0x03, 0x04, 0x05, 0x06, 0x07,
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let array = canon.root().array().unwrap();
+
+    let chunks: Vec<_> = array.chunks(3).map(Result::unwrap).collect();
+    assert_eq!(
+        chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+        [3, 3, 1]
+    );
+    assert_eq!(chunks[0].get(1).unwrap().unwrap().text().unwrap(), "two");
+    assert_eq!(chunks[0].get(2).unwrap().unwrap().offset(), 6);
+    let second: Vec<_> = chunks[1]
+        .iter()
+        .map(|v| v.unwrap().integer().unwrap().as_i64().unwrap())
+        .collect();
+    assert_eq!(second, [4, 5, 6]);
+    assert!(chunks[2].get(1).unwrap().is_none());
+
+    assert_eq!(array.chunks(10).count(), 1);
+    let empty = validate_canonical(&[0x80], DecodeLimits::for_bytes(1)).unwrap();
+    assert_eq!(empty.root().array().unwrap().chunks(2).count(), 0);
+
+    let slices: Vec<_> = array.as_slices().map(Result::unwrap).collect();
+    assert_eq!(slices.len(), 7);
+    assert_eq!(slices[1], [0x63, b't', b'w', b'o']);
+    assert_eq!(slices[2], [0x81, 0x03]);
+    assert_eq!(slices.concat(), bytes[1..]);
+}
+
+#[test]
+fn expect_kind_reports_actual_kind() {
+    // { "a": [true] }
+    let bytes = [0xa1, 0x61, 0x61, 0x81, 0xf5];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let root = canon.root();
+
+    let map = root.expect(CborKind::Map).unwrap().map().unwrap();
+    let a = map.get("a").unwrap().unwrap();
+    let err = a.expect(CborKind::Text).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ExpectedText);
+    assert_eq!(err.offset(), 3);
+    assert_eq!(err.found_kind(), Some(CborKind::Array));
+    #[cfg(feature = "alloc")]
+    assert_eq!(
+        err.to_string(),
+        "cbor error at 3: expected CBOR text string (found array)"
+    );
+
+    // A secondary offset is not mistaken for a kind.
+    let err = sacp_cbor::CborError::new(ErrorCode::ExpectedText, 5).with_detail(3);
+    assert_eq!(err.found_kind(), None);
+    #[cfg(feature = "alloc")]
+    assert_eq!(
+        err.to_string(),
+        "cbor error at 5: expected CBOR text string (prev key at 3)"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn display_pretty_renders_tree() {
+    let mut enc = sacp_cbor::Encoder::new();
+    enc.map(5, |m| {
+        m.entry("e", |e| e.map(0, |_| Ok(())))?;
+        m.entry("id", |e| e.int(42))?;
+        m.entry("big", |e| e.bignum(true, &[0x20, 0, 0, 0, 0, 0, 0]))?;
+        m.entry("opt", |e| e.null())?;
+        m.entry("tags", |e| {
+            e.array(3, |a| {
+                a.text("x")?;
+                a.bytes(&[1, 2])?;
+                a.array(1, |a| a.bool(true))
+            })
+        })
+    })
+    .unwrap();
+    let canon = enc.into_canonical().unwrap();
+    let expected = "e: {}\nid: 42\nbig: -9007199254740993\nopt: null\ntags:\n  - \"x\"\n  - 0x0102\n  -\n    - true";
+    assert_eq!(canon.root().display_pretty(2).unwrap(), expected);
+
+    let root = canon.root().map().unwrap().get("id").unwrap().unwrap();
+    assert_eq!(root.display_pretty(2).unwrap(), "42");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn display_pretty_escapes_keys() {
+    let mut enc = sacp_cbor::Encoder::new();
+    enc.map(4, |m| {
+        m.entry("", |e| e.int(0))?;
+        m.entry("a.b", |e| e.int(1))?;
+        m.entry("x\nadmin: true", |e| e.int(2))?;
+        m.entry("ok\nadmin: true", |e| e.int(3))
+    })
+    .unwrap();
+    let canon = enc.into_canonical().unwrap();
+    let out = canon.root().display_pretty(2).unwrap();
+    assert_eq!(
+        out,
+        "\"\": 0\na.b: 1\n\"x\\nadmin: true\": 2\n\"ok\\nadmin: true\": 3"
+    );
+    // A newline in a key never starts a line of its own.
+    assert_eq!(out.lines().count(), 4);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn map_iter_encoded_reemits_keys_verbatim() {
+    let src = sacp_cbor::cbor_bytes!({ a: 1, bb: "keep", ccc: [true] }).unwrap();
+    let map = src.root().map().unwrap();
+
+    let mut enc = sacp_cbor::Encoder::new();
+    enc.map(map.len(), |m| {
+        for entry in map.iter_encoded() {
+            let (key, encoded, value) = entry?;
+            assert_eq!(&encoded.as_bytes()[1..], key.as_bytes());
+            if key == "a" {
+                m.entry_raw_key(encoded, |e| e.int(2))?;
+            } else {
+                m.entry_raw_key(encoded, |e| e.raw_value_ref(value))?;
+            }
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    let expected = sacp_cbor::cbor_bytes!({ a: 2, bb: "keep", ccc: [true] }).unwrap();
+    assert_eq!(enc.as_bytes(), expected.as_bytes());
+}
+
+#[test]
+fn raw_reader_walks_headers_and_payloads() {
+    // { "a": 1000, "bb": h'ff' }
+    let bytes = [
+        0xa2, 0x61, b'a', 0x19, 0x03, 0xe8, 0x62, b'b', b'b', 0x41, 0xff,
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let mut r = RawReader::new(canon);
+
+    let (major, ai, off) = r.read_header().unwrap();
+    assert_eq!((major, off), (5, 0));
+    assert_eq!(r.read_len(ai, off).unwrap(), 2);
+
+    let (major, ai, off) = r.read_header().unwrap();
+    let n = r.read_len(ai, off).unwrap();
+    assert_eq!((major, r.read_bytes(n).unwrap()), (3, &b"a"[..]));
+    let (major, ai, off) = r.read_header().unwrap();
+    assert_eq!((major, r.read_uint_arg(ai, off).unwrap()), (0, 1000));
+
+    let (_, ai, off) = r.read_header().unwrap();
+    let n = r.read_len(ai, off).unwrap();
+    assert_eq!(r.read_bytes(n).unwrap(), b"bb");
+    let (major, ai, off) = r.read_header().unwrap();
+    assert_eq!(major, 2);
+    let n = r.read_len(ai, off).unwrap();
+    assert_eq!(r.read_bytes(n).unwrap(), [0xff]);
+    assert_eq!((r.position(), r.remaining()), (bytes.len(), 0));
+
+    let err = r.read_header().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MalformedCanonical);
+    assert_eq!(err.offset(), bytes.len());
+}
+
+#[test]
+fn map_keys_and_values_follow_canonical_order() {
+    // { "b": 2, "aa": [1], "a": null } encoded canonically: "a", "b", "aa".
+    let bytes = [
+        0xa3, 0x61, b'a', 0xf6, 0x61, b'b', 0x02, 0x62, b'a', b'a', 0x81, 0x01,
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let map = canon.root().map().unwrap();
+
+    let keys: Vec<&str> = map.keys().collect::<Result<_, _>>().unwrap();
+    assert_eq!(keys, ["a", "b", "aa"]);
+
+    let kinds: Vec<CborKind> = map.values().map(|v| v.unwrap().kind().unwrap()).collect();
+    assert_eq!(kinds, [CborKind::Null, CborKind::Integer, CborKind::Array]);
+    assert_eq!(map.values().nth(2).unwrap().unwrap().offset(), 10);
+}
+
+#[test]
+fn container_len_reads_only_the_header() {
+    // { "a": [1, 2, 3], "b": "x" }
+    let bytes = [
+        0xa2, 0x61, b'a', 0x83, 0x01, 0x02, 0x03, 0x61, b'b', 0x61, b'x',
+    ];
+    let canon = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let root = canon.root();
+    assert_eq!(root.container_len().unwrap(), Some(2));
+    assert_eq!(
+        root.get_key("a").unwrap().unwrap().container_len().unwrap(),
+        Some(3)
+    );
+    assert_eq!(
+        root.get_key("b").unwrap().unwrap().container_len().unwrap(),
+        None
+    );
+}
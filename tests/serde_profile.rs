@@ -1,13 +1,16 @@
 #![cfg(feature = "serde")]
 
-use sacp_cbor::{from_slice, to_vec, DecodeLimits, ErrorCode};
+use sacp_cbor::{
+    from_slice, from_slice_prefix, from_slice_traced, to_vec, to_vec_with, validate_canonical,
+    DecodeLimits, Encoder, ErrorCode, MapKeyPolicy, PathElem,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 #[test]
 fn serde_rejects_negative_zero() {
     let err = to_vec(&(-0.0_f64)).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NegativeZeroForbidden);
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
 }
 
 #[test]
@@ -35,7 +38,74 @@ fn serde_rejects_non_text_map_keys() {
     m.insert(1u8, 2u8);
 
     let err = to_vec(&m).unwrap_err();
-    assert_eq!(err.code, ErrorCode::MapKeyMustBeText);
+    assert_eq!(err.code(), ErrorCode::MapKeyMustBeText);
+}
+
+#[test]
+fn serde_integer_keys_as_decimal_text_is_opt_in() {
+    let m: BTreeMap<u32, bool> = [(1, true), (10, false), (2, true)].into_iter().collect();
+    let err = to_vec_with(&m, MapKeyPolicy::TextOnly).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MapKeyMustBeText);
+
+    let bytes = to_vec_with(&m, MapKeyPolicy::IntegersAsDecimalText).unwrap();
+    let decoded: BTreeMap<String, bool> =
+        from_slice(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    let expected: BTreeMap<String, bool> = [("1", true), ("2", true), ("10", false)]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    assert_eq!(decoded, expected);
+
+    // Negative keys sort before positive ones numerically but not canonically.
+    let m: BTreeMap<i32, bool> = [(-1, true), (1, true)].into_iter().collect();
+    let bytes = to_vec_with(&m, MapKeyPolicy::IntegersAsDecimalText).unwrap();
+    let decoded: Vec<(String, bool)> = map_entries(&bytes);
+    assert_eq!(decoded, [("1".to_string(), true), ("-1".to_string(), true)]);
+}
+
+fn map_entries(bytes: &[u8]) -> Vec<(String, bool)> {
+    let canon = validate_canonical(bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    canon
+        .root()
+        .map()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            let (k, v) = entry.unwrap();
+            (k.to_string(), v.bool().unwrap())
+        })
+        .collect()
+}
+
+/// A map that serializes its entries in whatever order they were inserted.
+struct Unordered(Vec<(i64, bool)>);
+
+impl Serialize for Unordered {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(k, v)| (k, v)))
+    }
+}
+
+#[test]
+fn integer_keys_from_unordered_map_are_sorted() {
+    let entries: Vec<(i64, bool)> = (-20..20).map(|n| (n * 7, n % 2 == 0)).collect();
+    let bytes = to_vec_with(
+        &Unordered(entries.clone()),
+        MapKeyPolicy::IntegersAsDecimalText,
+    )
+    .unwrap();
+    let decoded = map_entries(&bytes);
+    assert_eq!(decoded.len(), entries.len());
+    for (k, v) in &decoded {
+        let k: i64 = k.parse().unwrap();
+        assert!(entries.contains(&(k, *v)));
+    }
+    // Same entries, different insertion order: identical bytes.
+    let reversed = Unordered(entries.into_iter().rev().collect());
+    assert_eq!(
+        to_vec_with(&reversed, MapKeyPolicy::IntegersAsDecimalText).unwrap(),
+        bytes
+    );
 }
 
 #[test]
@@ -97,3 +167,177 @@ fn serde_roundtrip_vec_and_option() {
     let decoded: Option<u8> = from_slice(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
     assert_eq!(decoded, opt);
 }
+
+#[test]
+fn serde_values_inside_manual_containers() {
+    #[derive(Serialize)]
+    struct Point {
+        x: i64,
+        label: String,
+    }
+
+    let p = Point {
+        x: 3,
+        label: "p".to_string(),
+    };
+    let standalone = to_vec(&p).unwrap();
+
+    let mut enc = Encoder::new();
+    enc.array(2, |a| {
+        a.serde_value(&p)?;
+        a.int(1)
+    })
+    .unwrap();
+    let bytes = enc.into_vec();
+    assert_eq!(bytes[0], 0x82);
+    assert_eq!(&bytes[1..=standalone.len()], standalone.as_slice());
+
+    let mut enc = Encoder::new();
+    enc.map(2, |m| {
+        m.entry("a", |e| e.int(0))?;
+        m.entry_serde("pt", &p)
+    })
+    .unwrap();
+    let bytes = enc.into_vec();
+    assert!(bytes.ends_with(&standalone));
+
+    let mut enc = Encoder::new();
+    let err = enc.array(1, |a| a.serde_value(&-0.0_f64)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
+}
+
+#[test]
+fn serde_flatten_collects_unknown_entries() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Envelope {
+        id: u32,
+        #[serde(flatten)]
+        extra: BTreeMap<String, i64>,
+    }
+
+    let mut enc = Encoder::new();
+    enc.map(4, |m| {
+        m.entry("z", |e| e.int(-1))?;
+        m.entry("bb", |e| e.int(2))?;
+        m.entry("id", |e| e.int(7))?;
+        m.entry("big", |e| e.int_i128(i128::from(i64::MAX)))
+    })
+    .unwrap();
+    let bytes = enc.into_vec();
+    let value: Envelope = from_slice(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap();
+    assert_eq!(value.id, 7);
+    let extra: Vec<_> = value.extra.into_iter().collect();
+    assert_eq!(
+        extra,
+        [
+            ("bb".to_string(), 2),
+            ("big".to_string(), i64::MAX),
+            ("z".to_string(), -1)
+        ]
+    );
+
+    // The flattened struct still sees canonical-order checks on the whole map.
+    let bad = [0xa2, 0x62, b'i', b'd', 0x07, 0x61, b'z', 0x01];
+    let err = from_slice::<Envelope>(&bad, DecodeLimits::for_bytes(bad.len())).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalMapOrder);
+    assert_eq!((err.offset(), err.detail()), (5, Some(1)));
+}
+
+#[test]
+fn serde_prefix_reports_consumed_length() {
+    let mut bytes = to_vec(&vec![1u32, 2, 3]).unwrap();
+    let first = bytes.len();
+    bytes.extend(to_vec(&"next").unwrap());
+    let limits = DecodeLimits::for_bytes(bytes.len());
+
+    let (items, used) = from_slice_prefix::<Vec<u32>>(&bytes, limits).unwrap();
+    assert_eq!((items, used), (vec![1, 2, 3], first));
+    let (text, used) = from_slice_prefix::<String>(&bytes[first..], limits).unwrap();
+    assert_eq!((text.as_str(), used), ("next", 5));
+
+    let err = from_slice::<Vec<u32>>(&bytes, limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TrailingBytes);
+    let err = from_slice_prefix::<Vec<u32>>(&bytes[..first - 1], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+
+    // Only the item must fit the input limit, not the trailing data.
+    let item_only = DecodeLimits::for_bytes(first);
+    assert!(from_slice_prefix::<Vec<u32>>(&bytes, item_only).is_ok());
+    let too_small = DecodeLimits::for_bytes(first - 1);
+    let err = from_slice_prefix::<Vec<u32>>(&bytes, too_small).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MessageLenLimitExceeded);
+}
+
+#[test]
+fn serde_traced_errors_report_the_failing_path() {
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Tls {
+        cert: String,
+    }
+    #[derive(Debug, Serialize, Deserialize)]
+    enum Transport {
+        Tcp { port: u16 },
+        Tls(Tls),
+    }
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Config {
+        name: String,
+        servers: Vec<Transport>,
+    }
+
+    let mut enc = Encoder::new();
+    enc.map(2, |m| {
+        m.entry("name", |e| e.text("edge"))?;
+        m.entry("servers", |e| {
+            e.array(2, |a| {
+                a.map(1, |v| {
+                    v.entry("Tcp", |e| e.map(1, |p| p.entry("port", |e| e.int(80))))
+                })?;
+                // The `Tls` payload lacks `cert`.
+                a.map(1, |v| v.entry("Tls", |e| e.map(0, |_| Ok(()))))
+            })
+        })
+    })
+    .unwrap();
+    let bytes = enc.into_vec();
+    let limits = DecodeLimits::for_bytes(bytes.len());
+
+    let err = from_slice_traced::<Config>(&bytes, limits).unwrap_err();
+    assert_eq!(err.error.code(), ErrorCode::SerdeError);
+    assert_eq!(
+        err.path,
+        [
+            PathElem::Key("servers"),
+            PathElem::Index(1),
+            PathElem::Key("Tls")
+        ]
+    );
+    assert!(err.to_string().ends_with(" at /servers/1/Tls"));
+    assert_eq!(from_slice::<Config>(&bytes, limits).unwrap_err(), err.error);
+
+    // A type mismatch deep in the document, and a success, decode as usual.
+    let bad_port = to_vec(&BTreeMap::from([("port", "eighty")])).unwrap();
+    let err = from_slice_traced::<BTreeMap<String, u16>>(&bad_port, limits).unwrap_err();
+    assert_eq!(err.path, [PathElem::Key("port")]);
+    let good = to_vec(&vec![1u8, 2]).unwrap();
+    assert_eq!(from_slice_traced::<Vec<u8>>(&good, limits).unwrap(), [1, 2]);
+}
+
+#[test]
+fn deserialize_any_rejects_passthrough_tags() {
+    #[derive(Debug, Deserialize)]
+    #[serde(untagged)]
+    #[allow(dead_code)]
+    enum Any {
+        Int(i64),
+        Text(String),
+    }
+
+    // [1(1)] with tag 1 admitted: only `IgnoredAny` can step over the tag.
+    let bytes = [0x81, 0xc1, 0x01];
+    let limits = DecodeLimits::for_bytes(bytes.len()).with_extra_allowed_tags(&[1]);
+    from_slice::<Vec<serde::de::IgnoredAny>>(&bytes, limits).unwrap();
+    let err = from_slice::<Vec<Any>>(&bytes, limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ForbiddenOrMalformedTag);
+    assert_eq!(err.offset(), 1);
+}
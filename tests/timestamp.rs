@@ -0,0 +1,81 @@
+#![cfg(all(feature = "alloc", any(feature = "chrono", feature = "time")))]
+
+use sacp_cbor::{decode, encode_to_vec, DecodeLimits, ErrorCode, MAX_SAFE_INTEGER_I64};
+
+const LIMITS: DecodeLimits = DecodeLimits::unlimited();
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_datetimes_encode_as_epoch_millis() {
+    use chrono::{DateTime, Utc};
+
+    let at = DateTime::from_timestamp(1_700_000_000, 123_456_789).unwrap();
+    let bytes = encode_to_vec(&at).unwrap();
+    assert_eq!(bytes, encode_to_vec(&1_700_000_000_123i64).unwrap());
+    let back = decode::<DateTime<Utc>>(&bytes, LIMITS).unwrap();
+    assert_eq!(
+        back,
+        DateTime::from_timestamp_millis(1_700_000_000_123).unwrap()
+    );
+
+    // Sub-millisecond precision truncates toward negative infinity.
+    let before_epoch = DateTime::from_timestamp(-1, 999_999_999).unwrap();
+    assert_eq!(
+        encode_to_vec(&before_epoch).unwrap(),
+        encode_to_vec(&-1i64).unwrap()
+    );
+
+    let list = vec![at, before_epoch];
+    let bytes = encode_to_vec(&list).unwrap();
+    assert_eq!(
+        decode::<Vec<DateTime<Utc>>>(&bytes, LIMITS).unwrap().len(),
+        2
+    );
+
+    // Representable as a safe integer, but beyond chrono's range.
+    let far = encode_to_vec(&MAX_SAFE_INTEGER_I64).unwrap();
+    let err = decode::<DateTime<Utc>>(&far, LIMITS).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ValueOutOfRange);
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn time_datetimes_encode_as_epoch_millis() {
+    use time::{OffsetDateTime, UtcOffset};
+
+    let at = OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_456_789).unwrap();
+    let bytes = encode_to_vec(&at).unwrap();
+    assert_eq!(bytes, encode_to_vec(&1_700_000_000_123i64).unwrap());
+    let back = decode::<OffsetDateTime>(&bytes, LIMITS).unwrap();
+    assert_eq!(back.unix_timestamp_nanos(), 1_700_000_000_123_000_000);
+
+    // The offset is dropped; the instant is preserved.
+    let shifted = at.to_offset(UtcOffset::from_hms(5, 30, 0).unwrap());
+    assert_eq!(encode_to_vec(&shifted).unwrap(), bytes);
+
+    let before_epoch = OffsetDateTime::from_unix_timestamp_nanos(-1).unwrap();
+    assert_eq!(
+        encode_to_vec(&before_epoch).unwrap(),
+        encode_to_vec(&-1i64).unwrap()
+    );
+
+    let far = encode_to_vec(&MAX_SAFE_INTEGER_I64).unwrap();
+    let err = decode::<OffsetDateTime>(&far, LIMITS).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ValueOutOfRange);
+}
+
+#[test]
+fn timestamps_beyond_the_safe_range_are_rejected() {
+    // 2^53 as a uint64: outside the safe-integer window.
+    let bytes = [0x1b, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    #[cfg(feature = "chrono")]
+    {
+        let err = decode::<chrono::DateTime<chrono::Utc>>(&bytes, LIMITS).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+    }
+    #[cfg(feature = "time")]
+    {
+        let err = decode::<time::OffsetDateTime>(&bytes, LIMITS).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::IntegerOutsideSafeRange);
+    }
+}
@@ -1,9 +1,9 @@
-use sacp_cbor::{validate_canonical, DecodeLimits, ErrorCode};
+use sacp_cbor::{validate_canonical, validate_many, validate_prefix, DecodeLimits, ErrorCode};
 
 fn assert_invalid(bytes: &[u8], limits: DecodeLimits, code: ErrorCode) -> usize {
     let err = validate_canonical(bytes, limits).unwrap_err();
-    assert_eq!(err.code, code);
-    err.offset
+    assert_eq!(err.code(), code);
+    err.offset()
 }
 
 fn tstr_encoded(len: usize, fill: u8) -> Vec<u8> {
@@ -55,42 +55,103 @@ fn rejects_input_len_over_limit() {
     let bytes = [0xf6]; // null
     let limits = DecodeLimits::for_bytes(0);
     let err = validate_canonical(&bytes, limits).unwrap_err();
-    assert_eq!(err.code, ErrorCode::MessageLenLimitExceeded);
+    assert_eq!(err.code(), ErrorCode::MessageLenLimitExceeded);
 
     let err = validate_canonical(&bytes, limits).unwrap_err();
-    assert_eq!(err.code, ErrorCode::MessageLenLimitExceeded);
+    assert_eq!(err.code(), ErrorCode::MessageLenLimitExceeded);
+}
+
+#[test]
+fn long_map_keys_are_bounded_separately() {
+    // { <10 KiB key>: <10 KiB text> }
+    let mut bytes = vec![0xa1];
+    bytes.extend(tstr_encoded(10 * 1024, b'k'));
+    bytes.extend(tstr_encoded(10 * 1024, b'v'));
+    let limits = DecodeLimits::for_bytes(bytes.len());
+    assert_eq!(
+        assert_invalid(&bytes, limits, ErrorCode::KeyLenLimitExceeded),
+        1
+    );
+    let err = sacp_cbor::decode::<sacp_cbor::CborValueRef<'_>>(&bytes, limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::KeyLenLimitExceeded);
+
+    // The same text is fine as a value, and a raised key limit admits the key.
+    let mut value_only = vec![0xa1, 0x61, b'a'];
+    value_only.extend(tstr_encoded(10 * 1024, b'v'));
+    assert!(validate_canonical(&value_only, limits).is_ok());
+    let relaxed = limits.with_max_key_len(10 * 1024);
+    let canon = validate_canonical(&bytes, relaxed).unwrap();
+
+    // Trusted decoding does not re-apply the key cap to already-validated bytes.
+    let map =
+        sacp_cbor::decode_canonical::<std::collections::BTreeMap<String, &str>>(canon).unwrap();
+    assert_eq!(map.keys().next().map(String::len), Some(10 * 1024));
+    let root = canon.root();
+    let value: &str = root
+        .map()
+        .unwrap()
+        .get_as(&"k".repeat(10 * 1024))
+        .unwrap()
+        .unwrap();
+    assert_eq!(value.len(), 10 * 1024);
+    assert!(root.decode_as::<sacp_cbor::CborValueRef<'_>>().is_ok());
+}
+
+#[test]
+fn validate_prefix_reports_first_item_length() {
+    // {"a":[1,2]} followed by "x" and a truncated third item.
+    let bytes = [0xa1, 0x61, b'a', 0x82, 0x01, 0x02, 0x61, b'x', 0x82];
+    let limits = DecodeLimits::for_bytes(16);
+    assert_eq!(validate_prefix(&bytes, limits).unwrap(), 6);
+    assert_eq!(validate_prefix(&bytes[6..], limits).unwrap(), 2);
+    assert_eq!(
+        validate_canonical(&bytes[..6], limits).unwrap().as_bytes(),
+        &bytes[..6]
+    );
+
+    let err = validate_prefix(&bytes[8..], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+    let err = validate_prefix(&[0x18, 0x01, 0x00], limits).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
+
+    // The limit applies to the item, not to what follows it.
+    let small = DecodeLimits::for_bytes(6);
+    assert_eq!(validate_prefix(&bytes, small).unwrap(), 6);
+    let tiny = DecodeLimits::for_bytes(5);
+    let err = validate_prefix(&bytes, tiny).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MessageLenLimitExceeded);
 }
 
 #[test]
 fn ai_31_is_reserved_for_int_and_tag() {
     let bytes = [0x1f]; // major 0, ai=31
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::ReservedAdditionalInfo);
+    assert_eq!(err.code(), ErrorCode::ReservedAdditionalInfo);
 
     let bytes = [0xdf]; // major 6 (tag), ai=31
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::ReservedAdditionalInfo);
+    assert_eq!(err.code(), ErrorCode::ReservedAdditionalInfo);
 }
 
 #[test]
 fn rejects_trailing_bytes() {
     let bytes = [0xa0, 0x00]; // {} then trailing 0x00
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::TrailingBytes);
+    assert_eq!(err.code(), ErrorCode::TrailingBytes);
 }
 
 #[test]
 fn rejects_indefinite_length_text() {
     let bytes = [0x7f, 0xff]; // indefinite text, immediately break
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::IndefiniteLengthForbidden);
+    assert_eq!(err.code(), ErrorCode::IndefiniteLengthForbidden);
 }
 
 #[test]
 fn rejects_non_canonical_uint_encoding() {
     let bytes = [0x18, 0x17]; // 23 encoded with 1-byte length argument (non-canonical)
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalEncoding);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
 }
 
 #[test]
@@ -99,14 +160,14 @@ fn rejects_non_canonical_length_encoding_for_text() {
     let mut bytes = vec![0x78, 23];
     bytes.extend(std::iter::repeat(b'a').take(23));
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalEncoding);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
 }
 
 #[test]
 fn rejects_non_canonical_nint_encoding() {
     let bytes = [0x38, 0x17]; // -24, should be 0x37
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalEncoding);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
 }
 
 #[test]
@@ -114,8 +175,8 @@ fn rejects_non_canonical_tag_encoding() {
     // tag(2) encoded with ai=24 (non-canonical), followed by bstr magnitude
     let bytes = [0xd8, 0x02, 0x47, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalEncoding);
-    assert_eq!(err.offset, 0);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
+    assert_eq!(err.offset(), 0);
 }
 
 #[test]
@@ -123,21 +184,21 @@ fn rejects_non_canonical_length_encoding_for_bytes() {
     let mut bytes = vec![0x58, 23];
     bytes.extend(std::iter::repeat(0u8).take(23));
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalEncoding);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
 }
 
 #[test]
 fn rejects_non_canonical_length_encoding_for_array_header() {
     let bytes = [0x98, 0x17]; // array length 23 encoded with ai=24
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalEncoding);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
 }
 
 #[test]
 fn rejects_non_canonical_length_encoding_for_map_header() {
     let bytes = [0xb8, 0x17]; // map length 23 encoded with ai=24
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalEncoding);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
 }
 
 #[test]
@@ -145,7 +206,7 @@ fn rejects_map_key_not_text() {
     // { h'00': 0 }
     let bytes = [0xa1, 0x41, 0x00, 0x00];
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::MapKeyMustBeText);
+    assert_eq!(err.code(), ErrorCode::MapKeyMustBeText);
 }
 
 #[test]
@@ -153,7 +214,12 @@ fn rejects_duplicate_map_key() {
     // {"a": 0, "a": 1}
     let bytes = [0xa2, 0x61, 0x61, 0x00, 0x61, 0x61, 0x01];
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::DuplicateMapKey);
+    assert_eq!(err.code(), ErrorCode::DuplicateMapKey);
+    assert_eq!((err.offset(), err.detail()), (4, Some(1)));
+    assert_eq!(
+        err.to_string(),
+        "cbor error at 4: duplicate map key (prev key at 1)"
+    );
 }
 
 #[test]
@@ -161,7 +227,7 @@ fn rejects_map_out_of_order_same_length() {
     // {"b": 0, "a": 1} (keys same encoded length; should be lexicographically sorted)
     let bytes = [0xa2, 0x61, 0x62, 0x00, 0x61, 0x61, 0x01];
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalMapOrder);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalMapOrder);
 }
 
 #[test]
@@ -178,7 +244,7 @@ fn rejects_map_out_of_order_23_24_boundary() {
     bytes.push(0x01);
 
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalMapOrder);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalMapOrder);
 }
 
 #[test]
@@ -194,14 +260,14 @@ fn rejects_map_out_of_order_255_256_boundary() {
     bytes.push(0x01);
 
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalMapOrder);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalMapOrder);
 }
 
 #[test]
 fn rejects_forbidden_tag() {
     let bytes = [0xc1, 0x00]; // tag(1) 0
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::ForbiddenOrMalformedTag);
+    assert_eq!(err.code(), ErrorCode::ForbiddenOrMalformedTag);
 }
 
 #[test]
@@ -210,7 +276,7 @@ fn rejects_bignum_in_safe_range() {
     let mut bytes = vec![0xc2];
     bytes.extend_from_slice(&bstr_encoded(&[0x01]));
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::BignumMustBeOutsideSafeRange);
+    assert_eq!(err.code(), ErrorCode::BignumMustBeOutsideSafeRange);
 }
 
 #[test]
@@ -219,7 +285,7 @@ fn rejects_bignum_with_leading_zero() {
     let mut bytes = vec![0xc2];
     bytes.extend_from_slice(&bstr_encoded(&[0x00, 0x01]));
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::BignumNotCanonical);
+    assert_eq!(err.code(), ErrorCode::BignumNotCanonical);
 }
 
 #[test]
@@ -227,7 +293,7 @@ fn rejects_negative_zero_float64() {
     let mut bytes = vec![0xfb];
     bytes.extend_from_slice(&0x8000_0000_0000_0000u64.to_be_bytes());
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NegativeZeroForbidden);
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
 }
 
 #[test]
@@ -235,7 +301,7 @@ fn rejects_non_canonical_nan_float64() {
     let mut bytes = vec![0xfb];
     bytes.extend_from_slice(&0x7ff9_0000_0000_0000u64.to_be_bytes());
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalNaN);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalNaN);
 }
 
 #[test]
@@ -479,13 +545,13 @@ fn rejects_reserved_additional_info_for_simple() {
 fn unexpected_eof_offsets_are_stable() {
     let bytes = [0x18]; // uint8 additional info but missing byte
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::UnexpectedEof);
-    assert_eq!(err.offset, 1);
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+    assert_eq!(err.offset(), 1);
 
     let bytes = [0xfb, 0x00]; // float64 missing 7 bytes
     let err = validate_canonical(&bytes, DecodeLimits::for_bytes(bytes.len())).unwrap_err();
-    assert_eq!(err.code, ErrorCode::UnexpectedEof);
-    assert_eq!(err.offset, 1);
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+    assert_eq!(err.offset(), 1);
 }
 
 #[test]
@@ -523,3 +589,93 @@ fn enforces_limits() {
     limits.max_total_items = 1;
     assert_invalid(&bytes_map, limits, ErrorCode::TotalItemsLimitExceeded);
 }
+
+#[test]
+fn decode_limits_try_new_rejects_contradictions() {
+    let ok = DecodeLimits::try_new(1024, 16, 512, 256, 256, 1024, 1024).unwrap();
+    assert_eq!(ok.max_total_items, 512);
+    assert!(DecodeLimits::for_bytes(4096).validate().is_ok());
+    assert!(DecodeLimits::for_bytes(1 << 20).validate().is_ok());
+    // Small messages cap the key length at the text length so the defaults stay valid.
+    let small = DecodeLimits::for_bytes(16);
+    assert_eq!(small.max_key_len, 16);
+    assert!(small.validate().is_ok());
+    assert_eq!(
+        DecodeLimits::try_new(64, 16, 64, 64, 64, 64, 64)
+            .unwrap()
+            .max_key_len,
+        64
+    );
+
+    for bad in [
+        DecodeLimits::try_new(0, 16, 512, 256, 256, 0, 0),
+        DecodeLimits::try_new(1024, 16, 8, 256, 8, 1024, 1024),
+        DecodeLimits::try_new(1024, 16, 8, 8, 256, 1024, 1024),
+        DecodeLimits::try_new(1024, 16, 512, 256, 256, 2048, 1024),
+        DecodeLimits::try_new(1024, 16, 512, 256, 256, 1024, 2048),
+        DecodeLimits::for_bytes(4096)
+            .with_max_text_len(512)
+            .with_max_key_len(1024)
+            .validate()
+            .map(|()| DecodeLimits::unlimited()),
+    ] {
+        assert_eq!(bad.unwrap_err().code(), ErrorCode::InvalidLimits);
+    }
+}
+
+#[test]
+fn unlimited_limits_only_disable_size_caps() {
+    let limits = DecodeLimits::unlimited();
+    assert!(limits.validate().is_ok());
+
+    let mut deep = vec![0x81; 4096];
+    deep.push(0x00);
+    assert!(validate_canonical(&deep, limits).is_ok());
+
+    // Profile and structural rules still apply.
+    assert_invalid(&[0x18, 0x01], limits, ErrorCode::NonCanonicalEncoding);
+    assert_invalid(
+        &[0x9b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        limits,
+        ErrorCode::UnexpectedEof,
+    );
+}
+
+#[test]
+fn validate_many_stops_at_first_bad_frame() {
+    let frames: [&[u8]; 4] = [&[0x01], &[0x61, b'x'], &[0x18, 0x01], &[0xf6]];
+    let limits = DecodeLimits::for_bytes(16);
+    assert_eq!(validate_many(frames[..2].iter().copied(), limits), Ok(2));
+
+    let (index, err) = validate_many(frames.iter().copied(), limits).unwrap_err();
+    assert_eq!(index, 2);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalEncoding);
+
+    assert_eq!(validate_many(core::iter::empty(), limits), Ok(0));
+}
+
+#[cfg(all(feature = "std", feature = "alloc"))]
+#[test]
+fn deep_nesting_is_bounded_by_depth_limit_not_stack() {
+    const DEPTH: usize = 10_000;
+    // [[[...[0]...]], 1]: the deep item is skipped again when indexing past it.
+    let mut bytes = vec![0x82];
+    bytes.extend(std::iter::repeat(0x81).take(DEPTH));
+    bytes.extend([0x00, 0x01]);
+
+    // A small thread stack: any recursion per nesting level would overflow it.
+    let handle = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || {
+            let mut limits = DecodeLimits::for_bytes(bytes.len());
+            limits.max_depth = DEPTH + 2;
+            let canon = validate_canonical(&bytes, limits).unwrap();
+            let second = canon.root().array().unwrap().get(1).unwrap().unwrap();
+            assert_eq!(second.integer().unwrap().as_i64(), Some(1));
+
+            limits.max_depth = DEPTH;
+            assert_invalid(&bytes, limits, ErrorCode::DepthLimitExceeded);
+        })
+        .unwrap();
+    handle.join().unwrap();
+}
@@ -5,10 +5,10 @@ use sacp_cbor::{BigInt, ErrorCode, F64Bits};
 #[test]
 fn bigint_rejects_empty_and_leading_zero() {
     let err = BigInt::new(false, Vec::new()).unwrap_err();
-    assert_eq!(err.code, ErrorCode::BignumNotCanonical);
+    assert_eq!(err.code(), ErrorCode::BignumNotCanonical);
 
     let err = BigInt::new(false, vec![0x00, 0x01]).unwrap_err();
-    assert_eq!(err.code, ErrorCode::BignumNotCanonical);
+    assert_eq!(err.code(), ErrorCode::BignumNotCanonical);
 }
 
 #[test]
@@ -17,10 +17,10 @@ fn bigint_rejects_safe_range_magnitudes() {
     let max_safe_minus_one = vec![0x1f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe];
 
     let err = BigInt::new(false, max_safe.clone()).unwrap_err();
-    assert_eq!(err.code, ErrorCode::BignumMustBeOutsideSafeRange);
+    assert_eq!(err.code(), ErrorCode::BignumMustBeOutsideSafeRange);
 
     let err = BigInt::new(true, max_safe_minus_one).unwrap_err();
-    assert_eq!(err.code, ErrorCode::BignumMustBeOutsideSafeRange);
+    assert_eq!(err.code(), ErrorCode::BignumMustBeOutsideSafeRange);
 
     let ok = BigInt::new(true, max_safe).unwrap();
     assert!(ok.is_negative());
@@ -29,10 +29,10 @@ fn bigint_rejects_safe_range_magnitudes() {
 #[test]
 fn f64bits_rejects_negative_zero_and_non_canonical_nan() {
     let err = F64Bits::new(0x8000_0000_0000_0000).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NegativeZeroForbidden);
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
 
     let err = F64Bits::new(0x7ff9_0000_0000_0000).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NonCanonicalNaN);
+    assert_eq!(err.code(), ErrorCode::NonCanonicalNaN);
 }
 
 #[test]
@@ -44,5 +44,44 @@ fn f64bits_try_from_f64_canonicalizes_nan() {
 #[test]
 fn f64bits_try_from_f64_rejects_negative_zero() {
     let err = F64Bits::try_from_f64(-0.0).unwrap_err();
-    assert_eq!(err.code, ErrorCode::NegativeZeroForbidden);
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
+}
+
+#[test]
+fn f64bits_from_bits_checked_and_accessors() {
+    let err = F64Bits::from_bits_checked((-0.0f64).to_bits()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NegativeZeroForbidden);
+    let err = F64Bits::from_bits_checked(0xfff8_0000_0000_0000).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalNaN);
+    let err = F64Bits::from_bits_checked(0x7ff0_0000_0000_0001).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NonCanonicalNaN);
+
+    let nan = F64Bits::from_bits_checked(0x7ff8_0000_0000_0000).unwrap();
+    assert!(nan.is_nan() && !nan.is_infinite());
+    assert_eq!(nan.as_bits(), 0x7ff8_0000_0000_0000);
+
+    let neg_inf = F64Bits::from_bits_checked(f64::NEG_INFINITY.to_bits()).unwrap();
+    assert!(neg_inf.is_infinite() && !neg_inf.is_nan());
+    let one = F64Bits::from_bits_checked(1.0f64.to_bits()).unwrap();
+    assert!(!one.is_infinite() && !one.is_nan());
+    assert_eq!(one.to_f64(), 1.0);
+}
+
+#[test]
+fn f64bits_total_order() {
+    let mut values: Vec<F64Bits> = [f64::NAN, 1.5, f64::NEG_INFINITY, 0.0, -2.0, f64::INFINITY]
+        .into_iter()
+        .map(|v| F64Bits::try_from_f64(v).unwrap())
+        .collect();
+    values.sort();
+    let sorted: Vec<u64> = values.iter().map(|v| v.bits()).collect();
+    let expected: Vec<u64> = [f64::NEG_INFINITY, -2.0, 0.0, 1.5, f64::INFINITY]
+        .iter()
+        .map(|v| v.to_bits())
+        .chain([0x7ff8_0000_0000_0000])
+        .collect();
+    assert_eq!(sorted, expected);
+
+    let nan = F64Bits::try_from_f64(f64::NAN).unwrap();
+    assert_eq!(nan.total_cmp(nan), core::cmp::Ordering::Equal);
 }
@@ -0,0 +1,48 @@
+#![cfg(feature = "zeroize")]
+// Observes freed buffers through a counting global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use sacp_cbor::{CanonicalCbor, DecodeLimits};
+
+const MARKER: &[u8] = b"zeroize-marker-5f3a";
+
+static MARKERS_FREED: AtomicUsize = AtomicUsize::new(0);
+
+struct ScanningAlloc;
+
+unsafe impl GlobalAlloc for ScanningAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let freed = std::slice::from_raw_parts(ptr, layout.size());
+        if freed.windows(MARKER.len()).any(|w| w == MARKER) {
+            MARKERS_FREED.fetch_add(1, Ordering::SeqCst);
+        }
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: ScanningAlloc = ScanningAlloc;
+
+fn secret_message() -> CanonicalCbor {
+    let mut bytes = vec![0x40 | 0x13];
+    bytes.extend_from_slice(MARKER);
+    CanonicalCbor::from_vec(bytes, DecodeLimits::for_bytes(64)).unwrap()
+}
+
+#[test]
+fn drop_wipes_buffer_but_into_bytes_hands_off() {
+    let before = MARKERS_FREED.load(Ordering::SeqCst);
+    drop(secret_message());
+    assert_eq!(MARKERS_FREED.load(Ordering::SeqCst), before);
+
+    let raw = secret_message().into_bytes();
+    assert!(raw.ends_with(MARKER));
+    drop(raw);
+    assert_eq!(MARKERS_FREED.load(Ordering::SeqCst), before + 1);
+}